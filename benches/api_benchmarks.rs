@@ -31,12 +31,12 @@ fn benchmark_ticker_normalization(c: &mut Criterion) {
 fn benchmark_rate_limiter(c: &mut Criterion) {
     let mut group = c.benchmark_group("rate_limiter");
     
-    group.bench_function("check_rate_limit", |b| {
+    group.bench_function("check_and_record", |b| {
         let limiter = RateLimiter::new(1000);
         b.iter(|| {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
-                black_box(limiter.check_rate_limit().await);
+                black_box(limiter.check_and_record().await);
             });
         });
     });
@@ -0,0 +1,253 @@
+//! API-key authentication for protected public routes.
+//!
+//! Requests to a configured set of route prefixes must carry a known key in
+//! the `X-API-Key` header; the key's identity is attached to the request as
+//! an [`ApiKeyIdentity`] extension. Every key gets its own sliding-window
+//! budget in a dedicated [`RateLimiter`], so one abusive key is throttled
+//! without touching other keys or the upstream budget. Admin keys are
+//! accepted too, without a budget. `/health` and `/metrics` are never
+//! protected.
+
+use crate::api::kaspacom_handlers::ErrorResponse;
+use crate::api::state::AppState;
+use crate::infrastructure::RateLimiter;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Paths that stay open even when covered by a protected prefix
+const ALWAYS_OPEN: &[&str] = &["/health", "/health/ready", "/metrics"];
+
+/// Default requests per minute for keys without their own limit
+pub const DEFAULT_KEY_REQUESTS_PER_MINUTE: u32 = 120;
+
+/// One client API key
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiKeyConfig {
+    /// Identity of the key holder (logged and attached to requests)
+    pub name: String,
+    /// The key clients send in `X-API-Key`
+    pub key: String,
+    /// Requests per minute for this key (default: `default_requests_per_minute`)
+    pub requests_per_minute: Option<u32>,
+}
+
+/// API-key authentication settings
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiAuthConfig {
+    /// Route prefixes that require a key; empty disables authentication
+    #[serde(default)]
+    pub protected_routes: Vec<String>,
+    /// Requests per minute for keys without their own limit
+    #[serde(default = "default_key_requests_per_minute")]
+    pub default_requests_per_minute: u32,
+    /// Accepted client keys
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+}
+
+impl Default for ApiAuthConfig {
+    fn default() -> Self {
+        Self {
+            protected_routes: Vec::new(),
+            default_requests_per_minute: default_key_requests_per_minute(),
+            api_keys: Vec::new(),
+        }
+    }
+}
+
+fn default_key_requests_per_minute() -> u32 {
+    DEFAULT_KEY_REQUESTS_PER_MINUTE
+}
+
+/// Identity of the API key that authenticated a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyIdentity(pub String);
+
+/// Key lookup and per-key rate limits for protected routes
+pub struct ApiKeyAuth {
+    /// Key mapped to the identity of its holder
+    keys: HashMap<String, String>,
+    protected_routes: Vec<String>,
+    /// One bucket per identity
+    limiter: RateLimiter,
+}
+
+impl ApiKeyAuth {
+    pub fn new(config: &ApiAuthConfig) -> Self {
+        let keys = config
+            .api_keys
+            .iter()
+            .map(|k| (k.key.clone(), k.name.clone()))
+            .collect();
+        let limits = config
+            .api_keys
+            .iter()
+            .map(|k| {
+                let rpm = k.requests_per_minute.unwrap_or(config.default_requests_per_minute);
+                (k.name.clone(), rpm)
+            })
+            .collect();
+        Self {
+            keys,
+            protected_routes: config.protected_routes.clone(),
+            limiter: RateLimiter::new(config.default_requests_per_minute).with_category_limits(limits),
+        }
+    }
+
+    /// Whether any routes require a key
+    pub fn is_enabled(&self) -> bool {
+        !self.protected_routes.is_empty()
+    }
+
+    /// Whether `path` requires a key
+    ///
+    /// Prefixes match whole path segments, so `/v1/api/admin` covers
+    /// `/v1/api/admin/stats` but not `/v1/api/admin-foo`.
+    pub fn is_protected(&self, path: &str) -> bool {
+        !ALWAYS_OPEN.contains(&path)
+            && self
+                .protected_routes
+                .iter()
+                .any(|prefix| covers_segments(prefix, path))
+    }
+
+    /// Identity behind a key, if the key is known
+    pub fn identify(&self, key: &str) -> Option<&str> {
+        self.keys.get(key).map(String::as_str)
+    }
+}
+
+/// Reject requests to protected routes without a valid, in-budget key
+pub async fn require_api_key(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let auth = &state.api_key_auth;
+    if !auth.is_protected(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let key = request
+        .headers()
+        .get("X-API-Key")
+        .and_then(|v| v.to_str().ok());
+
+    // Admin keys share the header and pass without a client budget
+    if let Some(admin) = key.and_then(|key| state.admin_api_keys.get(key)) {
+        let identity = ApiKeyIdentity(admin.clone());
+        request.extensions_mut().insert(identity);
+        return next.run(request).await;
+    }
+
+    let Some(identity) = key.and_then(|key| auth.identify(key)).map(str::to_string) else {
+        let error = if key.is_some() { "Invalid API key" } else { "Missing X-API-Key header" };
+        return error_response(StatusCode::UNAUTHORIZED, error, None);
+    };
+
+    if !auth.limiter.check_and_record_for(&identity).await {
+        warn!("API key {} exceeded its rate limit", identity);
+        let retry_after = auth.limiter.retry_after_for(&identity).await;
+        let mut response = error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "API key rate limit exceeded",
+            Some(format!(
+                "Limit is {} requests per minute",
+                auth.limiter.limit_for(&identity)
+            )),
+        );
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    request.extensions_mut().insert(ApiKeyIdentity(identity));
+    next.run(request).await
+}
+
+/// Whether `path` is `prefix` or lies below it
+fn covers_segments(prefix: &str, path: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'),
+        None => false,
+    }
+}
+
+fn error_response(status: StatusCode, error: &str, details: Option<String>) -> Response {
+    (
+        status,
+        Json(ErrorResponse::new(error, None, details)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ApiAuthConfig {
+        ApiAuthConfig {
+            protected_routes: vec!["/v1/api/kaspa".to_string(), "/".to_string()],
+            default_requests_per_minute: 2,
+            api_keys: vec![
+                ApiKeyConfig {
+                    name: "alice".to_string(),
+                    key: "key-a".to_string(),
+                    requests_per_minute: Some(1),
+                },
+                ApiKeyConfig {
+                    name: "bob".to_string(),
+                    key: "key-b".to_string(),
+                    requests_per_minute: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_health_and_metrics_stay_open() {
+        let auth = ApiKeyAuth::new(&config());
+        assert!(auth.is_protected("/v1/api/kaspa/floor-price"));
+        assert!(!auth.is_protected("/health"));
+        assert!(!auth.is_protected("/metrics"));
+        assert!(!ApiKeyAuth::new(&ApiAuthConfig::default()).is_protected("/v1/api/kaspa/floor-price"));
+    }
+
+    #[test]
+    fn test_prefixes_match_whole_segments() {
+        let auth = ApiKeyAuth::new(&ApiAuthConfig {
+            protected_routes: vec!["/v1/api/admin".to_string(), "/v2/".to_string()],
+            ..ApiAuthConfig::default()
+        });
+        assert!(auth.is_protected("/v1/api/admin"));
+        assert!(auth.is_protected("/v1/api/admin/stats"));
+        assert!(!auth.is_protected("/v1/api/admin-foo"));
+        assert!(!auth.is_protected("/v1/api/adminx/stats"));
+        assert!(auth.is_protected("/v2/graphql"));
+        assert!(!auth.is_protected("/v2"));
+    }
+
+    #[tokio::test]
+    async fn test_each_key_has_its_own_budget() {
+        let auth = ApiKeyAuth::new(&config());
+        assert_eq!(auth.identify("key-a"), Some("alice"));
+        assert_eq!(auth.identify("nope"), None);
+
+        assert!(auth.limiter.check_and_record_for("alice").await);
+        assert!(!auth.limiter.check_and_record_for("alice").await);
+
+        // Alice being throttled doesn't affect Bob's default budget
+        assert!(auth.limiter.check_and_record_for("bob").await);
+        assert!(auth.limiter.check_and_record_for("bob").await);
+        assert!(!auth.limiter.check_and_record_for("bob").await);
+    }
+}
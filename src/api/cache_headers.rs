@@ -0,0 +1,234 @@
+//! ETag and Cache-Control headers for cache-backed responses.
+//!
+//! Successful GET responses built from Parquet cache entries get a strong
+//! ETag derived from each entry's key and `cached_at`, and
+//! `Cache-Control: public, max-age=<remaining TTL>` so an edge cache keeps
+//! them exactly as long as the underlying entries are fresh. The max-age is
+//! the shortest remaining TTL of the entries used, so it never outlives data
+//! we have already refreshed. Matching `If-None-Match` requests get 304.
+//!
+//! They also get `X-Cache: HIT|MISS`, `X-Cache-Tier: redis|parquet|origin`
+//! and `Age` (seconds since the oldest entry was fetched upstream, 0 on a
+//! miss), so clients can tell fresh data from cached data.
+//!
+//! Responses built from plain Redis keys (the ticker endpoints) have no
+//! entry metadata to derive a tag from. When the service reported the
+//! remaining TTL of the keys used, they get a weak ETag hashed from the
+//! body and the same `Cache-Control` instead.
+
+use crate::application::cache_service::{
+    fnv1a, track_response_max_age, track_served_entries, ServedEntries,
+};
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Attach ETag/Cache-Control to responses served from the cache
+pub async fn cache_headers(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let ((mut response, served), reported_max_age) =
+        track_response_max_age(track_served_entries(next.run(request))).await;
+
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    insert_cache_status(response.headers_mut(), &served);
+
+    // Leave handlers that set their own caching policy alone
+    if has_cache_policy(response.headers()) {
+        return response;
+    }
+    if let (Some(etag), Some(max_age)) = (served.etag(), served.max_age_secs()) {
+        return with_cache_policy(response, &served, if_none_match, etag, max_age);
+    }
+
+    // Only when no entry left the response partly undescribed
+    let Some(max_age) = reported_max_age.filter(|_| served.entries.is_empty() && !served.untracked) else {
+        return response;
+    };
+    let (parts, body) = response.into_parts();
+    if body.size_hint().exact().is_none_or(|len| len > MAX_HASHED_BODY_BYTES) {
+        return Response::from_parts(parts, body);
+    }
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_HASHED_BODY_BYTES as usize).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let etag = format!("W/\"{:016x}\"", fnv1a(&bytes));
+    let response = Response::from_parts(parts, Body::from(bytes));
+    with_cache_policy(response, &served, if_none_match, etag, max_age)
+}
+
+/// Largest body hashed for a weak ETag; bigger responses go without one
+const MAX_HASHED_BODY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Set ETag and Cache-Control, answering 304 when `If-None-Match` matches
+fn with_cache_policy(
+    mut response: Response,
+    served: &ServedEntries,
+    if_none_match: Option<HeaderValue>,
+    etag: String,
+    max_age: u64,
+) -> Response {
+    let (Ok(etag_value), Ok(cache_control)) = (
+        HeaderValue::from_str(&etag),
+        HeaderValue::from_str(&format!("public, max-age={}", max_age)),
+    ) else {
+        return response;
+    };
+
+    if if_none_match.is_some_and(|value| etag_matches(&value, &etag)) {
+        response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+        insert_cache_status(response.headers_mut(), served);
+    }
+    response.headers_mut().insert(header::ETAG, etag_value);
+    response.headers_mut().insert(header::CACHE_CONTROL, cache_control);
+    response
+}
+
+/// Set `X-Cache`, `X-Cache-Tier` and `Age` from the served entries
+fn insert_cache_status(headers: &mut HeaderMap, served: &ServedEntries) {
+    let (Some(status), Some(tier), Some(age)) = (served.cache_status(), served.tier(), served.age_secs()) else {
+        return;
+    };
+    headers.insert(HeaderName::from_static("x-cache"), HeaderValue::from_static(status));
+    headers.insert(HeaderName::from_static("x-cache-tier"), HeaderValue::from_static(tier));
+    headers.insert(header::AGE, HeaderValue::from(age));
+}
+
+fn has_cache_policy(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::ETAG) || headers.contains_key(header::CACHE_CONTROL)
+}
+
+/// Whether an `If-None-Match` header value matches `etag`
+///
+/// Uses weak comparison as RFC 9110 requires for `If-None-Match`.
+fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    let opaque = |tag: &str| tag.strip_prefix("W/").unwrap_or(tag).to_string();
+    let etag = opaque(etag);
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || opaque(candidate) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cache_status_headers_on_miss_then_hit() {
+        use crate::application::CacheService;
+        use crate::infrastructure::{InMemoryCache, KaspaComClient, ParquetStore, RateLimiter};
+        use axum::{extract::State, routing::get, Router};
+        use std::sync::Arc;
+        use tower::ServiceExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Arc::new(CacheService::new(
+            Arc::new(InMemoryCache::new()),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        ));
+        let app = Router::new()
+            .route(
+                "/logo",
+                get(|State(cache): State<Arc<CacheService>>| async move {
+                    let logo: String = cache
+                        .get_cached("kaspa:logos:NACHO", "logos", "NACHO", (60, 600), || async {
+                            Ok(serde_json::json!("logo"))
+                        })
+                        .await
+                        .unwrap();
+                    logo
+                }),
+            )
+            .layer(axum::middleware::from_fn(cache_headers))
+            .with_state(cache);
+        let get = |app: Router| async move {
+            let response = app.oneshot(Request::get("/logo").body(Body::empty()).unwrap()).await.unwrap();
+            let header = |name: &str| response.headers().get(name).map(|v| v.to_str().unwrap().to_string());
+            (header("x-cache"), header("x-cache-tier"), header("age"))
+        };
+
+        let miss = get(app.clone()).await;
+        assert_eq!(miss, (Some("MISS".into()), Some("origin".into()), Some("0".into())));
+        let (status, tier, age) = get(app).await;
+        assert_eq!((status, tier), (Some("HIT".into()), Some("redis".into())));
+        assert!(age.unwrap().parse::<u64>().unwrap() <= 1);
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = "\"00ff\"";
+        assert!(etag_matches(&HeaderValue::from_static("\"00ff\""), etag));
+        assert!(etag_matches(&HeaderValue::from_static("\"abcd\", W/\"00ff\""), etag));
+        assert!(etag_matches(&HeaderValue::from_static("*"), etag));
+        assert!(!etag_matches(&HeaderValue::from_static("\"abcd\""), etag));
+        assert!(etag_matches(&HeaderValue::from_static("\"00ff\""), "W/\"00ff\""));
+    }
+
+    #[tokio::test]
+    async fn test_weak_etag_for_reported_max_age() {
+        use crate::application::cache_service::record_response_max_age;
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route(
+                "/stats",
+                get(|| async {
+                    record_response_max_age(300);
+                    record_response_max_age(120);
+                    axum::Json(serde_json::json!({ "token": "kaspa" }))
+                }),
+            )
+            .route("/uncached", get(|| async { "no ttl" }))
+            .route(
+                "/missing",
+                get(|| async {
+                    record_response_max_age(300);
+                    StatusCode::NOT_FOUND
+                }),
+            )
+            .layer(axum::middleware::from_fn(cache_headers));
+        let send = |uri: &str, if_none_match: Option<&str>| {
+            let mut request = Request::get(uri);
+            if let Some(tag) = if_none_match {
+                request = request.header(header::IF_NONE_MATCH, tag);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = send("/stats", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=120");
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(etag.starts_with("W/\""), "{}", etag);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, r#"{"token":"kaspa"}"#);
+
+        let revalidated = send("/stats", Some(&etag)).await.unwrap();
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(revalidated.headers()[header::ETAG], etag.as_str());
+        let stale = send("/stats", Some("W/\"0000000000000000\"")).await.unwrap();
+        assert_eq!(stale.status(), StatusCode::OK);
+
+        for uri in ["/uncached", "/missing"] {
+            let response = send(uri, None).await.unwrap();
+            assert!(!has_cache_policy(response.headers()), "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Cross-origin (CORS) policy from config.
+//!
+//! `server.cors` lists the origins, methods and headers browsers may use,
+//! whether credentials are allowed, and how long preflight results may be
+//! cached. A lone `"*"` in a list allows anything; that is the default for
+//! all three, matching the permissive policy used before this was
+//! configurable. Browsers reject wildcards on credentialed requests, so
+//! `allow_credentials` requires explicit lists and is checked at startup.
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+/// Wildcard entry allowing any origin, method or header
+const WILDCARD: &str = "*";
+
+/// CORS settings (`server.cors`)
+#[derive(Deserialize, Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API, e.g. `https://app.example.com`
+    #[serde(default = "wildcard")]
+    pub allowed_origins: Vec<String>,
+    /// Methods allowed in cross-origin requests
+    #[serde(default = "wildcard")]
+    pub allowed_methods: Vec<String>,
+    /// Request headers allowed in cross-origin requests
+    #[serde(default = "wildcard")]
+    pub allowed_headers: Vec<String>,
+    /// Allow cookies and `Authorization` on cross-origin requests
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// Seconds browsers may cache a preflight response
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: wildcard(),
+            allowed_methods: wildcard(),
+            allowed_headers: wildcard(),
+            allow_credentials: false,
+            max_age_secs: None,
+        }
+    }
+}
+
+fn wildcard() -> Vec<String> {
+    vec![WILDCARD.to_string()]
+}
+
+/// Whether a list is the lone wildcard
+fn is_wildcard(values: &[String]) -> bool {
+    matches!(values, [only] if only.trim() == WILDCARD)
+}
+
+/// Parse each entry, naming the setting and entry on failure
+fn parse_all<T>(setting: &str, values: &[String], parse: impl Fn(&str) -> Option<T>) -> anyhow::Result<Vec<T>> {
+    if values.is_empty() {
+        anyhow::bail!("cors.{} must not be empty; use [\"*\"] to allow any", setting);
+    }
+    values
+        .iter()
+        .map(|value| {
+            let value = value.trim();
+            if value == WILDCARD {
+                anyhow::bail!("cors.{}: \"*\" must be the only entry", setting);
+            }
+            parse(value).ok_or_else(|| anyhow::anyhow!("cors.{}: invalid entry '{}'", setting, value))
+        })
+        .collect()
+}
+
+impl CorsConfig {
+    /// Build the CORS layer, rejecting invalid entries and wildcards
+    /// combined with `allow_credentials`.
+    pub fn layer(&self) -> anyhow::Result<CorsLayer> {
+        if self.allow_credentials {
+            for (setting, values) in [
+                ("allowed_origins", &self.allowed_origins),
+                ("allowed_methods", &self.allowed_methods),
+                ("allowed_headers", &self.allowed_headers),
+            ] {
+                if is_wildcard(values) {
+                    anyhow::bail!(
+                        "cors.{} can't be \"*\" with allow_credentials: list the allowed values explicitly",
+                        setting
+                    );
+                }
+            }
+        }
+
+        let origins: AllowOrigin = if is_wildcard(&self.allowed_origins) {
+            Any.into()
+        } else {
+            AllowOrigin::list(parse_all("allowed_origins", &self.allowed_origins, |origin| {
+                HeaderValue::from_str(origin).ok()
+            })?)
+        };
+        let methods: AllowMethods = if is_wildcard(&self.allowed_methods) {
+            Any.into()
+        } else {
+            AllowMethods::list(parse_all("allowed_methods", &self.allowed_methods, |method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes()).ok()
+            })?)
+        };
+        let headers: AllowHeaders = if is_wildcard(&self.allowed_headers) {
+            Any.into()
+        } else {
+            AllowHeaders::list(parse_all("allowed_headers", &self.allowed_headers, |header| {
+                HeaderName::from_bytes(header.as_bytes()).ok()
+            })?)
+        };
+
+        let mut layer = CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(self.allow_credentials);
+        if !self.allow_credentials {
+            layer = layer.expose_headers(Any);
+        }
+        if let Some(secs) = self.max_age_secs {
+            layer = layer.max_age(Duration::from_secs(secs));
+        }
+        Ok(layer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request}, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn config(yaml: &str) -> CorsConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    async fn allowed_origin(cors: &CorsConfig, origin: &str) -> Option<String> {
+        let app = Router::new().route("/", get(|| async { "ok" })).layer(cors.layer().unwrap());
+        let request = Request::get("/").header(header::ORIGIN, origin).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|v| v.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_listed_origin_allowed_and_others_not() {
+        let cors = config(
+            "allowed_origins: [\"https://app.example.com\"]\n\
+             allowed_methods: [get, POST]\n\
+             allowed_headers: [content-type, x-api-key]\n\
+             allow_credentials: true\n\
+             max_age_secs: 600\n",
+        );
+
+        assert_eq!(
+            allowed_origin(&cors, "https://app.example.com").await.as_deref(),
+            Some("https://app.example.com")
+        );
+        assert_eq!(allowed_origin(&cors, "https://evil.example.com").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_default_allows_any_origin() {
+        let cors = config("{}");
+        assert_eq!(allowed_origin(&cors, "https://anywhere.example").await.as_deref(), Some("*"));
+    }
+
+    #[test]
+    fn test_wildcards_rejected_with_credentials() {
+        let err = config("allow_credentials: true").layer().unwrap_err();
+        assert!(err.to_string().contains("allowed_origins"), "{}", err);
+
+        let err = config("allowed_origins: [\"https://app.example.com\"]\nallow_credentials: true")
+            .layer()
+            .unwrap_err();
+        assert!(err.to_string().contains("allowed_methods"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_entries_rejected() {
+        assert!(config("allowed_origins: [\"https://a.example\", \"*\"]").layer().is_err());
+        assert!(config("allowed_methods: [\"NOT A METHOD\"]").layer().is_err());
+        assert!(config("allowed_headers: []").layer().is_err());
+    }
+}
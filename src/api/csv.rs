@@ -0,0 +1,187 @@
+//! CSV rendering for chart data endpoints.
+//!
+//! Ticker history and timeseries handlers return JSON by default and CSV
+//! when the client sends `Accept: text/csv` or `?format=csv`. An explicit
+//! `format` wins over the `Accept` header.
+
+use crate::application::ticker_service::{OhlcvPoint, TimeseriesPoint};
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+/// Media type of CSV responses
+pub const TEXT_CSV: &str = "text/csv; charset=utf-8";
+
+/// A struct that renders as one CSV row
+pub trait CsvRow {
+    /// Column names, in row order
+    const HEADER: &'static [&'static str];
+
+    /// Append this row's fields, in [`Self::HEADER`] order
+    fn write_fields(&self, fields: &mut Vec<String>);
+}
+
+impl CsvRow for OhlcvPoint {
+    const HEADER: &'static [&'static str] = &["timestamp", "open", "high", "low", "close", "volume"];
+
+    fn write_fields(&self, fields: &mut Vec<String>) {
+        fields.push(self.timestamp.to_string());
+        fields.extend([self.open, self.high, self.low, self.close, self.volume].map(|v| v.to_string()));
+    }
+}
+
+impl CsvRow for TimeseriesPoint {
+    const HEADER: &'static [&'static str] = &["timestamp", "price"];
+
+    fn write_fields(&self, fields: &mut Vec<String>) {
+        fields.push(self.timestamp.to_string());
+        fields.push(self.price.to_string());
+    }
+}
+
+/// Render `rows` as CSV with a header line; lines end in CRLF (RFC 4180)
+pub fn to_csv<T: CsvRow>(rows: &[T]) -> String {
+    let mut out = T::HEADER.join(",");
+    out.push_str("\r\n");
+
+    let mut fields = Vec::with_capacity(T::HEADER.len());
+    for row in rows {
+        fields.clear();
+        row.write_fields(&mut fields);
+        let escaped: Vec<String> = fields.iter().map(|f| escape_field(f)).collect();
+        out.push_str(&escaped.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quote a field containing a delimiter, quote or line break
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A CSV body served as `text/csv`
+pub struct Csv(pub String);
+
+impl IntoResponse for Csv {
+    fn into_response(self) -> Response {
+        (
+            [
+                (header::CONTENT_TYPE, HeaderValue::from_static(TEXT_CSV)),
+                (header::VARY, HeaderValue::from_static("accept")),
+            ],
+            self.0,
+        )
+            .into_response()
+    }
+}
+
+/// Response format of a chart data endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+}
+
+/// Query parameter selecting the response format.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+pub struct FormatQuery {
+    /// Response format: json or csv (default: from the Accept header, else json)
+    #[param(example = "csv")]
+    pub format: Option<String>,
+}
+
+impl FormatQuery {
+    /// Pick the output format from `?format=`, falling back to `Accept`
+    ///
+    /// Returns the rejected value when `format` is neither `json` nor `csv`.
+    pub fn negotiate(&self, headers: &HeaderMap) -> Result<OutputFormat, String> {
+        match self.format.as_deref().map(str::trim) {
+            Some(f) if f.eq_ignore_ascii_case("csv") => Ok(OutputFormat::Csv),
+            Some(f) if f.eq_ignore_ascii_case("json") => Ok(OutputFormat::Json),
+            Some(other) => Err(other.to_string()),
+            None if accepts_csv(headers) => Ok(OutputFormat::Csv),
+            None => Ok(OutputFormat::Json),
+        }
+    }
+}
+
+/// Whether the `Accept` header lists `text/csv`
+fn accepts_csv(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media| media.split(';').next())
+        .any(|media| media.trim().eq_ignore_ascii_case("text/csv"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(timestamp: i64, close: f64) -> OhlcvPoint {
+        OhlcvPoint {
+            timestamp,
+            open: 1.0,
+            high: 2.0,
+            low: 0.5,
+            close,
+            volume: 100.0,
+            vwap: close,
+        }
+    }
+
+    #[test]
+    fn test_ohlcv_csv_has_header_and_one_line_per_point() {
+        let points = vec![point(1_704_067_200, 1.5), point(1_704_070_800, 1.25)];
+        let csv = to_csv(&points);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "timestamp,open,high,low,close,volume");
+        assert_eq!(lines.len(), points.len() + 1);
+        assert_eq!(lines[1], "1704067200,1,2,0.5,1.5,100");
+        assert!(csv.ends_with("\r\n"));
+    }
+
+    #[test]
+    fn test_timeseries_csv_has_header_and_one_line_per_point() {
+        let points: Vec<TimeseriesPoint> = (0..3)
+            .map(|i| TimeseriesPoint { timestamp: i * 60, price: 0.045 })
+            .collect();
+        let csv = to_csv(&points);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "timestamp,price");
+        assert_eq!(lines.len(), points.len() + 1);
+        assert_eq!(lines[3], "120,0.045");
+        assert_eq!(to_csv::<TimeseriesPoint>(&[]), "timestamp,price\r\n");
+    }
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("1.5"), "1.5");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_negotiate_format() {
+        let mut csv_accept = HeaderMap::new();
+        csv_accept.insert(header::ACCEPT, HeaderValue::from_static("application/json;q=0.5, text/csv"));
+        let query = |format: Option<&str>| FormatQuery { format: format.map(str::to_string) };
+
+        assert_eq!(query(None).negotiate(&HeaderMap::new()), Ok(OutputFormat::Json));
+        assert_eq!(query(None).negotiate(&csv_accept), Ok(OutputFormat::Csv));
+        assert_eq!(query(Some("CSV")).negotiate(&HeaderMap::new()), Ok(OutputFormat::Csv));
+        assert_eq!(query(Some("json")).negotiate(&csv_accept), Ok(OutputFormat::Json));
+        assert_eq!(query(Some("xml")).negotiate(&HeaderMap::new()), Err("xml".to_string()));
+    }
+}
@@ -5,16 +5,22 @@ use utoipa::OpenApi;
     paths(
         // System & Content Handlers
         crate::api::handlers::health_handler,
+        crate::api::handlers::ready_handler,
         crate::api::handlers::metrics_handler,
         crate::api::handlers::rate_limit_handler,
         // Kaspa.com KRC20 Handlers
         crate::api::kaspacom_handlers::trade_stats_handler,
         crate::api::kaspacom_handlers::floor_price_handler,
         crate::api::kaspacom_handlers::sold_orders_handler,
+        crate::api::kaspacom_handlers::sold_orders_export_handler,
         crate::api::kaspacom_handlers::last_order_sold_handler,
         crate::api::kaspacom_handlers::hot_mints_handler,
         crate::api::kaspacom_handlers::token_info_handler,
         crate::api::kaspacom_handlers::tokens_logos_handler,
+        crate::api::kaspacom_handlers::market_overview_handler,
+        crate::api::kaspacom_handlers::token_detail_handler,
+        crate::api::kaspacom_handlers::search_handler,
+        crate::api::kaspacom_handlers::logo_proxy_handler,
         crate::api::kaspacom_handlers::open_orders_handler,
         crate::api::kaspacom_handlers::historical_data_handler,
         // Kaspa.com KRC721 Handlers
@@ -26,22 +32,44 @@ use utoipa::OpenApi;
         crate::api::kaspacom_handlers::krc721_floor_price_handler,
         crate::api::kaspacom_handlers::krc721_tokens_handler,
         crate::api::kaspacom_handlers::krc721_collection_info_handler,
+        crate::api::kaspacom_handlers::krc721_rarity_handler,
+        crate::api::kaspacom_handlers::krc721_traits_handler,
         crate::api::kaspacom_handlers::krc721_metadata_handler,
         crate::api::kaspacom_handlers::krc721_image_url_handler,
         // Kaspa.com KNS Handlers
         crate::api::kaspacom_handlers::kns_sold_orders_handler,
         crate::api::kaspacom_handlers::kns_trade_stats_handler,
         crate::api::kaspacom_handlers::kns_listed_orders_handler,
+        crate::api::kaspacom_handlers::kns_domain_handler,
         // Kaspa.com Configuration Handlers
         crate::api::kaspacom_handlers::available_tokens_handler,
         crate::api::kaspacom_handlers::token_exchanges_handler,
-        crate::api::kaspacom_handlers::cache_stats_handler
+        crate::api::kaspacom_handlers::cache_stats_handler,
+        // Exchange Index Handlers
+        crate::api::kaspacom_handlers::index_status_handler,
+        crate::api::kaspacom_handlers::index_rebuild_handler,
+        crate::api::kaspacom_handlers::ticker_stats_batch_handler,
+        crate::api::kaspacom_handlers::ticker_compare_handler,
+        crate::api::kaspacom_handlers::ticker_pairs_handler,
+        crate::api::kaspacom_handlers::ticker_search_handler,
+        crate::api::kaspacom_handlers::ticker_leaderboard_handler,
+        crate::api::kaspacom_handlers::ticker_history_handler,
+        crate::api::kaspacom_handlers::ticker_timeseries_handler,
+        crate::api::kaspacom_handlers::ticker_latest_handler,
+        crate::api::kaspacom_handlers::ticker_ws_handler,
+        // Admin Handlers
+        crate::api::kaspacom_handlers::admin_cache_override_handler,
+        crate::api::kaspacom_handlers::admin_freshness_handler,
+        crate::api::kaspacom_handlers::admin_cache_pin_handler,
+        crate::api::kaspacom_handlers::admin_cache_unpin_handler
     ),
     components(
         schemas(
             // Existing schemas
             crate::api::handlers::HealthResponse,
             crate::api::handlers::HealthDependencies,
+            crate::api::handlers::ReadinessResponse,
+            crate::api::handlers::ReadinessCheck,
             crate::api::handlers::RateLimitResponse,
             crate::api::handlers::RateLimitResources,
             crate::api::handlers::RateLimitInfo,
@@ -58,8 +86,37 @@ use utoipa::OpenApi;
             crate::api::kaspacom_handlers::AvailableTokensResponse,
             crate::api::kaspacom_handlers::TokenExchangesResponse,
             crate::api::kaspacom_handlers::ErrorResponse,
+            crate::api::kaspacom_handlers::CacheOverrideRequest,
+            crate::api::kaspacom_handlers::CacheOverrideResponse,
+            crate::api::kaspacom_handlers::CachePinResponse,
+            crate::api::kaspacom_handlers::IndexRebuildRequest,
+            crate::api::kaspacom_handlers::IndexRebuildResponse,
+            crate::api::kaspacom_handlers::FreshnessResponse,
+            crate::application::kaspacom_service::FreshnessCheck,
+            crate::application::kaspacom_service::MarketOverviewRow,
+            crate::application::kaspacom_service::TokenDetail,
+            crate::application::kaspacom_service::SearchResults,
+            crate::application::ExchangeIndexStatus,
+            crate::application::ticker_service::TickerStatsBatchEntry,
+            crate::application::ticker_service::TickerStatsResponse,
+            crate::application::ticker_service::ExchangeStats,
+            crate::application::ticker_service::AggregateStats,
+            crate::application::ticker_service::LatestPriceResponse,
+            crate::application::ticker_service::ComparisonResponse,
+            crate::application::ticker_service::ComparisonSeries,
+            crate::application::ticker_service::NormalizedPoint,
+            crate::application::ticker_service::TickerPair,
+            crate::application::ticker_service::TickerSearchResponse,
+            crate::application::ticker_service::LeaderboardResponse,
+            crate::application::ticker_service::LeaderboardEntry,
+            crate::application::ticker_service::TickerHistoryResponse,
+            crate::application::ticker_service::OhlcvPoint,
+            crate::application::ticker_service::TimeseriesResponse,
+            crate::application::ticker_service::TimeseriesPoint,
+            crate::application::price_ticks::PriceTick,
             crate::domain::NftMint,
             crate::domain::NftOrder,
+            crate::domain::NftTokenFilter,
             crate::domain::NftTokensResponse,
             crate::domain::NftTradeStatsResponse,
             crate::domain::NftToken,
@@ -67,13 +124,20 @@ use utoipa::OpenApi;
             crate::domain::KnsOrder,
             crate::domain::KnsTradeStatsResponse,
             crate::domain::KnsListedOrdersResponse,
+            crate::domain::KnsDomainStatus,
             crate::domain::Krc721CollectionInfo,
+            crate::domain::NftRarityResponse,
+            crate::domain::NftRarityEntry,
+            crate::domain::NftTraitDistribution,
+            crate::domain::NftTraitCount,
             crate::domain::NftMetadata,
             crate::domain::NftAttribute,
             crate::domain::CollectionMetadataInfo,
             crate::domain::CollectionHolder,
             crate::infrastructure::CacheStats,
-            crate::infrastructure::CategoryStats
+            crate::infrastructure::CategoryStats,
+            crate::infrastructure::CircuitBreakerStatus,
+            crate::infrastructure::circuit_breaker::BreakerState
         )
     ),
     tags(
@@ -81,8 +145,10 @@ use utoipa::OpenApi;
         (name = "KRC20", description = "KRC20 Token endpoints from Kaspa.com L1 Marketplace"),
         (name = "KRC721", description = "KRC721 NFT endpoints from Kaspa.com L1 Marketplace"),
         (name = "KNS", description = "KNS Domain endpoints from Kaspa.com L1 Marketplace"),
+        (name = "Search", description = "Search across KRC20 tokens, KRC721 collections and KNS domains"),
         (name = "Configuration", description = "API Configuration endpoints"),
-        (name = "Cache", description = "Cache management and statistics")
+        (name = "Cache", description = "Cache management and statistics"),
+        (name = "Ticker", description = "Local ticker data and exchange index")
     ),
     info(
         title = "KaspaDev KaspaCom Data API",
@@ -95,3 +161,24 @@ use utoipa::OpenApi;
     )
 )]
 pub struct ApiDoc;
+
+/// Admin SQL query endpoint, only present with the `datafusion` feature
+#[cfg(feature = "datafusion")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(crate::api::kaspacom_handlers::admin_sql_query_handler),
+    components(schemas(
+        crate::api::kaspacom_handlers::SqlQueryRequest,
+        crate::infrastructure::SqlQueryResult
+    ))
+)]
+pub struct SqlQueryApiDoc;
+
+/// Full OpenAPI spec, including feature-gated endpoints
+pub fn openapi_spec() -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut spec = ApiDoc::openapi();
+    #[cfg(feature = "datafusion")]
+    spec.merge(SqlQueryApiDoc::openapi());
+    spec
+}
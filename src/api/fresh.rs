@@ -0,0 +1,78 @@
+//! `?fresh=true` cache bypass for kaspa.com endpoints.
+//!
+//! Clients that can't wait out a TTL (e.g. right after placing an order)
+//! add `fresh=true` to a `/v1/api/kaspa/` request. Cache lookups for that
+//! request then refetch through [`CacheService::refresh`], which still
+//! populates both tiers and spends upstream rate-limit budget. Since every
+//! such request costs an upstream call, the flag is only honored for
+//! callers with a valid `X-API-Key`; anonymous callers get cached data.
+//!
+//! [`CacheService::refresh`]: crate::application::CacheService::refresh
+
+use crate::api::auth::ApiKeyIdentity;
+use crate::api::state::AppState;
+use crate::application::cache_service::bypass_cache;
+use axum::{
+    extract::{Query, Request, State},
+    http::Uri,
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use tracing::debug;
+
+/// Routes that accept `fresh=true`
+const FRESH_PREFIX: &str = "/v1/api/kaspa/";
+
+#[derive(Deserialize)]
+struct FreshQuery {
+    fresh: Option<bool>,
+}
+
+/// Bypass the cache for authenticated `fresh=true` requests
+pub async fn fresh(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !wants_fresh(request.uri()) {
+        return next.run(request).await;
+    }
+    if !is_authenticated(&state, &request) {
+        debug!("Ignoring fresh=true without a valid API key");
+        return next.run(request).await;
+    }
+    bypass_cache(next.run(request)).await
+}
+
+/// Whether the request is for a kaspa.com route with `fresh=true`
+fn wants_fresh(uri: &Uri) -> bool {
+    uri.path().starts_with(FRESH_PREFIX)
+        && Query::<FreshQuery>::try_from_uri(uri).is_ok_and(|Query(q)| q.fresh == Some(true))
+}
+
+/// Authenticated by `require_api_key`, or carrying a known key on an open route
+fn is_authenticated(state: &AppState, request: &Request) -> bool {
+    if request.extensions().get::<ApiKeyIdentity>().is_some() {
+        return true;
+    }
+    request
+        .headers()
+        .get("X-API-Key")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|key| {
+            state.admin_api_keys.contains_key(key) || state.api_key_auth.identify(key).is_some()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_kaspacom_routes_with_fresh_true_qualify() {
+        let wants = |uri: &str| wants_fresh(&uri.parse().unwrap());
+        assert!(wants("/v1/api/kaspa/floor-price?fresh=true"));
+        assert!(wants("/v1/api/kaspa/floor-price?ticker=NACHO&fresh=true"));
+        assert!(!wants("/v1/api/kaspa/floor-price"));
+        assert!(!wants("/v1/api/kaspa/floor-price?fresh=false"));
+        assert!(!wants("/v1/api/kaspa/floor-price?fresh=yes"));
+        assert!(!wants("/v1/api/ticker/kaspa/history?fresh=true"));
+    }
+}
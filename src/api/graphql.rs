@@ -1,13 +1,28 @@
 //! GraphQL schema and handlers for flexible data queries.
 
+use crate::api::metric_labels::{ERROR_CODE_LABELS, OPERATION_LABELS};
+use crate::api::persisted_queries::{PersistedQueries, PERSISTED_QUERY_EXTENSION};
+use crate::api::request_id::current_request_id;
 use crate::api::state::AppState;
+use crate::application::ticker_service::{
+    AggregateStats, AvailableTickersResponse, ExchangeInfo, ExchangeStats, ExchangesResponse,
+    InvalidParameter, OhlcvPoint, TickerHistoryResponse, TickerStatsResponse,
+};
+use crate::application::KaspaComService;
 use crate::domain::{
-    HistoricalDataResponse, HotMint, KnsOrder, KnsTradeStatsResponse,
-    Krc721CollectionInfo, NftMetadata, NftMint, NftOrder, NftTradeStatsResponse, OpenOrdersResponse,
-    SoldOrder, TokenInfo, TokenLogo, TradeStatsResponse,
+    normalize_ticker, ApiError, HistoricalDataResponse, HotMint, KnsDomainStatus, KnsOrder,
+    KnsTradeStatsResponse, Krc721CollectionInfo, NftMetadata, NftMint, NftOrder,
+    NftTradeStatsResponse, OpenOrdersResponse, OrderCursor, Page, SoldOrder, TokenInfo, TokenLogo,
+    TradeStatsResponse, MAX_ORDERS_PAGE_LIMIT,
 };
+use crate::infrastructure::RateLimitExceeded;
+use async_graphql::dataloader::{DataLoader, Loader};
 use async_graphql::{Context, ErrorExtensions, Object, Result as GraphQLResult, ServerError};
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// Helper function to create GraphQL errors with proper error codes and context
@@ -16,16 +31,211 @@ fn create_graphql_error(
     error_code: &str,
     operation: Option<&str>,
 ) -> async_graphql::Error {
-    let error = ServerError::new(message, None);
-    error.extend_with(|_, e| {
+    async_graphql::Error::new(message).extend_with(|_, e| {
         e.set("code", error_code);
         e.set("timestamp", chrono::Utc::now().to_rfc3339());
-        e.set("request_id", Uuid::new_v4().to_string());
+        e.set("request_id", current_request_id().unwrap_or_else(|| Uuid::new_v4().to_string()));
         if let Some(op) = operation {
             e.set("operation", op);
         }
-    });
-    error.into()
+    })
+}
+
+/// Map a service error to a GraphQL error.
+///
+/// Upstream rate-limit rejections become `RATE_LIMITED` with a `retryAfter`
+/// (seconds) extension, mirroring the backoff hint REST clients get.
+/// Unsupported arguments (e.g. an unknown `range` or a token name that
+/// isn't a safe path segment) become `VALIDATION_ERROR`; any other failure
+/// uses the resolver's own error code.
+fn service_error(
+    err: &anyhow::Error,
+    message: impl Into<String>,
+    error_code: &str,
+    operation: Option<&str>,
+) -> async_graphql::Error {
+    if let Some(invalid) = err.downcast_ref::<InvalidParameter>() {
+        return create_graphql_error(invalid.to_string(), "VALIDATION_ERROR", operation);
+    }
+    if let Some(ApiError::Validation(message)) = ApiError::find(err) {
+        return create_graphql_error(message.clone(), "VALIDATION_ERROR", operation);
+    }
+    let Some(limited) = err.downcast_ref::<RateLimitExceeded>() else {
+        return create_graphql_error(message, error_code, operation);
+    };
+
+    let retry_after = limited.retry_after_secs;
+    create_graphql_error(limited.to_string(), "RATE_LIMITED", operation)
+        .extend_with(|_, e| e.set("retryAfter", retry_after))
+}
+
+/// Fetches token info for a single normalized ticker
+type TokenInfoFetcher = Arc<dyn Fn(String) -> BoxFuture<'static, anyhow::Result<TokenInfo>> + Send + Sync>;
+
+/// DataLoader batching ticker -> TokenInfo resolution.
+///
+/// Tickers requested several times while a batch is being collected (e.g.
+/// via aliases in one query) are fetched once. Failures are kept per ticker
+/// so one bad ticker doesn't fail the others in the batch.
+pub struct TokenInfoLoader {
+    fetch: TokenInfoFetcher,
+}
+
+impl TokenInfoLoader {
+    /// Create a loader backed by the cache-first Kaspa.com service
+    pub fn new(service: Arc<KaspaComService>) -> Self {
+        Self::with_fetcher(Arc::new(move |ticker| {
+            let service = service.clone();
+            Box::pin(async move { service.get_token_info(&ticker).await })
+        }))
+    }
+
+    fn with_fetcher(fetch: TokenInfoFetcher) -> Self {
+        Self { fetch }
+    }
+}
+
+impl Loader<String> for TokenInfoLoader {
+    type Value = Result<TokenInfo, Arc<anyhow::Error>>;
+    type Error = Infallible;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let results = futures::future::join_all(keys.iter().map(|ticker| (self.fetch)(ticker.clone()))).await;
+
+        Ok(keys
+            .iter()
+            .cloned()
+            .zip(results.into_iter().map(|r| r.map_err(Arc::new)))
+            .collect())
+    }
+}
+
+/// Cap a page `limit` argument the way the REST endpoints validate it
+fn clamp_page_limit(limit: Option<usize>) -> Option<usize> {
+    limit.map(|limit| limit.clamp(1, MAX_ORDERS_PAGE_LIMIT))
+}
+
+/// Decode an optional pagination cursor argument
+fn decode_cursor(after: Option<&str>, operation: &str) -> GraphQLResult<Option<OrderCursor>> {
+    after
+        .map(OrderCursor::decode)
+        .transpose()
+        .map_err(|e| create_graphql_error(e.to_string(), "INVALID_CURSOR", Some(operation)))
+}
+
+/// Fetch a page of sold orders for `soldOrders` or `soldOrdersPage`
+async fn fetch_sold_orders_page(
+    ctx: &Context<'_>,
+    ticker: Option<String>,
+    minutes: Option<f64>,
+    limit: Option<usize>,
+    after: Option<String>,
+    since: Option<i64>,
+    operation: &str,
+) -> GraphQLResult<OrderPage> {
+    let state = ctx.data::<AppState>()?;
+    let after = decode_cursor(after.as_deref(), operation)?;
+    let response = state
+        .kaspacom_service
+        .get_sold_orders_page(ticker.as_deref(), minutes, since, clamp_page_limit(limit), after.as_ref())
+        .await
+        .map_err(|e| service_error(
+            &e,
+            format!("Failed to get sold orders: {}", e),
+            "SOLD_ORDERS_ERROR",
+            Some(operation),
+        ))?;
+
+    Ok(OrderPage::from(response))
+}
+
+/// Fetch a page of sold NFT orders for `krc721SoldOrders` or `krc721SoldOrdersPage`
+async fn fetch_krc721_sold_orders_page(
+    ctx: &Context<'_>,
+    ticker: Option<String>,
+    minutes: Option<f64>,
+    limit: Option<usize>,
+    after: Option<String>,
+    since: Option<i64>,
+    operation: &str,
+) -> GraphQLResult<NftOrderPage> {
+    let state = ctx.data::<AppState>()?;
+    let after = decode_cursor(after.as_deref(), operation)?;
+    let response = state
+        .kaspacom_service
+        .get_krc721_sold_orders_page(ticker.as_deref(), minutes, since, clamp_page_limit(limit), after.as_ref())
+        .await
+        .map_err(|e| service_error(
+            &e,
+            format!("Failed to get KRC721 sold orders: {}", e),
+            "KRC721_SOLD_ORDERS_ERROR",
+            Some(operation),
+        ))?;
+
+    Ok(NftOrderPage::from(response))
+}
+
+/// Maximum query depth
+pub const MAX_QUERY_DEPTH: usize = 10;
+
+/// Maximum query complexity
+///
+/// Fields cost 1 each, except resolvers whose work grows with a time window,
+/// which multiply their selection's cost by a weight:
+/// - `soldOrders`, `krc721SoldOrders` and their `...Page` variants: 1 per hour of
+///   `minutes` (default 60)
+/// - `historicalData`: 1 per day of `timeFrame`
+/// - `tickerHistory`: 1 per 100 buckets of `range` at `resolution`
+pub const MAX_QUERY_COMPLEXITY: usize = 1000;
+
+/// Hours covered by a time window such as `15m`, `6h`, `7d`, `1w`, `1M`
+/// or `today`; unparseable windows count as one hour
+fn window_hours(window: &str) -> f64 {
+    let window = window.trim();
+    if window == "today" {
+        return 24.0;
+    }
+    let Some(unit) = window.chars().last() else {
+        return 1.0;
+    };
+    let Ok(amount) = window[..window.len() - unit.len_utf8()].parse::<f64>() else {
+        return 1.0;
+    };
+    let hours_per_unit = match unit {
+        'm' => 1.0 / 60.0,
+        'h' => 1.0,
+        'd' => 24.0,
+        'w' => 24.0 * 7.0,
+        'M' => 24.0 * 30.0,
+        _ => return 1.0,
+    };
+    amount * hours_per_unit
+}
+
+/// Scale a selection's complexity by `weight`, counting at least 1
+fn weighted(child_complexity: usize, weight: f64) -> usize {
+    // `as` saturates, and NaN becomes 0
+    child_complexity.saturating_mul((weight.ceil() as usize).max(1))
+}
+
+/// Complexity of a sold-orders page: 1 per hour of the `minutes` window
+fn sold_orders_complexity(minutes: Option<f64>, child_complexity: usize) -> usize {
+    weighted(child_complexity, minutes.unwrap_or(60.0) / 60.0)
+}
+
+/// Complexity of `historicalData`: 1 per day of `timeFrame`
+fn historical_data_complexity(time_frame: &str, child_complexity: usize) -> usize {
+    weighted(child_complexity, window_hours(time_frame) / 24.0)
+}
+
+/// Complexity of `tickerHistory`: 1 per 100 buckets
+fn ticker_history_complexity(
+    range: Option<&str>,
+    resolution: Option<&str>,
+    child_complexity: usize,
+) -> usize {
+    let buckets = window_hours(range.unwrap_or("7d")) / window_hours(resolution.unwrap_or("1h"));
+    weighted(child_complexity, buckets / 100.0)
 }
 
 /// GraphQL root query type.
@@ -55,7 +265,8 @@ impl Query {
             .kaspacom_service
             .get_trade_stats(time_frame, ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get trade stats: {}", e),
                 "TRADE_STATS_ERROR",
                 Some("tradeStats"),
@@ -79,7 +290,8 @@ impl Query {
             .kaspacom_service
             .get_floor_prices(ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get floor prices: {}", e),
                 "FLOOR_PRICES_ERROR",
                 Some("krc20FloorPrices"),
@@ -90,27 +302,45 @@ impl Query {
 
     /// Get recently sold orders for KRC20 tokens.
     /// 
-    /// Returns all completed trades within the specified time window (in minutes).
-    /// Includes order details, prices, and participant addresses.
-    #[graphql(name = "soldOrders")]
+    /// Returns all completed trades within the specified time window (in
+    /// minutes), newest first. Use `since` to only receive orders newer than
+    /// a previous poll, or `soldOrdersPage` to page through large windows.
+    #[graphql(
+        name = "soldOrders",
+        complexity = "sold_orders_complexity(minutes, child_complexity)"
+    )]
     async fn sold_orders(
         &self,
         ctx: &Context<'_>,
         ticker: Option<String>,
         minutes: Option<f64>,
+        #[graphql(desc = "Only orders with createdAt greater than this, e.g. the newest createdAt already seen")]
+        since: Option<i64>,
     ) -> GraphQLResult<Vec<Order>> {
-        let state = ctx.data::<AppState>()?;
-        let response = state
-            .kaspacom_service
-            .get_sold_orders(ticker.as_deref(), minutes)
-            .await
-            .map_err(|e| create_graphql_error(
-                format!("Failed to get sold orders: {}", e),
-                "SOLD_ORDERS_ERROR",
-                Some("soldOrders"),
-            ))?;
-        
-        Ok(response.into_iter().map(Order::from).collect())
+        let page = fetch_sold_orders_page(ctx, ticker, minutes, None, None, since, "soldOrders").await?;
+        Ok(page.items)
+    }
+
+    /// Get a page of recently sold orders for KRC20 tokens.
+    /// 
+    /// Same window as `soldOrders`, newest first. Use `limit` (at most 1000)
+    /// and `after` to page through the window, and `since` to only receive
+    /// orders newer than a previous poll.
+    #[graphql(
+        name = "soldOrdersPage",
+        complexity = "sold_orders_complexity(minutes, child_complexity)"
+    )]
+    async fn sold_orders_page(
+        &self,
+        ctx: &Context<'_>,
+        ticker: Option<String>,
+        minutes: Option<f64>,
+        limit: Option<usize>,
+        after: Option<String>,
+        #[graphql(desc = "Only orders with createdAt greater than this, e.g. the previous latestTimestamp")]
+        since: Option<i64>,
+    ) -> GraphQLResult<OrderPage> {
+        fetch_sold_orders_page(ctx, ticker, minutes, limit, after, since, "soldOrdersPage").await
     }
 
     /// Get the most recent sold order.
@@ -127,7 +357,8 @@ impl Query {
             .kaspacom_service
             .get_last_order_sold()
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get last order sold: {}", e),
                 "LAST_ORDER_SOLD_ERROR",
                 Some("lastOrderSold"),
@@ -152,7 +383,8 @@ impl Query {
             .kaspacom_service
             .get_hot_mints(time_interval)
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get hot mints: {}", e),
                 "HOT_MINTS_ERROR",
                 Some("hotMints"),
@@ -171,12 +403,14 @@ impl Query {
         ctx: &Context<'_>,
         ticker: String,
     ) -> GraphQLResult<TokenInfoData> {
-        let state = ctx.data::<AppState>()?;
-        let response = state
-            .kaspacom_service
-            .get_token_info(&ticker)
-            .await
-            .map_err(|e| create_graphql_error(
+        let loader = ctx.data::<DataLoader<TokenInfoLoader>>()?;
+        let ticker = normalize_ticker(&ticker);
+        let response = loader
+            .load_one(ticker.clone())
+            .await?
+            .unwrap_or_else(|| Err(Arc::new(anyhow::anyhow!("no result for {}", ticker))))
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get token info: {}", e),
                 "TOKEN_INFO_ERROR",
                 Some("tokenInfo"),
@@ -199,7 +433,8 @@ impl Query {
             .kaspacom_service
             .get_tokens_logos(ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get token logos: {}", e),
                 "TOKEN_LOGOS_ERROR",
                 Some("tokenLogos"),
@@ -222,7 +457,8 @@ impl Query {
             .kaspacom_service
             .get_open_orders()
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get open orders: {}", e),
                 "OPEN_ORDERS_ERROR",
                 Some("openOrders"),
@@ -234,7 +470,10 @@ impl Query {
     /// Get historical price/volume data.
     /// 
     /// Returns historical trading data for charting and analysis.
-    #[graphql(name = "historicalData")]
+    #[graphql(
+        name = "historicalData",
+        complexity = "historical_data_complexity(&time_frame, child_complexity)"
+    )]
     async fn historical_data(
         &self,
         ctx: &Context<'_>,
@@ -246,7 +485,8 @@ impl Query {
             .kaspacom_service
             .get_historical_data(&time_frame, &ticker)
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get historical data: {}", e),
                 "HISTORICAL_DATA_ERROR",
                 Some("historicalData"),
@@ -274,7 +514,8 @@ impl Query {
             .kaspacom_service
             .get_krc721_mints(ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KRC721 mints: {}", e),
                 "KRC721_MINTS_ERROR",
                 Some("krc721Mints"),
@@ -285,25 +526,39 @@ impl Query {
 
     /// Get sold NFT orders.
     /// 
-    /// Returns completed NFT trades within the specified time window.
+    /// Returns all completed NFT trades within the specified time window,
+    /// newest first. Use `since` to only receive orders newer than a previous
+    /// poll, or `krc721SoldOrdersPage` to page through large windows.
+    #[graphql(complexity = "sold_orders_complexity(minutes, child_complexity)")]
     async fn krc721_sold_orders(
         &self,
         ctx: &Context<'_>,
         ticker: Option<String>,
         minutes: Option<f64>,
+        #[graphql(desc = "Only orders with createdAt greater than this, e.g. the newest createdAt already seen")]
+        since: Option<i64>,
     ) -> GraphQLResult<Vec<NftOrderData>> {
-        let state = ctx.data::<AppState>()?;
-        let response = state
-            .kaspacom_service
-            .get_krc721_sold_orders(ticker.as_deref(), minutes)
-            .await
-            .map_err(|e| create_graphql_error(
-                format!("Failed to get KRC721 sold orders: {}", e),
-                "KRC721_SOLD_ORDERS_ERROR",
-                Some("krc721SoldOrders"),
-            ))?;
-        
-        Ok(response.into_iter().map(NftOrderData::from).collect())
+        let page = fetch_krc721_sold_orders_page(ctx, ticker, minutes, None, None, since, "krc721SoldOrders").await?;
+        Ok(page.items)
+    }
+
+    /// Get a page of sold NFT orders.
+    /// 
+    /// Same window as `krc721SoldOrders`, newest first. Use `limit` (at most
+    /// 1000) and `after` to page through the window, and `since` to only
+    /// receive orders newer than a previous poll.
+    #[graphql(complexity = "sold_orders_complexity(minutes, child_complexity)")]
+    async fn krc721_sold_orders_page(
+        &self,
+        ctx: &Context<'_>,
+        ticker: Option<String>,
+        minutes: Option<f64>,
+        limit: Option<usize>,
+        after: Option<String>,
+        #[graphql(desc = "Only orders with createdAt greater than this, e.g. the previous latestTimestamp")]
+        since: Option<i64>,
+    ) -> GraphQLResult<NftOrderPage> {
+        fetch_krc721_sold_orders_page(ctx, ticker, minutes, limit, after, since, "krc721SoldOrdersPage").await
     }
 
     /// Get listed NFT orders.
@@ -319,7 +574,8 @@ impl Query {
             .kaspacom_service
             .get_krc721_listed_orders(ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KRC721 listed orders: {}", e),
                 "KRC721_LISTED_ORDERS_ERROR",
                 Some("krc721ListedOrders"),
@@ -344,7 +600,8 @@ impl Query {
             .kaspacom_service
             .get_krc721_trade_stats(time_frame, ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KRC721 trade stats: {}", e),
                 "KRC721_TRADE_STATS_ERROR",
                 Some("krc721TradeStats"),
@@ -368,7 +625,8 @@ impl Query {
             .kaspacom_service
             .get_krc721_hot_mints(time_interval)
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KRC721 hot mints: {}", e),
                 "KRC721_HOT_MINTS_ERROR",
                 Some("krc721HotMints"),
@@ -391,7 +649,8 @@ impl Query {
             .kaspacom_service
             .get_krc721_floor_prices(ticker.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KRC721 floor prices: {}", e),
                 "KRC721_FLOOR_PRICES_ERROR",
                 Some("krc721FloorPrices"),
@@ -414,7 +673,8 @@ impl Query {
             .kaspacom_service
             .get_krc721_collection_info(&ticker)
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get collection info: {}", e),
                 "COLLECTION_INFO_ERROR",
                 Some("krc721CollectionInfo"),
@@ -438,7 +698,8 @@ impl Query {
             .kaspacom_service
             .get_nft_metadata(&ticker, token_id)
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get NFT metadata: {}", e),
                 "NFT_METADATA_ERROR",
                 Some("nftMetadata"),
@@ -465,7 +726,8 @@ impl Query {
             .kaspacom_service
             .get_kns_sold_orders(minutes)
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KNS sold orders: {}", e),
                 "KNS_SOLD_ORDERS_ERROR",
                 Some("knsSoldOrders"),
@@ -490,7 +752,8 @@ impl Query {
             .kaspacom_service
             .get_kns_trade_stats(time_frame, asset.as_deref())
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KNS trade stats: {}", e),
                 "KNS_TRADE_STATS_ERROR",
                 Some("knsTradeStats"),
@@ -512,7 +775,8 @@ impl Query {
             .kaspacom_service
             .get_kns_listed_orders()
             .await
-            .map_err(|e| create_graphql_error(
+            .map_err(|e| service_error(
+                &e,
                 format!("Failed to get KNS listed orders: {}", e),
                 "KNS_LISTED_ORDERS_ERROR",
                 Some("knsListedOrders"),
@@ -520,6 +784,134 @@ impl Query {
         
         Ok(response.into_iter().map(KnsOrderData::from).collect())
     }
+
+    /// Get the marketplace status of one KNS domain.
+    ///
+    /// `name` may be given with or without the `.kas` suffix. Returns null
+    /// when the domain is neither listed nor sold in the last week.
+    #[graphql(name = "knsDomain")]
+    async fn kns_domain(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "KNS domain, e.g. mywallet or mywallet.kas")] name: String,
+    ) -> GraphQLResult<Option<KnsDomainData>> {
+        let state = ctx.data::<AppState>()?;
+        match state.kaspacom_service.get_kns_domain(&name).await {
+            Ok(status) => Ok(Some(KnsDomainData::from(status))),
+            Err(e) if matches!(ApiError::find(&e), Some(ApiError::NotFound(_))) => Ok(None),
+            Err(e) => Err(service_error(
+                &e,
+                format!("Failed to get KNS domain: {}", e),
+                "KNS_DOMAIN_ERROR",
+                Some("knsDomain"),
+            )),
+        }
+    }
+
+    // ========================================================================
+    // Exchange Data Discovery Queries
+    // ========================================================================
+
+    /// Get tokens with exchange data.
+    /// 
+    /// Returns the token names found in the exchange data repository, with
+    /// the same fields as the REST `AvailableTickersResponse`.
+    #[graphql(name = "availableTickers")]
+    async fn available_tickers(&self, ctx: &Context<'_>) -> GraphQLResult<AvailableTickers> {
+        let state = ctx.data::<AppState>()?;
+        let response = state
+            .ticker_service
+            .get_available_tickers()
+            .await
+            .map_err(|e| service_error(
+                &e,
+                format!("Failed to get available tickers: {}", e),
+                "AVAILABLE_TICKERS_ERROR",
+                Some("availableTickers"),
+            ))?;
+
+        Ok(AvailableTickers::from(response))
+    }
+
+    /// Get exchanges and the tokens listed on each.
+    /// 
+    /// Returns the exchanges found in the exchange data repository, with the
+    /// same fields as the REST `ExchangesResponse`.
+    async fn exchanges(&self, ctx: &Context<'_>) -> GraphQLResult<ExchangeList> {
+        let state = ctx.data::<AppState>()?;
+        let response = state
+            .ticker_service
+            .get_exchanges()
+            .await
+            .map_err(|e| service_error(
+                &e,
+                format!("Failed to get exchanges: {}", e),
+                "EXCHANGES_ERROR",
+                Some("exchanges"),
+            ))?;
+
+        Ok(ExchangeList::from(response))
+    }
+
+    /// Get current stats for a token across all exchanges.
+    /// 
+    /// `range` is one of today, 7d, 30d (default: today). Same stats as
+    /// `POST /v1/api/ticker/stats/batch` returns per token.
+    #[graphql(name = "tickerStats")]
+    async fn ticker_stats(
+        &self,
+        ctx: &Context<'_>,
+        token: String,
+        range: Option<String>,
+    ) -> GraphQLResult<TickerStatsData> {
+        let state = ctx.data::<AppState>()?;
+        let range = range.unwrap_or_else(|| "today".to_string());
+        let response = state
+            .ticker_service
+            .get_ticker_stats(token, range)
+            .await
+            .map_err(|e| service_error(
+                &e,
+                format!("Failed to get ticker stats: {}", e),
+                "TICKER_STATS_ERROR",
+                Some("tickerStats"),
+            ))?;
+
+        Ok(TickerStatsData::from(response))
+    }
+
+    /// Get OHLCV history for a token.
+    /// 
+    /// `range` is one of today, 7d, 30d (default: 7d); `resolution` one of
+    /// 1m, 5m, 15m, 30m, 1h, 4h, 1d, 1w, 1M (default: 1h). Same data as
+    /// `GET /v1/api/ticker/{token}/history`.
+    #[graphql(
+        name = "tickerHistory",
+        complexity = "ticker_history_complexity(range.as_deref(), resolution.as_deref(), child_complexity)"
+    )]
+    async fn ticker_history(
+        &self,
+        ctx: &Context<'_>,
+        token: String,
+        range: Option<String>,
+        resolution: Option<String>,
+    ) -> GraphQLResult<TickerHistoryData> {
+        let state = ctx.data::<AppState>()?;
+        let range = range.unwrap_or_else(|| "7d".to_string());
+        let resolution = resolution.unwrap_or_else(|| "1h".to_string());
+        let response = state
+            .ticker_service
+            .get_ticker_history(token, range, resolution)
+            .await
+            .map_err(|e| service_error(
+                &e,
+                format!("Failed to get ticker history: {}", e),
+                "TICKER_HISTORY_ERROR",
+                Some("tickerHistory"),
+            ))?;
+
+        Ok(TickerHistoryData::from(response))
+    }
 }
 
 // ============================================================================
@@ -690,6 +1082,43 @@ impl From<SoldOrder> for Order {
     }
 }
 
+/// Page of sold orders.
+#[derive(Debug, Clone)]
+pub struct OrderPage {
+    pub items: Vec<Order>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    pub latest_timestamp: Option<i64>,
+}
+
+#[Object]
+impl OrderPage {
+    async fn items(&self) -> &Vec<Order> {
+        &self.items
+    }
+    async fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+    async fn has_more(&self) -> bool {
+        self.has_more
+    }
+    /// Newest createdAt across all pages; pass it as `since` on the next poll
+    async fn latest_timestamp(&self) -> Option<i64> {
+        self.latest_timestamp
+    }
+}
+
+impl From<Page<SoldOrder>> for OrderPage {
+    fn from(page: Page<SoldOrder>) -> Self {
+        Self {
+            items: page.items.into_iter().map(Order::from).collect(),
+            next_cursor: page.next_cursor,
+            has_more: page.has_more,
+            latest_timestamp: page.latest_timestamp,
+        }
+    }
+}
+
 /// Hot minting token data.
 #[derive(Debug, Clone)]
 pub struct HotMintData {
@@ -936,6 +1365,14 @@ impl HistoricalDataPoint {
     async fn average_price(&self) -> f64 {
         self.average_price
     }
+    /// Volume-weighted price for the bucket.
+    ///
+    /// Kaspa.com reports each bucket as a single aggregate of its trades, so
+    /// this is the bucket's average price when volume traded, and null when
+    /// the bucket is empty (rather than a misleading 0).
+    async fn vwap(&self) -> Option<f64> {
+        (self.total_volume_kas > 0.0).then_some(self.average_price)
+    }
     async fn trade_count(&self) -> i32 {
         self.trade_count
     }
@@ -1058,6 +1495,43 @@ impl From<NftOrder> for NftOrderData {
     }
 }
 
+/// Page of sold NFT orders.
+#[derive(Debug, Clone)]
+pub struct NftOrderPage {
+    pub items: Vec<NftOrderData>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    pub latest_timestamp: Option<i64>,
+}
+
+#[Object]
+impl NftOrderPage {
+    async fn items(&self) -> &Vec<NftOrderData> {
+        &self.items
+    }
+    async fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+    async fn has_more(&self) -> bool {
+        self.has_more
+    }
+    /// Newest createdAt across all pages; pass it as `since` on the next poll
+    async fn latest_timestamp(&self) -> Option<i64> {
+        self.latest_timestamp
+    }
+}
+
+impl From<Page<NftOrder>> for NftOrderPage {
+    fn from(page: Page<NftOrder>) -> Self {
+        Self {
+            items: page.items.into_iter().map(NftOrderData::from).collect(),
+            next_cursor: page.next_cursor,
+            has_more: page.has_more,
+            latest_timestamp: page.latest_timestamp,
+        }
+    }
+}
+
 /// NFT trade statistics.
 #[derive(Debug, Clone)]
 pub struct NftTradeStats {
@@ -1314,6 +1788,42 @@ impl From<KnsOrder> for KnsOrderData {
     }
 }
 
+/// Marketplace status of one KNS domain.
+#[derive(Debug, Clone)]
+pub struct KnsDomainData {
+    pub domain: String,
+    pub status: String,
+    pub listing: Option<KnsOrderData>,
+    pub last_sale: Option<KnsOrderData>,
+}
+
+#[Object]
+impl KnsDomainData {
+    async fn domain(&self) -> &str {
+        &self.domain
+    }
+    async fn status(&self) -> &str {
+        &self.status
+    }
+    async fn listing(&self) -> Option<&KnsOrderData> {
+        self.listing.as_ref()
+    }
+    async fn last_sale(&self) -> Option<&KnsOrderData> {
+        self.last_sale.as_ref()
+    }
+}
+
+impl From<KnsDomainStatus> for KnsDomainData {
+    fn from(status: KnsDomainStatus) -> Self {
+        Self {
+            domain: status.domain,
+            status: status.status,
+            listing: status.listing.map(KnsOrderData::from),
+            last_sale: status.last_sale.map(KnsOrderData::from),
+        }
+    }
+}
+
 /// KNS trade statistics.
 #[derive(Debug, Clone)]
 pub struct KnsTradeStats {
@@ -1347,13 +1857,350 @@ impl From<KnsTradeStatsResponse> for KnsTradeStats {
 
 /// Create the GraphQL schema with security and performance features.
 pub fn create_schema(state: AppState) -> Schema<Query, EmptyMutation, async_graphql::EmptySubscription> {
-    Schema::build(Query, EmptyMutation::default(), async_graphql::EmptySubscription)
+    let token_info_loader = DataLoader::new(
+        TokenInfoLoader::new(state.kaspacom_service.clone()),
+        tokio::spawn,
+    );
+
+    let persisted_queries = PersistedQueries::new(state.redis_repo.clone());
+
+    Schema::build(Query, EmptyMutation, async_graphql::EmptySubscription)
+        .extension(persisted_queries)
+        .data(token_info_loader)
         .data(state)
-        .limit_depth(10) // Maximum query depth
-        .limit_complexity(1000) // Maximum query complexity
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
         .finish()
 }
 
+/// Tokens with exchange data.
+#[derive(Debug, Clone)]
+pub struct AvailableTickers {
+    pub tickers: Vec<String>,
+    pub count: usize,
+}
+
+#[Object]
+impl AvailableTickers {
+    async fn tickers(&self) -> &Vec<String> {
+        &self.tickers
+    }
+    async fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl From<AvailableTickersResponse> for AvailableTickers {
+    fn from(resp: AvailableTickersResponse) -> Self {
+        Self {
+            tickers: resp.tickers,
+            count: resp.count,
+        }
+    }
+}
+
+/// Exchanges with their tokens.
+#[derive(Debug, Clone)]
+pub struct ExchangeList {
+    pub exchanges: Vec<ExchangeData>,
+    pub count: usize,
+}
+
+#[Object]
+impl ExchangeList {
+    async fn exchanges(&self) -> &Vec<ExchangeData> {
+        &self.exchanges
+    }
+    async fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl From<ExchangesResponse> for ExchangeList {
+    fn from(resp: ExchangesResponse) -> Self {
+        Self {
+            exchanges: resp.exchanges.into_iter().map(ExchangeData::from).collect(),
+            count: resp.count,
+        }
+    }
+}
+
+/// An exchange and the tokens listed on it.
+#[derive(Debug, Clone)]
+pub struct ExchangeData {
+    pub exchange: String,
+    pub tokens: Vec<String>,
+    pub token_count: usize,
+}
+
+#[Object]
+impl ExchangeData {
+    async fn exchange(&self) -> &str {
+        &self.exchange
+    }
+    async fn tokens(&self) -> &Vec<String> {
+        &self.tokens
+    }
+    async fn token_count(&self) -> usize {
+        self.token_count
+    }
+}
+
+impl From<ExchangeInfo> for ExchangeData {
+    fn from(info: ExchangeInfo) -> Self {
+        Self {
+            exchange: info.exchange,
+            tokens: info.tokens,
+            token_count: info.token_count,
+        }
+    }
+}
+
+/// Current stats for a token across exchanges.
+#[derive(Debug, Clone)]
+pub struct TickerStatsData {
+    pub token: String,
+    pub timestamp: String,
+    pub range: String,
+    pub exchanges: Vec<ExchangeStatsData>,
+    pub aggregate: AggregateStatsData,
+}
+
+#[Object]
+impl TickerStatsData {
+    async fn token(&self) -> &str {
+        &self.token
+    }
+    async fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+    async fn range(&self) -> &str {
+        &self.range
+    }
+    async fn exchanges(&self) -> &Vec<ExchangeStatsData> {
+        &self.exchanges
+    }
+    async fn aggregate(&self) -> &AggregateStatsData {
+        &self.aggregate
+    }
+}
+
+impl From<TickerStatsResponse> for TickerStatsData {
+    fn from(resp: TickerStatsResponse) -> Self {
+        Self {
+            token: resp.token,
+            timestamp: resp.timestamp,
+            range: resp.range,
+            exchanges: resp.exchanges.into_iter().map(ExchangeStatsData::from).collect(),
+            aggregate: AggregateStatsData::from(resp.aggregate),
+        }
+    }
+}
+
+/// Stats for a token on one exchange.
+#[derive(Debug, Clone)]
+pub struct ExchangeStatsData {
+    pub exchange: String,
+    pub last: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub volume_24h: Option<f64>,
+    pub change_pct: Option<f64>,
+    pub data_points: usize,
+    pub reliable: bool,
+    pub delay_secs: u64,
+    pub weight: f64,
+}
+
+#[Object]
+impl ExchangeStatsData {
+    async fn exchange(&self) -> &str {
+        &self.exchange
+    }
+    async fn last(&self) -> Option<f64> {
+        self.last
+    }
+    async fn high(&self) -> Option<f64> {
+        self.high
+    }
+    async fn low(&self) -> Option<f64> {
+        self.low
+    }
+    #[graphql(name = "volume24h")]
+    async fn volume_24h(&self) -> Option<f64> {
+        self.volume_24h
+    }
+    async fn change_pct(&self) -> Option<f64> {
+        self.change_pct
+    }
+    async fn data_points(&self) -> usize {
+        self.data_points
+    }
+    async fn reliable(&self) -> bool {
+        self.reliable
+    }
+    async fn delay_secs(&self) -> u64 {
+        self.delay_secs
+    }
+    /// Weight of this exchange's price in the aggregate average
+    async fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+impl From<ExchangeStats> for ExchangeStatsData {
+    fn from(stats: ExchangeStats) -> Self {
+        Self {
+            exchange: stats.exchange,
+            last: stats.last,
+            high: stats.high,
+            low: stats.low,
+            volume_24h: stats.volume_24h,
+            change_pct: stats.change_pct,
+            data_points: stats.data_points,
+            reliable: stats.reliable,
+            delay_secs: stats.delay_secs,
+            weight: stats.weight,
+        }
+    }
+}
+
+/// Stats aggregated across exchanges.
+#[derive(Debug, Clone)]
+pub struct AggregateStatsData {
+    pub avg_price: Option<f64>,
+    pub total_volume_24h: Option<f64>,
+    pub vwap: Option<f64>,
+    pub exchange_count: usize,
+    pub outliers_excluded: usize,
+    pub filtered_out: Vec<String>,
+}
+
+#[Object]
+impl AggregateStatsData {
+    async fn avg_price(&self) -> Option<f64> {
+        self.avg_price
+    }
+    #[graphql(name = "totalVolume24h")]
+    async fn total_volume_24h(&self) -> Option<f64> {
+        self.total_volume_24h
+    }
+    async fn vwap(&self) -> Option<f64> {
+        self.vwap
+    }
+    async fn exchange_count(&self) -> usize {
+        self.exchange_count
+    }
+    async fn outliers_excluded(&self) -> usize {
+        self.outliers_excluded
+    }
+    /// Exchanges excluded as price outliers
+    async fn filtered_out(&self) -> &[String] {
+        &self.filtered_out
+    }
+}
+
+impl From<AggregateStats> for AggregateStatsData {
+    fn from(stats: AggregateStats) -> Self {
+        Self {
+            avg_price: stats.avg_price,
+            total_volume_24h: stats.total_volume_24h,
+            vwap: stats.vwap,
+            exchange_count: stats.exchange_count,
+            outliers_excluded: stats.outliers_excluded,
+            filtered_out: stats.filtered_out,
+        }
+    }
+}
+
+/// OHLCV history for a token.
+#[derive(Debug, Clone)]
+pub struct TickerHistoryData {
+    pub token: String,
+    pub range: String,
+    pub resolution: String,
+    pub data: Vec<OhlcvPointData>,
+}
+
+#[Object]
+impl TickerHistoryData {
+    async fn token(&self) -> &str {
+        &self.token
+    }
+    async fn range(&self) -> &str {
+        &self.range
+    }
+    async fn resolution(&self) -> &str {
+        &self.resolution
+    }
+    async fn data(&self) -> &Vec<OhlcvPointData> {
+        &self.data
+    }
+}
+
+impl From<TickerHistoryResponse> for TickerHistoryData {
+    fn from(resp: TickerHistoryResponse) -> Self {
+        Self {
+            token: resp.token,
+            range: resp.range,
+            resolution: resp.resolution,
+            data: resp.data.into_iter().map(OhlcvPointData::from).collect(),
+        }
+    }
+}
+
+/// One OHLCV bucket.
+#[derive(Debug, Clone)]
+pub struct OhlcvPointData {
+    pub timestamp: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub vwap: f64,
+}
+
+#[Object]
+impl OhlcvPointData {
+    /// Unix timestamp (seconds)
+    async fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+    async fn open(&self) -> f64 {
+        self.open
+    }
+    async fn high(&self) -> f64 {
+        self.high
+    }
+    async fn low(&self) -> f64 {
+        self.low
+    }
+    async fn close(&self) -> f64 {
+        self.close
+    }
+    async fn volume(&self) -> f64 {
+        self.volume
+    }
+    async fn vwap(&self) -> f64 {
+        self.vwap
+    }
+}
+
+impl From<OhlcvPoint> for OhlcvPointData {
+    fn from(point: OhlcvPoint) -> Self {
+        Self {
+            timestamp: point.timestamp,
+            open: point.open,
+            high: point.high,
+            low: point.low,
+            close: point.close,
+            volume: point.volume,
+            vwap: point.vwap,
+        }
+    }
+}
+
 /// Placeholder for mutations (read-only for now).
 #[derive(async_graphql::MergedObject, Default)]
 pub struct EmptyMutation;
@@ -1372,9 +2219,9 @@ pub async fn graphql_handler(
 ) -> GraphQLResponse {
     let request = req.into_inner();
     
-    // Extract operation name for metrics (if available) - convert to static string for metrics compatibility
+    // Extract operation name for metrics (if available); unknown names beyond the cap become "other"
     let operation_name = request.operation_name.as_deref().unwrap_or("unknown").to_string();
-    let op_name_static: &'static str = Box::leak(operation_name.clone().into_boxed_str());
+    let op_name_static = OPERATION_LABELS.intern(&operation_name);
     
     // Validate query size
     if request.query.len() > MAX_QUERY_SIZE {
@@ -1400,8 +2247,8 @@ pub async fn graphql_handler(
         return response.into();
     }
     
-    // Validate query is not empty
-    if request.query.trim().is_empty() {
+    // Validate query is not empty; persisted queries may send only a hash
+    if request.query.trim().is_empty() && !request.extensions.contains_key(PERSISTED_QUERY_EXTENSION) {
         // Record metrics for validation error
         metrics::counter!("graphql_queries_total", "operation" => op_name_static, "status" => "validation_error", "error_code" => "EMPTY_QUERY")
             .increment(1);
@@ -1468,8 +2315,8 @@ pub async fn graphql_handler(
                 })
                 .unwrap_or_else(|| "UNKNOWN_ERROR".to_string());
             
-            // Convert to static string for metrics
-            let error_code_static: &'static str = Box::leak(error_code_str.into_boxed_str());
+            // Bounded static label for metrics
+            let error_code_static = ERROR_CODE_LABELS.intern(&error_code_str);
             
             metrics::counter!("graphql_errors_total", "operation" => op_name_static, "error_code" => error_code_static)
                 .increment(1);
@@ -1499,3 +2346,176 @@ pub async fn graphql_playground() -> impl axum::response::IntoResponse {
         )
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_token_info_loader_dedupes_aliased_lookups() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let loader = TokenInfoLoader::with_fetcher(Arc::new(move |ticker| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                Ok(serde_json::from_value(serde_json::json!({
+                    "ticker": ticker,
+                    "totalSupply": 1000,
+                    "totalMintTimes": 10,
+                    "totalMinted": 1000,
+                    "totalHolders": 5,
+                    "mintLimit": 100,
+                    "state": "finished"
+                }))?)
+            })
+        }));
+
+        let schema = Schema::build(Query, EmptyMutation, async_graphql::EmptySubscription)
+            .data(DataLoader::new(loader, tokio::spawn))
+            .finish();
+
+        let response = schema
+            .execute(
+                r#"{
+                    a: tokenInfo(ticker: "SLOW") { ticker }
+                    b: tokenInfo(ticker: "slow") { ticker }
+                    c: tokenInfo(ticker: "SLOW") { ticker }
+                }"#,
+            )
+            .await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["a"]["ticker"], "SLOW");
+        assert_eq!(data["b"]["ticker"], "SLOW");
+        assert_eq!(data["c"]["ticker"], "SLOW");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_resolver_reports_retry_after() {
+        let loader = TokenInfoLoader::with_fetcher(Arc::new(|_| {
+            Box::pin(async {
+                Err(RateLimitExceeded {
+                    category: "token_info".to_string(),
+                    limit: 60,
+                    retry_after_secs: 17,
+                }
+                .into())
+            })
+        }));
+
+        let schema = Schema::build(Query, EmptyMutation, async_graphql::EmptySubscription)
+            .data(DataLoader::new(loader, tokio::spawn))
+            .finish();
+
+        let response = schema.execute(r#"{ tokenInfo(ticker: "NACHO") { ticker } }"#).await;
+
+        assert_eq!(response.errors.len(), 1);
+        let extensions = response.errors[0].extensions.as_ref().unwrap();
+        assert_eq!(
+            extensions.get("code"),
+            Some(&async_graphql::Value::from("RATE_LIMITED"))
+        );
+        assert_eq!(extensions.get("retryAfter"), Some(&async_graphql::Value::from(17u64)));
+        assert_eq!(
+            extensions.get("operation"),
+            Some(&async_graphql::Value::from("tokenInfo"))
+        );
+    }
+
+    fn limited_schema() -> Schema<Query, EmptyMutation, async_graphql::EmptySubscription> {
+        Schema::build(Query, EmptyMutation, async_graphql::EmptySubscription)
+            .limit_depth(MAX_QUERY_DEPTH)
+            .limit_complexity(MAX_QUERY_COMPLEXITY)
+            .finish()
+    }
+
+    fn is_complexity_error(response: &async_graphql::Response) -> bool {
+        response.errors.iter().any(|e| e.message.contains("too complex"))
+    }
+
+    #[test]
+    fn test_window_weights() {
+        assert_eq!(window_hours("15m"), 0.25);
+        assert_eq!(window_hours("today"), 24.0);
+        assert_eq!(window_hours("7d"), 168.0);
+        assert_eq!(window_hours("1M"), 720.0);
+        assert_eq!(window_hours("bogus"), 1.0);
+
+        assert_eq!(sold_orders_complexity(None, 10), 10);
+        assert_eq!(sold_orders_complexity(Some(24.0 * 60.0), 10), 240);
+        assert_eq!(sold_orders_complexity(Some(f64::NAN), 10), 10);
+        assert_eq!(sold_orders_complexity(Some(f64::INFINITY), 10), usize::MAX);
+        assert_eq!(historical_data_complexity("6h", 5), 5);
+        assert_eq!(historical_data_complexity("30d", 5), 150);
+        assert_eq!(ticker_history_complexity(None, None, 4), 8);
+        assert_eq!(ticker_history_complexity(Some("30d"), Some("1m"), 4), 1728);
+    }
+
+    #[tokio::test]
+    async fn test_expensive_query_rejected_before_execution() {
+        let schema = limited_schema();
+
+        // A month of sold orders: 720 hours x the selection's cost
+        let response = schema
+            .execute(r#"{ soldOrdersPage(minutes: 43200) { items { id ticker amount } hasMore } }"#)
+            .await;
+        assert!(is_complexity_error(&response), "{:?}", response.errors);
+
+        let response = schema
+            .execute(
+                r#"{ tickerHistory(token: "kaspa", range: "30d", resolution: "1m") { data { timestamp close } } }"#,
+            )
+            .await;
+        assert!(is_complexity_error(&response), "{:?}", response.errors);
+
+        // The default one-hour window passes validation; it then fails only
+        // because this schema has no AppState
+        let response = schema
+            .execute(r#"{ soldOrders { id ticker amount } }"#)
+            .await;
+        assert!(!response.errors.is_empty());
+        assert!(!is_complexity_error(&response), "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn test_handler_accepts_hash_only_persisted_query() {
+        use crate::api::persisted_queries::sha256_hex;
+        use tower::ServiceExt;
+
+        let schema = Schema::build(Query, EmptyMutation, async_graphql::EmptySubscription)
+            .extension(PersistedQueries::new(Arc::new(crate::infrastructure::InMemoryCache::new())))
+            .finish();
+        let app = axum::Router::new()
+            .route("/graphql", axum::routing::post(graphql_handler))
+            .layer(Extension(schema));
+        let post = |body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = axum::http::Request::post("/graphql")
+                    .header("content-type", "application/json")
+                    .body(axum::body::Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+            }
+        };
+
+        let query = "{ __typename }";
+        let extensions = serde_json::json!({
+            "persistedQuery": { "version": 1, "sha256Hash": sha256_hex(query) }
+        });
+
+        let body = post(serde_json::json!({ "extensions": extensions })).await;
+        assert_eq!(body["errors"][0]["extensions"]["code"], "PERSISTED_QUERY_NOT_FOUND", "{}", body);
+
+        let body = post(serde_json::json!({ "query": query, "extensions": extensions })).await;
+        assert_eq!(body["data"]["__typename"], "Query", "{}", body);
+
+        let body = post(serde_json::json!({ "extensions": extensions })).await;
+        assert_eq!(body["data"]["__typename"], "Query", "{}", body);
+    }
+}
@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 use tracing::instrument;
 use validator::Validate;
 
+use crate::api::kaspacom_handlers::ErrorResponse;
 use crate::api::state::AppState;
 use utoipa::{IntoParams, ToSchema};
 
@@ -17,6 +18,9 @@ use serde_json::json; // Used in utoipa::path examples
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Longest the readiness probe waits for Redis to answer PING
+pub const READY_PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
 #[derive(Deserialize, IntoParams, ToSchema, Debug, Validate)]
 pub struct AggregateQuery {
     /// Enable aggregation mode to combine multiple files
@@ -100,6 +104,115 @@ pub async fn health_handler(
     }
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// "ready" or "not_ready"
+    pub status: String,
+    /// Outcome of each dependency check
+    pub checks: Vec<ReadinessCheck>,
+    /// Names of the dependencies that are not ready
+    pub failed: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessCheck {
+    /// Dependency name (redis, data_path, exchange_index)
+    pub name: String,
+    /// "ok", "failed" or "skipped" (dependency not configured)
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Round-trip time of the check in milliseconds, where measured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+}
+
+impl ReadinessCheck {
+    fn new(name: &str, status: &str, detail: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: status.to_string(),
+            detail,
+            latency_ms: None,
+        }
+    }
+
+    fn with_latency(mut self, latency: std::time::Duration) -> Self {
+        self.latency_ms = Some(latency.as_secs_f64() * 1000.0);
+        self
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "system",
+    responses(
+        (status = 200, description = "All dependencies are ready", body = ReadinessResponse),
+        (status = 503, description = "One or more dependencies are not ready", body = ReadinessResponse)
+    )
+)]
+pub async fn ready_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ReadinessResponse>, (StatusCode, Json<ReadinessResponse>)> {
+    let mut checks = Vec::new();
+
+    // Redis answers PING
+    checks.push(if !state.redis_repo.is_configured() {
+        ReadinessCheck::new("redis", "skipped", None)
+    } else {
+        match tokio::time::timeout(READY_PING_TIMEOUT, state.redis_repo.ping()).await {
+            Ok(Ok(latency)) => ReadinessCheck::new("redis", "ok", None).with_latency(latency),
+            Ok(Err(e)) => ReadinessCheck::new("redis", "failed", Some(e.to_string())),
+            Err(_) => ReadinessCheck::new(
+                "redis",
+                "failed",
+                Some(format!("PING timed out after {:?}", READY_PING_TIMEOUT)),
+            ),
+        }
+    });
+
+    // Configured local data path is mounted
+    checks.push(match &state.data_path {
+        None => ReadinessCheck::new("data_path", "skipped", None),
+        Some(path) if std::path::Path::new(path).is_dir() => {
+            ReadinessCheck::new("data_path", "ok", None)
+        }
+        Some(path) => {
+            ReadinessCheck::new("data_path", "failed", Some(format!("{} is not mounted", path)))
+        }
+    });
+
+    // Exchange index finished its initial rebuild
+    checks.push(match &state.exchange_index {
+        None => ReadinessCheck::new("exchange_index", "skipped", None),
+        Some(index) if index.has_rebuilt() => ReadinessCheck::new("exchange_index", "ok", None),
+        Some(_) => ReadinessCheck::new(
+            "exchange_index",
+            "failed",
+            Some("initial rebuild has not completed".to_string()),
+        ),
+    });
+
+    let failed: Vec<String> = checks
+        .iter()
+        .filter(|c| c.status == "failed")
+        .map(|c| c.name.clone())
+        .collect();
+
+    let response = ReadinessResponse {
+        status: if failed.is_empty() { "ready" } else { "not_ready" }.to_string(),
+        checks,
+        failed,
+    };
+
+    if response.failed.is_empty() {
+        Ok(Json(response))
+    } else {
+        Err((StatusCode::SERVICE_UNAVAILABLE, Json(response)))
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/metrics",
@@ -108,10 +221,22 @@ pub async fn health_handler(
         (status = 200, description = "Prometheus metrics", content_type = "text/plain")
     )
 )]
-pub async fn metrics_handler() -> impl IntoResponse {
-    let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
-        .install_recorder()
-        .expect("Failed to install Prometheus recorder");
+pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    // The recorder can only be installed once per process
+    static HANDLE: std::sync::OnceLock<metrics_exporter_prometheus::PrometheusHandle> =
+        std::sync::OnceLock::new();
+    let handle = HANDLE.get_or_init(|| {
+        metrics_exporter_prometheus::PrometheusBuilder::new()
+            .install_recorder()
+            .expect("Failed to install Prometheus recorder")
+    });
+
+    // Refresh the redis_up / redis_ping_latency_seconds gauges
+    if state.redis_repo.is_configured() {
+        let _ = state.redis_repo.ping().await;
+    }
+    // Refresh the kaspacom_circuit_state gauge
+    state.kaspacom_service.circuit_breaker_status();
     handle.render()
 }
 
@@ -125,6 +250,9 @@ pub struct RateLimitResources {
     pub core: RateLimitInfo,
     pub search: Option<RateLimitInfo>,
     pub graphql: Option<RateLimitInfo>,
+    /// Budgets for cache categories with a dedicated limit
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub categories: std::collections::HashMap<String, RateLimitInfo>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -133,6 +261,8 @@ pub struct RateLimitInfo {
     pub remaining: u32,
     pub reset: i64,
     pub used: u32,
+    /// Requests counted in the trailing 60-second window
+    pub current_usage: u32,
 }
 
 /// Get kaspa.com API rate limit status.
@@ -151,7 +281,8 @@ pub struct RateLimitInfo {
                         "limit": 1000,
                         "remaining": 850,
                         "reset": 1735678800,
-                        "used": 150
+                        "used": 150,
+                        "current_usage": 150
                     }
                 }
             })
@@ -171,9 +302,26 @@ pub async fn rate_limit_handler(
                 remaining: stats.remaining,
                 reset: stats.reset,
                 used: stats.used,
+                current_usage: stats.current_usage,
             },
             search: None,
             graphql: None,
+            categories: stats
+                .categories
+                .iter()
+                .map(|(category, cat)| {
+                    (
+                        category.clone(),
+                        RateLimitInfo {
+                            limit: cat.limit,
+                            remaining: cat.remaining,
+                            reset: cat.reset,
+                            used: cat.used,
+                            current_usage: cat.used,
+                        },
+                    )
+                })
+                .collect(),
         },
     };
     
@@ -306,3 +454,18 @@ pub async fn dashboard_js_handler() -> impl IntoResponse {
 pub async fn dashboard_css_handler() -> impl IntoResponse {
     ([(axum::http::header::CONTENT_TYPE, "text/css")], DASHBOARD_CSS)
 }
+
+/// Fallback for requests that match no route.
+///
+/// Returns the same JSON error shape as every other endpoint instead of
+/// Axum's default empty 404 body.
+pub async fn not_found_handler(uri: axum::http::Uri) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new(
+            "Not Found",
+            Some("ROUTE_NOT_FOUND"),
+            Some(format!("No route for path '{}'", uri.path())),
+        )),
+    )
+}
@@ -0,0 +1,74 @@
+//! Count of requests currently being handled.
+//!
+//! Read at shutdown to report how many requests the drain is waiting on
+//! (and how many were dropped when the grace period ran out), and exported
+//! as the `http_requests_in_flight` gauge.
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Requests currently being handled
+pub fn in_flight_requests() -> usize {
+    IN_FLIGHT.load(Ordering::Acquire)
+}
+
+/// Decrements the count when the request finishes or is dropped
+struct InFlightGuard;
+
+impl InFlightGuard {
+    fn enter() -> Self {
+        IN_FLIGHT.fetch_add(1, Ordering::AcqRel);
+        metrics::gauge!("http_requests_in_flight").increment(1.0);
+        Self
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::AcqRel);
+        metrics::gauge!("http_requests_in_flight").decrement(1.0);
+    }
+}
+
+/// Count the request as in flight until its response is ready
+pub async fn track_in_flight(request: Request, next: Next) -> Response {
+    let _guard = InFlightGuard::enter();
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_running_request_is_counted() {
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+        let started_tx = Arc::new(Mutex::new(Some(started_tx)));
+        let release_rx = Arc::new(Mutex::new(Some(release_rx)));
+        let app = Router::new()
+            .route(
+                "/",
+                get(move || {
+                    let started = started_tx.lock().unwrap().take();
+                    let release = release_rx.lock().unwrap().take();
+                    async move {
+                        let _ = started.unwrap().send(in_flight_requests());
+                        let _ = release.unwrap().await;
+                    }
+                }),
+            )
+            .layer(axum::middleware::from_fn(track_in_flight));
+
+        let request = tokio::spawn(app.oneshot(Request::get("/").body(Body::empty()).unwrap()));
+        // Other tests may run concurrently, so only a lower bound is exact
+        assert!(started_rx.await.unwrap() >= 1);
+        release_tx.send(()).unwrap();
+        request.await.unwrap().unwrap();
+    }
+}
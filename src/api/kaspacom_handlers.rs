@@ -3,22 +3,45 @@
 //! These handlers provide cache-first access to the Kaspa.com API,
 //! serving data from local cache when available.
 
+use crate::api::csv::{to_csv, Csv, CsvRow, FormatQuery, OutputFormat};
+use crate::api::ndjson::NdjsonBatches;
+use crate::api::request_id::current_request_id;
 use crate::api::state::AppState;
+use crate::application::kaspacom_service::{
+    FreshnessCheck, KnsListingOptions, ListingOptions, MarketOverviewRow, SearchResults,
+    TokenDetail, MAX_MARKET_OVERVIEW_TOKENS,
+};
+use crate::application::live_feed::FeedEvent;
+use crate::application::price_ticks::{PriceTick, PriceTickSubscription};
+use crate::application::ticker_service::{
+    ComparisonQuery, ComparisonResponse, InvalidParameter, LatestPriceResponse, LeaderboardQuery,
+    LeaderboardResponse, OverlayQuery, PairsQuery, TickerHistoryQuery, TickerHistoryResponse,
+    TickerPair, TickerSearchQuery, TickerSearchResponse, TickerStatsBatchEntry, TickerStatsQuery,
+    TimeseriesResponse, MAX_BATCH_TOKENS, MAX_COMPARE_TOKENS,
+};
+use crate::application::ExchangeIndexStatus;
 use crate::domain::{
-    FloorPriceEntry, HistoricalDataResponse, HotMint, KnsOrder, KnsTradeStatsResponse,
-    Krc721CollectionInfo, NftMetadata, NftMint, NftOrder, NftTokensResponse, NftTradeStatsResponse,
-    OpenOrdersResponse, SoldOrder, TokenInfo, TokenLogo, TradeStatsResponse,
+    ApiError, FloorPriceEntry, HistoricalDataResponse, HotMint, KnsDomainStatus, KnsOrder,
+    KnsTradeStatsResponse, Krc721CollectionInfo, NftMetadata, NftMint, NftOrder, NftRarityResponse,
+    NftTokenFilter, NftTokensResponse, NftTradeStatsResponse, NftTraitDistribution,
+    OpenOrdersResponse, OrderCursor, SoldOrder, TokenInfo, TokenLogo, TradeStatsResponse,
+    MAX_ORDERS_PAGE_LIMIT,
 };
-use crate::infrastructure::CacheStats;
+use crate::infrastructure::{cache_categories, CacheStats, RateLimitExceeded};
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{
+        rejection::JsonRejection,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use utoipa::{IntoParams, ToSchema};
-use validator::Validate;
+use validator::{Validate, ValidationErrors};
 
 // ============================================================================
 // Query Parameters
@@ -45,6 +68,49 @@ pub struct FloorPriceQuery {
     pub ticker: Option<String>,
 }
 
+/// Query parameters for the KRC20 floor price endpoint
+#[derive(Debug, Clone, Deserialize, IntoParams, Validate)]
+pub struct Krc20FloorPriceQuery {
+    /// Optional ticker filter
+    #[validate(length(max = 50))]
+    pub ticker: Option<String>,
+    /// Minimum 24h trade volume in KAS; tokens below it are omitted (default: no filter)
+    #[validate(range(min = 0.0))]
+    pub min_volume: Option<f64>,
+    /// Sort field: floor_price, volume (24h KAS) or ticker (default: upstream order)
+    pub sort_by: Option<String>,
+    /// Sort direction: asc or desc (default: asc)
+    pub order: Option<String>,
+}
+
+/// Query parameters for the market overview endpoint
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct MarketOverviewQuery {
+    /// Comma-separated tickers (default: all configured tokens)
+    #[param(example = "nacho,kasper")]
+    pub tickers: Option<String>,
+    /// Minimum 24h trade volume in KAS; tokens below it are omitted (default: no filter)
+    pub min_volume: Option<f64>,
+    /// Sort field: floor_price, volume (24h KAS) or ticker (default: request order)
+    pub sort_by: Option<String>,
+    /// Sort direction: asc or desc (default: asc)
+    pub order: Option<String>,
+}
+
+impl MarketOverviewQuery {
+    /// Requested tickers, trimmed, with empty entries dropped
+    pub fn tickers(&self) -> Option<Vec<String>> {
+        self.tickers.as_ref().map(|tickers| {
+            tickers
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+    }
+}
+
 /// Query parameters for sold orders endpoint
 #[derive(Debug, Clone, Deserialize, IntoParams, Validate)]
 pub struct SoldOrdersQuery {
@@ -54,6 +120,52 @@ pub struct SoldOrdersQuery {
     /// Time window in minutes (default: 60)
     #[validate(range(min = 1.0, max = 10080.0))] // 1 minute to 7 days
     pub minutes: Option<f64>,
+    /// Maximum number of orders per page (default: all). Giving `limit`,
+    /// `after` or `since` returns a page envelope instead of a bare array
+    #[validate(range(min = 1, max = MAX_ORDERS_PAGE_LIMIT))]
+    pub limit: Option<usize>,
+    /// Opaque cursor from a previous page's `nextCursor`
+    #[validate(length(max = 200))]
+    pub after: Option<String>,
+    /// Only orders with `createdAt` strictly greater than this, e.g. the
+    /// previous response's `latestTimestamp`
+    pub since: Option<i64>,
+}
+
+/// Query parameters for the sold orders export
+#[derive(Debug, Clone, Deserialize, IntoParams, Validate)]
+pub struct SoldOrdersExportQuery {
+    /// Optional ticker filter
+    #[validate(length(max = 50))]
+    pub ticker: Option<String>,
+    /// Time window in minutes (default: 60)
+    #[validate(range(min = 1.0, max = 10080.0))] // 1 minute to 7 days
+    pub minutes: Option<f64>,
+}
+
+impl SoldOrdersQuery {
+    /// Whether the client asked for a page envelope; plain requests keep
+    /// getting the bare array they always did, while polls with `since`
+    /// need `latestTimestamp` to pass back next time
+    fn wants_page(&self) -> bool {
+        self.limit.is_some() || self.after.is_some() || self.since.is_some()
+    }
+
+    /// Validate the query and decode the pagination cursor
+    fn validated_cursor(&self) -> Result<Option<OrderCursor>, (StatusCode, Json<ErrorResponse>)> {
+        self.validate().map_err(validation_error)?;
+
+        self.after
+            .as_deref()
+            .map(OrderCursor::decode)
+            .transpose()
+            .map_err(|e| {
+                error_response(
+                    "Invalid pagination cursor",
+                    ApiError::Validation(e.to_string()),
+                )
+            })
+    }
 }
 
 /// Query parameters for hot mints endpoint
@@ -78,6 +190,28 @@ pub struct HistoricalDataQuery {
     pub ticker: String,
 }
 
+/// Query parameters for the token detail endpoint
+#[derive(Debug, Clone, Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenDetailQuery {
+    /// Time frame of the historical series (default: 24h)
+    #[serde(default = "default_detail_time_frame")]
+    #[validate(length(min = 1, max = 10))]
+    pub time_frame: String,
+}
+
+/// Query parameters for the marketplace search endpoint
+#[derive(Debug, Clone, Deserialize, IntoParams, Validate)]
+pub struct SearchQuery {
+    /// Token ticker, collection ticker or KNS domain (e.g., nacho, bitcoin, mywallet.kas)
+    #[validate(length(min = 1, max = 64))]
+    pub q: String,
+}
+
+fn default_detail_time_frame() -> String {
+    "24h".to_string()
+}
+
 /// Query parameters for KNS trade stats endpoint
 #[derive(Debug, Clone, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
@@ -89,6 +223,20 @@ pub struct KnsTradeStatsQuery {
     pub asset: Option<String>,
 }
 
+/// Query parameters for the KNS listed orders endpoint
+#[derive(Debug, Clone, Deserialize, IntoParams, Validate)]
+pub struct KnsListedOrdersQuery {
+    /// Sort field: price or created_at (default: upstream order)
+    pub sort_by: Option<String>,
+    /// Sort direction: asc or desc (default: asc)
+    pub order: Option<String>,
+    /// Orders to skip after sorting (default: 0)
+    pub offset: Option<usize>,
+    /// Maximum number of orders returned (default: all)
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<usize>,
+}
+
 fn default_time_frame() -> String {
     "6h".to_string()
 }
@@ -123,8 +271,79 @@ pub struct TokenExchangesResponse {
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
+    /// Machine-readable error code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    /// Correlation ID of the failed request (also in `X-Request-Id`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorResponse {
+    /// Error body tagged with the current request's correlation ID
+    pub fn new(error: impl Into<String>, code: Option<&str>, details: Option<String>) -> Self {
+        Self {
+            error: error.into(),
+            code: code.map(str::to_string),
+            details,
+            request_id: current_request_id(),
+        }
+    }
+}
+
+/// 400 response for query or body parameters that failed validation
+fn validation_error(errors: ValidationErrors) -> (StatusCode, Json<ErrorResponse>) {
+    error_response(
+        "Validation failed",
+        ApiError::Validation(errors.to_string()),
+    )
+}
+
+/// Status code for each error kind
+fn error_status(error: &ApiError) -> StatusCode {
+    match error {
+        ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+        ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+        ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+        ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Classify a service failure by the typed error it carries
+///
+/// Rate limits and invalid parameters are raised as their own types, so
+/// they're mapped here; anything unclassified is an internal error.
+fn classify_error(e: &anyhow::Error) -> ApiError {
+    if let Some(error) = ApiError::find(e) {
+        return error.clone();
+    }
+    if let Some(limited) = e.downcast_ref::<RateLimitExceeded>() {
+        return limited.into();
+    }
+    if let Some(invalid) = e.downcast_ref::<InvalidParameter>() {
+        return invalid.into();
+    }
+    ApiError::Internal(e.to_string())
+}
+
+/// Error response with `error` describing what failed and a stable `code`
+fn error_response(context: &str, error: ApiError) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        error_status(&error),
+        Json(ErrorResponse::new(
+            context,
+            Some(error.code()),
+            Some(error.message().to_string()),
+        )),
+    )
+}
+
+/// Map a service failure to an error response
+fn service_error(context: &str, e: anyhow::Error) -> (StatusCode, Json<ErrorResponse>) {
+    error_response(context, classify_error(&e))
 }
 
 // ============================================================================
@@ -149,61 +368,45 @@ pub async fn trade_stats_handler(
     State(state): State<AppState>,
 ) -> Result<Json<TradeStatsResponse>, (StatusCode, Json<ErrorResponse>)> {
     // Validate input
-    if let Err(validation_errors) = query.validate() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Validation failed".to_string(),
-                details: Some(format!("{:?}", validation_errors)),
-            }),
-        ));
-    }
+    query.validate().map_err(validation_error)?;
     state
         .kaspacom_service
         .get_trade_stats(&query.time_frame, query.ticker.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch trade stats".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch trade stats", e))
 }
 
 /// Get floor prices for KRC20 tokens
 #[utoipa::path(
     get,
     path = "/v1/api/kaspa/floor-price",
-    params(FloorPriceQuery),
+    params(Krc20FloorPriceQuery),
     responses(
         (status = 200, description = "Floor price data", body = Vec<FloorPriceEntry>),
+        (status = 400, description = "Invalid input parameters", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
-    description = "Returns the lowest listing price per token across all active orders. Can fetch for a specific ticker or all tokens.",
+    description = "Returns the lowest listing price per token across all active orders. Can fetch for a specific ticker or all tokens. With `min_volume`, tokens whose 24h trade volume (KAS) is below the threshold are dropped. `sort_by` (floor_price, volume, ticker) and `order` (asc, desc) sort the result; tokens without a value for the sort field come last.",
     tag = "KRC20"
 )]
 pub async fn floor_price_handler(
-    Query(query): Query<FloorPriceQuery>,
+    Query(query): Query<Krc20FloorPriceQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<FloorPriceEntry>>, (StatusCode, Json<ErrorResponse>)> {
+    query.validate().map_err(validation_error)?;
+    let options = ListingOptions::parse(
+        query.sort_by.as_deref(),
+        query.order.as_deref(),
+        query.min_volume,
+    )
+    .map_err(|e| service_error("Invalid query parameter", e))?;
     state
         .kaspacom_service
-        .get_floor_prices(query.ticker.as_deref())
+        .get_floor_prices_with_options(query.ticker.as_deref(), &options)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch floor prices".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch floor prices", e))
 }
 
 /// Get recently sold orders for KRC20 tokens
@@ -212,30 +415,63 @@ pub async fn floor_price_handler(
     path = "/v1/api/kaspa/sold-orders",
     params(SoldOrdersQuery),
     responses(
-        (status = 200, description = "List of sold orders", body = Vec<SoldOrder>),
+        (status = 200, description = "Sold orders; a `Page<SoldOrder>` envelope when `limit`, `after` or `since` is given", body = Vec<SoldOrder>),
+        (status = 400, description = "Invalid input parameters or cursor", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
-    description = "Returns all completed trades within the specified time window (in minutes). Includes order details, prices, and participant addresses.",
+    description = "Returns completed trades within the specified time window (in minutes), newest first. Includes order details, prices, and participant addresses. Use `limit` and `after` to page through the window. Polling clients can pass the previous response's `latestTimestamp` as `since` to only receive newer orders. Any of the three switches the response to a page envelope.",
     tag = "KRC20"
 )]
 pub async fn sold_orders_handler(
     Query(query): Query<SoldOrdersQuery>,
     State(state): State<AppState>,
-) -> Result<Json<Vec<SoldOrder>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let after = query.validated_cursor()?;
+
+    let page = state
+        .kaspacom_service
+        .get_sold_orders_page(
+            query.ticker.as_deref(),
+            query.minutes,
+            query.since,
+            query.limit,
+            after.as_ref(),
+        )
+        .await
+        .map_err(|e| service_error("Failed to fetch sold orders", e))?;
+
+    Ok(if query.wants_page() {
+        Json(page).into_response()
+    } else {
+        Json(page.items).into_response()
+    })
+}
+
+/// Export sold orders as JSON lines
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/sold-orders/export",
+    params(SoldOrdersExportQuery),
+    responses(
+        (status = 200, description = "One sold order per line", body = SoldOrder, content_type = "application/x-ndjson"),
+        (status = 400, description = "Invalid input parameters", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Streams every completed trade in the time window as newline-delimited JSON (`application/x-ndjson`), one order per line, newest first. Intended for bulk exports of windows too large to page through `/v1/api/kaspa/sold-orders`; cached windows are read and sent a batch at a time.",
+    tag = "KRC20"
+)]
+pub async fn sold_orders_export_handler(
+    Query(query): Query<SoldOrdersExportQuery>,
+    State(state): State<AppState>,
+) -> Result<NdjsonBatches<SoldOrder>, (StatusCode, Json<ErrorResponse>)> {
+    query.validate().map_err(validation_error)?;
+
     state
         .kaspacom_service
-        .get_sold_orders(query.ticker.as_deref(), query.minutes)
+        .export_sold_orders(query.ticker.as_deref(), query.minutes)
         .await
-        .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch sold orders".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map(NdjsonBatches::new)
+        .map_err(|e| service_error("Failed to export sold orders", e))
 }
 
 /// Get the most recent sold order
@@ -257,15 +493,7 @@ pub async fn last_order_sold_handler(
         .get_last_order_sold()
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch last sold order".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch last sold order", e))
 }
 
 /// Get hot minting tokens
@@ -289,15 +517,7 @@ pub async fn hot_mints_handler(
         .get_hot_mints(&query.time_interval)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch hot mints".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch hot mints", e))
 }
 
 /// Get comprehensive token info
@@ -324,21 +544,7 @@ pub async fn token_info_handler(
         .get_token_info(&ticker)
         .await
         .map(Json)
-        .map_err(|e| {
-            let error_str = e.to_string();
-            let status = if error_str.contains("404") {
-                StatusCode::NOT_FOUND
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
-            (
-                status,
-                Json(ErrorResponse {
-                    error: "Failed to fetch token info".to_string(),
-                    details: Some(error_str),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch token info", e))
 }
 
 /// Get token logos
@@ -362,17 +568,193 @@ pub async fn tokens_logos_handler(
         .get_tokens_logos(query.ticker.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
+        .map_err(|e| service_error("Failed to fetch token logos", e))
+}
+
+/// Get floor price, price, market cap and logo for many tokens at once
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/market-overview",
+    params(MarketOverviewQuery),
+    responses(
+        (status = 200, description = "One row per ticker", body = Vec<MarketOverviewRow>),
+        (status = 400, description = "Too many tickers or unknown sort field/order", body = ErrorResponse)
+    ),
+    description = "Joins floor prices, token info and logos into one row per ticker for marketplace grids. Without `tickers` all configured tokens are returned. Parts that fail to load are omitted from the row and listed in `missing` instead of failing the request. Accepts the same `min_volume`, `sort_by` and `order` parameters as the floor-price endpoint.",
+    tag = "KRC20"
+)]
+pub async fn market_overview_handler(
+    Query(query): Query<MarketOverviewQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<MarketOverviewRow>>, (StatusCode, Json<ErrorResponse>)> {
+    let tickers = query.tickers();
+    if let Some(tickers) = &tickers {
+        if tickers.is_empty() || tickers.len() > MAX_MARKET_OVERVIEW_TOKENS {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(
+                    format!(
+                        "Market overview takes between 1 and {} tickers",
+                        MAX_MARKET_OVERVIEW_TOKENS
+                    ),
+                    Some("INVALID_TOKEN_COUNT"),
+                    Some(format!("Received {} tickers", tickers.len())),
+                )),
+            ));
+        }
+    }
+
+    let options = ListingOptions::parse(
+        query.sort_by.as_deref(),
+        query.order.as_deref(),
+        query.min_volume,
+    )
+    .map_err(|e| service_error("Invalid query parameter", e))?;
+    state
+        .kaspacom_service
+        .get_market_overview(tickers, &options)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to build market overview", e))
+}
+
+/// Get info, floor price, recent trades and history for one token
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/token/{ticker}/detail",
+    params(
+        ("ticker" = String, Path, description = "Token ticker", example = "NACHO"),
+        TokenDetailQuery
+    ),
+    responses(
+        (status = 200, description = "Composite token detail", body = TokenDetail),
+        (status = 400, description = "Invalid time frame", body = ErrorResponse),
+        (status = 500, description = "None of the parts could be loaded", body = ErrorResponse)
+    ),
+    description = "Combines token info, floor price, the most recent sold orders and the historical series for `timeFrame` into one response for token detail pages. Parts that fail to load are omitted and named in `missing` instead of failing the request.",
+    tag = "KRC20"
+)]
+pub async fn token_detail_handler(
+    Path(ticker): Path<String>,
+    Query(query): Query<TokenDetailQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<TokenDetail>, (StatusCode, Json<ErrorResponse>)> {
+    query.validate().map_err(validation_error)?;
+    state
+        .kaspacom_service
+        .get_token_detail(&ticker, &query.time_frame)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to fetch token detail", e))
+}
+
+/// Search tokens, NFT collections and KNS domains by name
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Matches by category", body = SearchResults),
+        (status = 400, description = "Missing or invalid query", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Returns configured KRC20 tickers containing `q` (`tokens`), the KRC721 collection with ticker `q` (`collections`) and the KNS domain `q` (`domains`), each empty when nothing matched. A query ending in `.kas` is only looked up as a domain. Lookups that fail are named in `missing`. Results are cached per query with the HOT TTLs.",
+    tag = "Search"
+)]
+pub async fn search_handler(
+    Query(query): Query<SearchQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SearchResults>, (StatusCode, Json<ErrorResponse>)> {
+    query.validate().map_err(validation_error)?;
+    state
+        .kaspacom_service
+        .search(&query.q)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to search", e))
+}
+
+/// Proxy a token's logo image through the cache
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/logo/{ticker}",
+    params(
+        ("ticker" = String, Path, description = "Token ticker", example = "NACHO")
+    ),
+    responses(
+        (status = 200, description = "Logo image bytes", content_type = "image/*"),
+        (status = 404, description = "Logo proxy disabled or no logo for this ticker", body = ErrorResponse),
+        (status = 502, description = "Failed to fetch the logo from its upstream host", body = ErrorResponse)
+    ),
+    description = "Serves the token logo referenced by the cached `tokens-logos` data, caching the image bytes for 7 days. Only URLs from that data are ever fetched, and SVG logos are refused since they can carry scripts.",
+    tag = "KRC20"
+)]
+pub async fn logo_proxy_handler(
+    Path(ticker): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let not_found = |code: &str, error: String| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(error, Some(code), None)),
+        )
+    };
+    let bad_gateway = |details: String| {
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(ErrorResponse::new(
+                "Failed to fetch token logo",
+                Some("LOGO_FETCH_FAILED"),
+                Some(details),
+            )),
+        )
+    };
+
+    if !state.kaspacom_service.logo_proxy_enabled() {
+        return Err(not_found(
+            "LOGO_PROXY_DISABLED",
+            "Logo proxy is disabled".to_string(),
+        ));
+    }
+
+    let image = state
+        .kaspacom_service
+        .get_logo_image(&ticker)
+        .await
+        .map_err(|e| bad_gateway(e.to_string()))?
+        .ok_or_else(|| not_found("LOGO_NOT_FOUND", format!("No logo for ticker {}", ticker)))?;
+    let bytes = general_purpose::STANDARD
+        .decode(&image.data)
+        .map_err(|e| bad_gateway(e.to_string()))?;
+
+    Ok((
+        [
+            (axum::http::header::CONTENT_TYPE, image.content_type),
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch token logos".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+                axum::http::header::CACHE_CONTROL,
+                format!("public, max-age={}", LOGO_MAX_AGE_SECS),
+            ),
+            (
+                axum::http::header::X_CONTENT_TYPE_OPTIONS,
+                "nosniff".to_string(),
+            ),
+            (
+                axum::http::header::CONTENT_SECURITY_POLICY,
+                LOGO_CSP.to_string(),
+            ),
+        ],
+        bytes,
+    ))
 }
 
+/// Browser cache lifetime for proxied logos
+const LOGO_MAX_AGE_SECS: u64 = 86400;
+
+/// Keeps a proxied logo opened directly from running anything on our origin
+const LOGO_CSP: &str = "default-src 'none'; style-src 'unsafe-inline'; sandbox";
+
 /// Get tickers with active open orders
 #[utoipa::path(
     get,
@@ -392,15 +774,7 @@ pub async fn open_orders_handler(
         .get_open_orders()
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch open orders".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch open orders", e))
 }
 
 /// Get historical price/volume data
@@ -420,29 +794,13 @@ pub async fn historical_data_handler(
     State(state): State<AppState>,
 ) -> Result<Json<HistoricalDataResponse>, (StatusCode, Json<ErrorResponse>)> {
     // Validate input
-    if let Err(validation_errors) = query.validate() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Validation failed".to_string(),
-                details: Some(format!("{:?}", validation_errors)),
-            }),
-        ));
-    }
+    query.validate().map_err(validation_error)?;
     state
         .kaspacom_service
         .get_historical_data(&query.time_frame, &query.ticker)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch historical data".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch historical data", e))
 }
 
 // ============================================================================
@@ -470,15 +828,7 @@ pub async fn krc721_mints_handler(
         .get_krc721_mints(query.ticker.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 mints".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 mints", e))
 }
 
 /// Get sold NFT orders
@@ -487,29 +837,36 @@ pub async fn krc721_mints_handler(
     path = "/v1/api/kaspa/krc721/sold-orders",
     params(SoldOrdersQuery),
     responses(
-        (status = 200, description = "Sold NFT orders", body = Vec<NftOrder>),
+        (status = 200, description = "Sold NFT orders; a `Page<NftOrder>` envelope when `limit`, `after` or `since` is given", body = Vec<NftOrder>),
+        (status = 400, description = "Invalid input parameters or cursor", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
+    description = "Returns sold NFT orders within the time window, newest first. Use `limit` and `after` to page through the window, and `since` (e.g. the previous `latestTimestamp`) to only receive newer orders. Any of the three switches the response to a page envelope.",
     tag = "KRC721"
 )]
 pub async fn krc721_sold_orders_handler(
     Query(query): Query<SoldOrdersQuery>,
     State(state): State<AppState>,
-) -> Result<Json<Vec<NftOrder>>, (StatusCode, Json<ErrorResponse>)> {
-    state
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let after = query.validated_cursor()?;
+
+    let page = state
         .kaspacom_service
-        .get_krc721_sold_orders(query.ticker.as_deref(), query.minutes)
+        .get_krc721_sold_orders_page(
+            query.ticker.as_deref(),
+            query.minutes,
+            query.since,
+            query.limit,
+            after.as_ref(),
+        )
         .await
-        .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 sold orders".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 sold orders", e))?;
+
+    Ok(if query.wants_page() {
+        Json(page).into_response()
+    } else {
+        Json(page.items).into_response()
+    })
 }
 
 /// Get listed NFT orders
@@ -532,15 +889,7 @@ pub async fn krc721_listed_orders_handler(
         .get_krc721_listed_orders(query.ticker.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 listed orders".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 listed orders", e))
 }
 
 /// Get NFT trade statistics
@@ -563,15 +912,7 @@ pub async fn krc721_trade_stats_handler(
         .get_krc721_trade_stats(&query.time_frame, query.ticker.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 trade stats".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 trade stats", e))
 }
 
 /// Get hot minting NFT collections
@@ -594,15 +935,7 @@ pub async fn krc721_hot_mints_handler(
         .get_krc721_hot_mints(&query.time_interval)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 hot mints".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 hot mints", e))
 }
 
 /// Get NFT floor prices
@@ -625,47 +958,43 @@ pub async fn krc721_floor_price_handler(
         .get_krc721_floor_prices(query.ticker.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 floor prices".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 floor prices", e))
 }
 
 /// Get filtered NFT tokens with pagination
 #[utoipa::path(
     post,
     path = "/v1/api/kaspa/krc721/tokens",
-    request_body = serde_json::Value,
+    request_body = NftTokenFilter,
     responses(
         (status = 200, description = "Filtered NFT tokens", body = NftTokensResponse),
-        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 400, description = "Malformed or invalid filter", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
+    description = "`page` starts at 1 and `limit` is at least 1; limits above 100 are lowered to 100 and reported with `limitCapped`. `minPrice` must not exceed `maxPrice`. Fields not listed in the schema are forwarded to kaspa.com unchanged. The response echoes the `page`, and the `limit` sent with `totalPages` when one was requested.",
     tag = "KRC721"
 )]
 pub async fn krc721_tokens_handler(
     State(state): State<AppState>,
-    Json(filter): Json<serde_json::Value>,
+    filter: Result<Json<NftTokenFilter>, JsonRejection>,
 ) -> Result<Json<NftTokensResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Json(filter) = filter.map_err(|rejection| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "Invalid filter",
+                None,
+                Some(rejection.body_text()),
+            )),
+        )
+    })?;
+    filter.validate().map_err(validation_error)?;
     state
         .kaspacom_service
         .get_krc721_tokens(&filter)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KRC721 tokens".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KRC721 tokens", e))
 }
 
 /// Get KRC721 collection info (holders, supply, rarity)
@@ -691,62 +1020,90 @@ pub async fn krc721_collection_info_handler(
         .get_krc721_collection_info(&ticker)
         .await
         .map(Json)
-        .map_err(|e| {
-            let error_str = e.to_string();
-            let status = if error_str.contains("404") {
-                StatusCode::NOT_FOUND
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
-            (
-                status,
-                Json(ErrorResponse {
-                    error: "Failed to fetch collection info".to_string(),
-                    details: Some(error_str),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch collection info", e))
 }
 
-/// Get NFT metadata (image, name, traits) from krc721.stream cache
+/// Get a KRC721 collection's tokens ranked by rarity
 #[utoipa::path(
     get,
-    path = "/v1/api/kaspa/krc721/metadata/{ticker}/{token_id}",
+    path = "/v1/api/kaspa/krc721/rarity/{ticker}",
     params(
-        ("ticker" = String, Path, description = "NFT collection ticker"),
-        ("token_id" = i64, Path, description = "Token ID within the collection")
+        ("ticker" = String, Path, description = "NFT collection ticker (e.g., BITCOIN)")
     ),
     responses(
-        (status = 200, description = "NFT metadata", body = NftMetadata),
-        (status = 404, description = "Metadata not found", body = ErrorResponse),
+        (status = 200, description = "Tokens ranked rarest first", body = NftRarityResponse),
+        (status = 400, description = "Collection too large to scan", body = ErrorResponse),
+        (status = 404, description = "Collection not found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
+    description = "Scans the whole collection (up to 10,000 tokens) and returns `{tokenId, rarityRank, traits}` per token, rarest first. Upstream ranks are used when every token has one, otherwise ranks are computed from trait rarity (`rankSource`). Finished collections are cached with the COLD TTLs (1 hour by default). While a collection is still minting, ranks can change with every mint, so `minting` is true and the result is only cached with the WARM TTLs (15 minutes by default).",
     tag = "KRC721"
 )]
-pub async fn krc721_metadata_handler(
-    Path((ticker, token_id)): Path<(String, i64)>,
+pub async fn krc721_rarity_handler(
+    Path(ticker): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<NftMetadata>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<NftRarityResponse>, (StatusCode, Json<ErrorResponse>)> {
     state
         .kaspacom_service
-        .get_nft_metadata(&ticker, token_id)
+        .get_krc721_rarity(&ticker)
         .await
         .map(Json)
-        .map_err(|e| {
-            let error_str = e.to_string();
-            let status = if error_str.contains("404") {
-                StatusCode::NOT_FOUND
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
-            (
-                status,
-                Json(ErrorResponse {
-                    error: "Failed to fetch NFT metadata".to_string(),
-                    details: Some(error_str),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to rank collection by rarity", e))
+}
+
+/// Get how many tokens of a KRC721 collection have each trait value
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/krc721/traits/{ticker}",
+    params(
+        ("ticker" = String, Path, description = "NFT collection ticker (e.g., BITCOIN)")
+    ),
+    responses(
+        (status = 200, description = "Trait value counts and percentages", body = NftTraitDistribution),
+        (status = 400, description = "Collection too large to scan", body = ErrorResponse),
+        (status = 404, description = "Collection not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Scans the whole collection (up to 10,000 tokens, 100 per page) and counts each `traitType` → `value`, with its percentage of the scanned tokens, most common first. Cached like the rarity ranking: COLD TTLs for finished collections, WARM TTLs with `minting: true` while tokens are still being minted.",
+    tag = "KRC721"
+)]
+pub async fn krc721_traits_handler(
+    Path(ticker): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<NftTraitDistribution>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .kaspacom_service
+        .get_krc721_trait_distribution(&ticker)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to aggregate collection traits", e))
+}
+
+/// Get NFT metadata (image, name, traits) from krc721.stream cache
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/krc721/metadata/{ticker}/{token_id}",
+    params(
+        ("ticker" = String, Path, description = "NFT collection ticker"),
+        ("token_id" = i64, Path, description = "Token ID within the collection")
+    ),
+    responses(
+        (status = 200, description = "NFT metadata", body = NftMetadata),
+        (status = 404, description = "Metadata not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "KRC721"
+)]
+pub async fn krc721_metadata_handler(
+    Path((ticker, token_id)): Path<(String, i64)>,
+    State(state): State<AppState>,
+) -> Result<Json<NftMetadata>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .kaspacom_service
+        .get_nft_metadata(&ticker, token_id)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to fetch NFT metadata", e))
 }
 
 /// Get optimized NFT image URL from krc721.stream CDN
@@ -794,15 +1151,7 @@ pub async fn kns_sold_orders_handler(
         .get_kns_sold_orders(query.minutes)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KNS sold orders".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KNS sold orders", e))
 }
 
 /// Get KNS trade statistics
@@ -825,44 +1174,68 @@ pub async fn kns_trade_stats_handler(
         .get_kns_trade_stats(&query.time_frame, query.asset.as_deref())
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KNS trade stats".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KNS trade stats", e))
 }
 
 /// Get listed KNS domains
 #[utoipa::path(
     get,
     path = "/v1/api/kaspa/kns/listed-orders",
+    params(KnsListedOrdersQuery),
     responses(
         (status = 200, description = "Listed KNS domains", body = Vec<KnsOrder>),
+        (status = 400, description = "Invalid input parameters", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
+    description = "`sort_by` (price, created_at) and `order` (asc, desc) sort the listing, then `offset` orders are skipped and at most `limit` returned. Without `sort_by` the upstream order is kept.",
     tag = "KNS"
 )]
 pub async fn kns_listed_orders_handler(
+    Query(query): Query<KnsListedOrdersQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<KnsOrder>>, (StatusCode, Json<ErrorResponse>)> {
+    query.validate().map_err(validation_error)?;
+    let options = KnsListingOptions::parse(
+        query.sort_by.as_deref(),
+        query.order.as_deref(),
+        query.offset,
+        query.limit,
+    )
+    .map_err(|e| service_error("Invalid query parameter", e))?;
     state
         .kaspacom_service
-        .get_kns_listed_orders()
+        .get_kns_listed_orders_with_options(&options)
         .await
         .map(Json)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to fetch KNS listed orders".to_string(),
-                    details: Some(e.to_string()),
-                }),
-            )
-        })
+        .map_err(|e| service_error("Failed to fetch KNS listed orders", e))
+}
+
+/// Get the marketplace status of one KNS domain
+#[utoipa::path(
+    get,
+    path = "/v1/api/kaspa/kns/domain/{name}",
+    params(
+        ("name" = String, Path, description = "KNS domain, with or without the .kas suffix (e.g., mywallet or mywallet.kas)")
+    ),
+    responses(
+        (status = 200, description = "Domain listing and last sale", body = KnsDomainStatus),
+        (status = 400, description = "Invalid domain name", body = ErrorResponse),
+        (status = 404, description = "Domain not listed and not sold in the last week", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Kaspa.com has no per-domain endpoint, so the status is derived from the cached listed orders and the sales of the last 7 days. `status` is `listed` when there is an open order (`listing`), otherwise `sold`; `lastSale` is the most recent sale in that window.",
+    tag = "KNS"
+)]
+pub async fn kns_domain_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<KnsDomainStatus>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .kaspacom_service
+        .get_kns_domain(&name)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to fetch KNS domain", e))
 }
 
 // ============================================================================
@@ -912,10 +1285,11 @@ pub async fn token_exchanges_handler(
         })),
         None => Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Token '{}' not found in configuration", token),
-                details: None,
-            }),
+            Json(ErrorResponse::new(
+                format!("Token '{}' not found in configuration", token),
+                None,
+                None,
+            )),
         )),
     }
 }
@@ -937,13 +1311,950 @@ pub async fn cache_stats_handler(
         .kaspacom_service
         .get_cache_stats()
         .map(Json)
+        .map_err(|e| service_error("Failed to get cache stats", e))
+}
+
+// ============================================================================
+// Admin Handlers
+// ============================================================================
+
+/// Header carrying the admin API key
+const ADMIN_API_KEY_HEADER: &str = "x-api-key";
+
+/// Request body for manually overriding a cached entry
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CacheOverrideRequest {
+    /// JSON value to store in the cache
+    pub data: serde_json::Value,
+    /// TTL for the entry in seconds
+    pub ttl_seconds: u64,
+    /// Optional Redis key to write as well (e.g. "kaspa:floor_price:NACHO")
+    pub redis_key: Option<String>,
+}
+
+/// Response for a cache override
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CacheOverrideResponse {
+    pub category: String,
+    pub key: String,
+    pub ttl_seconds: u64,
+    pub redis_written: bool,
+    /// Identity of the API key that set the entry
+    pub set_by: String,
+}
+
+/// Resolve the identity behind the request's admin API key
+fn authenticate_admin(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    let unauthorized = |error: &str| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse::new(error, None, None)),
+        )
+    };
+
+    if state.admin_api_keys.is_empty() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::new(
+                "Admin endpoints are disabled",
+                None,
+                Some("Set ADMIN_API_KEYS to enable them".to_string()),
+            )),
+        ));
+    }
+
+    let key = headers
+        .get(ADMIN_API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| unauthorized("Missing API key"))?;
+
+    state
+        .admin_api_keys
+        .get(key)
+        .cloned()
+        .ok_or_else(|| unauthorized("Invalid API key"))
+}
+
+/// Cache keys become file names, so only allow a conservative character set
+fn is_valid_cache_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 200
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Optional body for a manual exchange index rebuild
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct IndexRebuildRequest {
+    /// Re-scan only this token's directory instead of the whole data path
+    pub token: Option<String>,
+}
+
+/// Response for a manual exchange index rebuild
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IndexRebuildResponse {
+    /// "accepted" for a background full rebuild (poll the status endpoint
+    /// for completion), "updated" for a single-token update
+    pub status: String,
+    /// Token that was re-scanned, for single-token updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Number of exchanges now listing the token, for single-token updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchanges: Option<usize>,
+    /// Identity of the admin key holder that triggered the rebuild
+    pub triggered_by: String,
+}
+
+fn index_unavailable() -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new(
+            "Exchange index is not available",
+            Some("INDEX_UNAVAILABLE"),
+            Some("The local data path is not mounted".to_string()),
+        )),
+    )
+}
+
+/// Get exchange index build status
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/index/status",
+    responses(
+        (status = 200, description = "Exchange index status", body = ExchangeIndexStatus),
+        (status = 404, description = "No local data path, so no exchange index", body = ErrorResponse)
+    ),
+    description = "Returns whether the exchange index has been built, its exchange and token counts, and when it was last rebuilt.",
+    tag = "Ticker"
+)]
+pub async fn index_status_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ExchangeIndexStatus>, (StatusCode, Json<ErrorResponse>)> {
+    let index = state
+        .exchange_index
+        .as_ref()
+        .ok_or_else(index_unavailable)?;
+    Ok(Json(index.status().await))
+}
+
+/// Trigger an exchange index rebuild
+#[utoipa::path(
+    post,
+    path = "/v1/api/ticker/index/rebuild",
+    request_body(content = Option<IndexRebuildRequest>, description = "Set `token` to re-scan a single token"),
+    responses(
+        (status = 200, description = "Single token updated", body = IndexRebuildResponse),
+        (status = 202, description = "Rebuild started", body = IndexRebuildResponse),
+        (status = 400, description = "Invalid token name", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+        (status = 403, description = "Admin endpoints disabled", body = ErrorResponse),
+        (status = 404, description = "No local data path, so no exchange index", body = ErrorResponse),
+        (status = 409, description = "A rebuild is already running", body = ErrorResponse),
+        (status = 500, description = "Failed to read the token's directory", body = ErrorResponse)
+    ),
+    description = "Rescans the local data directory in the background so newly added tokens are picked up without a restart. Returns immediately; poll the status endpoint for completion. With a `token` in the body, only that token's directory is re-scanned and the index is updated before responding. Requires an admin key in the X-API-Key header.",
+    tag = "Ticker"
+)]
+pub async fn index_rebuild_handler(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    body: Option<Json<IndexRebuildRequest>>,
+) -> Result<(StatusCode, Json<IndexRebuildResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let identity = authenticate_admin(&headers, &state)?;
+    let index = state
+        .exchange_index
+        .as_ref()
+        .ok_or_else(index_unavailable)?;
+
+    if let Some(token) = body.and_then(|Json(b)| b.token) {
+        let exchanges = index
+            .add_token(&token)
+            .await
+            .map_err(|e| service_error(&format!("Failed to update token '{}'", token), e))?;
+        tracing::info!("Exchange index entry for {} updated by {}", token, identity);
+        return Ok((
+            StatusCode::OK,
+            Json(IndexRebuildResponse {
+                status: "updated".to_string(),
+                token: Some(token),
+                exchanges: Some(exchanges),
+                triggered_by: identity,
+            }),
+        ));
+    }
+
+    if !index.spawn_rebuild() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new(
+                "Exchange index rebuild already running",
+                Some("REBUILD_IN_PROGRESS"),
+                None,
+            )),
+        ));
+    }
+
+    tracing::info!("Exchange index rebuild triggered by {}", identity);
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(IndexRebuildResponse {
+            status: "accepted".to_string(),
+            token: None,
+            exchanges: None,
+            triggered_by: identity,
+        }),
+    ))
+}
+
+/// Get current stats for several tokens in one call
+#[utoipa::path(
+    post,
+    path = "/v1/api/ticker/stats/batch",
+    params(TickerStatsQuery),
+    request_body(content = Vec<String>, description = "Tokens to fetch stats for", example = json!(["SLOW", "NACHO"])),
+    responses(
+        (status = 200, description = "Stats or error per token", body = BTreeMap<String, TickerStatsBatchEntry>),
+        (status = 400, description = "Empty or oversized batch, or unsupported range", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Returns current stats for up to 50 tokens, keyed by token. Each entry holds either `stats` or an `error`, so one unknown token does not fail the batch. Tokens are served from the same cache as the single-token stats endpoint.",
+    tag = "Ticker"
+)]
+pub async fn ticker_stats_batch_handler(
+    State(state): State<AppState>,
+    Query(query): Query<TickerStatsQuery>,
+    Json(tokens): Json<Vec<String>>,
+) -> Result<Json<BTreeMap<String, TickerStatsBatchEntry>>, (StatusCode, Json<ErrorResponse>)> {
+    if tokens.is_empty() || tokens.len() > MAX_BATCH_TOKENS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                format!(
+                    "Batch must contain between 1 and {} tokens",
+                    MAX_BATCH_TOKENS
+                ),
+                Some("INVALID_BATCH_SIZE"),
+                Some(format!("Received {} tokens", tokens.len())),
+            )),
+        ));
+    }
+
+    state
+        .ticker_service
+        .get_ticker_stats_batch(tokens, query.range().to_string())
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to fetch batch stats", e))
+}
+
+/// Compare tokens' price series rebased to a common start
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/compare",
+    params(ComparisonQuery),
+    responses(
+        (status = 200, description = "Rebased series per token", body = ComparisonResponse),
+        (status = 400, description = "Too few or too many tokens, or unsupported range/resolution", body = ErrorResponse),
+        (status = 404, description = "One of the tokens is unknown", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Overlays 2 to 5 tokens on one scale: each token's close prices are rebased to 100 at the first bucket all tokens share. Only buckets with data for every token are returned, so tokens with differing coverage line up.",
+    tag = "Ticker"
+)]
+pub async fn ticker_compare_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ComparisonQuery>,
+) -> Result<Json<ComparisonResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let tokens = query.tokens();
+    if !(2..=MAX_COMPARE_TOKENS).contains(&tokens.len()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                format!(
+                    "Comparison needs between 2 and {} tokens",
+                    MAX_COMPARE_TOKENS
+                ),
+                Some("INVALID_TOKEN_COUNT"),
+                Some(format!("Received {} tokens", tokens.len())),
+            )),
+        ));
+    }
+
+    state
+        .ticker_service
+        .get_comparison(
+            tokens,
+            query.range().to_string(),
+            query.resolution().to_string(),
+        )
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to compare tokens", e))
+}
+
+/// Like [`service_error`], but unclassified failures mean a token had no history
+fn history_error(context: &str, e: anyhow::Error) -> (StatusCode, Json<ErrorResponse>) {
+    let error = match classify_error(&e) {
+        ApiError::Internal(message) => ApiError::NotFound(message),
+        other => other,
+    };
+    error_response(context, error)
+}
+
+/// Resolve the output format, rejecting unknown `format` values
+fn negotiate_format(
+    format: &FormatQuery,
+    headers: &HeaderMap,
+) -> Result<OutputFormat, (StatusCode, Json<ErrorResponse>)> {
+    format.negotiate(headers).map_err(|value| {
+        error_response(
+            "Invalid format",
+            ApiError::Validation(format!(
+                "Unsupported format '{}', expected json or csv",
+                value
+            )),
+        )
+    })
+}
+
+/// Serve `body` as JSON, or its `rows` as CSV
+fn chart_response<T: Serialize, R: CsvRow>(
+    output: OutputFormat,
+    body: T,
+    rows: impl FnOnce(&T) -> &[R],
+) -> Response {
+    match output {
+        OutputFormat::Csv => Csv(to_csv(rows(&body))).into_response(),
+        OutputFormat::Json => (
+            [(header::VARY, HeaderValue::from_static("accept"))],
+            Json(body),
+        )
+            .into_response(),
+    }
+}
+
+/// Get OHLCV history for a token
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/{token}/history",
+    params(
+        ("token" = String, Path, description = "Token identifier (case-insensitive)", example = "kaspa"),
+        TickerHistoryQuery,
+        OverlayQuery,
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "OHLCV buckets as JSON, or CSV rows (timestamp, open, high, low, close, volume)", content(
+            (TickerHistoryResponse = "application/json"),
+            (String = "text/csv")
+        )),
+        (status = 400, description = "Invalid token name, or unsupported range, resolution, overlay window or format", body = ErrorResponse),
+        (status = 404, description = "No data for this token", body = ErrorResponse)
+    ),
+    description = "Returns OHLCV buckets for charting. `sma` and `ema` add simple and exponential moving averages of the closes as `sma`/`ema` arrays parallel to `data`, with `null` until the window fills (JSON only). Send `Accept: text/csv` or `?format=csv` for CSV with a header line; `format` overrides `Accept`. JSON is the default.",
+    tag = "Ticker"
+)]
+pub async fn ticker_history_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<TickerHistoryQuery>,
+    Query(overlays): Query<OverlayQuery>,
+    Query(format): Query<FormatQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let output = negotiate_format(&format, &headers)?;
+    let history = state
+        .ticker_service
+        .get_ticker_history_with_overlays(
+            token,
+            query.range().to_string(),
+            query.resolution().to_string(),
+            &overlays,
+        )
+        .await
+        .map_err(|e| history_error("Failed to fetch ticker history", e))?;
+    Ok(chart_response(output, history, |h| &h.data))
+}
+
+/// Get a close-price timeseries for a token
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/{token}/timeseries",
+    params(
+        ("token" = String, Path, description = "Token identifier (case-insensitive)", example = "kaspa"),
+        TickerHistoryQuery,
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Price points as JSON, or CSV rows (timestamp, price)", content(
+            (TimeseriesResponse = "application/json"),
+            (String = "text/csv")
+        )),
+        (status = 400, description = "Invalid token name, or unsupported range, resolution or format", body = ErrorResponse),
+        (status = 404, description = "No data for this token", body = ErrorResponse)
+    ),
+    description = "Returns `[timestamp, price]` points (bucket close) for simple line charts. Send `Accept: text/csv` or `?format=csv` for CSV with a header line; `format` overrides `Accept`. JSON is the default.",
+    tag = "Ticker"
+)]
+pub async fn ticker_timeseries_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<TickerHistoryQuery>,
+    Query(format): Query<FormatQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let output = negotiate_format(&format, &headers)?;
+    let timeseries = state
+        .ticker_service
+        .get_timeseries(
+            token,
+            query.range().to_string(),
+            query.resolution().to_string(),
+        )
+        .await
+        .map_err(|e| history_error("Failed to fetch ticker timeseries", e))?;
+    Ok(chart_response(output, timeseries, |t| &t.data))
+}
+
+/// Get a token's current price without the per-exchange breakdown
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/{token}/latest",
+    params(
+        ("token" = String, Path, description = "Token identifier (case-insensitive)", example = "kaspa")
+    ),
+    responses(
+        (status = 200, description = "Current price", body = LatestPriceResponse),
+        (status = 400, description = "Invalid token name", body = ErrorResponse),
+        (status = 404, description = "No price for this token", body = ErrorResponse)
+    ),
+    description = "Lightweight current price for tickers and price widgets. Returns today's aggregate average when full stats are cached (`source: aggregate`), otherwise the last price from a single exchange, preferring reliable exchanges with the lowest reporting delay (`source` names it). Cached for 30 seconds.",
+    tag = "Ticker"
+)]
+pub async fn ticker_latest_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<LatestPriceResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .ticker_service
+        .get_latest_price(token)
+        .await
+        .map(Json)
+        .map_err(|e| history_error("Failed to fetch latest price", e))
+}
+
+/// Stream a token's aggregate price over a WebSocket
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/{token}/ws",
+    params(
+        ("token" = String, Path, description = "Token identifier (case-insensitive)", example = "kaspa")
+    ),
+    responses(
+        (status = 101, description = "Switching to WebSocket; each text message is a PriceTick", body = PriceTick),
+        (status = 400, description = "Invalid token name", body = ErrorResponse),
+        (status = 404, description = "No data for this token", body = ErrorResponse),
+        (status = 503, description = "Too many sockets already follow this token", body = ErrorResponse)
+    ),
+    description = "Sends the token's current aggregate price (today's stats) on connect, then a new `PriceTick` JSON message whenever the price changes. The price is polled once per `live_feed.poll_interval_secs` for all sockets on the token, and at most `live_feed.max_subscribers_per_feed` sockets may follow one token.",
+    tag = "Ticker"
+)]
+pub async fn ticker_ws_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let latest = state
+        .price_ticks
+        .latest(&token)
+        .await
+        .map_err(|e| history_error("Failed to fetch ticker stats", e))?;
+    let Some(subscription) = state.price_ticks.subscribe(&token) else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse::new(
+                "Too many connections for this token",
+                Some("TOO_MANY_SUBSCRIBERS"),
+                Some("Retry later or poll /v1/api/ticker/{token}/stats instead".to_string()),
+            )),
+        ));
+    };
+
+    Ok(ws.on_upgrade(move |socket| stream_price_ticks(socket, latest, subscription)))
+}
+
+/// Send `latest`, then every tick whose price differs from the last one sent,
+/// until the client disconnects
+async fn stream_price_ticks(
+    mut socket: WebSocket,
+    latest: PriceTick,
+    mut subscription: PriceTickSubscription,
+) {
+    let mut last_sent = latest;
+    if send_price_tick(&mut socket, &last_sent).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = subscription.next() => match event {
+                Some(FeedEvent::Update(tick)) if tick.price_changed(&last_sent) => {
+                    if send_price_tick(&mut socket, &tick).await.is_err() {
+                        break;
+                    }
+                    last_sent = tick;
+                }
+                // Unchanged, or skipped ticks that the next update supersedes
+                Some(_) => {}
+                None => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; other client messages are ignored
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send_price_tick(socket: &mut WebSocket, tick: &PriceTick) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(tick).map_err(axum::Error::new)?;
+    socket.send(Message::Text(text.into())).await
+}
+
+/// List all token/exchange pairs
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/pairs",
+    params(PairsQuery),
+    responses(
+        (status = 200, description = "Token/exchange pairs sorted by token", body = Vec<TickerPair>),
+        (status = 500, description = "Failed to list exchange data", body = ErrorResponse)
+    ),
+    description = "Flat list of every token/exchange pair with exchange data, for autocomplete. Built from the exchange index when available and cached for an hour. `exchange` limits the list to one exchange.",
+    tag = "Ticker"
+)]
+pub async fn ticker_pairs_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PairsQuery>,
+) -> Result<Json<Vec<TickerPair>>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .ticker_service
+        .get_pairs(query.exchange.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to list ticker pairs", e))
+}
+
+/// Search token names
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/search",
+    params(TickerSearchQuery),
+    responses(
+        (status = 200, description = "Matching tokens, best match first", body = TickerSearchResponse),
+        (status = 400, description = "Empty search query", body = ErrorResponse),
+        (status = 500, description = "Failed to list tokens", body = ErrorResponse)
+    ),
+    description = "Case-insensitive prefix and substring search over token names for search-as-you-type UIs. Prefix matches rank first, then shorter names. `limit` defaults to and is capped at the configured maximum (20 unless overridden).",
+    tag = "Ticker"
+)]
+pub async fn ticker_search_handler(
+    State(state): State<AppState>,
+    Query(query): Query<TickerSearchQuery>,
+) -> Result<Json<TickerSearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .ticker_service
+        .search_tickers(&query.q, query.limit)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to search tickers", e))
+}
+
+/// Rank tokens by cross-exchange 24h volume
+#[utoipa::path(
+    get,
+    path = "/v1/api/ticker/leaderboard",
+    params(LeaderboardQuery),
+    responses(
+        (status = 200, description = "Tokens by 24h volume, highest first", body = LeaderboardResponse),
+        (status = 400, description = "Unsupported range", body = ErrorResponse),
+        (status = 500, description = "Failed to list tokens", body = ErrorResponse)
+    ),
+    description = "Sums each token's 24h volume across its exchanges (the `aggregate.total_volume_24h` of the ticker stats) and returns the top `limit` tokens (default 20, max 100). Tokens without volume are left out. Computing the ranking reads every token's stats, so it is cached for 10 minutes per range.",
+    tag = "Ticker"
+)]
+pub async fn ticker_leaderboard_handler(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .ticker_service
+        .get_volume_leaderboard(query.range().to_string(), query.limit)
+        .await
+        .map(Json)
+        .map_err(|e| service_error("Failed to build volume leaderboard", e))
+}
+
+/// Reject unknown cache categories and unsafe Parquet keys
+fn validate_cache_target(
+    category: &str,
+    key: &str,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if !cache_categories::ALL.contains(&category) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                format!("Unknown cache category '{}'", category),
+                None,
+                Some(format!(
+                    "Expected one of: {}",
+                    cache_categories::ALL.join(", ")
+                )),
+            )),
+        ));
+    }
+
+    if !is_valid_cache_key(key) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                format!("Invalid cache key '{}'", key),
+                None,
+                Some("Keys may only contain letters, digits, '_', '-' and '.'".to_string()),
+            )),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Manually override a cached entry
+#[utoipa::path(
+    post,
+    path = "/v1/admin/cache/{category}/{key}",
+    params(
+        ("category" = String, Path, description = "Cache category", example = "floor_prices"),
+        ("key" = String, Path, description = "Parquet cache key", example = "NACHO")
+    ),
+    request_body = CacheOverrideRequest,
+    responses(
+        (status = 200, description = "Cache entry written", body = CacheOverrideResponse),
+        (status = 400, description = "Invalid category or key", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+        (status = 403, description = "Admin endpoints disabled", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Writes a JSON value directly to the Parquet cache (and optionally Redis) with a custom TTL, bypassing the upstream API. Requires an admin key in the X-API-Key header.",
+    tag = "Cache"
+)]
+pub async fn admin_cache_override_handler(
+    Path((category, key)): Path<(String, String)>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CacheOverrideRequest>,
+) -> Result<Json<CacheOverrideResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let identity = authenticate_admin(&headers, &state)?;
+    validate_cache_target(&category, &key)?;
+
+    state
+        .kaspacom_service
+        .override_cache_entry(
+            &category,
+            &key,
+            body.redis_key.as_deref(),
+            &body.data,
+            body.ttl_seconds,
+        )
+        .await
+        .map_err(|e| service_error("Failed to write cache entry", e))?;
+
+    tracing::info!(
+        "Cache entry {}/{} overridden by {} (ttl {}s, redis key {:?})",
+        category,
+        key,
+        identity,
+        body.ttl_seconds,
+        body.redis_key
+    );
+
+    Ok(Json(CacheOverrideResponse {
+        category,
+        key,
+        ttl_seconds: body.ttl_seconds,
+        redis_written: body.redis_key.is_some(),
+        set_by: identity,
+    }))
+}
+
+/// Response for pinning or unpinning a cache entry
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CachePinResponse {
+    pub category: String,
+    pub key: String,
+    pub pinned: bool,
+    /// Identity of the API key that changed the pin
+    pub set_by: String,
+}
+
+/// Pin a cached entry so it never expires from the Parquet cache
+#[utoipa::path(
+    post,
+    path = "/v1/admin/cache/{category}/{key}/pin",
+    params(
+        ("category" = String, Path, description = "Cache category", example = "logos"),
+        ("key" = String, Path, description = "Parquet cache key", example = "NACHO")
+    ),
+    responses(
+        (status = 200, description = "Entry pinned", body = CachePinResponse),
+        (status = 400, description = "Invalid category or key", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+        (status = 403, description = "Admin endpoints disabled", body = ErrorResponse),
+        (status = 404, description = "No such cache entry, and it can't be fetched on demand (request it once first)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 502, description = "Fetching the missing entry from upstream failed", body = ErrorResponse)
+    ),
+    description = "Pins a Parquet entry: it is skipped by expiry cleanup and served even past its TTL while a background refresh is attempted. Token info (`tokens/{TICKER}`), logos (`logos/{TICKER}`, `logos/all`, `logos/image_{TICKER}`) and collection info (`krc721/collection_{TICKER}`) that aren't cached yet are fetched first; other entries must be requested once before pinning. Requires an admin key in the X-API-Key header.",
+    tag = "Cache"
+)]
+pub async fn admin_cache_pin_handler(
+    Path((category, key)): Path<(String, String)>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<CachePinResponse>, (StatusCode, Json<ErrorResponse>)> {
+    set_cache_pin(category, key, true, &state, &headers).await
+}
+
+/// Unpin a cached entry so it expires normally again
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/cache/{category}/{key}/pin",
+    params(
+        ("category" = String, Path, description = "Cache category", example = "logos"),
+        ("key" = String, Path, description = "Parquet cache key", example = "NACHO")
+    ),
+    responses(
+        (status = 200, description = "Entry unpinned", body = CachePinResponse),
+        (status = 400, description = "Invalid category or key", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+        (status = 403, description = "Admin endpoints disabled", body = ErrorResponse),
+        (status = 404, description = "No such cache entry", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    description = "Removes the pin from a Parquet entry. Requires an admin key in the X-API-Key header.",
+    tag = "Cache"
+)]
+pub async fn admin_cache_unpin_handler(
+    Path((category, key)): Path<(String, String)>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<CachePinResponse>, (StatusCode, Json<ErrorResponse>)> {
+    set_cache_pin(category, key, false, &state, &headers).await
+}
+
+/// Response for the data-freshness SLA check
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FreshnessResponse {
+    /// "fresh" when every target is within its SLA, otherwise "stale"
+    pub status: String,
+    /// Outcome per configured target
+    pub checks: Vec<FreshnessCheck>,
+    /// `category/key` of each target that is stale or missing
+    pub failed: Vec<String>,
+}
+
+/// Check configured cache entries against their freshness SLA
+#[utoipa::path(
+    get,
+    path = "/v1/admin/freshness",
+    responses(
+        (status = 200, description = "All targets are within their SLA", body = FreshnessResponse),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+        (status = 403, description = "Admin endpoints disabled", body = ErrorResponse),
+        (status = 503, description = "One or more targets are stale or missing", body = FreshnessResponse)
+    ),
+    description = "Reports the age of each Parquet entry listed under `freshness` in config.yaml and whether it is within its `max_age_secs`. Returns 503 if any entry is stale or missing, so monitoring can alert on the status code. Requires an admin key in the X-API-Key header.",
+    tag = "Cache"
+)]
+pub async fn admin_freshness_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<FreshnessResponse>), (StatusCode, Json<ErrorResponse>)> {
+    authenticate_admin(&headers, &state)?;
+
+    let checks = state.kaspacom_service.check_freshness();
+    let failed: Vec<String> = checks
+        .iter()
+        .filter(|c| !c.is_ok())
+        .map(|c| format!("{}/{}", c.category, c.key))
+        .collect();
+
+    let status = if failed.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok((
+        status,
+        Json(FreshnessResponse {
+            status: if failed.is_empty() { "fresh" } else { "stale" }.to_string(),
+            checks,
+            failed,
+        }),
+    ))
+}
+
+async fn set_cache_pin(
+    category: String,
+    key: String,
+    pinned: bool,
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Json<CachePinResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let identity = authenticate_admin(headers, state)?;
+    validate_cache_target(&category, &key)?;
+
+    let found = state
+        .kaspacom_service
+        .pin_cache_entry(&category, &key, pinned)
+        .await
+        .map_err(|e| service_error("Failed to update cache entry", e))?;
+    if !found {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                format!("No cache entry {}/{}", category, key),
+                None,
+                Some("Request the data once to cache it, then pin it".to_string()),
+            )),
+        ));
+    }
+
+    tracing::info!(
+        "Cache entry {}/{} {} by {}",
+        category,
+        key,
+        if pinned { "pinned" } else { "unpinned" },
+        identity
+    );
+
+    Ok(Json(CachePinResponse {
+        category,
+        key,
+        pinned,
+        set_by: identity,
+    }))
+}
+
+/// Request body for a read-only SQL query over the Parquet cache
+#[cfg(feature = "datafusion")]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SqlQueryRequest {
+    /// A single SELECT statement; tables are named after cache categories
+    pub sql: String,
+}
+
+/// Run a read-only SQL query over the Parquet cache
+#[cfg(feature = "datafusion")]
+#[utoipa::path(
+    post,
+    path = "/v1/admin/query",
+    request_body = SqlQueryRequest,
+    responses(
+        (status = 200, description = "Query results", body = crate::infrastructure::SqlQueryResult),
+        (status = 400, description = "Statement rejected or invalid", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+        (status = 403, description = "Admin endpoints disabled", body = ErrorResponse),
+        (status = 408, description = "Query exceeded the time limit", body = ErrorResponse)
+    ),
+    description = "Runs a single read-only SELECT over the Parquet cache with DataFusion. Each cache category (e.g. `historical`, `orders`) is a table with `data` (JSON string) and `cached_at` columns. Results are capped by the configured row and time limits. Requires an admin key in the X-API-Key header.",
+    tag = "Cache"
+)]
+pub async fn admin_sql_query_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<SqlQueryRequest>,
+) -> Result<Json<crate::infrastructure::SqlQueryResult>, (StatusCode, Json<ErrorResponse>)> {
+    use crate::infrastructure::SqlQueryError;
+
+    let identity = authenticate_admin(&headers, &state)?;
+    tracing::info!("SQL query by {}: {}", identity, body.sql);
+
+    state
+        .sql_engine
+        .query(&body.sql)
+        .await
+        .map(Json)
         .map_err(|e| {
+            let (status, code) = match e {
+                SqlQueryError::Rejected(_) => (StatusCode::BAD_REQUEST, "QUERY_REJECTED"),
+                SqlQueryError::Invalid(_) => (StatusCode::BAD_REQUEST, "QUERY_INVALID"),
+                SqlQueryError::Timeout(_) => (StatusCode::REQUEST_TIMEOUT, "QUERY_TIMEOUT"),
+            };
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to get cache stats".to_string(),
-                    details: Some(e.to_string()),
-                }),
+                status,
+                Json(ErrorResponse::new(
+                    "Failed to run SQL query",
+                    Some(code),
+                    Some(e.to_string()),
+                )),
             )
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_errors_are_classified_by_type() {
+        let not_found: anyhow::Error = ApiError::NotFound("no such token".into()).into();
+        let (status, Json(body)) =
+            service_error("Failed to fetch token info", not_found.context("lookup"));
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body.code.as_deref(), Some("NOT_FOUND"));
+        assert_eq!(body.error, "Failed to fetch token info");
+
+        let limited = anyhow::Error::new(RateLimitExceeded {
+            category: "trade-stats".into(),
+            limit: 10,
+            retry_after_secs: 7,
+        });
+        assert_eq!(service_error("x", limited).0, StatusCode::TOO_MANY_REQUESTS);
+
+        let (status, Json(body)) = service_error("x", anyhow::anyhow!("no upstream 404 here"));
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body.code.as_deref(), Some("INTERNAL_ERROR"));
+    }
+
+    #[test]
+    fn test_invalid_queries_and_cursors_carry_validation_code() {
+        let query = SoldOrdersQuery {
+            ticker: None,
+            minutes: Some(0.0),
+            limit: None,
+            after: None,
+            since: None,
+        };
+        let (status, Json(body)) = query.validated_cursor().unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.code.as_deref(), Some("VALIDATION_ERROR"));
+        assert!(body.details.unwrap().starts_with("minutes: "));
+
+        let query = SoldOrdersQuery {
+            minutes: None,
+            after: Some("not a cursor".into()),
+            ..query
+        };
+        let (status, Json(body)) = query.validated_cursor().unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.code.as_deref(), Some("VALIDATION_ERROR"));
+        assert_eq!(body.error, "Invalid pagination cursor");
+    }
+}
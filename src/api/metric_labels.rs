@@ -0,0 +1,177 @@
+//! Bounded label values for client-controlled metric labels.
+//!
+//! Operation names and error codes come from the request, so recording them
+//! verbatim would let a client create unbounded Prometheus series. A
+//! [`LabelInterner`] admits a fixed number of distinct, well-formed values
+//! and maps everything else to [`OTHER_LABEL`]. Values known up front (the
+//! server's own error codes, operation names from config) are seeded at
+//! startup so early arbitrary values can't take their slots.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Label used for values that are malformed or arrive after the cap is reached
+pub const OTHER_LABEL: &str = "other";
+
+/// Longest value admitted as a label
+const MAX_LABEL_LEN: usize = 64;
+
+/// Distinct GraphQL operation names recorded before falling back to `other`
+pub static OPERATION_LABELS: LabelInterner = LabelInterner::new(200);
+
+/// Distinct GraphQL error codes recorded before falling back to `other`
+pub static ERROR_CODE_LABELS: LabelInterner = LabelInterner::new(50);
+
+/// Error codes the GraphQL API itself reports, seeded into [`ERROR_CODE_LABELS`]
+pub const KNOWN_ERROR_CODES: &[&str] = &[
+    "AVAILABLE_TICKERS_ERROR",
+    "COLLECTION_INFO_ERROR",
+    "EMPTY_QUERY",
+    "EXCHANGES_ERROR",
+    "FLOOR_PRICES_ERROR",
+    "HISTORICAL_DATA_ERROR",
+    "HOT_MINTS_ERROR",
+    "INVALID_PERSISTED_QUERY",
+    "KNS_DOMAIN_ERROR",
+    "KNS_LISTED_ORDERS_ERROR",
+    "KNS_SOLD_ORDERS_ERROR",
+    "KNS_TRADE_STATS_ERROR",
+    "LAST_ORDER_SOLD_ERROR",
+    "NFT_METADATA_ERROR",
+    "OPEN_ORDERS_ERROR",
+    "PERSISTED_QUERY_NOT_FOUND",
+    "QUERY_TOO_LARGE",
+    "RATE_LIMITED",
+    "SOLD_ORDERS_ERROR",
+    "TICKER_HISTORY_ERROR",
+    "TICKER_STATS_ERROR",
+    "TOKEN_INFO_ERROR",
+    "TOKEN_LOGOS_ERROR",
+    "TRADE_STATS_ERROR",
+    "UNKNOWN_ERROR",
+    "VALIDATION_ERROR",
+];
+
+/// Seed the label sets with values known at startup
+///
+/// `operations` are the GraphQL operation names clients are expected to send.
+pub fn seed_graphql_labels<'a>(operations: impl IntoIterator<Item = &'a str>) {
+    OPERATION_LABELS.seed(std::iter::once("unknown").chain(operations));
+    ERROR_CODE_LABELS.seed(KNOWN_ERROR_CODES.iter().copied());
+}
+
+/// Interns up to `capacity` distinct label values as `&'static str`
+///
+/// Each admitted value is leaked once, so memory stays bounded by the
+/// capacity (plus the seeded values) no matter how many distinct values are
+/// offered.
+pub struct LabelInterner {
+    capacity: usize,
+    labels: Mutex<Labels>,
+}
+
+struct Labels {
+    values: BTreeSet<&'static str>,
+    /// Values admitted by [`LabelInterner::intern`], counted against the capacity
+    interned: usize,
+}
+
+impl LabelInterner {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            labels: Mutex::new(Labels {
+                values: BTreeSet::new(),
+                interned: 0,
+            }),
+        }
+    }
+
+    /// Admit `values` without using up the capacity
+    ///
+    /// Malformed values are skipped. Seeding a value that was already
+    /// interned leaves its slot taken.
+    pub fn seed<'a>(&self, values: impl IntoIterator<Item = &'a str>) {
+        let mut labels = self.labels.lock().unwrap_or_else(|e| e.into_inner());
+        for value in values {
+            if is_valid_label(value) && !labels.values.contains(value) {
+                labels.values.insert(Box::leak(value.to_string().into_boxed_str()));
+            }
+        }
+    }
+
+    /// Static label for `value`, or [`OTHER_LABEL`] if it can't be admitted
+    pub fn intern(&self, value: &str) -> &'static str {
+        if !is_valid_label(value) {
+            return OTHER_LABEL;
+        }
+        let mut labels = self.labels.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = labels.values.get(value) {
+            return existing;
+        }
+        if labels.interned >= self.capacity {
+            return OTHER_LABEL;
+        }
+        let leaked: &'static str = Box::leak(value.to_string().into_boxed_str());
+        labels.values.insert(leaked);
+        labels.interned += 1;
+        leaked
+    }
+
+    /// Number of distinct values admitted so far, seeded ones included
+    pub fn len(&self) -> usize {
+        self.labels.lock().unwrap_or_else(|e| e.into_inner()).values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// GraphQL name syntax (`[_A-Za-z][_0-9A-Za-z]*`), capped in length
+fn is_valid_label(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+        && value.len() <= MAX_LABEL_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct_values_stay_bounded() {
+        let interner = LabelInterner::new(100);
+        for i in 0..10_000 {
+            interner.intern(&format!("Operation{}", i));
+        }
+        assert_eq!(interner.len(), 100);
+        assert_eq!(interner.intern("Operation9999"), OTHER_LABEL);
+        assert_eq!(interner.intern("Operation0"), "Operation0");
+        assert_eq!(interner.len(), 100);
+    }
+
+    #[test]
+    fn test_malformed_values_map_to_other() {
+        let interner = LabelInterner::new(10);
+        assert_eq!(interner.intern(""), OTHER_LABEL);
+        assert_eq!(interner.intern("has space"), OTHER_LABEL);
+        assert_eq!(interner.intern("1leadingDigit"), OTHER_LABEL);
+        assert_eq!(interner.intern(&"a".repeat(MAX_LABEL_LEN + 1)), OTHER_LABEL);
+        assert_eq!(interner.intern("QUERY_TOO_LARGE"), "QUERY_TOO_LARGE");
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn test_seeded_values_survive_a_full_interner() {
+        let interner = LabelInterner::new(2);
+        interner.seed(["GetFloorPrices", "RATE_LIMITED", "not valid"]);
+        interner.intern("Random1");
+        interner.intern("Random2");
+        assert_eq!(interner.intern("Random3"), OTHER_LABEL);
+        assert_eq!(interner.intern("GetFloorPrices"), "GetFloorPrices");
+        assert_eq!(interner.intern("RATE_LIMITED"), "RATE_LIMITED");
+        assert_eq!(interner.len(), 4);
+    }
+}
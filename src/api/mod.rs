@@ -1,7 +1,17 @@
+pub mod auth;
+pub mod cache_headers;
+pub mod cors;
+pub mod csv;
 pub mod doc;
+pub mod fresh;
 pub mod graphql;
 pub mod handlers;
+pub mod in_flight;
 pub mod kaspacom_handlers;
+pub mod metric_labels;
+pub mod ndjson;
+pub mod persisted_queries;
+pub mod request_id;
 pub mod routes;
 pub mod state;
 
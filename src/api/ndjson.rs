@@ -0,0 +1,129 @@
+//! JSON-lines (NDJSON) streaming for bulk export endpoints.
+//!
+//! Export endpoints can return windows far larger than a paged response.
+//! Rather than serializing the whole set into one JSON array before the
+//! first byte goes out, [`Ndjson`] writes one record per line as the body
+//! is polled, so only the record being sent is held in serialized form.
+//! [`NdjsonBatches`] goes further for cached entries: each batch is only
+//! read from the cache when the previous one has been sent.
+
+use crate::infrastructure::JsonBatches;
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// Media type of NDJSON responses
+pub const APPLICATION_NDJSON: &str = "application/x-ndjson";
+
+/// Records streamed as `application/x-ndjson`, one JSON object per line
+pub struct Ndjson<T>(pub Vec<T>);
+
+/// Serialize one record as a newline-terminated JSON line
+fn to_line<T: Serialize>(record: &T) -> std::io::Result<Bytes> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    Ok(Bytes::from(line))
+}
+
+impl<T> IntoResponse for Ndjson<T>
+where
+    T: Serialize + Send + 'static,
+{
+    fn into_response(self) -> Response {
+        let lines = futures::stream::iter(self.0).map(|record| to_line(&record));
+        (
+            [(header::CONTENT_TYPE, HeaderValue::from_static(APPLICATION_NDJSON))],
+            Body::from_stream(lines),
+        )
+            .into_response()
+    }
+}
+
+/// Cached records streamed as `application/x-ndjson`, one chunk per batch
+///
+/// Each record is checked against `T` before it is written. Batches are
+/// read on the blocking pool; a failed batch ends the body early.
+pub struct NdjsonBatches<T> {
+    batches: JsonBatches,
+    record: PhantomData<fn() -> T>,
+}
+
+impl<T> NdjsonBatches<T> {
+    pub fn new(batches: JsonBatches) -> Self {
+        Self {
+            batches,
+            record: PhantomData,
+        }
+    }
+}
+
+/// Serialize one batch of records as `T`, one line each
+fn batch_to_lines<T: DeserializeOwned + Serialize>(records: Vec<serde_json::Value>) -> std::io::Result<Bytes> {
+    let mut lines = Vec::new();
+    for record in records {
+        let record: T = serde_json::from_value(record)?;
+        serde_json::to_writer(&mut lines, &record)?;
+        lines.push(b'\n');
+    }
+    Ok(Bytes::from(lines))
+}
+
+impl<T> IntoResponse for NdjsonBatches<T>
+where
+    T: DeserializeOwned + Serialize + Send + 'static,
+{
+    fn into_response(self) -> Response {
+        let chunks = futures::stream::unfold(Some(self.batches), |batches| async move {
+            let mut batches = batches?;
+            let (batch, batches) = tokio::task::spawn_blocking(move || (batches.next(), batches))
+                .await
+                .ok()?;
+            match batch? {
+                Ok(records) => Some((batch_to_lines::<T>(records), Some(batches))),
+                Err(e) => Some((Err(std::io::Error::other(e)), None)),
+            }
+        })
+        .filter(|chunk| futures::future::ready(!chunk.as_ref().is_ok_and(Bytes::is_empty)));
+        (
+            [(header::CONTENT_TYPE, HeaderValue::from_static(APPLICATION_NDJSON))],
+            Body::from_stream(chunks),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_records_are_streamed_one_per_line() {
+        let records: Vec<Value> = (0..250).map(|i| serde_json::json!({ "id": i, "note": "a\nb" })).collect();
+        let response = Ndjson(records).into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], APPLICATION_NDJSON);
+
+        let mut chunks = response.into_body().into_data_stream();
+        let mut body = Vec::new();
+        let mut chunk_count = 0;
+        while let Some(chunk) = chunks.next().await {
+            body.extend_from_slice(&chunk.unwrap());
+            chunk_count += 1;
+        }
+        assert_eq!(chunk_count, 250);
+
+        let lines: Vec<Value> = body
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 250);
+        assert_eq!(lines[249]["id"], 249);
+        assert_eq!(lines[0]["note"], "a\nb");
+    }
+}
@@ -0,0 +1,206 @@
+//! Automatic persisted queries (APQ) for the GraphQL endpoint.
+//!
+//! Follows Apollo's protocol: a client first sends only the query's SHA-256
+//! in the `persistedQuery` request extension. If the hash is unknown the
+//! response carries a `PERSISTED_QUERY_NOT_FOUND` error and the client
+//! retries with the full query plus the hash, which registers it. Later
+//! requests send the hash alone. Requests without the extension are
+//! executed as-is.
+//!
+//! Queries are stored in the cache repository (Redis) under
+//! `v1:graphql:apq:<sha256>`. If the store is unavailable, registration
+//! still executes the query and lookups miss, so clients fall back to
+//! sending full queries.
+
+use crate::application::CacheKey;
+use crate::domain::CacheRepository;
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest};
+use async_graphql::{ErrorExtensions, Pos, Request, ServerError, ServerResult};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Request extension carrying the query hash
+pub const PERSISTED_QUERY_EXTENSION: &str = "persistedQuery";
+
+/// How long a registered query is kept (24 hours)
+pub const PERSISTED_QUERY_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// The `persistedQuery` extension payload
+#[derive(Debug, Deserialize)]
+struct PersistedQuery {
+    version: i32,
+    #[serde(rename = "sha256Hash")]
+    sha256_hash: String,
+}
+
+/// Schema extension resolving persisted queries against a cache repository
+pub struct PersistedQueries {
+    store: Arc<dyn CacheRepository>,
+}
+
+impl PersistedQueries {
+    pub fn new(store: Arc<dyn CacheRepository>) -> Self {
+        Self { store }
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueriesExtension {
+            store: self.store.clone(),
+        })
+    }
+}
+
+struct PersistedQueriesExtension {
+    store: Arc<dyn CacheRepository>,
+}
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueriesExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        mut request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let Some(value) = request.extensions.remove(PERSISTED_QUERY_EXTENSION) else {
+            return next.run(ctx, request).await;
+        };
+
+        let persisted: PersistedQuery = value
+            .into_json()
+            .ok()
+            .and_then(|json| serde_json::from_value(json).ok())
+            .ok_or_else(|| invalid("Invalid persistedQuery extension"))?;
+        if persisted.version != 1 {
+            return Err(invalid(format!(
+                "Unsupported persistedQuery version {}, expected 1",
+                persisted.version
+            )));
+        }
+        let hash = persisted.sha256_hash.to_ascii_lowercase();
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid("persistedQuery sha256Hash must be 64 hex characters"));
+        }
+        let key = CacheKey::builder("v1:graphql")
+            .part("apq")
+            .part(&hash)
+            .build()
+            .map_err(|e| invalid(e.to_string()))?;
+
+        if request.query.trim().is_empty() {
+            match self.store.get(key.redis_key()).await {
+                Ok(Some(query)) => {
+                    metrics::counter!("graphql_persisted_queries_total", "result" => "hit").increment(1);
+                    request.query = query;
+                }
+                result => {
+                    if let Err(e) = result {
+                        warn!("Persisted query lookup failed: {}", e);
+                    }
+                    metrics::counter!("graphql_persisted_queries_total", "result" => "miss").increment(1);
+                    return Err(error("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND"));
+                }
+            }
+        } else {
+            if sha256_hex(&request.query) != hash {
+                return Err(invalid("persistedQuery sha256Hash does not match the query"));
+            }
+            match self.store.set(key.redis_key(), &request.query, PERSISTED_QUERY_TTL_SECS).await {
+                Ok(()) => debug!("Registered persisted query {}", hash),
+                Err(e) => warn!("Failed to store persisted query {}: {}", hash, e),
+            }
+            metrics::counter!("graphql_persisted_queries_total", "result" => "registered").increment(1);
+        }
+
+        next.run(ctx, request).await
+    }
+}
+
+/// Lowercase hex SHA-256 of a query
+pub fn sha256_hex(query: &str) -> String {
+    Sha256::digest(query.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn error(message: impl Into<String>, code: &'static str) -> ServerError {
+    async_graphql::Error::new(message)
+        .extend_with(|_, e| e.set("code", code))
+        .into_server_error(Pos::default())
+}
+
+fn invalid(message: impl Into<String>) -> ServerError {
+    error(message, "INVALID_PERSISTED_QUERY")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::graphql::{EmptyMutation, Query};
+    use crate::infrastructure::InMemoryCache;
+    use async_graphql::{EmptySubscription, Schema, Value};
+
+    const QUERY: &str = "{ __typename }";
+
+    fn schema() -> Schema<Query, EmptyMutation, EmptySubscription> {
+        Schema::build(Query, EmptyMutation, EmptySubscription)
+            .extension(PersistedQueries::new(Arc::new(InMemoryCache::new())))
+            .finish()
+    }
+
+    fn request(query: &str, hash: &str) -> Request {
+        let mut request = Request::new(query);
+        request.extensions.insert(
+            PERSISTED_QUERY_EXTENSION.to_string(),
+            Value::from_json(serde_json::json!({ "version": 1, "sha256Hash": hash })).unwrap(),
+        );
+        request
+    }
+
+    fn error_code(response: &async_graphql::Response) -> Option<String> {
+        let extensions = response.errors.first()?.extensions.as_ref()?;
+        match extensions.get("code")? {
+            Value::String(code) => Some(code.clone()),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_hash_then_register_then_execute_by_hash() {
+        let schema = schema();
+        let hash = sha256_hex(QUERY);
+
+        let response = schema.execute(request("", &hash)).await;
+        assert_eq!(error_code(&response).as_deref(), Some("PERSISTED_QUERY_NOT_FOUND"));
+        assert_eq!(response.errors[0].message, "PersistedQueryNotFound");
+
+        let response = schema.execute(request(QUERY, &hash)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let response = schema.execute(request("", &hash)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.data.into_json().unwrap()["__typename"], "Query");
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_or_malformed_hash_is_rejected() {
+        let schema = schema();
+
+        let response = schema.execute(request(QUERY, &sha256_hex("{ other }"))).await;
+        assert_eq!(error_code(&response).as_deref(), Some("INVALID_PERSISTED_QUERY"));
+
+        let response = schema.execute(request("", "not-a-hash")).await;
+        assert_eq!(error_code(&response).as_deref(), Some("INVALID_PERSISTED_QUERY"));
+    }
+
+    #[tokio::test]
+    async fn test_inline_queries_unchanged() {
+        let response = schema().execute(QUERY).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+    }
+}
@@ -0,0 +1,101 @@
+//! Correlation IDs for requests.
+//!
+//! Every request gets an ID: the client's `X-Request-Id` if it sent a usable
+//! one, otherwise a fresh UUID. The ID is recorded on the `http_request`
+//! tracing span (so every log line for the request carries it), attached as
+//! a [`RequestId`] extension, echoed in the `X-Request-Id` response header,
+//! and included in REST `ErrorResponse` bodies and GraphQL error extensions
+//! via [`current_request_id`].
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+/// Header carrying the correlation ID in both directions
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied ID that is reused as-is
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Correlation ID of the current request (request extension)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// Correlation ID of the request being handled, if called within one
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Assign, record and echo the request's correlation ID
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    tracing::Span::current().record("request_id", id.as_str());
+    let header = HeaderValue::from_str(&id).expect("request IDs are visible ASCII");
+    request.headers_mut().insert(REQUEST_ID_HEADER.clone(), header.clone());
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut response = CURRENT_REQUEST_ID.scope(id, next.run(request)).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER.clone(), header);
+    response
+}
+
+/// Non-empty, bounded and made of visible ASCII (safe to log and echo)
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(|| async { current_request_id().unwrap_or_default() }))
+            .layer(axum::middleware::from_fn(request_id))
+    }
+
+    async fn send(request_id: Option<&str>) -> (String, String) {
+        let mut request = Request::get("/");
+        if let Some(id) = request_id {
+            request = request.header("X-Request-Id", id);
+        }
+        let response = app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let header = response.headers()[&REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_supplied_request_id_is_echoed() {
+        let (header, seen_by_handler) = send(Some("client-abc-123")).await;
+        assert_eq!(header, "client-abc-123");
+        assert_eq!(seen_by_handler, "client-abc-123");
+    }
+
+    #[tokio::test]
+    async fn test_missing_or_invalid_request_id_is_generated() {
+        let (header, seen_by_handler) = send(None).await;
+        assert!(Uuid::parse_str(&header).is_ok(), "{}", header);
+        assert_eq!(seen_by_handler, header);
+
+        let (header, _) = send(Some(&"x".repeat(MAX_REQUEST_ID_LEN + 1))).await;
+        assert!(Uuid::parse_str(&header).is_ok(), "{}", header);
+    }
+}
@@ -1,76 +1,56 @@
-use crate::api::doc::ApiDoc;
+use crate::api::auth::require_api_key;
+use crate::api::cache_headers::cache_headers;
+use crate::api::fresh::fresh;
+use crate::api::doc::openapi_spec;
+use crate::api::in_flight::track_in_flight;
 use crate::api::graphql::{create_schema, graphql_handler, graphql_playground};
-use crate::api::handlers::{content_handler, health_handler, metrics_handler, rate_limit_handler, dashboard_handler, dashboard_js_handler, dashboard_css_handler};
+use crate::api::handlers::{content_handler, health_handler, ready_handler, metrics_handler, not_found_handler, rate_limit_handler, dashboard_handler, dashboard_js_handler, dashboard_css_handler};
 use crate::api::kaspacom_handlers::{
     // KRC20 handlers
-    trade_stats_handler, floor_price_handler, sold_orders_handler, last_order_sold_handler,
-    hot_mints_handler, token_info_handler, tokens_logos_handler, open_orders_handler,
-    historical_data_handler,
+    trade_stats_handler, floor_price_handler, sold_orders_handler, sold_orders_export_handler, last_order_sold_handler,
+    hot_mints_handler, token_info_handler, tokens_logos_handler, logo_proxy_handler, open_orders_handler,
+    historical_data_handler, market_overview_handler, token_detail_handler, search_handler,
     // KRC721 handlers
     krc721_mints_handler, krc721_sold_orders_handler, krc721_listed_orders_handler,
     krc721_trade_stats_handler, krc721_hot_mints_handler, krc721_floor_price_handler,
-    krc721_tokens_handler, krc721_collection_info_handler, krc721_metadata_handler,
-    krc721_image_url_handler,
+    krc721_tokens_handler, krc721_collection_info_handler, krc721_rarity_handler, krc721_traits_handler,
+    krc721_metadata_handler, krc721_image_url_handler,
     // KNS handlers
-    kns_sold_orders_handler, kns_trade_stats_handler, kns_listed_orders_handler,
+    kns_sold_orders_handler, kns_trade_stats_handler, kns_listed_orders_handler, kns_domain_handler,
     // Configuration handlers
     available_tokens_handler as kaspa_tokens_handler, token_exchanges_handler, cache_stats_handler,
+    // Exchange index handlers
+    index_status_handler, index_rebuild_handler, ticker_stats_batch_handler, ticker_compare_handler,
+    ticker_pairs_handler, ticker_search_handler, ticker_leaderboard_handler, ticker_history_handler, ticker_timeseries_handler, ticker_latest_handler, ticker_ws_handler,
+    // Admin handlers
+    admin_cache_override_handler, admin_cache_pin_handler, admin_cache_unpin_handler,
+    admin_freshness_handler,
 };
+use crate::api::request_id::request_id;
 use crate::api::state::AppState;
+use crate::infrastructure::telemetry;
 use axum::{routing::{get, post}, Router};
 
 use std::time::Duration;
 use tower::ServiceBuilder;
 use axum::http::HeaderValue;
-use tower_http::cors::{Any, AllowOrigin, CorsLayer};
-use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::CorsLayer;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 use tower_http::set_header::SetResponseHeaderLayer;
 use tower_http::trace::TraceLayer;
 use tracing::Level;
-use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-pub fn create_router(state: AppState, allowed_origins: String) -> Router {
+pub fn create_router(state: AppState, cors: CorsLayer, limits: RequestLimits) -> Router {
     // Create GraphQL schema
     let schema = create_schema(state.clone());
-    // Configure CORS based on configuration
-    let cors = if allowed_origins == "*" {
-        CorsLayer::permissive()
-    } else {
-        // Parse comma-separated origins, filter out invalid ones
-        let origin_values: Vec<HeaderValue> = allowed_origins
-            .split(',')
-            .filter_map(|s| {
-                let trimmed = s.trim();
-                if trimmed.is_empty() {
-                    None
-                } else {
-                    trimmed.parse::<HeaderValue>().ok()
-                }
-            })
-            .collect();
-        
-        if origin_values.is_empty() {
-            tracing::warn!("No valid CORS origins found, falling back to permissive CORS");
-            CorsLayer::permissive()
-        } else if origin_values.len() == 1 {
-            // Single origin
-            CorsLayer::new()
-                .allow_origin(AllowOrigin::exact(origin_values[0].clone()))
-                .allow_methods(Any)
-                .allow_headers(Any)
-        } else {
-            // Multiple origins - use list
-            CorsLayer::new()
-                .allow_origin(AllowOrigin::list(origin_values))
-                .allow_methods(Any)
-                .allow_headers(Any)
-        }
-    };
-
     // Create middleware stack with security headers and observability
     let middleware = ServiceBuilder::new()
+        // Counted for the shutdown drain and `http_requests_in_flight`
+        .layer(axum::middleware::from_fn(track_in_flight))
         // Request tracing and metrics
         .layer(
             TraceLayer::new_for_http()
@@ -79,13 +59,17 @@ pub fn create_router(state: AppState, allowed_origins: String) -> Router {
                     let uri = request.uri();
                     let path = uri.path();
                     
-                    tracing::span!(
+                    let span = tracing::span!(
                         Level::INFO,
                         "http_request",
                         method = %method,
                         path = %path,
-                        uri = %uri
-                    )
+                        uri = %uri,
+                        request_id = tracing::field::Empty
+                    );
+                    // Continue the caller's trace if it sent `traceparent`
+                    telemetry::set_parent_from_headers(&span, request.headers());
+                    span
                 })
                 .on_request(|_request: &axum::http::Request<_>, _span: &tracing::Span| {
                     // Request started
@@ -124,12 +108,8 @@ pub fn create_router(state: AppState, allowed_origins: String) -> Router {
                         .increment(1);
                 })
         )
-        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
-        .layer(PropagateRequestIdLayer::x_request_id())
-        .layer(TimeoutLayer::with_status_code(
-            axum::http::StatusCode::REQUEST_TIMEOUT,
-            Duration::from_secs(60),
-        ))
+        // Correlation ID, recorded on the span above and echoed back
+        .layer(axum::middleware::from_fn(request_id))
         // Security headers
         .layer(SetResponseHeaderLayer::overriding(
             axum::http::header::X_CONTENT_TYPE_OPTIONS,
@@ -143,20 +123,24 @@ pub fn create_router(state: AppState, allowed_origins: String) -> Router {
             axum::http::header::X_XSS_PROTECTION,
             HeaderValue::from_static("1; mode=block"),
         ))
+        // Compress large bodies per Accept-Encoding; CORS headers set inside
+        // are passed through untouched
+        .layer(compression_layer())
         .layer(cors);
 
-    Router::new()
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+    let router = Router::new()
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", openapi_spec()))
         // Dashboard (development)
         .route("/dashboard", get(dashboard_handler))
         .route("/krcbot-dashboard.js", get(dashboard_js_handler))
         .route("/theme.css", get(dashboard_css_handler))
         // System endpoints (no versioning)
         .route("/health", get(health_handler))
+        .route("/health/ready", get(ready_handler))
         .route("/metrics", get(metrics_handler))
         .route("/rate-limit", get(rate_limit_handler))
         // OpenAPI spec (downloadable)
-        .route("/v1/openapi.json", get(|| async { axum::Json(ApiDoc::openapi()) }))
+        .route("/v1/openapi.json", get(|| async { axum::Json(openapi_spec()) }))
         // V1 API endpoints (existing GitHub-based)
         // V1 API endpoints (existing GitHub-based) - moved to bottom
 
@@ -174,10 +158,15 @@ pub fn create_router(state: AppState, allowed_origins: String) -> Router {
         .route("/v1/api/kaspa/trade-stats", get(trade_stats_handler))
         .route("/v1/api/kaspa/floor-price", get(floor_price_handler))
         .route("/v1/api/kaspa/sold-orders", get(sold_orders_handler))
+        .route("/v1/api/kaspa/sold-orders/export", get(sold_orders_export_handler))
         .route("/v1/api/kaspa/last-order-sold", get(last_order_sold_handler))
         .route("/v1/api/kaspa/hot-mints", get(hot_mints_handler))
         .route("/v1/api/kaspa/token-info/{ticker}", get(token_info_handler))
         .route("/v1/api/kaspa/tokens-logos", get(tokens_logos_handler))
+        .route("/v1/api/kaspa/market-overview", get(market_overview_handler))
+        .route("/v1/api/kaspa/token/{ticker}/detail", get(token_detail_handler))
+        .route("/v1/api/kaspa/search", get(search_handler))
+        .route("/v1/api/kaspa/logo/{ticker}", get(logo_proxy_handler))
         .route("/v1/api/kaspa/open-orders", get(open_orders_handler))
         .route("/v1/api/kaspa/historical-data", get(historical_data_handler))
         // KRC721 NFT endpoints
@@ -187,18 +176,51 @@ pub fn create_router(state: AppState, allowed_origins: String) -> Router {
         .route("/v1/api/kaspa/krc721/trade-stats", get(krc721_trade_stats_handler))
         .route("/v1/api/kaspa/krc721/hot-mints", get(krc721_hot_mints_handler))
         .route("/v1/api/kaspa/krc721/floor-price", get(krc721_floor_price_handler))
-        .route("/v1/api/kaspa/krc721/tokens", post(krc721_tokens_handler))
+        .route(
+            "/v1/api/kaspa/krc721/tokens",
+            post(krc721_tokens_handler).layer(krc721_filter_limit()),
+        )
         .route("/v1/api/kaspa/krc721/collection/{ticker}", get(krc721_collection_info_handler))
+        .route("/v1/api/kaspa/krc721/rarity/{ticker}", get(krc721_rarity_handler))
+        .route("/v1/api/kaspa/krc721/traits/{ticker}", get(krc721_traits_handler))
         .route("/v1/api/kaspa/krc721/metadata/{ticker}/{token_id}", get(krc721_metadata_handler))
         .route("/v1/api/kaspa/krc721/image/{ticker}/{token_id}", get(krc721_image_url_handler))
         // KNS Domain endpoints
         .route("/v1/api/kaspa/kns/sold-orders", get(kns_sold_orders_handler))
         .route("/v1/api/kaspa/kns/trade-stats", get(kns_trade_stats_handler))
         .route("/v1/api/kaspa/kns/listed-orders", get(kns_listed_orders_handler))
+        .route("/v1/api/kaspa/kns/domain/{name}", get(kns_domain_handler))
         // Configuration & Cache endpoints
         .route("/v1/api/kaspa/tokens", get(kaspa_tokens_handler))
         .route("/v1/api/kaspa/tokens/{token}/exchanges", get(token_exchanges_handler))
         .route("/v1/api/kaspa/cache/stats", get(cache_stats_handler))
+        // Exchange index (rebuild requires X-API-Key)
+        .route("/v1/api/ticker/index/status", get(index_status_handler))
+        .route("/v1/api/ticker/index/rebuild", post(index_rebuild_handler))
+        .route("/v1/api/ticker/stats/batch", post(ticker_stats_batch_handler))
+        .route("/v1/api/ticker/compare", get(ticker_compare_handler))
+        .route("/v1/api/ticker/pairs", get(ticker_pairs_handler))
+        .route("/v1/api/ticker/search", get(ticker_search_handler))
+        .route("/v1/api/ticker/leaderboard", get(ticker_leaderboard_handler))
+        .route("/v1/api/ticker/{token}/history", get(ticker_history_handler))
+        .route("/v1/api/ticker/{token}/timeseries", get(ticker_timeseries_handler))
+        .route("/v1/api/ticker/{token}/latest", get(ticker_latest_handler))
+        .route("/v1/api/ticker/{token}/ws", get(ticker_ws_handler))
+        // Admin endpoints (require X-API-Key)
+        .route("/v1/admin/cache/{category}/{key}", post(admin_cache_override_handler))
+        .route(
+            "/v1/admin/cache/{category}/{key}/pin",
+            post(admin_cache_pin_handler).delete(admin_cache_unpin_handler),
+        )
+        .route("/v1/admin/freshness", get(admin_freshness_handler));
+
+    #[cfg(feature = "datafusion")]
+    let router = router.route(
+        "/v1/admin/query",
+        post(crate::api::kaspacom_handlers::admin_sql_query_handler),
+    );
+
+    let router = router
         // GraphQL endpoint (schema passed via extension layer)
         .route("/graphql", get(graphql_playground).post(graphql_handler))
         // Legacy route for backwards compatibility (can be removed later)
@@ -208,7 +230,159 @@ pub fn create_router(state: AppState, allowed_origins: String) -> Router {
             "/v1/api/{source}/{owner}/{repo}/{*path}",
             get(content_handler),
         )
-        .layer(axum::Extension(schema))
+        // Uniform JSON 404 for unknown routes
+        .fallback(not_found_handler)
+        .layer(axum::middleware::from_fn_with_state(state.clone(), fresh))
+        .layer(axum::middleware::from_fn(cache_headers))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), require_api_key))
+        .layer(axum::Extension(schema));
+
+    with_request_limits(router, &limits)
         .layer(middleware)
         .with_state(state)
 }
+
+/// Apply the global timeout and body-size limit
+fn with_request_limits<S>(router: Router<S>, limits: &RequestLimits) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(RequestBodyLimitLayer::new(limits.max_body_bytes))
+        .layer(TimeoutLayer::with_status_code(
+            axum::http::StatusCode::REQUEST_TIMEOUT,
+            limits.timeout,
+        ))
+}
+
+/// Largest accepted body for the KRC721 tokens filter
+const KRC721_FILTER_MAX_BYTES: usize = 16 * 1024;
+
+/// Body limit for the KRC721 tokens filter route
+fn krc721_filter_limit() -> RequestBodyLimitLayer {
+    RequestBodyLimitLayer::new(KRC721_FILTER_MAX_BYTES)
+}
+
+/// Global limits applied to every request
+#[derive(Debug, Clone)]
+pub struct RequestLimits {
+    /// Requests still running after this are answered with 408
+    pub timeout: Duration,
+    /// Larger request bodies are rejected with 413
+    pub max_body_bytes: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            // Longer than the 30s upstream client timeout
+            timeout: Duration::from_secs(35),
+            max_body_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Responses smaller than this are sent uncompressed (e.g. `/health`)
+const COMPRESSION_MIN_BYTES: u16 = 1024;
+
+/// gzip/br/deflate compression for responses above [`COMPRESSION_MIN_BYTES`]
+///
+/// Keeps the default exclusions (images, SSE, gRPC).
+fn compression_layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .compress_when(DefaultPredicate::new().and(SizeAbove::new(COMPRESSION_MIN_BYTES)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/large", get(|| async { axum::Json(vec!["sold-order"; 500]) }))
+            .route("/health", get(|| async { axum::Json(serde_json::json!({"status": "ok"})) }))
+            .layer(compression_layer())
+    }
+
+    async fn encoding(path: &str) -> Option<String> {
+        let request = Request::get(path)
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_large_json_is_gzipped() {
+        assert_eq!(encoding("/large").await.as_deref(), Some("gzip"));
+    }
+
+    #[tokio::test]
+    async fn test_small_response_is_not_compressed() {
+        assert_eq!(encoding("/health").await, None);
+    }
+
+    fn post_json(path: &str, bytes: usize) -> Request<Body> {
+        let body = format!("{{\"filter\":\"{}\"}}", "x".repeat(bytes));
+        Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn echo(axum::Json(value): axum::Json<serde_json::Value>) -> axum::Json<serde_json::Value> {
+        axum::Json(value)
+    }
+
+    #[tokio::test]
+    async fn test_krc721_filter_body_limit() {
+        let app = Router::new().route("/filter", post(echo).layer(krc721_filter_limit()));
+
+        let ok = app.clone().oneshot(post_json("/filter", 1024)).await.unwrap();
+        assert_eq!(ok.status(), StatusCode::OK);
+
+        let too_large = app.oneshot(post_json("/filter", KRC721_FILTER_MAX_BYTES)).await.unwrap();
+        assert_eq!(too_large.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_global_body_limit() {
+        let limits = RequestLimits {
+            max_body_bytes: 4096,
+            ..RequestLimits::default()
+        };
+        let app = with_request_limits(Router::new().route("/echo", post(echo)), &limits);
+
+        let response = app.oneshot(post_json("/echo", 8192)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_request_times_out() {
+        let limits = RequestLimits {
+            timeout: Duration::from_secs(35),
+            ..RequestLimits::default()
+        };
+        let app = with_request_limits(
+            Router::new().route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    "done"
+                }),
+            ),
+            &limits,
+        );
+
+        let request = Request::get("/slow").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+}
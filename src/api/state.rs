@@ -1,5 +1,7 @@
-use crate::application::{ContentService, KaspaComService, TickerService};
-use crate::infrastructure::RateLimiter;
+use crate::api::auth::ApiKeyAuth;
+use crate::application::{ContentService, ExchangeIndex, KaspaComService, PriceTickFeeds, TickerService};
+use crate::infrastructure::{RateLimiter, RedisRepository};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -7,6 +9,21 @@ pub struct AppState {
     pub content_service: Arc<ContentService>,
     pub ticker_service: Arc<TickerService>,
     pub kaspacom_service: Arc<KaspaComService>,
+    /// Live price ticks for ticker WebSocket clients
+    pub price_ticks: Arc<PriceTickFeeds>,
     pub rate_limiter: Arc<RateLimiter>,
+    /// Admin API keys mapped to the identity of their holder
+    pub admin_api_keys: Arc<HashMap<String, String>>,
+    /// Client API keys and per-key limits for protected routes
+    pub api_key_auth: Arc<ApiKeyAuth>,
+    /// Redis connection used by the readiness probe
+    pub redis_repo: Arc<RedisRepository>,
+    /// Local data path, if explicitly configured via `DATA_PATH`
+    pub data_path: Option<String>,
+    /// Exchange index, present when the local data path is available
+    pub exchange_index: Option<Arc<ExchangeIndex>>,
+    /// Read-only SQL engine over the Parquet cache
+    #[cfg(feature = "datafusion")]
+    pub sql_engine: Arc<crate::infrastructure::ParquetSqlEngine>,
 }
 
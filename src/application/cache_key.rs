@@ -0,0 +1,348 @@
+//! Cache key construction.
+//!
+//! Every Redis and Parquet key used by the services is built through
+//! [`CacheKey`] so components are normalized the same way everywhere:
+//! the namespace is lowercased, tickers are canonicalized, and minute
+//! windows are rendered as whole numbers. This keeps `nacho` and `NACHO`
+//! (or `60` and `60.0`) from fragmenting the cache into separate entries.
+//!
+//! Keys derived from structured input hash its [`canonical_json`] form, so
+//! `HashMap` iteration order can't give one logical value several keys.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::domain::{normalize_ticker, ApiError};
+
+/// Placeholder component used when an optional filter is absent
+const ALL: &str = "all";
+
+/// A validated cache key with its Redis and Parquet forms.
+///
+/// The Redis key is the namespace followed by every component, joined with
+/// `:` (e.g. `kaspa:sold_orders:NACHO:60`). The Parquet key is the
+/// components alone joined with `_` (e.g. `NACHO_60`), since the namespace
+/// is already implied by the Parquet category directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    redis: String,
+    parquet: String,
+}
+
+impl CacheKey {
+    /// Start building a key under `namespace` (e.g. `kaspa:krc721`)
+    pub fn builder(namespace: &str) -> CacheKeyBuilder {
+        CacheKeyBuilder {
+            namespace: namespace.trim().to_lowercase(),
+            components: Vec::new(),
+            parquet_name: None,
+        }
+    }
+
+    /// Key used in Redis
+    pub fn redis_key(&self) -> &str {
+        &self.redis
+    }
+
+    /// Key used within a Parquet cache category
+    pub fn parquet_key(&self) -> &str {
+        &self.parquet
+    }
+}
+
+/// Builder for [`CacheKey`]; components are appended in call order.
+#[derive(Debug, Clone)]
+pub struct CacheKeyBuilder {
+    namespace: String,
+    components: Vec<String>,
+    parquet_name: Option<String>,
+}
+
+impl CacheKeyBuilder {
+    /// Append a literal component (time frame, interval, sub-resource, ...)
+    pub fn part(mut self, part: impl ToString) -> Self {
+        self.components.push(part.to_string().trim().to_string());
+        self
+    }
+
+    /// Append a canonicalized ticker
+    pub fn ticker(self, ticker: &str) -> Self {
+        self.part(normalize_ticker(ticker))
+    }
+
+    /// Append a canonicalized ticker, or `all` when no ticker filter is set
+    pub fn ticker_or_all(self, ticker: Option<&str>) -> Self {
+        match ticker {
+            Some(t) => self.ticker(t),
+            None => self.part(ALL),
+        }
+    }
+
+    /// Append a canonicalized ticker only when a ticker filter is set
+    pub fn optional_ticker(self, ticker: Option<&str>) -> Self {
+        match ticker {
+            Some(t) => self.ticker(t),
+            None => self,
+        }
+    }
+
+    /// Append a literal component only when it is set
+    pub fn optional_part(self, part: Option<&str>) -> Self {
+        match part {
+            Some(p) => self.part(p),
+            None => self,
+        }
+    }
+
+    /// Use `name` as the Parquet key instead of the joined components
+    ///
+    /// Only for keys whose Parquet name predates the builder, so existing
+    /// cache entries stay reachable.
+    pub fn parquet_name(mut self, name: &str) -> Self {
+        self.parquet_name = Some(name.trim().to_string());
+        self
+    }
+
+    /// Append a minute window as a whole number (`60.0` -> `60`)
+    pub fn minutes(self, minutes: f64) -> Self {
+        self.part(minutes as i64)
+    }
+
+    /// Validate all components and produce the key
+    pub fn build(self) -> Result<CacheKey> {
+        if self.namespace.is_empty()
+            || !self
+                .namespace
+                .split(':')
+                .all(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        {
+            bail!("Invalid cache key namespace '{}'", self.namespace);
+        }
+        if self.components.is_empty() {
+            bail!("Cache key '{}' has no components", self.namespace);
+        }
+        for component in self.components.iter().chain(&self.parquet_name) {
+            if !is_valid_component(component) {
+                // Components come from request input (tickers, time frames)
+                return Err(ApiError::Validation(format!(
+                    "Invalid cache key component '{}'",
+                    component
+                ))
+                .into());
+            }
+        }
+
+        Ok(CacheKey {
+            redis: format!("{}:{}", self.namespace, self.components.join(":")),
+            parquet: self
+                .parquet_name
+                .unwrap_or_else(|| self.components.join("_")),
+        })
+    }
+}
+
+/// Compact JSON for `value` with object keys sorted at every level
+///
+/// Unlike `serde_json::to_string`, the output doesn't depend on the
+/// iteration order of maps inside `value`, so equal values always produce
+/// identical bytes. Use it for anything that hashes serialized output.
+pub fn canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    Ok(render_sorted(&serde_json::to_value(value)?))
+}
+
+/// First 16 bytes of the SHA-256 of `value`'s [`canonical_json`], as hex
+///
+/// Short enough for a key component, long enough that distinct inputs
+/// won't collide in practice.
+pub fn content_hash<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let digest = Sha256::digest(canonical_json(value)?.as_bytes());
+    Ok(digest[..16].iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn render_sorted(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!("{}:{}", Value::String(key.clone()), render_sorted(value))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(render_sorted).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Components become file names in the Parquet store, so only a safe
+/// character set is allowed and leading dots are rejected.
+fn is_valid_component(component: &str) -> bool {
+    !component.is_empty()
+        && !component.starts_with('.')
+        && component
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(builder: CacheKeyBuilder) -> (String, String) {
+        let key = builder.build().unwrap();
+        (key.redis_key().to_string(), key.parquet_key().to_string())
+    }
+
+    #[test]
+    fn test_krc20_keys() {
+        assert_eq!(
+            key(CacheKey::builder("kaspa:trade_stats")
+                .part("6h")
+                .optional_ticker(Some("nacho"))),
+            ("kaspa:trade_stats:6h:NACHO".into(), "6h_NACHO".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa:trade_stats")
+                .part("6h")
+                .optional_ticker(None)),
+            ("kaspa:trade_stats:6h".into(), "6h".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa:sold_orders")
+                .ticker_or_all(Some(" Nacho "))
+                .minutes(60.0)),
+            ("kaspa:sold_orders:NACHO:60".into(), "NACHO_60".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa")
+                .part("last_order_sold")
+                .parquet_name("last")),
+            ("kaspa:last_order_sold".into(), "last".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa:historical")
+                .ticker("slow")
+                .part("1w")),
+            ("kaspa:historical:SLOW:1w".into(), "SLOW_1w".into())
+        );
+    }
+
+    #[test]
+    fn test_nested_namespace_keys() {
+        assert_eq!(
+            key(CacheKey::builder("KASPA:KRC721")
+                .part("sold")
+                .ticker_or_all(None)
+                .minutes(1440.9)),
+            ("kaspa:krc721:sold:all:1440".into(), "sold_all_1440".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa:krc721")
+                .part("metadata")
+                .ticker("kaspunks")
+                .part(42)),
+            (
+                "kaspa:krc721:metadata:KASPUNKS:42".into(),
+                "metadata_KASPUNKS_42".into()
+            )
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa:krc721")
+                .part("stats")
+                .part("1d")
+                .optional_ticker(None)),
+            ("kaspa:krc721:stats:1d".into(), "stats_1d".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("kaspa:kns")
+                .part("stats")
+                .part("1d")
+                .optional_part(Some("kas"))),
+            ("kaspa:kns:stats:1d:kas".into(), "stats_1d_kas".into())
+        );
+        assert_eq!(
+            key(CacheKey::builder("v1:ticker")
+                .ticker("slow")
+                .part("stats")
+                .part("7d")),
+            ("v1:ticker:SLOW:stats:7d".into(), "SLOW_stats_7d".into())
+        );
+    }
+
+    #[test]
+    fn test_equivalent_inputs_share_a_key() {
+        let a = CacheKey::builder("kaspa:sold_orders")
+            .ticker("nacho")
+            .minutes(60.0)
+            .build()
+            .unwrap();
+        let b = CacheKey::builder("kaspa:sold_orders")
+            .ticker("NACHO")
+            .minutes(60.4)
+            .build()
+            .unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_canonical_json_is_byte_identical_for_equal_maps() {
+        use std::collections::HashMap;
+
+        // Separate HashMaps get separate random seeds, so their iteration
+        // (and plain serde_json) order differs between instances
+        let build = |order: &[&str]| -> HashMap<String, HashMap<String, u32>> {
+            order
+                .iter()
+                .map(|k| {
+                    (
+                        k.to_string(),
+                        order.iter().map(|v| (v.to_string(), 1)).collect(),
+                    )
+                })
+                .collect()
+        };
+        let keys = ["hat", "eyes", "mouth", "background", "skin", "accessory"];
+        let mut reversed = keys;
+        reversed.reverse();
+
+        let first = canonical_json(&build(&keys)).unwrap();
+        assert_eq!(first, canonical_json(&build(&keys)).unwrap());
+        assert_eq!(first, canonical_json(&build(&reversed)).unwrap());
+        assert!(
+            first.starts_with(r#"{"accessory":{"accessory":1,"background":1"#),
+            "{}",
+            first
+        );
+        assert_eq!(
+            content_hash(&build(&keys)).unwrap(),
+            content_hash(&build(&reversed)).unwrap()
+        );
+        assert_eq!(content_hash(&build(&keys)).unwrap().len(), 32);
+    }
+
+    #[test]
+    fn test_rejects_unsafe_components() {
+        assert!(CacheKey::builder("kaspa:logos")
+            .ticker("../etc")
+            .build()
+            .is_err());
+        assert!(CacheKey::builder("kaspa:logos")
+            .ticker("NA:CHO")
+            .build()
+            .is_err());
+        assert!(CacheKey::builder("kaspa:logos").part("").build().is_err());
+        assert!(CacheKey::builder("kaspa:logos").build().is_err());
+        assert!(CacheKey::builder("kaspa::logos")
+            .part("all")
+            .build()
+            .is_err());
+    }
+}
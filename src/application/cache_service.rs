@@ -5,12 +5,16 @@
 //! 2. Parquet (warm/cold cache) - for persistent local storage
 //! 3. Remote API - as a last resort when cache misses
 
-use crate::domain::CacheRepository;
-use crate::infrastructure::{KaspaComClient, ParquetStore, RateLimiter, RedisRepository};
+use crate::domain::{ApiError, CacheRepository};
+use crate::infrastructure::{
+    cache_categories, CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStatus, JsonBatches, KaspaComClient,
+    ParquetStore, RateLimitExceeded, RateLimiter,
+};
 use anyhow::Result;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
@@ -33,19 +37,261 @@ pub mod ttl {
     /// Static data - logos, metadata (1 hour Redis, 24 hours Parquet)
     pub const STATIC_REDIS_SECS: u64 = 3600;
     pub const STATIC_PARQUET_SECS: u64 = 86400;
+
+    /// Proxied assets - logo image bytes (1 hour Redis, 7 days Parquet)
+    pub const ASSET_REDIS_SECS: u64 = 3600;
+    pub const ASSET_PARQUET_SECS: u64 = 604800;
+
+    /// Upstream 404s - remembered in Redis only, so unknown tickers don't
+    /// spend rate-limit budget on every request
+    pub const NEGATIVE_REDIS_SECS: u64 = 60;
+}
+
+/// Redis and Parquet TTLs per data class, defaulting to the [`ttl`] constants
+///
+/// Loaded from the `cache_ttls` section of `config.yaml`; any field left
+/// out keeps its default. Longer TTLs conserve upstream rate-limit budget,
+/// shorter ones serve fresher data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TtlConfig {
+    pub hot_redis_secs: u64,
+    pub hot_parquet_secs: u64,
+    pub warm_redis_secs: u64,
+    pub warm_parquet_secs: u64,
+    pub cold_redis_secs: u64,
+    pub cold_parquet_secs: u64,
+    pub static_redis_secs: u64,
+    pub static_parquet_secs: u64,
+}
+
+impl Default for TtlConfig {
+    fn default() -> Self {
+        Self {
+            hot_redis_secs: ttl::HOT_REDIS_SECS,
+            hot_parquet_secs: ttl::HOT_PARQUET_SECS,
+            warm_redis_secs: ttl::WARM_REDIS_SECS,
+            warm_parquet_secs: ttl::WARM_PARQUET_SECS,
+            cold_redis_secs: ttl::COLD_REDIS_SECS,
+            cold_parquet_secs: ttl::COLD_PARQUET_SECS,
+            static_redis_secs: ttl::STATIC_REDIS_SECS,
+            static_parquet_secs: ttl::STATIC_PARQUET_SECS,
+        }
+    }
+}
+
+impl TtlConfig {
+    /// `(redis, parquet)` TTLs for hot data: floor prices, recent orders
+    pub fn hot(&self) -> (u64, u64) {
+        (self.hot_redis_secs, self.hot_parquet_secs)
+    }
+
+    /// `(redis, parquet)` TTLs for warm data: trade stats, token stats
+    pub fn warm(&self) -> (u64, u64) {
+        (self.warm_redis_secs, self.warm_parquet_secs)
+    }
+
+    /// `(redis, parquet)` TTLs for cold data: token info, historical data
+    pub fn cold(&self) -> (u64, u64) {
+        (self.cold_redis_secs, self.cold_parquet_secs)
+    }
+
+    /// `(redis, parquet)` TTLs for static data: logos, metadata
+    pub fn static_data(&self) -> (u64, u64) {
+        (self.static_redis_secs, self.static_parquet_secs)
+    }
+}
+
+/// Cache tiers, as used in the `tier` label of the hit/miss metrics
+pub mod tier {
+    pub const REDIS: &str = "redis";
+    pub const PARQUET: &str = "parquet";
+    /// Fetched from upstream for this request (misses only, not a metric label)
+    pub const ORIGIN: &str = "origin";
+}
+
+/// Prefix of Redis tombstones for upstream 404s
+///
+/// Not valid JSON, so a tombstone can never deserialize as cached data.
+const NOT_FOUND_MARKER: &str = "!not_found:";
+
+/// A cached entry that contributed to a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServedEntry {
+    pub category: String,
+    pub key: String,
+    /// Unix timestamp the entry was written
+    pub cached_at: i64,
+    /// Seconds until the entry is due to be refetched
+    pub remaining_ttl_secs: u64,
+    /// Tier the entry was served from ([`tier`])
+    pub tier: &'static str,
+    /// Seconds since the entry was fetched upstream; 0 from origin
+    pub age_secs: u64,
+}
+
+/// Cache entries served while handling one request (see [`track_served_entries`])
+#[derive(Debug, Default)]
+pub struct ServedEntries {
+    pub entries: Vec<ServedEntry>,
+    /// Set when data was served without Parquet metadata to describe it
+    pub untracked: bool,
+}
+
+impl ServedEntries {
+    fn describable(&self) -> bool {
+        !self.untracked && !self.entries.is_empty()
+    }
+
+    /// Strong ETag derived from each served key and when it was cached
+    ///
+    /// Stable across processes, so every replica produces the same tag for
+    /// the same data. `None` if the response isn't fully described by
+    /// cache entries.
+    pub fn etag(&self) -> Option<String> {
+        if !self.describable() {
+            return None;
+        }
+        let mut parts: Vec<String> = self
+            .entries
+            .iter()
+            .map(|e| format!("{}/{}@{}", e.category, e.key, e.cached_at))
+            .collect();
+        parts.sort();
+        parts.dedup();
+        Some(format!("\"{:016x}\"", fnv1a(parts.join("\n").as_bytes())))
+    }
+
+    /// Seconds the response stays valid: the shortest remaining TTL
+    pub fn max_age_secs(&self) -> Option<u64> {
+        if !self.describable() {
+            return None;
+        }
+        self.entries.iter().map(|e| e.remaining_ttl_secs).min()
+    }
+
+    /// `HIT` if every entry came from a cache tier, `MISS` if any was
+    /// fetched from origin
+    pub fn cache_status(&self) -> Option<&'static str> {
+        if !self.describable() {
+            return None;
+        }
+        Some(if self.entries.iter().any(|e| e.tier == tier::ORIGIN) { "MISS" } else { "HIT" })
+    }
+
+    /// The furthest tier any entry came from (redis < parquet < origin)
+    pub fn tier(&self) -> Option<&'static str> {
+        if !self.describable() {
+            return None;
+        }
+        [tier::ORIGIN, tier::PARQUET, tier::REDIS]
+            .into_iter()
+            .find(|t| self.entries.iter().any(|e| e.tier == *t))
+    }
+
+    /// Age of the oldest data in the response, in seconds
+    pub fn age_secs(&self) -> Option<u64> {
+        if !self.describable() {
+            return None;
+        }
+        self.entries.iter().map(|e| e.age_secs).max()
+    }
+}
+
+/// FNV-1a, 64-bit: a stable hash for ETags
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+tokio::task_local! {
+    static SERVED_ENTRIES: RefCell<ServedEntries>;
+    static RESPONSE_MAX_AGE: Cell<Option<u64>>;
+}
+
+/// Run `future`, collecting the shortest max-age reported within it by
+/// [`record_response_max_age`]
+///
+/// For responses built from values cached outside [`CacheService`] (plain
+/// Redis keys), which [`track_served_entries`] can't describe.
+pub async fn track_response_max_age<F: Future>(future: F) -> (F::Output, Option<u64>) {
+    RESPONSE_MAX_AGE
+        .scope(Cell::new(None), async move {
+            let output = future.await;
+            (output, RESPONSE_MAX_AGE.with(Cell::get))
+        })
+        .await
+}
+
+/// Whether [`record_response_max_age`] calls on this task are collected
+pub fn is_tracking_response_max_age() -> bool {
+    RESPONSE_MAX_AGE.try_with(|_| ()).is_ok()
+}
+
+/// Report that the response being built stays fresh for `secs` seconds
+///
+/// No-op outside [`track_response_max_age`].
+pub fn record_response_max_age(secs: u64) {
+    let _ = RESPONSE_MAX_AGE.try_with(|max_age| {
+        max_age.set(Some(max_age.get().map_or(secs, |current| current.min(secs))));
+    });
+}
+
+/// Run `future`, collecting every entry [`CacheService`] serves within it
+///
+/// Entries are only collected on the calling task, so work moved onto
+/// spawned tasks isn't seen.
+pub async fn track_served_entries<F: Future>(future: F) -> (F::Output, ServedEntries) {
+    SERVED_ENTRIES
+        .scope(RefCell::new(ServedEntries::default()), async move {
+            let output = future.await;
+            (output, SERVED_ENTRIES.with(RefCell::take))
+        })
+        .await
+}
+
+tokio::task_local! {
+    static BYPASS_CACHE: ();
+}
+
+/// Run `future` with cache reads bypassed
+///
+/// Every [`CacheService::get_cached`] and [`CacheService::get_cached_json`]
+/// lookup within it refetches through [`CacheService::refresh`], which
+/// still populates both tiers and is still rate limited. Like
+/// [`track_served_entries`], only lookups on the calling task are affected.
+pub async fn bypass_cache<F: Future>(future: F) -> F::Output {
+    BYPASS_CACHE.scope((), future).await
+}
+
+fn is_bypassing_cache() -> bool {
+    BYPASS_CACHE.try_with(|_| ()).is_ok()
+}
+
+/// What a Redis lookup found
+enum RedisEntry<T> {
+    Data(T),
+    /// Tombstone for an upstream 404, with the upstream message
+    NotFound(String),
 }
 
 /// Per-category cache statistics
 #[derive(Debug, Default)]
 struct CategoryCacheStats {
-    hits: AtomicU64,
+    redis_hits: AtomicU64,
+    parquet_hits: AtomicU64,
     misses: AtomicU64,
     requests: AtomicU64,
 }
 
 /// Tiered cache service combining Redis (hot) and Parquet (warm/cold) caching
+#[derive(Clone)]
 pub struct CacheService {
-    redis: Arc<RedisRepository>,
+    redis: Arc<dyn CacheRepository>,
     parquet: Arc<ParquetStore>,
     client: Arc<KaspaComClient>,
     rate_limiter: Arc<RateLimiter>,
@@ -53,12 +299,20 @@ pub struct CacheService {
     cache_hits: Arc<AtomicU64>,
     /// Per-category cache statistics
     category_stats: Arc<Mutex<HashMap<String, CategoryCacheStats>>>,
+    /// Redis keys of pinned entries with a background refresh in flight
+    pinned_refreshes: Arc<Mutex<HashSet<String>>>,
+    /// How long upstream 404s are remembered; 0 disables negative caching
+    negative_ttl_secs: u64,
+    /// Fails upstream calls fast while kaspa.com is down
+    breaker: Arc<CircuitBreaker>,
+    /// TTLs services pass for each data class
+    ttls: TtlConfig,
 }
 
 impl CacheService {
     /// Create a new cache service
     pub fn new(
-        redis: Arc<RedisRepository>,
+        redis: Arc<dyn CacheRepository>,
         parquet: Arc<ParquetStore>,
         client: Arc<KaspaComClient>,
         rate_limiter: Arc<RateLimiter>,
@@ -70,24 +324,108 @@ impl CacheService {
             rate_limiter,
             cache_hits: Arc::new(AtomicU64::new(0)),
             category_stats: Arc::new(Mutex::new(HashMap::new())),
+            pinned_refreshes: Arc::new(Mutex::new(HashSet::new())),
+            negative_ttl_secs: ttl::NEGATIVE_REDIS_SECS,
+            breaker: Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default())),
+            ttls: TtlConfig::default(),
         }
     }
 
-    /// Record a cache hit for a category
-    fn record_category_hit(&self, category: &str) {
+    /// Set how long upstream 404s are cached (0 disables negative caching)
+    pub fn with_negative_ttl(mut self, negative_ttl_secs: u64) -> Self {
+        self.negative_ttl_secs = negative_ttl_secs;
+        self
+    }
+
+    /// Override the per-class TTLs (default: the [`ttl`] constants)
+    pub fn with_ttls(mut self, ttls: TtlConfig) -> Self {
+        self.ttls = ttls;
+        self
+    }
+
+    /// TTLs to cache each data class with
+    pub fn ttls(&self) -> &TtlConfig {
+        &self.ttls
+    }
+
+    /// Replace the upstream circuit breaker (default: 5 failures, 30s cooldown)
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Arc::new(breaker);
+        self
+    }
+
+    /// State of the upstream circuit breaker
+    pub fn circuit_breaker_status(&self) -> CircuitBreakerStatus {
+        self.breaker.status()
+    }
+
+    /// Note an entry served from `tier` for [`track_served_entries`], if tracking
+    ///
+    /// The entry's metadata file is read on the blocking pool, so Redis hits
+    /// don't stall the runtime on disk IO.
+    async fn record_served(
+        &self,
+        category: &str,
+        key: &str,
+        parquet_ttl_secs: u64,
+        tier: &'static str,
+    ) {
+        if SERVED_ENTRIES.try_with(|_| ()).is_err() {
+            return;
+        }
+        let parquet = self.parquet.clone();
+        let (meta_category, meta_key) = (category.to_string(), key.to_string());
+        let meta = tokio::task::spawn_blocking(move || parquet.metadata(&meta_category, &meta_key))
+            .await
+            .ok()
+            .flatten();
+        let _ = SERVED_ENTRIES.try_with(|served| {
+            let mut served = served.borrow_mut();
+            match meta {
+                Some(meta) => served.entries.push(ServedEntry {
+                    category: category.to_string(),
+                    key: key.to_string(),
+                    cached_at: meta.cached_at,
+                    remaining_ttl_secs: parquet_ttl_secs.saturating_sub(meta.age_secs()),
+                    tier,
+                    age_secs: if tier == tier::ORIGIN { 0 } else { meta.age_secs() },
+                }),
+                None => served.untracked = true,
+            }
+        });
+    }
+
+    /// Record a cache hit for a category in the tier that served it
+    ///
+    /// Also exported as `cache_hits_total{category,tier}`; a Parquet hit
+    /// counts as a Redis miss, since Redis was checked first.
+    fn record_category_hit(&self, category: &str, served_by: &'static str) {
+        metrics::counter!("cache_hits_total", "category" => category.to_string(), "tier" => served_by)
+            .increment(1);
+        if served_by == tier::PARQUET {
+            metrics::counter!("cache_misses_total", "category" => category.to_string(), "tier" => tier::REDIS)
+                .increment(1);
+        }
+
         if let Ok(mut stats) = self.category_stats.lock() {
-            let cat_stats = stats.entry(category.to_string()).or_insert_with(|| CategoryCacheStats::default());
-            cat_stats.hits.fetch_add(1, Ordering::Relaxed);
+            let cat_stats = stats.entry(category.to_string()).or_default();
+            let tier_hits = if served_by == tier::PARQUET { &cat_stats.parquet_hits } else { &cat_stats.redis_hits };
+            tier_hits.fetch_add(1, Ordering::Relaxed);
             cat_stats.requests.fetch_add(1, Ordering::Relaxed);
         } else {
             warn!("Failed to acquire lock for category stats (mutex poisoned)");
         }
     }
 
-    /// Record a cache miss for a category
+    /// Record a cache miss for a category (missed by both tiers)
     fn record_category_miss(&self, category: &str) {
+        for missed in [tier::REDIS, tier::PARQUET] {
+            metrics::counter!("cache_misses_total", "category" => category.to_string(), "tier" => missed)
+                .increment(1);
+        }
+
         if let Ok(mut stats) = self.category_stats.lock() {
-            let cat_stats = stats.entry(category.to_string()).or_insert_with(|| CategoryCacheStats::default());
+            let cat_stats = stats.entry(category.to_string()).or_default();
             cat_stats.misses.fetch_add(1, Ordering::Relaxed);
             cat_stats.requests.fetch_add(1, Ordering::Relaxed);
         } else {
@@ -95,37 +433,106 @@ impl CacheService {
         }
     }
 
+    /// Record the time of a successful upstream fetch for a category
+    ///
+    /// Exposed as the `kaspacom_last_success_timestamp{endpoint}` gauge so
+    /// alerting can detect stale data independently of request counts.
+    fn record_upstream_success(&self, category: &str) {
+        metrics::gauge!("kaspacom_last_success_timestamp", "endpoint" => category.to_string())
+            .set(chrono::Utc::now().timestamp() as f64);
+    }
+
     /// Get the underlying Kaspa.com client for direct API access
     pub fn client(&self) -> &KaspaComClient {
         &self.client
     }
 
+    /// Check whether a Parquet entry exists and is younger than its TTL
+    pub fn is_fresh(&self, parquet_category: &str, parquet_key: &str, parquet_ttl_secs: u64) -> bool {
+        self.parquet.is_valid(parquet_category, parquet_key, parquet_ttl_secs)
+    }
+
+    /// Check whether the rate limiter has budget left for another API call in a category
+    pub async fn has_rate_limit_budget(&self, parquet_category: &str) -> bool {
+        self.rate_limiter.remaining_for(parquet_category).await > 0
+    }
+
+    /// Fail with [`RateLimitExceeded`] unless a category has budget left for
+    /// `calls` more API calls
+    ///
+    /// Nothing is recorded; each call still counts when it's made.
+    pub async fn ensure_rate_limit_budget(&self, parquet_category: &str, calls: u32) -> Result<()> {
+        if self.rate_limiter.remaining_for(parquet_category).await < calls {
+            return Err(self.rate_limit_exceeded(parquet_category).await.into());
+        }
+        Ok(())
+    }
+
+    /// Get a value cached with [`Self::set_redis_only`]
+    pub async fn get_redis_only<T: DeserializeOwned>(&self, redis_key: &str) -> Option<T> {
+        match self.get_from_redis::<T>(redis_key).await {
+            Ok(Some(RedisEntry::Data(data))) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Cache a value in Redis only, for short-lived results that aren't
+    /// worth a Parquet entry
+    pub async fn set_redis_only<T: Serialize>(&self, redis_key: &str, value: &T, ttl_secs: u64) {
+        if let Ok(json) = serde_json::to_string(value) {
+            if let Err(e) = self.redis.set(redis_key, &json, ttl_secs).await {
+                warn!("Failed to write to Redis cache: {}", e);
+            }
+        }
+    }
+
     /// Get data with tiered cache lookup
     ///
     /// Flow:
     /// 1. Check Redis (hot cache)
     /// 2. Check Parquet (warm/cold cache)  
     /// 3. Fetch from API & populate both caches
+    ///
+    /// `ttls` are the Redis and Parquet TTLs in seconds, e.g.
+    /// [`TtlConfig::hot`]. Pinned Parquet entries past their TTL are still
+    /// served from step 2, with the refresh moved to a background task.
     pub async fn get_cached<T, F, Fut>(
         &self,
         redis_key: &str,
         parquet_category: &str,
         parquet_key: &str,
-        redis_ttl_secs: u64,
-        parquet_ttl_secs: u64,
+        ttls: (u64, u64),
         fetcher: F,
     ) -> Result<T>
     where
         T: Serialize + DeserializeOwned + Clone,
-        F: FnOnce() -> Fut,
-        Fut: Future<Output = Result<Value>>,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value>> + Send,
     {
+        let (redis_ttl_secs, parquet_ttl_secs) = ttls;
+        if is_bypassing_cache() {
+            let value = self
+                .refresh(redis_key, parquet_category, parquet_key, ttls, fetcher)
+                .await?;
+            self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::ORIGIN).await;
+            return Ok(serde_json::from_value(value)?);
+        }
+
         // 1. Try Redis first (hot cache)
-        if let Ok(Some(cached)) = self.get_from_redis::<T>(redis_key).await {
-            debug!("Redis cache hit: {}", redis_key);
-            self.cache_hits.fetch_add(1, Ordering::Relaxed);
-            self.record_category_hit(parquet_category);
-            return Ok(cached);
+        match self.get_from_redis::<T>(redis_key).await {
+            Ok(Some(RedisEntry::Data(cached))) => {
+                debug!("Redis cache hit: {}", redis_key);
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                self.record_category_hit(parquet_category, tier::REDIS);
+                self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::REDIS).await;
+                return Ok(cached);
+            }
+            Ok(Some(RedisEntry::NotFound(message))) => {
+                debug!("Negative cache hit: {}", redis_key);
+                self.record_category_hit(parquet_category, tier::REDIS);
+                return Err(ApiError::NotFound(message).into());
+            }
+            _ => {}
         }
 
         // 2. Try Parquet (warm/cold cache)
@@ -133,30 +540,44 @@ impl CacheService {
             if let Ok(Some(cached)) = self.parquet.read::<T>(parquet_category, parquet_key) {
                 debug!("Parquet cache hit: {}/{}", parquet_category, parquet_key);
                 self.cache_hits.fetch_add(1, Ordering::Relaxed);
-                self.record_category_hit(parquet_category);
+                self.record_category_hit(parquet_category, tier::PARQUET);
                 
                 // Populate Redis for faster subsequent access
                 if let Ok(json) = serde_json::to_string(&cached) {
                     let _ = self.redis.set(redis_key, &json, redis_ttl_secs).await;
                 }
                 
+                self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::PARQUET).await;
                 return Ok(cached);
             }
         }
+        let fetcher = match self
+            .serve_stale_pinned(
+                redis_key,
+                parquet_category,
+                parquet_key,
+                ttls,
+                fetcher,
+                |parquet| parquet.read::<T>(parquet_category, parquet_key),
+            )
+            .await
+        {
+            Ok(cached) => return Ok(cached),
+            Err(fetcher) => fetcher,
+        };
 
         // 3. Fetch from remote API (with rate limiting)
         info!("Cache miss, fetching from API: {}", redis_key);
         self.record_category_miss(parquet_category);
         
-        // Check rate limit before making API call
-        if !self.rate_limiter.check_and_record().await {
-            anyhow::bail!(
-                "Rate limit exceeded: {} requests/minute limit reached. Please wait before retrying.",
-                self.rate_limiter.get_stats().await.limit
-            );
+        // Fail fast while upstream is down, then check rate limit before making API call
+        self.check_circuit()?;
+        if !self.rate_limiter.check_and_record_for(parquet_category).await {
+            return Err(self.rate_limit_exceeded(parquet_category).await.into());
         }
         
-        let value = fetcher().await?;
+        let value = self.fetch_or_remember_not_found(redis_key, fetcher).await?;
+        self.record_upstream_success(parquet_category);
 
         // Parse the response
         let data: T = serde_json::from_value(value.clone())?;
@@ -171,6 +592,7 @@ impl CacheService {
             parquet_ttl_secs,
         )
         .await;
+        self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::ORIGIN).await;
 
         Ok(data)
     }
@@ -181,20 +603,34 @@ impl CacheService {
         redis_key: &str,
         parquet_category: &str,
         parquet_key: &str,
-        redis_ttl_secs: u64,
-        parquet_ttl_secs: u64,
+        ttls: (u64, u64),
         fetcher: F,
     ) -> Result<Value>
     where
-        F: FnOnce() -> Fut,
-        Fut: Future<Output = Result<Value>>,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value>> + Send,
     {
+        let (redis_ttl_secs, parquet_ttl_secs) = ttls;
+        if is_bypassing_cache() {
+            let value = self
+                .refresh(redis_key, parquet_category, parquet_key, ttls, fetcher)
+                .await?;
+            self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::ORIGIN).await;
+            return Ok(value);
+        }
+
         // 1. Try Redis first (hot cache)
         if let Ok(Some(cached)) = self.redis.get(redis_key).await {
+            if let Some(message) = cached.strip_prefix(NOT_FOUND_MARKER) {
+                debug!("Negative cache hit (JSON): {}", redis_key);
+                self.record_category_hit(parquet_category, tier::REDIS);
+                return Err(ApiError::NotFound(message.to_string()).into());
+            }
             if let Ok(value) = serde_json::from_str::<Value>(&cached) {
                 debug!("Redis cache hit (JSON): {}", redis_key);
                 self.cache_hits.fetch_add(1, Ordering::Relaxed);
-                self.record_category_hit(parquet_category);
+                self.record_category_hit(parquet_category, tier::REDIS);
+                self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::REDIS).await;
                 return Ok(value);
             }
         }
@@ -204,30 +640,44 @@ impl CacheService {
             if let Ok(Some(value)) = self.parquet.read_json(parquet_category, parquet_key) {
                 debug!("Parquet cache hit (JSON): {}/{}", parquet_category, parquet_key);
                 self.cache_hits.fetch_add(1, Ordering::Relaxed);
-                self.record_category_hit(parquet_category);
+                self.record_category_hit(parquet_category, tier::PARQUET);
                 
                 // Populate Redis
                 if let Ok(json) = serde_json::to_string(&value) {
                     let _ = self.redis.set(redis_key, &json, redis_ttl_secs).await;
                 }
                 
+                self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::PARQUET).await;
                 return Ok(value);
             }
         }
+        let fetcher = match self
+            .serve_stale_pinned(
+                redis_key,
+                parquet_category,
+                parquet_key,
+                ttls,
+                fetcher,
+                |parquet| parquet.read_json(parquet_category, parquet_key),
+            )
+            .await
+        {
+            Ok(value) => return Ok(value),
+            Err(fetcher) => fetcher,
+        };
 
         // 3. Fetch from API (with rate limiting)
         info!("Cache miss (JSON), fetching from API: {}", redis_key);
         self.record_category_miss(parquet_category);
         
-        // Check rate limit before making API call
-        if !self.rate_limiter.check_and_record().await {
-            anyhow::bail!(
-                "Rate limit exceeded: {} requests/minute limit reached. Please wait before retrying.",
-                self.rate_limiter.get_stats().await.limit
-            );
+        // Fail fast while upstream is down, then check rate limit before making API call
+        self.check_circuit()?;
+        if !self.rate_limiter.check_and_record_for(parquet_category).await {
+            return Err(self.rate_limit_exceeded(parquet_category).await.into());
         }
         
-        let value = fetcher().await?;
+        let value = self.fetch_or_remember_not_found(redis_key, fetcher).await?;
+        self.record_upstream_success(parquet_category);
 
         // Populate caches
         self.populate_caches(
@@ -239,35 +689,88 @@ impl CacheService {
             parquet_ttl_secs,
         )
         .await;
+        self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::ORIGIN).await;
 
         Ok(value)
     }
 
+    /// Get a list of records with tiered cache lookup, a batch at a time
+    ///
+    /// A fresh (or stale pinned) Parquet entry is read batch by batch (see
+    /// [`JsonBatches`]) rather than loaded whole, so large entries can be
+    /// streamed out. Otherwise this is [`Self::get_cached_json`], whose
+    /// value comes back as a single batch.
+    pub async fn get_cached_batches<F, Fut>(
+        &self,
+        redis_key: &str,
+        parquet_category: &str,
+        parquet_key: &str,
+        ttls: (u64, u64),
+        fetcher: F,
+    ) -> Result<JsonBatches>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value>> + Send,
+    {
+        let (_, parquet_ttl_secs) = ttls;
+        if !is_bypassing_cache() && self.parquet.is_valid(parquet_category, parquet_key, parquet_ttl_secs) {
+            if let Ok(Some(batches)) = self.parquet.read_json_batches(parquet_category, parquet_key) {
+                debug!("Parquet cache hit (batches): {}/{}", parquet_category, parquet_key);
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                self.record_category_hit(parquet_category, tier::PARQUET);
+                self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::PARQUET).await;
+                return Ok(batches);
+            }
+        }
+        let fetcher = if is_bypassing_cache() {
+            fetcher
+        } else {
+            match self
+                .serve_stale_pinned(
+                    redis_key,
+                    parquet_category,
+                    parquet_key,
+                    ttls,
+                    fetcher,
+                    |parquet| parquet.read_json_batches(parquet_category, parquet_key),
+                )
+                .await
+            {
+                Ok(batches) => return Ok(batches),
+                Err(fetcher) => fetcher,
+            }
+        };
+
+        let value = self
+            .get_cached_json(redis_key, parquet_category, parquet_key, ttls, fetcher)
+            .await?;
+        Ok(JsonBatches::from_value(value))
+    }
+
     /// Force refresh from API and update all cache layers
     pub async fn refresh<F, Fut>(
         &self,
         redis_key: &str,
         parquet_category: &str,
         parquet_key: &str,
-        redis_ttl_secs: u64,
-        parquet_ttl_secs: u64,
+        ttls: (u64, u64),
         fetcher: F,
     ) -> Result<Value>
     where
         F: FnOnce() -> Fut,
         Fut: Future<Output = Result<Value>>,
     {
+        let (redis_ttl_secs, parquet_ttl_secs) = ttls;
         info!("Force refreshing: {}", redis_key);
         
-        // Check rate limit before making API call
-        if !self.rate_limiter.check_and_record().await {
-            anyhow::bail!(
-                "Rate limit exceeded: {} requests/minute limit reached. Please wait before retrying.",
-                self.rate_limiter.get_stats().await.limit
-            );
+        // Fail fast while upstream is down, then check rate limit before making API call
+        self.check_circuit()?;
+        if !self.rate_limiter.check_and_record_for(parquet_category).await {
+            return Err(self.rate_limit_exceeded(parquet_category).await.into());
         }
         
-        let value = fetcher().await?;
+        let value = self.fetch_upstream(fetcher).await?;
+        self.record_upstream_success(parquet_category);
 
         self.populate_caches(
             redis_key,
@@ -282,6 +785,141 @@ impl CacheService {
         Ok(value)
     }
 
+    /// Write an entry directly to both cache layers, bypassing the upstream API
+    ///
+    /// Used by admin tooling to pin corrections with a custom TTL. Redis is
+    /// only written when a Redis key is given, since Redis keys don't map
+    /// one-to-one onto Parquet keys.
+    pub async fn put_override(
+        &self,
+        redis_key: Option<&str>,
+        parquet_category: &str,
+        parquet_key: &str,
+        value: &Value,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        self.parquet.write_simple(parquet_category, parquet_key, value, ttl_secs)?;
+
+        if let Some(redis_key) = redis_key {
+            let json = serde_json::to_string(value)?;
+            self.redis.set(redis_key, &json, ttl_secs).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Serve a pinned Parquet entry that is past its TTL, refreshing it in
+    /// the background
+    ///
+    /// Shared by the cache getters, each reading the entry through `read` in
+    /// its own shape. Hands `fetcher` back when the entry isn't pinned or
+    /// can't be read, so the caller can fetch in the foreground instead.
+    async fn serve_stale_pinned<R, F, Fut>(
+        &self,
+        redis_key: &str,
+        parquet_category: &str,
+        parquet_key: &str,
+        ttls: (u64, u64),
+        fetcher: F,
+        read: impl FnOnce(&ParquetStore) -> Result<Option<R>>,
+    ) -> std::result::Result<R, F>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value>> + Send,
+    {
+        if !self.parquet.is_pinned(parquet_category, parquet_key) {
+            return Err(fetcher);
+        }
+        let Ok(Some(cached)) = read(&self.parquet) else {
+            return Err(fetcher);
+        };
+
+        let (redis_ttl_secs, parquet_ttl_secs) = ttls;
+        debug!("Serving stale pinned entry: {}/{}", parquet_category, parquet_key);
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        self.record_category_hit(parquet_category, tier::PARQUET);
+        // Before the refresh can rewrite the metadata
+        self.record_served(parquet_category, parquet_key, parquet_ttl_secs, tier::PARQUET).await;
+        self.spawn_pinned_refresh(
+            redis_key,
+            parquet_category,
+            parquet_key,
+            redis_ttl_secs,
+            parquet_ttl_secs,
+            fetcher,
+        );
+        Ok(cached)
+    }
+
+    /// Refresh a stale pinned entry without blocking the caller
+    ///
+    /// At most one refresh per key runs at a time. Failures leave the pinned
+    /// entry in place, so it keeps being served during upstream outages.
+    fn spawn_pinned_refresh<F, Fut>(
+        &self,
+        redis_key: &str,
+        parquet_category: &str,
+        parquet_key: &str,
+        redis_ttl_secs: u64,
+        parquet_ttl_secs: u64,
+        fetcher: F,
+    ) where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value>> + Send,
+    {
+        match self.pinned_refreshes.lock() {
+            Ok(mut in_flight) => {
+                if !in_flight.insert(redis_key.to_string()) {
+                    return;
+                }
+            }
+            Err(_) => {
+                warn!("Failed to acquire lock for pinned refreshes (mutex poisoned)");
+                return;
+            }
+        }
+
+        let this = self.clone();
+        let (redis_key, parquet_category, parquet_key) =
+            (redis_key.to_string(), parquet_category.to_string(), parquet_key.to_string());
+        tokio::spawn(async move {
+            if let Err(e) = this
+                .refresh(
+                    &redis_key,
+                    &parquet_category,
+                    &parquet_key,
+                    (redis_ttl_secs, parquet_ttl_secs),
+                    fetcher,
+                )
+                .await
+            {
+                warn!("Background refresh of pinned entry {} failed: {}", redis_key, e);
+            }
+            if let Ok(mut in_flight) = this.pinned_refreshes.lock() {
+                in_flight.remove(&redis_key);
+            }
+        });
+    }
+
+    /// Pin or unpin a Parquet entry (see [`ParquetStore::set_pinned`])
+    pub fn set_pinned(&self, parquet_category: &str, parquet_key: &str, pinned: bool) -> Result<bool> {
+        self.parquet.set_pinned(parquet_category, parquet_key, pinned)
+    }
+
+    /// Age of a Parquet entry in seconds (see [`ParquetStore::age_secs`])
+    pub fn entry_age_secs(&self, parquet_category: &str, parquet_key: &str) -> Option<u64> {
+        self.parquet.age_secs(parquet_category, parquet_key)
+    }
+
+    /// Build the error returned when the upstream budget for a category is spent
+    async fn rate_limit_exceeded(&self, parquet_category: &str) -> RateLimitExceeded {
+        RateLimitExceeded {
+            category: parquet_category.to_string(),
+            limit: self.rate_limiter.limit_for(parquet_category),
+            retry_after_secs: self.rate_limiter.retry_after_for(parquet_category).await,
+        }
+    }
+
     /// Populate both cache layers
     async fn populate_caches(
         &self,
@@ -299,27 +937,80 @@ impl CacheService {
             }
         }
 
-        // Write to Parquet
-        if let Err(e) = self.parquet.write_simple(parquet_category, parquet_key, value, parquet_ttl_secs) {
+        // Write to Parquet, as real columns where the payload is a list of records
+        let written = if cache_categories::COLUMNAR.contains(&parquet_category) {
+            self.parquet.write_json(parquet_category, parquet_key, value, parquet_ttl_secs)
+        } else {
+            self.parquet.write_simple(parquet_category, parquet_key, value, parquet_ttl_secs)
+        };
+        if let Err(e) = written {
             warn!("Failed to write to Parquet cache: {}", e);
         }
     }
 
-    /// Get from Redis and deserialize
-    async fn get_from_redis<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
-        match self.redis.get(key).await? {
-            Some(cached) => {
-                let data: T = serde_json::from_str(&cached)?;
-                Ok(Some(data))
+    /// Run `fetcher`, storing a tombstone in Redis if upstream says 404
+    ///
+    /// Only used on cache misses: a failed refresh of a pinned entry must not
+    /// shadow the entry it is refreshing.
+    async fn fetch_or_remember_not_found<F, Fut>(&self, redis_key: &str, fetcher: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        let result = self.fetch_upstream(fetcher).await;
+        if self.negative_ttl_secs > 0 {
+            if let Some(ApiError::NotFound(message)) = result.as_ref().err().and_then(ApiError::find) {
+                let tombstone = format!("{}{}", NOT_FOUND_MARKER, message);
+                if let Err(e) = self.redis.set(redis_key, &tombstone, self.negative_ttl_secs).await {
+                    warn!("Failed to write negative cache entry: {}", e);
+                }
             }
+        }
+        result
+    }
+
+    /// Fail with an `Upstream` error while the circuit breaker is open
+    fn check_circuit(&self) -> Result<()> {
+        self.breaker.try_acquire().map_err(|retry_after_secs| {
+            ApiError::Upstream(format!(
+                "kaspa.com is unavailable (circuit breaker open), retry in {}s",
+                retry_after_secs
+            ))
+            .into()
+        })
+    }
+
+    /// Call upstream and report the outcome to the circuit breaker
+    ///
+    /// Only `Upstream` and `RateLimited` errors count as failures; a 404 or
+    /// a rejected request still means kaspa.com is answering.
+    async fn fetch_upstream<F, Fut>(&self, fetcher: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        let result = fetcher().await;
+        match result.as_ref().err().and_then(ApiError::find) {
+            Some(ApiError::Upstream(_) | ApiError::RateLimited { .. }) => self.breaker.record_failure(),
+            _ => self.breaker.record_success(),
+        }
+        result
+    }
+
+    /// Get from Redis and deserialize, recognizing 404 tombstones
+    async fn get_from_redis<T: DeserializeOwned>(&self, key: &str) -> Result<Option<RedisEntry<T>>> {
+        match self.redis.get(key).await? {
+            Some(cached) => match cached.strip_prefix(NOT_FOUND_MARKER) {
+                Some(message) => Ok(Some(RedisEntry::NotFound(message.to_string()))),
+                None => Ok(Some(RedisEntry::Data(serde_json::from_str(&cached)?))),
+            },
             None => Ok(None),
         }
     }
 
     /// Invalidate cache entry in both layers
     pub async fn invalidate(&self, redis_key: &str, parquet_category: &str, parquet_key: &str) -> Result<()> {
-        // Redis doesn't have a delete method in the trait, so we just let it expire
-        // For Parquet, we can delete the file
+        self.redis.delete(redis_key).await?;
         self.parquet.delete(parquet_category, parquet_key)?;
         info!("Invalidated cache: {}", redis_key);
         Ok(())
@@ -329,6 +1020,7 @@ impl CacheService {
     pub fn get_stats(&self) -> Result<crate::infrastructure::CacheStats> {
         let mut stats = self.parquet.get_stats()?;
         stats.cache_hits = self.cache_hits.load(Ordering::Relaxed);
+        stats.circuit_breaker = Some(self.breaker.status());
         
         // Merge per-category cache statistics
         let category_stats_map = match self.category_stats.lock() {
@@ -339,27 +1031,26 @@ impl CacheService {
             }
         };
         for (category, cat_stats) in category_stats_map.iter() {
-            let hits = cat_stats.hits.load(Ordering::Relaxed);
+            let redis_hits = cat_stats.redis_hits.load(Ordering::Relaxed);
+            let parquet_hits = cat_stats.parquet_hits.load(Ordering::Relaxed);
             let misses = cat_stats.misses.load(Ordering::Relaxed);
             let requests = cat_stats.requests.load(Ordering::Relaxed);
+            stats.redis_hits += redis_hits;
+            stats.parquet_hits += parquet_hits;
             
             if let Some(cat_stat) = stats.categories.get_mut(category) {
                 // Update existing category
-                cat_stat.hits = hits;
-                cat_stat.misses = misses;
-                cat_stat.requests = requests;
+                cat_stat.set_counts(redis_hits, parquet_hits, misses, requests);
             } else {
                 // Create new category entry if it has cache activity but no parquet files yet
                 if requests > 0 {
                     use crate::infrastructure::parquet_store::CategoryStats;
-                    stats.categories.insert(category.clone(), CategoryStats {
-                        keys: 0,
-                        size_bytes: 0,
+                    let mut cat_stat = CategoryStats {
                         description: format!("{} (cache activity)", category),
-                        hits,
-                        misses,
-                        requests,
-                    });
+                        ..CategoryStats::default()
+                    };
+                    cat_stat.set_counts(redis_hits, parquet_hits, misses, requests);
+                    stats.categories.insert(category.clone(), cat_stat);
                 }
             }
         }
@@ -371,13 +1062,480 @@ impl CacheService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::infrastructure::RedisRepository;
 
     #[test]
     fn test_ttl_values() {
         // Ensure TTL hierarchy makes sense
-        assert!(ttl::HOT_REDIS_SECS < ttl::HOT_PARQUET_SECS);
-        assert!(ttl::WARM_REDIS_SECS < ttl::WARM_PARQUET_SECS);
-        assert!(ttl::COLD_REDIS_SECS < ttl::COLD_PARQUET_SECS);
-        assert!(ttl::STATIC_REDIS_SECS < ttl::STATIC_PARQUET_SECS);
+        const {
+            assert!(ttl::HOT_REDIS_SECS < ttl::HOT_PARQUET_SECS);
+            assert!(ttl::WARM_REDIS_SECS < ttl::WARM_PARQUET_SECS);
+            assert!(ttl::COLD_REDIS_SECS < ttl::COLD_PARQUET_SECS);
+            assert!(ttl::STATIC_REDIS_SECS < ttl::STATIC_PARQUET_SECS);
+        }
+    }
+
+    #[test]
+    fn test_ttl_config_defaults_missing_fields() {
+        let ttls: TtlConfig = serde_yaml::from_str("hot_redis_secs: 90").unwrap();
+        assert_eq!(ttls.hot_redis_secs, 90);
+        assert_eq!(ttls.hot_parquet_secs, ttl::HOT_PARQUET_SECS);
+        assert_eq!(ttls, TtlConfig { hot_redis_secs: 90, ..TtlConfig::default() });
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_removes_redis_and_parquet_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = CacheService::new(
+            redis.clone(),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+
+        let redis_key = "kaspa:test:invalidate";
+        let value = serde_json::json!({"ticker": "NACHO"});
+        service
+            .put_override(Some(redis_key), "test", "invalidate", &value, 60)
+            .await
+            .unwrap();
+        assert!(redis.get(redis_key).await.unwrap().is_some());
+
+        service.invalidate(redis_key, "test", "invalidate").await.unwrap();
+
+        assert!(redis.get(redis_key).await.unwrap().is_none());
+        assert!(!service.is_fresh("test", "invalidate", 60));
+    }
+
+    #[test]
+    fn test_last_success_gauge_tracks_successful_upstream_fetches() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = CacheService::new(
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let fetched: String = service
+                    .get_cached("kaspa:logos:NACHO", "logos", "NACHO", (60, 600), || async {
+                        Ok(serde_json::json!("logo"))
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(fetched, "logo");
+                let failed: anyhow::Result<String> = service
+                    .get_cached("kaspa:floor:NACHO", "floor_prices", "NACHO", (60, 600), || async {
+                        anyhow::bail!("upstream down")
+                    })
+                    .await;
+                assert!(failed.is_err());
+            });
+        });
+
+        let rendered = handle.render();
+        let logos = rendered
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix(r#"kaspacom_last_success_timestamp{endpoint="logos"} "#)
+            })
+            .unwrap_or_else(|| panic!("no logos gauge in {}", rendered));
+        let age = chrono::Utc::now().timestamp() as f64 - logos.parse::<f64>().unwrap();
+        assert!((0.0..60.0).contains(&age), "{}", rendered);
+        assert!(!rendered.contains(r#"endpoint="floor_prices""#), "{}", rendered);
+    }
+
+    #[tokio::test]
+    async fn test_stale_pinned_entry_is_served_while_refresh_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let parquet = Arc::new(ParquetStore::new(dir.path().to_str().unwrap()));
+        let service = CacheService::new(
+            Arc::new(RedisRepository::new(None)),
+            parquet.clone(),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+
+        parquet.write_simple("logos", "NACHO", &serde_json::json!("old"), 60).unwrap();
+        parquet.set_pinned("logos", "NACHO", true).unwrap();
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let served: String = service
+            .get_cached("kaspa:logos:NACHO", "logos", "NACHO", (60, 0), move || async move {
+                let _ = tx.send(());
+                anyhow::bail!("upstream down")
+            })
+            .await
+            .unwrap();
+
+        // The stale value is returned and the refresh happens in the background
+        assert_eq!(served, "old");
+        rx.await.unwrap();
+        assert!(parquet.is_pinned("logos", "NACHO"));
+    }
+
+    #[tokio::test]
+    async fn test_stale_pinned_json_and_batches_are_served_while_refresh_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let parquet = Arc::new(ParquetStore::new(dir.path().to_str().unwrap()));
+        let service = CacheService::new(
+            Arc::new(RedisRepository::new(None)),
+            parquet.clone(),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+        let old = serde_json::json!([{ "id": 1 }, { "id": 2 }]);
+        for key in ["rarity_NACHO", "orders_NACHO"] {
+            parquet.write_simple("krc721", key, &old, 60).unwrap();
+            parquet.set_pinned("krc721", key, true).unwrap();
+        }
+        let failing_fetch = || {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            let fetch = move || async move {
+                let _ = tx.send(());
+                anyhow::bail!("upstream down")
+            };
+            (fetch, rx)
+        };
+
+        let (fetch, rx) = failing_fetch();
+        let served = service
+            .get_cached_json(
+                "kaspa:krc721:rarity:NACHO",
+                "krc721",
+                "rarity_NACHO",
+                (60, 0),
+                fetch,
+            )
+            .await
+            .unwrap();
+        assert_eq!(served, old);
+        rx.await.unwrap();
+
+        let (fetch, rx) = failing_fetch();
+        let batches = service
+            .get_cached_batches(
+                "kaspa:krc721:orders:NACHO",
+                "krc721",
+                "orders_NACHO",
+                (60, 0),
+                fetch,
+            )
+            .await
+            .unwrap();
+        let served: Vec<Value> = batches.map(|batch| batch.unwrap()).collect::<Vec<_>>().concat();
+        assert_eq!(Value::Array(served), old);
+        rx.await.unwrap();
+        assert!(parquet.is_pinned("krc721", "orders_NACHO"));
+    }
+
+    #[tokio::test]
+    async fn test_served_entries_are_tracked_with_remaining_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = CacheService::new(
+            Arc::new(RedisRepository::new(None)),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+
+        let fetch = || async {
+            let value: String = service
+                .get_cached("kaspa:logos:NACHO", "logos", "NACHO", (60, 600), || async {
+                    Ok(serde_json::json!("logo"))
+                })
+                .await
+                .unwrap();
+            value
+        };
+
+        // Untracked calls still work
+        assert_eq!(fetch().await, "logo");
+
+        let (_, first) = track_served_entries(fetch()).await;
+        let (_, second) = track_served_entries(fetch()).await;
+        assert_eq!(first.entries.len(), 1);
+        assert!(first.max_age_secs().unwrap() <= 600);
+        assert!(first.etag().is_some());
+        assert_eq!(first.etag(), second.etag());
+
+        let (_, none) = track_served_entries(async {}).await;
+        assert_eq!(none.etag(), None);
+        assert_eq!(none.max_age_secs(), None);
+        assert_eq!(none.cache_status(), None);
+    }
+
+    #[test]
+    fn test_served_entries_report_furthest_tier_and_oldest_age() {
+        let entry = |tier, age_secs| ServedEntry {
+            category: "floor_prices".to_string(),
+            key: "all".to_string(),
+            cached_at: 100,
+            remaining_ttl_secs: 60,
+            tier,
+            age_secs,
+        };
+        let hit = ServedEntries {
+            entries: vec![entry(tier::REDIS, 20), entry(tier::PARQUET, 200)],
+            untracked: false,
+        };
+        assert_eq!(hit.cache_status(), Some("HIT"));
+        assert_eq!(hit.tier(), Some(tier::PARQUET));
+        assert_eq!(hit.age_secs(), Some(200));
+
+        let miss = ServedEntries {
+            entries: vec![entry(tier::ORIGIN, 0), entry(tier::REDIS, 20)],
+            untracked: false,
+        };
+        assert_eq!(miss.cache_status(), Some("MISS"));
+        assert_eq!(miss.tier(), Some(tier::ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn test_bypass_cache_refetches_valid_entries() {
+        use std::sync::atomic::AtomicUsize;
+
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = CacheService::new(
+            redis.clone(),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let fetch = || {
+            let fetches = fetches.clone();
+            service.get_cached::<usize, _, _>("kaspa:token_info:NACHO", "token_info", "NACHO", (60, 600), move || async move {
+                Ok(serde_json::json!(fetches.fetch_add(1, Ordering::SeqCst) + 1))
+            })
+        };
+
+        assert_eq!(fetch().await.unwrap(), 1);
+        assert_eq!(fetch().await.unwrap(), 1);
+        assert!(service.is_fresh("token_info", "NACHO", 600));
+
+        // The entry is still valid, but the fetcher runs and both tiers get the new value
+        assert_eq!(bypass_cache(fetch()).await.unwrap(), 2);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_eq!(redis.get("kaspa:token_info:NACHO").await.unwrap().as_deref(), Some("2"));
+        assert_eq!(fetch().await.unwrap(), 2);
+    }
+
+    #[test]
+    fn test_served_entries_use_shortest_ttl_and_change_etag() {
+        let entry = |key: &str, cached_at, remaining_ttl_secs| ServedEntry {
+            category: "floor_prices".to_string(),
+            key: key.to_string(),
+            cached_at,
+            remaining_ttl_secs,
+            tier: tier::PARQUET,
+            age_secs: 0,
+        };
+        let served = ServedEntries {
+            entries: vec![entry("all", 100, 240), entry("NACHO", 100, 30)],
+            untracked: false,
+        };
+        assert_eq!(served.max_age_secs(), Some(30));
+
+        let refreshed = ServedEntries {
+            entries: vec![entry("all", 100, 240), entry("NACHO", 400, 300)],
+            untracked: false,
+        };
+        assert_ne!(served.etag(), refreshed.etag());
+
+        let partial = ServedEntries {
+            entries: vec![entry("all", 100, 240)],
+            untracked: true,
+        };
+        assert_eq!(partial.etag(), None);
+    }
+
+    #[tokio::test]
+    async fn test_upstream_404_is_negatively_cached() {
+        use std::sync::atomic::AtomicUsize;
+
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = CacheService::new(
+            redis.clone(),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        )
+        .with_negative_ttl(45);
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        for _ in 0..2 {
+            let fetches = fetches.clone();
+            let err = service
+                .get_cached::<serde_json::Value, _, _>("kaspa:token_info:NOPE", "token_info", "NOPE", (60, 60), move || async move {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    Err(ApiError::NotFound("no such token".into()).into())
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(ApiError::find(&err), Some(&ApiError::NotFound("no such token".into())));
+        }
+
+        // The second lookup was answered by the tombstone
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(redis.ttl_of("kaspa:token_info:NOPE"), Some(45));
+
+        // Raw JSON lookups honour it too, rather than parsing it as data
+        let err = service
+            .get_cached_json("kaspa:token_info:NOPE", "token_info", "NOPE", (60, 60), || async {
+                Ok(serde_json::json!({"ticker": "NOPE"}))
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(ApiError::find(&err).map(ApiError::code), Some("NOT_FOUND"));
+    }
+
+    #[tokio::test]
+    async fn test_negative_cache_can_be_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = CacheService::new(
+            redis.clone(),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        )
+        .with_negative_ttl(0);
+
+        let result = service
+            .get_cached_json("kaspa:token_info:NOPE", "token_info", "NOPE", (60, 60), || async {
+                Err(ApiError::NotFound("no such token".into()).into())
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(redis.get("kaspa:token_info:NOPE").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_category_hit_ratio() {
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = CacheService::new(
+            redis.clone(),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        );
+
+        // One miss, two hits served by Redis, then one by Parquet once
+        // the Redis entry is gone
+        for i in 0..4 {
+            if i == 3 {
+                redis.delete("kaspa:hot_mints").await.unwrap();
+            }
+            let _: serde_json::Value = service
+                .get_cached("kaspa:hot_mints", "hot_mints", "all", (60, 60), || async {
+                    Ok(serde_json::json!([{"ticker": "NACHO"}]))
+                })
+                .await
+                .unwrap();
+        }
+
+        let stats = service.get_stats().unwrap();
+        let hot_mints = &stats.categories["hot_mints"];
+        assert_eq!((hot_mints.hits, hot_mints.misses, hot_mints.requests), (3, 1, 4));
+        assert_eq!((hot_mints.redis_hits, hot_mints.parquet_hits), (2, 1));
+        assert_eq!((stats.redis_hits, stats.parquet_hits), (2, 1));
+        assert_eq!(hot_mints.hit_ratio, 0.75);
+        assert_eq!(stats.categories["kns"].hit_ratio, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_fails_fast_during_outage() {
+        use crate::infrastructure::circuit_breaker::BreakerState;
+        use crate::infrastructure::rate_limiter::MockClock;
+        use std::sync::atomic::AtomicUsize;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let clock = MockClock::new();
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown_secs: 30,
+        };
+        let service = CacheService::new(
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        )
+        .with_negative_ttl(0)
+        .with_circuit_breaker(CircuitBreaker::with_clock(config, clock.clone()));
+
+        // Mock upstream: counts calls, fails unless told to succeed
+        let calls = Arc::new(AtomicUsize::new(0));
+        let fetch = |key: &str, ok: bool| {
+            let calls = calls.clone();
+            let service = service.clone();
+            let key = key.to_string();
+            async move {
+                service
+                    .get_cached::<Value, _, _>(&key, "hot_mints", &key, (60, 60), move || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        if ok {
+                            Ok(serde_json::json!([]))
+                        } else {
+                            Err(ApiError::Upstream("kaspa.com unreachable".to_string()).into())
+                        }
+                    })
+                    .await
+            }
+        };
+        let state = || service.get_stats().unwrap().circuit_breaker.unwrap().state;
+
+        // 404s mean upstream is answering and never open the breaker
+        for i in 0..3 {
+            let key = format!("missing{}", i);
+            let missing = service
+                .get_cached::<Value, _, _>(&key, "hot_mints", &key, (60, 60), || async {
+                    Err(ApiError::NotFound("no such token".to_string()).into())
+                })
+                .await;
+            assert!(missing.is_err());
+        }
+        assert_eq!(state(), BreakerState::Closed);
+
+        // Closed -> open after two consecutive failures
+        assert!(fetch("a", false).await.is_err());
+        assert_eq!(state(), BreakerState::Closed);
+        assert!(fetch("b", false).await.is_err());
+        assert_eq!(state(), BreakerState::Open);
+
+        // Open: fail fast with a typed Upstream error, upstream untouched
+        let err = fetch("c", true).await.unwrap_err();
+        assert!(matches!(ApiError::find(&err), Some(ApiError::Upstream(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Half-open probe fails -> open again
+        clock.advance(Duration::from_secs(30));
+        assert!(fetch("d", false).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(state(), BreakerState::Open);
+        assert!(fetch("e", true).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Half-open probe succeeds -> closed
+        clock.advance(Duration::from_secs(30));
+        assert!(fetch("f", true).await.is_ok());
+        assert_eq!(state(), BreakerState::Closed);
+        assert!(fetch("g", true).await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
     }
 }
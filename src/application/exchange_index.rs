@@ -1,13 +1,20 @@
 //! Exchange index for fast lookup of exchange-to-tokens mapping.
 //!
 //! Builds and maintains an in-memory index from the local filesystem,
-//! allowing fast lookups without GitHub API calls.
+//! allowing fast lookups without GitHub API calls. With persistence enabled
+//! each rebuild is also written to a JSON file, which the next process loads
+//! at startup so lookups work before its own rebuild finishes.
 
+use crate::domain::{normalize_exchange, ApiError};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
 /// In-memory index mapping exchanges to their tokens.
@@ -17,6 +24,49 @@ pub struct ExchangeIndex {
     exchange_to_tokens: Arc<RwLock<HashMap<String, Vec<String>>>>,
     /// Base data directory path
     data_path: String,
+    /// Unix timestamp of the last completed rebuild (0 = never)
+    last_built_at: Arc<AtomicI64>,
+    /// Set while a background rebuild started by [`Self::spawn_rebuild`] runs
+    rebuilding: Arc<AtomicBool>,
+    /// Cancelled at shutdown to abandon background rebuilds
+    shutdown: CancellationToken,
+    /// File the index is saved to after each rebuild and loaded from at startup
+    persist_path: Option<PathBuf>,
+    /// Age (seconds) beyond which a saved index is not loaded
+    persist_max_age_secs: i64,
+}
+
+/// On-disk form of the index
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex {
+    /// Unix timestamp of the rebuild that produced it
+    built_at: i64,
+    exchanges: HashMap<String, Vec<String>>,
+}
+
+/// Token names come from API callers for incremental updates, so only plain
+/// directory names are accepted (no separators or leading dots).
+fn is_valid_dir_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Snapshot of the index state for operators.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ExchangeIndexStatus {
+    /// Whether at least one rebuild has completed
+    pub initialized: bool,
+    /// Whether a background rebuild is currently running
+    pub rebuilding: bool,
+    /// Number of exchanges in the index
+    pub exchanges: usize,
+    /// Number of distinct tokens across all exchanges
+    pub tokens: usize,
+    /// Unix timestamp of the last completed rebuild
+    pub last_built_at: Option<i64>,
 }
 
 impl ExchangeIndex {
@@ -29,7 +79,75 @@ impl ExchangeIndex {
         Self {
             exchange_to_tokens: Arc::new(RwLock::new(HashMap::new())),
             data_path: data_path.as_ref().to_string_lossy().to_string(),
+            last_built_at: Arc::new(AtomicI64::new(0)),
+            rebuilding: Arc::new(AtomicBool::new(false)),
+            shutdown: CancellationToken::new(),
+            persist_path: None,
+            persist_max_age_secs: 0,
+        }
+    }
+
+    /// Stop background rebuilds when `shutdown` is cancelled
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Save each rebuild to `path`, and accept it back at startup while it is
+    /// at most `max_age_secs` old (see [`Self::load_persisted`]).
+    pub fn with_persistence<P: Into<PathBuf>>(mut self, path: P, max_age_secs: i64) -> Self {
+        self.persist_path = Some(path.into());
+        self.persist_max_age_secs = max_age_secs;
+        self
+    }
+
+    /// Load the index saved by a previous rebuild.
+    ///
+    /// Returns `false` when persistence is off, no file exists, or the file
+    /// is older than the configured max age; the index is left untouched
+    /// then. A loaded index counts as built at its original rebuild time,
+    /// so callers should still start a rebuild to pick up later changes.
+    pub async fn load_persisted(&self) -> anyhow::Result<bool> {
+        let Some(path) = &self.persist_path else {
+            return Ok(false);
+        };
+        let bytes = match fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        let persisted: PersistedIndex = serde_json::from_slice(&bytes)?;
+
+        let age = chrono::Utc::now().timestamp() - persisted.built_at;
+        if persisted.built_at <= 0 || age > self.persist_max_age_secs {
+            info!("Ignoring saved exchange index at {}: {}s old", path.display(), age);
+            return Ok(false);
+        }
+
+        let count = persisted.exchanges.len();
+        *self.exchange_to_tokens.write().await = persisted.exchanges;
+        self.last_built_at.store(persisted.built_at, Ordering::Release);
+        info!("Exchange index loaded from {}: {} exchanges", path.display(), count);
+        Ok(true)
+    }
+
+    /// Write the current index to the persistence file, if configured.
+    async fn persist(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+        let persisted = PersistedIndex {
+            built_at: self.last_built_at.load(Ordering::Acquire),
+            exchanges: self.exchange_to_tokens.read().await.clone(),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
         }
+        // Write then rename so a crash never leaves a truncated file behind
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(&persisted)?).await?;
+        fs::rename(&tmp, path).await?;
+        Ok(())
     }
 
     /// Build the index by scanning the local filesystem.
@@ -81,7 +199,7 @@ impl ExchangeIndex {
                     
                     exchange_map
                         .entry(exchange_name)
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .push(token_name.clone());
                 }
             }
@@ -96,23 +214,87 @@ impl ExchangeIndex {
         
         // Update the index
         *self.exchange_to_tokens.write().await = exchange_map;
+        self.last_built_at
+            .store(chrono::Utc::now().timestamp(), Ordering::Release);
 
         info!("Exchange index rebuilt: {} exchanges found", count);
+        if let Err(e) = self.persist().await {
+            warn!("Failed to save exchange index: {}", e);
+        }
         Ok(count)
     }
 
+    /// Re-scan a single token directory and update only its entries.
+    ///
+    /// Cheaper than [`Self::rebuild`] when one token was added or changed.
+    /// If the token directory no longer exists the token is removed.
+    /// Returns the number of exchanges now listing the token.
+    pub async fn add_token(&self, token: &str) -> anyhow::Result<usize> {
+        if !is_valid_dir_name(token) {
+            return Err(ApiError::Validation(format!("Invalid token name '{}'", token)).into());
+        }
+
+        let token_path = Path::new(&self.data_path).join(token);
+        let mut exchanges = Vec::new();
+        if token_path.is_dir() {
+            let mut token_dir = fs::read_dir(&token_path).await?;
+            while let Some(exchange_entry) = token_dir.next_entry().await? {
+                if exchange_entry.path().is_dir() {
+                    exchanges.push(exchange_entry.file_name().to_string_lossy().to_string());
+                }
+            }
+        }
+
+        // Swap the token's entries in one critical section so readers never
+        // observe it half-updated
+        let mut index = self.exchange_to_tokens.write().await;
+        Self::remove_from(&mut index, token);
+        for exchange in &exchanges {
+            Self::insert_into(&mut index, exchange, token);
+        }
+
+        info!("Exchange index updated for {}: {} exchanges", token, exchanges.len());
+        Ok(exchanges.len())
+    }
+
+    /// Remove a token from every exchange, dropping exchanges left empty.
+    pub async fn remove_token(&self, token: &str) {
+        let mut index = self.exchange_to_tokens.write().await;
+        Self::remove_from(&mut index, token);
+    }
+
+    /// Record that `exchange` lists `token`, without touching the filesystem.
+    pub async fn upsert_exchange(&self, exchange: &str, token: &str) {
+        let mut index = self.exchange_to_tokens.write().await;
+        Self::insert_into(&mut index, exchange, token);
+    }
+
+    fn remove_from(index: &mut HashMap<String, Vec<String>>, token: &str) {
+        index.retain(|_, tokens| {
+            tokens.retain(|t| t != token);
+            !tokens.is_empty()
+        });
+    }
+
+    fn insert_into(index: &mut HashMap<String, Vec<String>>, exchange: &str, token: &str) {
+        let tokens = index.entry(exchange.to_string()).or_default();
+        if let Err(pos) = tokens.binary_search_by(|t| t.as_str().cmp(token)) {
+            tokens.insert(pos, token.to_string());
+        }
+    }
+
     /// Get tokens for a specific exchange.
     ///
     /// Returns an empty vector if the exchange is not found.
     pub async fn get_tokens(&self, exchange: &str) -> Vec<String> {
         let index = self.exchange_to_tokens.read().await;
         index
-            .get(&exchange.to_lowercase())
+            .get(&normalize_exchange(exchange))
             .cloned()
             .unwrap_or_else(|| {
                 // Try case-insensitive match
                 for (key, value) in index.iter() {
-                    if key.to_lowercase() == exchange.to_lowercase() {
+                    if normalize_exchange(key) == normalize_exchange(exchange) {
                         return value.clone();
                     }
                 }
@@ -120,6 +302,13 @@ impl ExchangeIndex {
             })
     }
 
+    /// Get every token listed on at least one exchange, sorted.
+    pub async fn get_all_tokens(&self) -> Vec<String> {
+        let index = self.exchange_to_tokens.read().await;
+        let tokens: BTreeSet<&String> = index.values().flatten().collect();
+        tokens.into_iter().cloned().collect()
+    }
+
     /// Get all exchanges.
     pub async fn get_exchanges(&self) -> Vec<String> {
         let index = self.exchange_to_tokens.read().await;
@@ -134,6 +323,61 @@ impl ExchangeIndex {
         !index.is_empty()
     }
 
+    /// Check if a rebuild has completed at least once (even if it found no exchanges).
+    pub fn has_rebuilt(&self) -> bool {
+        self.last_built_at().is_some()
+    }
+
+    /// Unix timestamp of the last completed rebuild, if any.
+    pub fn last_built_at(&self) -> Option<i64> {
+        match self.last_built_at.load(Ordering::Acquire) {
+            0 => None,
+            ts => Some(ts),
+        }
+    }
+
+    /// Start a rebuild in the background.
+    ///
+    /// Returns `false` without starting anything if a background rebuild is
+    /// already running.
+    pub fn spawn_rebuild(&self) -> bool {
+        if self
+            .rebuilding
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return false;
+        }
+
+        let index = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                biased;
+                _ = index.shutdown.cancelled() => info!("Exchange index rebuild cancelled by shutdown"),
+                result = index.rebuild() => {
+                    if let Err(e) = result {
+                        warn!("Failed to build exchange index: {}", e);
+                    }
+                }
+            }
+            index.rebuilding.store(false, Ordering::Release);
+        });
+        true
+    }
+
+    /// Get a snapshot of the index state.
+    pub async fn status(&self) -> ExchangeIndexStatus {
+        let index = self.exchange_to_tokens.read().await;
+        let tokens: HashSet<&String> = index.values().flatten().collect();
+        ExchangeIndexStatus {
+            initialized: self.has_rebuilt(),
+            rebuilding: self.rebuilding.load(Ordering::Acquire),
+            exchanges: index.len(),
+            tokens: tokens.len(),
+            last_built_at: self.last_built_at(),
+        }
+    }
+
     /// Get the count of exchanges in the index.
     pub async fn exchange_count(&self) -> usize {
         let index = self.exchange_to_tokens.read().await;
@@ -141,3 +385,184 @@ impl ExchangeIndex {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_has_rebuilt_after_empty_rebuild() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ExchangeIndex::new(dir.path());
+        assert!(!index.has_rebuilt());
+
+        assert_eq!(index.rebuild().await.unwrap(), 0);
+
+        assert!(index.has_rebuilt());
+        assert!(!index.is_initialized().await);
+    }
+
+    #[tokio::test]
+    async fn test_status_counts_exchanges_and_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        for (token, exchange) in [("NACHO", "mexc"), ("NACHO", "xeggex"), ("SLOW", "mexc")] {
+            std::fs::create_dir_all(dir.path().join(token).join(exchange)).unwrap();
+        }
+        let index = ExchangeIndex::new(dir.path());
+        assert!(index.spawn_rebuild());
+
+        while index.status().await.rebuilding {
+            tokio::task::yield_now().await;
+        }
+
+        let status = index.status().await;
+        assert!(status.initialized);
+        assert_eq!(status.exchanges, 2);
+        assert_eq!(status.tokens, 2);
+        assert!(status.last_built_at.is_some());
+        assert_eq!(index.get_all_tokens().await, vec!["NACHO", "SLOW"]);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_stops_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("NACHO").join("mexc")).unwrap();
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        let index = ExchangeIndex::new(dir.path()).with_shutdown(shutdown);
+        assert!(index.spawn_rebuild());
+
+        while index.status().await.rebuilding {
+            tokio::task::yield_now().await;
+        }
+        assert!(!index.status().await.initialized);
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_single_token() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("SLOW").join("mexc")).unwrap();
+        let index = ExchangeIndex::new(dir.path());
+        index.rebuild().await.unwrap();
+
+        std::fs::create_dir_all(dir.path().join("NACHO").join("mexc")).unwrap();
+        std::fs::create_dir_all(dir.path().join("NACHO").join("xeggex")).unwrap();
+        assert_eq!(index.add_token("NACHO").await.unwrap(), 2);
+        assert_eq!(index.get_tokens("mexc").await, vec!["NACHO", "SLOW"]);
+        assert_eq!(index.get_tokens("xeggex").await, vec!["NACHO"]);
+
+        // Re-adding after a directory disappears drops the stale entries
+        std::fs::remove_dir_all(dir.path().join("NACHO").join("xeggex")).unwrap();
+        assert_eq!(index.add_token("NACHO").await.unwrap(), 1);
+        assert_eq!(index.get_exchanges().await, vec!["mexc"]);
+
+        index.remove_token("SLOW").await;
+        index.upsert_exchange("kucoin", "SLOW").await;
+        assert_eq!(index.get_tokens("mexc").await, vec!["NACHO"]);
+        assert_eq!(index.get_tokens("kucoin").await, vec!["SLOW"]);
+
+        let err = index.add_token("../etc").await.unwrap_err();
+        assert!(matches!(ApiError::find(&err), Some(ApiError::Validation(_))));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_reads_stay_consistent_during_incremental_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        for token in ["A", "B", "C"] {
+            std::fs::create_dir_all(dir.path().join(token).join("mexc")).unwrap();
+            std::fs::create_dir_all(dir.path().join(token).join("xeggex")).unwrap();
+        }
+        let index = ExchangeIndex::new(dir.path());
+        index.rebuild().await.unwrap();
+
+        let writer = {
+            let index = index.clone();
+            tokio::spawn(async move {
+                for i in 0..200 {
+                    let token = format!("T{}", i % 10);
+                    index.upsert_exchange("mexc", &token).await;
+                    index.upsert_exchange("kucoin", &token).await;
+                    index.remove_token(&token).await;
+                    index.add_token("B").await.unwrap();
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let index = index.clone();
+                tokio::spawn(async move {
+                    for _ in 0..200 {
+                        let tokens = index.get_tokens("mexc").await;
+                        // Always sorted, never duplicated, never missing the stable tokens
+                        assert!(tokens.windows(2).all(|w| w[0] < w[1]), "{:?}", tokens);
+                        for stable in ["A", "B", "C"] {
+                            assert!(tokens.iter().any(|t| t == stable), "{:?}", tokens);
+                        }
+                        let exchanges = index.get_exchanges().await;
+                        assert!(exchanges.iter().any(|e| e == "xeggex"));
+                        assert!(exchanges.iter().all(|e| ["mexc", "xeggex", "kucoin"].contains(&e.as_str())));
+                    }
+                })
+            })
+            .collect();
+
+        writer.await.unwrap();
+        for reader in readers {
+            reader.await.unwrap();
+        }
+
+        assert_eq!(index.get_tokens("mexc").await, vec!["A", "B", "C"]);
+        assert_eq!(index.get_exchanges().await, vec!["mexc", "xeggex"]);
+    }
+
+    #[tokio::test]
+    async fn test_persisted_index_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        for (token, exchange) in [("NACHO", "mexc"), ("NACHO", "xeggex"), ("SLOW", "mexc")] {
+            std::fs::create_dir_all(dir.path().join(token).join(exchange)).unwrap();
+        }
+        let cache = tempfile::tempdir().unwrap();
+        let index_file = cache.path().join("index").join("exchange-index.json");
+        let built = ExchangeIndex::new(dir.path()).with_persistence(&index_file, 3600);
+        built.rebuild().await.unwrap();
+        assert!(index_file.exists());
+
+        // A restarted process loads the same index without scanning
+        let loaded = ExchangeIndex::new("/nonexistent/exchange-index-data").with_persistence(&index_file, 3600);
+        assert!(loaded.load_persisted().await.unwrap());
+        assert!(loaded.has_rebuilt());
+        assert_eq!(loaded.last_built_at(), built.last_built_at());
+        assert_eq!(loaded.get_exchanges().await, built.get_exchanges().await);
+        for exchange in built.get_exchanges().await {
+            assert_eq!(loaded.get_tokens(&exchange).await, built.get_tokens(&exchange).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_or_missing_persisted_index_is_ignored() {
+        let cache = tempfile::tempdir().unwrap();
+        let index_file = cache.path().join("exchange-index.json");
+        let index = ExchangeIndex::new("/nonexistent/exchange-index-data").with_persistence(&index_file, 3600);
+        assert!(!index.load_persisted().await.unwrap());
+
+        let day_old = PersistedIndex {
+            built_at: chrono::Utc::now().timestamp() - 86400,
+            exchanges: HashMap::from([("mexc".to_string(), vec!["NACHO".to_string()])]),
+        };
+        std::fs::write(&index_file, serde_json::to_vec(&day_old).unwrap()).unwrap();
+        assert!(!index.load_persisted().await.unwrap());
+        assert!(!index.has_rebuilt());
+        assert!(index.get_exchanges().await.is_empty());
+
+        std::fs::write(&index_file, "not json").unwrap();
+        assert!(index.load_persisted().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_missing_data_path_is_not_rebuilt() {
+        let index = ExchangeIndex::new("/nonexistent/exchange-index-data");
+        assert_eq!(index.rebuild().await.unwrap(), 0);
+        assert!(!index.has_rebuilt());
+    }
+}
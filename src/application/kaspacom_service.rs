@@ -3,27 +3,221 @@
 //! This service provides access to all Kaspa.com API endpoints with automatic
 //! tiered caching (Redis + Parquet) to reduce load on the remote API.
 
+use crate::application::cache_key::{content_hash, CacheKey};
 use crate::application::cache_service::{ttl, CacheService};
+use crate::application::ticker_service::InvalidParameter;
 use crate::domain::{
-    FloorPriceEntry, HistoricalDataResponse, HotMint, KnsOrder, KnsListedOrdersResponse,
-    KnsTradeStatsResponse, Krc721CollectionInfo, NftMetadata, NftMint, NftOrder, NftTokensResponse,
-    NftTradeStatsResponse, OpenOrdersResponse, SoldOrder, TokenInfo, TokenLogo, TokensConfig,
-    TradeStatsResponse,
+    created_since, normalize_kns_domain, normalize_ticker, ApiError, FloorPriceEntry,
+    HistoricalDataResponse, HotMint, KnsDomainStatus, KnsListedOrdersResponse, KnsOrder,
+    KnsTradeStatsResponse, Krc721CollectionInfo, LogoImage, NftMetadata, NftMint, NftOrder,
+    NftRarityEntry, NftRarityResponse, NftToken, NftTokenFilter, NftTokensResponse,
+    NftTradeStatsResponse, NftTraitCount, NftTraitDistribution, OpenOrdersResponse, OrderCursor,
+    Page, SoldOrder, TokenInfo, TokenLogo, TokensConfig, TradeStatsResponse, KNS_DOMAIN_SUFFIX,
+    MAX_COLLECTION_SCAN_TOKENS, MAX_NFT_TOKENS_LIMIT,
 };
-use crate::infrastructure::{cache_categories, KaspaComClient};
+use crate::infrastructure::{cache_categories, JsonBatches};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
 use std::sync::Arc;
-use tracing::info;
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Default number of concurrent upstream fetches shared by all bulk requests
+pub const DEFAULT_BULK_CONCURRENCY: usize = 8;
+
+/// Counts one bulk fetch in `kaspacom_bulk_in_flight` until dropped, so a
+/// cancelled fetch is uncounted too
+struct BulkInFlight;
+
+impl BulkInFlight {
+    fn enter() -> Self {
+        metrics::gauge!("kaspacom_bulk_in_flight").increment(1.0);
+        Self
+    }
+}
+
+impl Drop for BulkInFlight {
+    fn drop(&mut self) {
+        metrics::gauge!("kaspacom_bulk_in_flight").decrement(1.0);
+    }
+}
+
+/// Window of KNS sales searched for a domain's last sale (one week)
+pub const KNS_DOMAIN_SALES_MINUTES: f64 = 7.0 * 24.0 * 60.0;
+
+/// Default HTTP gateway `ipfs://` URIs are rewritten to
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs";
+
+/// Rewrite an `ipfs://CID/path` URI to `{gateway}/CID/path`
+///
+/// Anything that isn't an `ipfs://` URI (e.g. an HTTP URL) is returned
+/// unchanged. The legacy `ipfs://ipfs/CID` form is accepted too.
+pub fn resolve_ipfs_uri(uri: &str, gateway: &str) -> String {
+    let Some(path) = uri.strip_prefix("ipfs://") else {
+        return uri.to_string();
+    };
+    let path = path.strip_prefix("ipfs/").unwrap_or(path);
+    format!("{}/{}", gateway.trim_end_matches('/'), path)
+}
+
+/// A cached entry that must have been refreshed within `max_age_secs`
+#[derive(Debug, Clone, Deserialize)]
+pub struct FreshnessTarget {
+    /// Parquet cache category (e.g. `floor_prices`)
+    pub category: String,
+    /// Parquet key within the category (e.g. `all`)
+    pub key: String,
+    /// Maximum acceptable age in seconds
+    pub max_age_secs: u64,
+}
+
+/// Outcome of checking one [`FreshnessTarget`]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FreshnessCheck {
+    pub category: String,
+    pub key: String,
+    pub max_age_secs: u64,
+    /// Seconds since the entry was cached; absent when there is no entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_secs: Option<u64>,
+    /// "ok", "stale" (older than `max_age_secs`) or "missing"
+    pub status: String,
+}
+
+impl FreshnessCheck {
+    /// Whether the entry is within its SLA
+    pub fn is_ok(&self) -> bool {
+        self.status == "ok"
+    }
+}
+
+/// Most tickers a single market-overview request may name
+pub const MAX_MARKET_OVERVIEW_TOKENS: usize = 50;
+
+/// One token's marketplace listing data, joined from floor prices,
+/// token info and logos
+///
+/// Each part is fetched independently; parts that failed are left empty and
+/// named in `missing` so a grid can still render the rest of the row.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MarketOverviewRow {
+    pub ticker: String,
+    /// Lowest listing price in KAS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor_price: Option<f64>,
+    /// Last price in KAS (from token info)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    /// Market cap (from token info)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap: Option<f64>,
+    /// Logo URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    /// Parts that could not be fetched: "floor_price", "token_info", "logo",
+    /// or "volume" when filtering or sorting by it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+/// Sold-order window, in minutes, used for a token detail's recent trades
+pub const TOKEN_DETAIL_TRADES_MINUTES: f64 = 60.0;
+
+/// Most recent trades included in a token detail
+pub const TOKEN_DETAIL_MAX_TRADES: usize = 20;
+
+/// Everything a token detail page shows, in one object
+///
+/// Saves a detail page four round trips. The response is only an error when
+/// nothing at all could be loaded.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TokenDetail {
+    pub ticker: String,
+    /// Time frame of `history`
+    pub time_frame: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<TokenInfo>,
+    /// Lowest listing price in KAS; absent when nothing is listed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor_price: Option<f64>,
+    /// Most recent sold orders of the last hour, newest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_trades: Option<Vec<SoldOrder>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoricalDataResponse>,
+    /// Parts that could not be fetched and are left out: "token_info",
+    /// "floor_price", "recent_trades", "history"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+/// Most configured tokens returned by [`KaspaComService::search`]
+pub const MAX_SEARCH_TOKENS: usize = 20;
+
+/// How long complete search results are cached in Redis (seconds)
+pub const SEARCH_TTL_SECS: u64 = 30;
+
+/// Matches for a free-text search, by category
+///
+/// `tokens` are configured KRC20 tickers containing the query (exact match
+/// first, then prefixes, then other substrings). `collections` and
+/// `domains` hold the KRC721 collection and KNS domain named by the query,
+/// if they exist. A lookup that failed is left empty and named in
+/// `missing`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SearchResults {
+    pub query: String,
+    pub tokens: Vec<String>,
+    pub collections: Vec<Krc721CollectionInfo>,
+    pub domains: Vec<KnsDomainStatus>,
+    /// Lookups that could not be completed: "collection", "domain"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+/// Held while a collection is scanned; see
+/// [`KaspaComService::lock_collection_scan`]
+struct CollectionScanGuard {
+    guard: Option<tokio::sync::OwnedMutexGuard<()>>,
+    ticker: String,
+    scans: Arc<std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl Drop for CollectionScanGuard {
+    fn drop(&mut self) {
+        let mut scans = self.scans.lock().unwrap_or_else(|e| e.into_inner());
+        self.guard.take();
+        // Only the map's own reference left: nobody holds or waits for it
+        if scans.get(&self.ticker).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            scans.remove(&self.ticker);
+        }
+    }
+}
 
 /// Kaspa.com marketplace data service
 ///
 /// Provides cache-first access to all Kaspa.com API endpoints.
 /// Data is fetched from local cache when available, with automatic
 /// refresh from the remote API on cache miss.
+#[derive(Clone)]
 pub struct KaspaComService {
     cache: Arc<CacheService>,
     tokens_config: TokensConfig,
+    /// Caps fan-out across all bulk requests, separate from single fetches
+    bulk_semaphore: Arc<Semaphore>,
+    /// Maximum proxied logo size in bytes; `None` disables the logo proxy
+    logo_proxy_max_bytes: Option<usize>,
+    /// Entries reported by the freshness SLA check
+    freshness_targets: Vec<FreshnessTarget>,
+    /// HTTP gateway NFT image and collection URIs are resolved through
+    ipfs_gateway: String,
+    /// One lock per collection being scanned, so concurrent misses for the
+    /// same collection scan it once
+    collection_scans: Arc<std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
 }
 
 impl KaspaComService {
@@ -36,9 +230,65 @@ impl KaspaComService {
         Self {
             cache,
             tokens_config,
+            bulk_semaphore: Arc::new(Semaphore::new(DEFAULT_BULK_CONCURRENCY)),
+            logo_proxy_max_bytes: None,
+            freshness_targets: Vec::new(),
+            ipfs_gateway: DEFAULT_IPFS_GATEWAY.to_string(),
+            collection_scans: Arc::default(),
         }
     }
 
+    /// Set the maximum number of concurrent fetches shared by bulk requests
+    pub fn with_bulk_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.bulk_semaphore = Arc::new(Semaphore::new(max_concurrency.max(1)));
+        self
+    }
+
+    /// Enable the logo image proxy, rejecting images larger than `max_bytes`
+    pub fn with_logo_proxy(mut self, max_bytes: usize) -> Self {
+        self.logo_proxy_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Whether the logo image proxy is enabled
+    pub fn logo_proxy_enabled(&self) -> bool {
+        self.logo_proxy_max_bytes.is_some()
+    }
+
+    /// Set the cache entries reported by [`Self::check_freshness`]
+    pub fn with_freshness_targets(mut self, targets: Vec<FreshnessTarget>) -> Self {
+        self.freshness_targets = targets;
+        self
+    }
+
+    /// Set the HTTP gateway `ipfs://` URIs are rewritten to
+    pub fn with_ipfs_gateway(mut self, gateway: impl Into<String>) -> Self {
+        self.ipfs_gateway = gateway.into();
+        self
+    }
+
+    /// Compare the age of each configured freshness target with its SLA
+    pub fn check_freshness(&self) -> Vec<FreshnessCheck> {
+        self.freshness_targets
+            .iter()
+            .map(|target| {
+                let age_secs = self.cache.entry_age_secs(&target.category, &target.key);
+                let status = match age_secs {
+                    None => "missing",
+                    Some(age) if age > target.max_age_secs => "stale",
+                    Some(_) => "ok",
+                };
+                FreshnessCheck {
+                    category: target.category.clone(),
+                    key: target.key.clone(),
+                    max_age_secs: target.max_age_secs,
+                    age_secs,
+                    status: status.to_string(),
+                }
+            })
+            .collect()
+    }
+
     /// Get the tokens configuration
     pub fn tokens_config(&self) -> &TokensConfig {
         &self.tokens_config
@@ -49,6 +299,57 @@ impl KaspaComService {
         self.cache.get_stats()
     }
 
+    /// State of the kaspa.com circuit breaker
+    pub fn circuit_breaker_status(&self) -> crate::infrastructure::CircuitBreakerStatus {
+        self.cache.circuit_breaker_status()
+    }
+
+    /// Manually override a cached entry with a custom TTL
+    pub async fn override_cache_entry(
+        &self,
+        category: &str,
+        key: &str,
+        redis_key: Option<&str>,
+        data: &Value,
+        ttl_seconds: u64,
+    ) -> Result<()> {
+        self.cache.put_override(redis_key, category, key, data, ttl_seconds).await
+    }
+
+    /// Pin or unpin a cached entry; returns `false` if the entry doesn't exist
+    ///
+    /// Pinning an entry that isn't cached yet fetches it first when its key
+    /// is one [`Self::warm_entry`] knows how to rebuild.
+    pub async fn pin_cache_entry(&self, category: &str, key: &str, pinned: bool) -> Result<bool> {
+        let found = self.cache.set_pinned(category, key, pinned)?;
+        if found || !pinned || !self.warm_entry(category, key).await? {
+            return Ok(found);
+        }
+        self.cache.set_pinned(category, key, true)
+    }
+
+    /// Fetch the entry behind a Parquet `category`/`key` through the cache
+    ///
+    /// Covers the reference data pins are meant for: token info, logo lists
+    /// and images, and KRC721 collection info. Returns `false` for keys of
+    /// any other shape, which have to be requested once to be cached.
+    async fn warm_entry(&self, category: &str, key: &str) -> Result<bool> {
+        match (category, key.split_once('_')) {
+            (cache_categories::TOKEN_INFO, None) => self.get_token_info(key).await.map(|_| true),
+            (cache_categories::LOGOS, None) => {
+                let ticker = (key != "all").then_some(key);
+                self.get_tokens_logos(ticker).await.map(|_| true)
+            }
+            (cache_categories::LOGOS, Some(("image", ticker))) => {
+                self.get_logo_image(ticker).await.map(|image| image.is_some())
+            }
+            (cache_categories::KRC721, Some(("collection", ticker))) => {
+                self.get_krc721_collection_info(ticker).await.map(|_| true)
+            }
+            _ => Ok(false),
+        }
+    }
+
     // ========================================================================
     // KRC20 Token Endpoints
     // ========================================================================
@@ -59,15 +360,11 @@ impl KaspaComService {
         time_frame: &str,
         ticker: Option<&str>,
     ) -> Result<TradeStatsResponse> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:trade_stats:{}:{}", time_frame, t),
-            None => format!("kaspa:trade_stats:{}", time_frame),
-        };
-        let parquet_key = match &ticker {
-            Some(t) => format!("{}_{}", time_frame, t),
-            None => time_frame.to_string(),
-        };
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:trade_stats")
+            .part(time_frame)
+            .optional_ticker(ticker.as_deref())
+            .build()?;
 
         let client = self.cache.client().clone();
         let tf = time_frame.to_string();
@@ -75,171 +372,254 @@ impl KaspaComService {
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::TRADE_STATS,
-                &parquet_key,
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_trade_stats(&tf, tk.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_trade_stats(&tf, tk.as_deref()).await },
             )
             .await
     }
 
     /// Get floor prices for KRC20 tokens
     pub async fn get_floor_prices(&self, ticker: Option<&str>) -> Result<Vec<FloorPriceEntry>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:floor_price:{}", t),
-            None => "kaspa:floor_price:all".to_string(),
-        };
-        let parquet_key = ticker.as_deref().unwrap_or("all").to_string();
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:floor_price").ticker_or_all(ticker.as_deref()).build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::FLOOR_PRICES,
-                &parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_floor_prices(tk.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_floor_prices(tk.as_deref()).await },
             )
             .await
     }
 
+    /// Get floor prices filtered and sorted per `options`
+    ///
+    /// The full set is cached as fetched and only the returned copy is
+    /// filtered/sorted, so every sort order shares one cache entry. Volume
+    /// is the trade volume over [`MIN_VOLUME_TIME_FRAME`] and is only
+    /// fetched when filtering or sorting by it.
+    pub async fn get_floor_prices_with_options(
+        &self,
+        ticker: Option<&str>,
+        options: &ListingOptions,
+    ) -> Result<Vec<FloorPriceEntry>> {
+        let floor_prices = self.get_floor_prices(ticker).await?;
+        let volumes = self.listing_volumes(options).await?;
+        Ok(apply_listing_options(floor_prices, options, Some(&volumes)))
+    }
+
+    /// Trade volumes by ticker over [`MIN_VOLUME_TIME_FRAME`], fetched only
+    /// when `options` filter or sort by volume
+    async fn listing_volumes(&self, options: &ListingOptions) -> Result<HashMap<String, f64>> {
+        if !options.needs_volume() {
+            return Ok(HashMap::new());
+        }
+        let stats = self.get_trade_stats(MIN_VOLUME_TIME_FRAME, None).await?;
+        Ok(volume_by_ticker(&stats))
+    }
+
     /// Get recently sold orders
+    ///
+    /// With `since`, only orders created after it are returned; the full
+    /// window is still what gets cached, so polling clients share one entry.
     pub async fn get_sold_orders(
         &self,
         ticker: Option<&str>,
         minutes: Option<f64>,
+        since: Option<i64>,
     ) -> Result<Vec<SoldOrder>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
+        let ticker = ticker.map(normalize_ticker);
         let mins = minutes.unwrap_or(60.0);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:sold_orders:{}:{}", t, mins as i64),
-            None => format!("kaspa:sold_orders:all:{}", mins as i64),
-        };
-        let parquet_key = match &ticker {
-            Some(t) => format!("{}_{}", t, mins as i64),
-            None => format!("all_{}", mins as i64),
-        };
+        let key = CacheKey::builder("kaspa:sold_orders")
+            .ticker_or_all(ticker.as_deref())
+            .minutes(mins)
+            .build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::ORDERS,
-                &parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_sold_orders(tk.as_deref(), Some(mins)).await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_sold_orders(tk.as_deref(), Some(mins)).await },
             )
             .await
+            .map(|orders| created_since(orders, since))
+    }
+
+    /// Get every sold order in the window, a batch at a time
+    ///
+    /// Same cache entry as [`Self::get_sold_orders`], but a Parquet hit is
+    /// read batch by batch instead of being loaded whole, for exports.
+    pub async fn export_sold_orders(&self, ticker: Option<&str>, minutes: Option<f64>) -> Result<JsonBatches> {
+        let ticker = ticker.map(normalize_ticker);
+        let mins = minutes.unwrap_or(60.0);
+        let key = CacheKey::builder("kaspa:sold_orders")
+            .ticker_or_all(ticker.as_deref())
+            .minutes(mins)
+            .build()?;
+
+        let client = self.cache.client().clone();
+        self.cache
+            .get_cached_batches(
+                key.redis_key(),
+                cache_categories::ORDERS,
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_sold_orders(ticker.as_deref(), Some(mins)).await },
+            )
+            .await
+    }
+
+    /// Get a page of recently sold orders, newest first
+    ///
+    /// `latest_timestamp` is the newest order's `created_at` (or `since`
+    /// when nothing newer arrived), to pass back as `since` on the next poll.
+    pub async fn get_sold_orders_page(
+        &self,
+        ticker: Option<&str>,
+        minutes: Option<f64>,
+        since: Option<i64>,
+        limit: Option<usize>,
+        after: Option<&OrderCursor>,
+    ) -> Result<Page<SoldOrder>> {
+        let orders = self.get_sold_orders(ticker, minutes, since).await?;
+        Ok(Page::paginate(orders, limit, after).polled_since(since))
     }
 
     /// Get the most recent sold order
     pub async fn get_last_order_sold(&self) -> Result<SoldOrder> {
-        let cache_key = "kaspa:last_order_sold";
-        let parquet_key = "last";
+        let key = CacheKey::builder("kaspa").part("last_order_sold").parquet_name("last").build()?;
 
         let client = self.cache.client().clone();
 
         self.cache
             .get_cached(
-                cache_key,
+                key.redis_key(),
                 cache_categories::ORDERS,
-                parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_last_order_sold().await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_last_order_sold().await },
             )
             .await
     }
 
     /// Get hot minting tokens
     pub async fn get_hot_mints(&self, time_interval: &str) -> Result<Vec<HotMint>> {
-        let cache_key = format!("kaspa:hot_mints:{}", time_interval);
-        let parquet_key = time_interval.to_string();
+        let key = CacheKey::builder("kaspa:hot_mints").part(time_interval).build()?;
 
         let client = self.cache.client().clone();
         let ti = time_interval.to_string();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::HOT_MINTS,
-                &parquet_key,
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_hot_mints(&ti).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_hot_mints(&ti).await },
             )
             .await
     }
 
     /// Get comprehensive token info
     pub async fn get_token_info(&self, ticker: &str) -> Result<TokenInfo> {
-        let ticker = KaspaComClient::normalize_ticker(ticker);
-        let cache_key = format!("kaspa:token_info:{}", ticker);
-        let parquet_key = ticker.clone();
+        let ticker = normalize_ticker(ticker);
+        let key = CacheKey::builder("kaspa:token_info").ticker(&ticker).build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::TOKEN_INFO,
-                &parquet_key,
-                ttl::COLD_REDIS_SECS,
-                ttl::COLD_PARQUET_SECS,
-                || async move { client.fetch_token_info(&tk).await },
+                key.parquet_key(),
+                self.cache.ttls().cold(),
+                move || async move { client.fetch_token_info(&tk).await },
             )
             .await
     }
 
     /// Get token logos
     pub async fn get_tokens_logos(&self, ticker: Option<&str>) -> Result<Vec<TokenLogo>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:logos:{}", t),
-            None => "kaspa:logos:all".to_string(),
-        };
-        let parquet_key = ticker.as_deref().unwrap_or("all").to_string();
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:logos").ticker_or_all(ticker.as_deref()).build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
+                cache_categories::LOGOS,
+                key.parquet_key(),
+                self.cache.ttls().static_data(),
+                move || async move { client.fetch_tokens_logos(tk.as_deref()).await },
+            )
+            .await
+    }
+
+    /// Get a token's logo image through the proxy cache
+    ///
+    /// Only the URL found in the cached `tokens-logos` data for `ticker` is
+    /// ever fetched, so callers cannot make the proxy request arbitrary
+    /// hosts. Returns `None` if the ticker has no logo.
+    pub async fn get_logo_image(&self, ticker: &str) -> Result<Option<LogoImage>> {
+        let Some(max_bytes) = self.logo_proxy_max_bytes else {
+            anyhow::bail!("Logo proxy is disabled");
+        };
+        let ticker = normalize_ticker(ticker);
+
+        let logos = self.get_tokens_logos(Some(&ticker)).await?;
+        let Some(url) = logos
+            .into_iter()
+            .find(|l| normalize_ticker(&l.ticker) == ticker)
+            .map(|l| l.logo)
+            .filter(|logo| !logo.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+
+        let key = CacheKey::builder("kaspa:logos").part("image").ticker(&ticker).build()?;
+        let client = self.cache.client().clone();
+
+        self.cache
+            .get_cached(
+                key.redis_key(),
                 cache_categories::LOGOS,
-                &parquet_key,
-                ttl::STATIC_REDIS_SECS,
-                ttl::STATIC_PARQUET_SECS,
-                || async move { client.fetch_tokens_logos(tk.as_deref()).await },
+                key.parquet_key(),
+                (ttl::ASSET_REDIS_SECS, ttl::ASSET_PARQUET_SECS),
+                move || async move { client.fetch_image(&url, max_bytes).await },
             )
             .await
+            .map(Some)
     }
 
     /// Get tickers with active open orders
     pub async fn get_open_orders(&self) -> Result<OpenOrdersResponse> {
-        let cache_key = "kaspa:open_orders";
-        let parquet_key = "active";
+        let key = CacheKey::builder("kaspa").part("open_orders").parquet_name("active").build()?;
 
         let client = self.cache.client().clone();
 
         self.cache
             .get_cached(
-                cache_key,
+                key.redis_key(),
                 cache_categories::ORDERS,
-                parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_open_orders().await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_open_orders().await },
             )
             .await
     }
@@ -250,9 +630,11 @@ impl KaspaComService {
         time_frame: &str,
         ticker: &str,
     ) -> Result<HistoricalDataResponse> {
-        let ticker = KaspaComClient::normalize_ticker(ticker);
-        let cache_key = format!("kaspa:historical:{}:{}", ticker, time_frame);
-        let parquet_key = format!("{}_{}", ticker, time_frame);
+        let ticker = normalize_ticker(ticker);
+        let key = CacheKey::builder("kaspa:historical")
+            .ticker(&ticker)
+            .part(time_frame)
+            .build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
@@ -260,12 +642,11 @@ impl KaspaComService {
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::HISTORICAL,
-                &parquet_key,
-                ttl::COLD_REDIS_SECS,
-                ttl::COLD_PARQUET_SECS,
-                || async move { client.fetch_historical_data(&tf, &tk).await },
+                key.parquet_key(),
+                self.cache.ttls().cold(),
+                move || async move { client.fetch_historical_data(&tf, &tk).await },
             )
             .await
     }
@@ -276,83 +657,88 @@ impl KaspaComService {
 
     /// Get recent NFT mints
     pub async fn get_krc721_mints(&self, ticker: Option<&str>) -> Result<Vec<NftMint>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:krc721:mints:{}", t),
-            None => "kaspa:krc721:mints:all".to_string(),
-        };
-        let parquet_key = ticker.as_deref().unwrap_or("all").to_string();
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("mints")
+            .ticker_or_all(ticker.as_deref())
+            .build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &format!("mints_{}", parquet_key),
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_krc721_mints(tk.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_krc721_mints(tk.as_deref()).await },
             )
             .await
     }
 
-    /// Get sold NFT orders
+    /// Get sold NFT orders, only those created after `since` if given
     pub async fn get_krc721_sold_orders(
         &self,
         ticker: Option<&str>,
         minutes: Option<f64>,
+        since: Option<i64>,
     ) -> Result<Vec<NftOrder>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
+        let ticker = ticker.map(normalize_ticker);
         let mins = minutes.unwrap_or(60.0);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:krc721:sold:{}:{}", t, mins as i64),
-            None => format!("kaspa:krc721:sold:all:{}", mins as i64),
-        };
-        let parquet_key = match &ticker {
-            Some(t) => format!("sold_{}_{}", t, mins as i64),
-            None => format!("sold_all_{}", mins as i64),
-        };
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("sold")
+            .ticker_or_all(ticker.as_deref())
+            .minutes(mins)
+            .build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_krc721_sold_orders(tk.as_deref(), Some(mins)).await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_krc721_sold_orders(tk.as_deref(), Some(mins)).await },
             )
             .await
+            .map(|orders| created_since(orders, since))
+    }
+
+    /// Get a page of sold NFT orders, newest first, with `latest_timestamp`
+    /// set like [`Self::get_sold_orders_page`]
+    pub async fn get_krc721_sold_orders_page(
+        &self,
+        ticker: Option<&str>,
+        minutes: Option<f64>,
+        since: Option<i64>,
+        limit: Option<usize>,
+        after: Option<&OrderCursor>,
+    ) -> Result<Page<NftOrder>> {
+        let orders = self.get_krc721_sold_orders(ticker, minutes, since).await?;
+        Ok(Page::paginate(orders, limit, after).polled_since(since))
     }
 
     /// Get listed NFT orders
     pub async fn get_krc721_listed_orders(&self, ticker: Option<&str>) -> Result<Vec<NftOrder>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:krc721:listed:{}", t),
-            None => "kaspa:krc721:listed:all".to_string(),
-        };
-        let parquet_key = match &ticker {
-            Some(t) => format!("listed_{}", t),
-            None => "listed_all".to_string(),
-        };
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("listed")
+            .ticker_or_all(ticker.as_deref())
+            .build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_krc721_listed_orders(tk.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_krc721_listed_orders(tk.as_deref()).await },
             )
             .await
     }
@@ -363,15 +749,12 @@ impl KaspaComService {
         time_frame: &str,
         ticker: Option<&str>,
     ) -> Result<NftTradeStatsResponse> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:krc721:stats:{}:{}", time_frame, t),
-            None => format!("kaspa:krc721:stats:{}", time_frame),
-        };
-        let parquet_key = match &ticker {
-            Some(t) => format!("stats_{}_{}", time_frame, t),
-            None => format!("stats_{}", time_frame),
-        };
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("stats")
+            .part(time_frame)
+            .optional_ticker(ticker.as_deref())
+            .build()?;
 
         let client = self.cache.client().clone();
         let tf = time_frame.to_string();
@@ -379,113 +762,308 @@ impl KaspaComService {
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_krc721_trade_stats(&tf, tk.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_krc721_trade_stats(&tf, tk.as_deref()).await },
             )
             .await
     }
 
     /// Get hot minting NFT collections
     pub async fn get_krc721_hot_mints(&self, time_interval: &str) -> Result<Vec<HotMint>> {
-        let cache_key = format!("kaspa:krc721:hot_mints:{}", time_interval);
-        let parquet_key = format!("hot_mints_{}", time_interval);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("hot_mints")
+            .part(time_interval)
+            .build()?;
 
         let client = self.cache.client().clone();
         let ti = time_interval.to_string();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_krc721_hot_mints(&ti).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_krc721_hot_mints(&ti).await },
             )
             .await
     }
 
     /// Get NFT floor prices
     pub async fn get_krc721_floor_prices(&self, ticker: Option<&str>) -> Result<Vec<FloorPriceEntry>> {
-        let ticker = ticker.map(KaspaComClient::normalize_ticker);
-        let cache_key = match &ticker {
-            Some(t) => format!("kaspa:krc721:floor:{}", t),
-            None => "kaspa:krc721:floor:all".to_string(),
-        };
-        let parquet_key = match &ticker {
-            Some(t) => format!("floor_{}", t),
-            None => "floor_all".to_string(),
-        };
+        let ticker = ticker.map(normalize_ticker);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("floor")
+            .ticker_or_all(ticker.as_deref())
+            .build()?;
 
         let client = self.cache.client().clone();
         let tk = ticker.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_krc721_floor_prices(tk.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_krc721_floor_prices(tk.as_deref()).await },
             )
             .await
     }
 
     /// Get filtered NFT tokens with pagination
-    pub async fn get_krc721_tokens(&self, filter: &Value) -> Result<NftTokensResponse> {
-        // For filtered queries, we don't cache as the filter varies too much
-        // In production, you might want to cache common filter combinations
-        let client = self.cache.client();
-        let value = client.fetch_krc721_tokens(filter).await?;
+    ///
+    /// Results are cached per canonical filter, except for per-wallet
+    /// `owner` filters.
+    /// A requested page size above [`crate::domain::MAX_NFT_TOKENS_LIMIT`]
+    /// is lowered to it (and `limit_capped` set) so one request can't pull an
+    /// arbitrarily large page from the upstream; the page, the limit sent and
+    /// `total_pages` are returned with the items.
+    pub async fn get_krc721_tokens(&self, filter: &NftTokenFilter) -> Result<NftTokensResponse> {
+        let (limit, limit_capped) = filter.capped_limit();
+        let page = filter.page.unwrap_or(1);
+        let filter = NftTokenFilter {
+            ticker: filter.ticker.as_deref().map(normalize_ticker),
+            limit,
+            ..filter.clone()
+        };
+        let body = serde_json::to_value(&filter)?;
+
+        let response: NftTokensResponse = match krc721_tokens_cache_key(&filter, &body)? {
+            Some(key) => {
+                let client = self.cache.client().clone();
+                self.cache
+                    .get_cached(
+                        key.redis_key(),
+                        cache_categories::KRC721,
+                        key.parquet_key(),
+                        self.cache.ttls().hot(),
+                        move || async move { client.fetch_krc721_tokens(&body).await },
+                    )
+                    .await?
+            }
+            None => serde_json::from_value(self.cache.client().fetch_krc721_tokens(&body).await?)?,
+        };
+        Ok(response.with_paging(page, limit, limit_capped))
+    }
+
+    /// Get every token of a collection ranked by rarity, rarest first
+    ///
+    /// Pages through [`Self::get_krc721_tokens`] for the whole collection
+    /// (at most [`MAX_COLLECTION_SCAN_TOKENS`] tokens). Upstream
+    /// `rarityRank`s are used when every token has one, otherwise ranks are
+    /// computed from the traits' rarity. Finished collections are cached
+    /// COLD; while a collection is still minting ranks can change with each
+    /// mint, so the result is only cached WARM and flagged `minting`.
+    /// Concurrent misses for one collection scan it once, and a scan only
+    /// starts when the rate limit has budget for all of its pages.
+    pub async fn get_krc721_rarity(&self, ticker: &str) -> Result<NftRarityResponse> {
+        let normalized = normalize_ticker(ticker);
+        let (minting, ttls) = self.collection_scan_ttls(&normalized).await?;
+        let _scan = self.lock_collection_scan(&normalized).await;
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("rarity")
+            .ticker(&normalized)
+            .build()?;
+
+        let this = self.clone();
+        let value = self
+            .cache
+            .get_cached_json(
+                key.redis_key(),
+                cache_categories::KRC721,
+                key.parquet_key(),
+                ttls,
+                move || async move {
+                    let mut tokens = Vec::new();
+                    this.scan_krc721_tokens(&normalized, |page| tokens.extend_from_slice(page))
+                        .await?;
+                    let (tokens, rank_source) = rank_by_rarity(tokens);
+                    Ok(serde_json::to_value(NftRarityResponse {
+                        ticker: normalized.clone(),
+                        tokens,
+                        minting,
+                        rank_source: rank_source.to_string(),
+                    })?)
+                },
+            )
+            .await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Count each trait value across a collection, with percentages
+    ///
+    /// Scans the collection page by page like [`Self::get_krc721_rarity`]
+    /// (same size limit and caching), but only the counts are kept, so
+    /// memory grows with the number of distinct trait values rather than
+    /// with the number of tokens.
+    pub async fn get_krc721_trait_distribution(&self, ticker: &str) -> Result<NftTraitDistribution> {
+        let normalized = normalize_ticker(ticker);
+        let (minting, ttls) = self.collection_scan_ttls(&normalized).await?;
+        let _scan = self.lock_collection_scan(&normalized).await;
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("traits")
+            .ticker(&normalized)
+            .build()?;
+
+        let this = self.clone();
+        let value = self
+            .cache
+            .get_cached_json(
+                key.redis_key(),
+                cache_categories::KRC721,
+                key.parquet_key(),
+                ttls,
+                move || async move {
+                    let mut total_tokens = 0u64;
+                    let mut counts: BTreeMap<String, HashMap<String, u64>> = BTreeMap::new();
+                    this.scan_krc721_tokens(&normalized, |page| {
+                        total_tokens += page.len() as u64;
+                        for (trait_type, t) in page.iter().filter_map(|token| token.traits.as_ref()).flatten() {
+                            *counts
+                                .entry(trait_type.clone())
+                                .or_default()
+                                .entry(t.value.clone())
+                                .or_default() += 1;
+                        }
+                    })
+                    .await?;
+                    Ok(serde_json::to_value(NftTraitDistribution {
+                        ticker: normalized.clone(),
+                        total_tokens,
+                        minting,
+                        traits: trait_percentages(counts, total_tokens),
+                    })?)
+                },
+            )
+            .await?;
         Ok(serde_json::from_value(value)?)
     }
 
+    /// Whether a collection is still minting, and the Redis and Parquet TTLs
+    /// for results computed by scanning it
+    ///
+    /// COLD once minting has finished; WARM while new mints can still
+    /// change the result.
+    async fn collection_scan_ttls(&self, ticker: &str) -> Result<(bool, (u64, u64))> {
+        let info = self.get_krc721_collection_info(ticker).await?;
+        let minting = info.total_minted < info.total_supply;
+        let ttls = self.cache.ttls();
+        Ok((minting, if minting { ttls.warm() } else { ttls.cold() }))
+    }
+
+    /// Wait for any other scan of `ticker` to finish
+    ///
+    /// The scan's result is cached by the time the lock is released, so
+    /// waiters are then served from cache. Locks are dropped from the map
+    /// once nobody holds or waits for them.
+    async fn lock_collection_scan(&self, ticker: &str) -> CollectionScanGuard {
+        let lock = {
+            let mut scans = self.collection_scans.lock().unwrap_or_else(|e| e.into_inner());
+            scans.entry(ticker.to_string()).or_default().clone()
+        };
+        CollectionScanGuard {
+            guard: Some(lock.lock_owned().await),
+            ticker: ticker.to_string(),
+            scans: self.collection_scans.clone(),
+        }
+    }
+
+    /// Call `visit` with each page of a collection's tokens, in order
+    ///
+    /// Pages are fetched one at a time at the maximum page size, so only one
+    /// page is held here at once. Fails with a validation error for
+    /// collections larger than [`MAX_COLLECTION_SCAN_TOKENS`], and with a
+    /// rate limit error when the first page shows the remaining pages
+    /// wouldn't fit in the KRC721 budget.
+    async fn scan_krc721_tokens(&self, ticker: &str, mut visit: impl FnMut(&[NftToken])) -> Result<()> {
+        let mut page = 1;
+        loop {
+            let filter = NftTokenFilter {
+                ticker: Some(ticker.to_string()),
+                page: Some(page),
+                limit: Some(MAX_NFT_TOKENS_LIMIT),
+                ..NftTokenFilter::default()
+            };
+            let response = self.get_krc721_tokens(&filter).await?;
+            if response.total_count > MAX_COLLECTION_SCAN_TOKENS {
+                return Err(ApiError::Validation(format!(
+                    "Collection {} has {} tokens; at most {} can be scanned",
+                    ticker, response.total_count, MAX_COLLECTION_SCAN_TOKENS
+                ))
+                .into());
+            }
+            // Always known here, as every page is requested with a limit
+            let total_pages = response.total_pages.unwrap_or_default();
+            if page == 1 {
+                let remaining_pages = u32::try_from(total_pages - 1).unwrap_or(0);
+                self.cache
+                    .ensure_rate_limit_budget(cache_categories::KRC721, remaining_pages)
+                    .await?;
+            }
+            visit(&response.items);
+            if response.items.is_empty() || i64::from(page) >= total_pages {
+                return Ok(());
+            }
+            page += 1;
+        }
+    }
+
     /// Get KRC721 collection info (holders, supply, rarity)
     pub async fn get_krc721_collection_info(&self, ticker: &str) -> Result<Krc721CollectionInfo> {
-        let normalized = ticker.to_uppercase();
-        let cache_key = format!("kaspa:krc721:collection:{}", normalized);
-        let parquet_key = format!("collection_{}", normalized);
+        let normalized = normalize_ticker(ticker);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("collection")
+            .ticker(&normalized)
+            .build()?;
 
         let client = self.cache.client().clone();
         let ticker_clone = normalized.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_krc721_collection_info(&ticker_clone).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_krc721_collection_info(&ticker_clone).await },
             )
             .await
+            .map(|mut info: Krc721CollectionInfo| {
+                info.buri = info.buri.map(|buri| resolve_ipfs_uri(&buri, &self.ipfs_gateway));
+                info
+            })
     }
 
     /// Get NFT metadata from krc721.stream
     pub async fn get_nft_metadata(&self, ticker: &str, token_id: i64) -> Result<NftMetadata> {
         // Metadata is relatively static, so we can cache it for longer
-        let normalized = ticker.to_uppercase();
-        let cache_key = format!("kaspa:krc721:metadata:{}:{}", normalized, token_id);
-        let parquet_key = format!("metadata_{}_{}", normalized, token_id);
+        let normalized = normalize_ticker(ticker);
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("metadata")
+            .ticker(&normalized)
+            .part(token_id)
+            .build()?;
 
         let client = self.cache.client().clone();
         let ticker_clone = normalized.clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KRC721,
-                &parquet_key,
-                ttl::COLD_REDIS_SECS, // Longer TTL for metadata
-                ttl::COLD_PARQUET_SECS,
-                || async move { client.fetch_nft_metadata(&ticker_clone, token_id).await },
+                key.parquet_key(),
+                self.cache.ttls().cold(), // Longer TTL for metadata
+                move || async move { client.fetch_nft_metadata(&ticker_clone, token_id).await },
             )
             .await
+            .map(|mut metadata: NftMetadata| {
+                metadata.image = resolve_ipfs_uri(&metadata.image, &self.ipfs_gateway);
+                metadata
+            })
     }
 
     // ========================================================================
@@ -495,19 +1073,17 @@ impl KaspaComService {
     /// Get sold KNS domain orders
     pub async fn get_kns_sold_orders(&self, minutes: Option<f64>) -> Result<Vec<KnsOrder>> {
         let mins = minutes.unwrap_or(60.0);
-        let cache_key = format!("kaspa:kns:sold:{}", mins as i64);
-        let parquet_key = format!("sold_{}", mins as i64);
+        let key = CacheKey::builder("kaspa:kns").part("sold").minutes(mins).build()?;
 
         let client = self.cache.client().clone();
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KNS,
-                &parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_kns_sold_orders(Some(mins)).await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_kns_sold_orders(Some(mins)).await },
             )
             .await
     }
@@ -518,14 +1094,11 @@ impl KaspaComService {
         time_frame: &str,
         asset: Option<&str>,
     ) -> Result<KnsTradeStatsResponse> {
-        let cache_key = match asset {
-            Some(a) => format!("kaspa:kns:stats:{}:{}", time_frame, a),
-            None => format!("kaspa:kns:stats:{}", time_frame),
-        };
-        let parquet_key = match asset {
-            Some(a) => format!("stats_{}_{}", time_frame, a),
-            None => format!("stats_{}", time_frame),
-        };
+        let key = CacheKey::builder("kaspa:kns")
+            .part("stats")
+            .part(time_frame)
+            .optional_part(asset)
+            .build()?;
 
         let client = self.cache.client().clone();
         let tf = time_frame.to_string();
@@ -533,38 +1106,243 @@ impl KaspaComService {
 
         self.cache
             .get_cached(
-                &cache_key,
+                key.redis_key(),
                 cache_categories::KNS,
-                &parquet_key,
-                ttl::WARM_REDIS_SECS,
-                ttl::WARM_PARQUET_SECS,
-                || async move { client.fetch_kns_trade_stats(&tf, ast.as_deref()).await },
+                key.parquet_key(),
+                self.cache.ttls().warm(),
+                move || async move { client.fetch_kns_trade_stats(&tf, ast.as_deref()).await },
             )
             .await
     }
 
     /// Get listed KNS domains
     pub async fn get_kns_listed_orders(&self) -> Result<Vec<KnsOrder>> {
-        let cache_key = "kaspa:kns:listed";
-        let parquet_key = "listed";
+        let key = CacheKey::builder("kaspa:kns").part("listed").build()?;
 
         let client = self.cache.client().clone();
 
         // Fetch wrapper and extract orders
         let wrapper: KnsListedOrdersResponse = self.cache
             .get_cached(
-                cache_key,
+                key.redis_key(),
                 cache_categories::KNS,
-                parquet_key,
-                ttl::HOT_REDIS_SECS,
-                ttl::HOT_PARQUET_SECS,
-                || async move { client.fetch_kns_listed_orders().await },
+                key.parquet_key(),
+                self.cache.ttls().hot(),
+                move || async move { client.fetch_kns_listed_orders().await },
             )
             .await?;
         
         Ok(wrapper.orders)
     }
 
+    /// Get listed KNS domains sorted and sliced per `options`
+    ///
+    /// Like [`Self::get_floor_prices_with_options`], the full listing is
+    /// cached as fetched and only the returned copy is sorted and sliced.
+    pub async fn get_kns_listed_orders_with_options(&self, options: &KnsListingOptions) -> Result<Vec<KnsOrder>> {
+        let orders = self.get_kns_listed_orders().await?;
+        Ok(options.apply(orders))
+    }
+
+    /// Marketplace status of one KNS domain
+    ///
+    /// Kaspa.com has no per-domain endpoint, so the status is derived from
+    /// the cached listed orders and the sales of the last
+    /// [`KNS_DOMAIN_SALES_MINUTES`]. The name may be given with or without
+    /// the `.kas` suffix; a domain in neither list is `NotFound`.
+    pub async fn get_kns_domain(&self, name: &str) -> Result<KnsDomainStatus> {
+        let domain = normalize_kns_domain(name)?;
+        let (listed, sold) = tokio::try_join!(
+            self.get_kns_listed_orders(),
+            self.get_kns_sold_orders(Some(KNS_DOMAIN_SALES_MINUTES)),
+        )?;
+        kns_domain_status(&domain, listed, sold)
+            .ok_or_else(|| ApiError::NotFound(format!("KNS domain '{}' not found", domain)).into())
+    }
+
+    // ========================================================================
+    // Market Overview
+    // ========================================================================
+
+    /// Floor price, price, market cap and logo for many tokens at once
+    ///
+    /// Floor prices and logos come from one cached all-token fetch each;
+    /// token info is fetched per ticker through the bulk semaphore. All three
+    /// run concurrently and a failed part only blanks that part of the rows.
+    /// Without `tickers` the configured token list is used. Rows keep the
+    /// request order with duplicates removed.
+    ///
+    /// `options` filters and sorts the joined rows like
+    /// [`Self::get_floor_prices_with_options`]; if the trade volumes they
+    /// need are unavailable, the rows are returned unfiltered with "volume"
+    /// in `missing`.
+    pub async fn get_market_overview(
+        &self,
+        tickers: Option<Vec<String>>,
+        options: &ListingOptions,
+    ) -> Result<Vec<MarketOverviewRow>> {
+        let mut seen = std::collections::HashSet::new();
+        let tickers: Vec<String> = tickers
+            .unwrap_or_else(|| self.get_configured_tokens())
+            .iter()
+            .map(|t| normalize_ticker(t))
+            .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+            .collect();
+
+        let (floor_prices, logos, token_infos) = tokio::join!(
+            self.get_floor_prices(None),
+            self.get_tokens_logos(None),
+            self.fetch_bulk(tickers.clone(), |ticker| async move {
+                self.get_token_info(&ticker).await
+            }),
+        );
+
+        // Parts fetched once for all rows go missing from every row
+        let mut missing_everywhere = Vec::new();
+        let floor_prices: Option<HashMap<String, f64>> =
+            detail_part(floor_prices, "floor_price", "market overview", &mut missing_everywhere)
+                .map(|entries| {
+                    entries
+                        .into_iter()
+                        .map(|e| (normalize_ticker(&e.ticker), e.floor_price))
+                        .collect()
+                });
+        let logos: Option<HashMap<String, String>> =
+            detail_part(logos, "logo", "market overview", &mut missing_everywhere).map(|entries| {
+                entries
+                    .into_iter()
+                    .map(|e| (normalize_ticker(&e.ticker), e.logo))
+                    .collect()
+            });
+
+        let mut rows = tickers
+            .into_iter()
+            .zip(token_infos)
+            .map(|(ticker, token_info)| {
+                let mut missing = missing_everywhere.clone();
+                let floor_price = floor_prices.as_ref().and_then(|m| m.get(&ticker).copied());
+                let logo = logos.as_ref().and_then(|m| m.get(&ticker).cloned());
+                let info = detail_part(token_info, "token_info", &ticker, &mut missing);
+                let price = info.as_ref().map(|i| i.price);
+                let market_cap = info.as_ref().map(|i| i.market_cap);
+                MarketOverviewRow { ticker, floor_price, price, market_cap, logo, missing }
+            })
+            .collect::<Vec<_>>();
+
+        let volumes = self.listing_volumes(options).await;
+        let mut missing_volume = Vec::new();
+        let volumes = detail_part(volumes, "volume", "market overview", &mut missing_volume);
+        for row in &mut rows {
+            row.missing.extend(missing_volume.iter().cloned());
+        }
+        Ok(apply_listing_options(rows, options, volumes.as_ref()))
+    }
+
+    /// Get token info, floor price, recent trades and history for one token
+    ///
+    /// The four lookups run concurrently through the tiered cache. Failed
+    /// parts are named in `missing`; only if all four fail is the token
+    /// info error returned instead.
+    pub async fn get_token_detail(&self, ticker: &str, time_frame: &str) -> Result<TokenDetail> {
+        let ticker = normalize_ticker(ticker);
+        let (info, floor_prices, trades, history) = tokio::join!(
+            self.get_token_info(&ticker),
+            self.get_floor_prices(Some(&ticker)),
+            self.get_sold_orders(Some(&ticker), Some(TOKEN_DETAIL_TRADES_MINUTES), None),
+            self.get_historical_data(time_frame, &ticker),
+        );
+
+        let info = match info {
+            Err(e) if floor_prices.is_err() && trades.is_err() && history.is_err() => return Err(e),
+            info => info,
+        };
+
+        let mut missing = Vec::new();
+        let info = detail_part(info, "token_info", &ticker, &mut missing);
+        let floor_price = detail_part(floor_prices, "floor_price", &ticker, &mut missing)
+            .and_then(|entries| entries.first().map(|e| e.floor_price));
+        let recent_trades = detail_part(trades, "recent_trades", &ticker, &mut missing).map(|mut trades| {
+            trades.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+            trades.truncate(TOKEN_DETAIL_MAX_TRADES);
+            trades
+        });
+        let history = detail_part(history, "history", &ticker, &mut missing);
+
+        Ok(TokenDetail {
+            ticker,
+            time_frame: time_frame.to_string(),
+            info,
+            floor_price,
+            recent_trades,
+            history,
+            missing,
+        })
+    }
+
+    /// Search configured tokens, KRC721 collections and KNS domains at once
+    ///
+    /// Token matches come from the configured token list; the collection
+    /// and domain lookups run concurrently through the tiered cache. A
+    /// query ending in `.kas` is only looked up as a domain, and one that
+    /// isn't alphanumeric is never a collection ticker. Complete results
+    /// are cached per normalized query in Redis only, for
+    /// [`SEARCH_TTL_SECS`]; results with missing lookups aren't cached.
+    pub async fn search(&self, query: &str) -> Result<SearchResults> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Err(ApiError::Validation("Search query must not be empty".to_string()).into());
+        }
+        let key = CacheKey::builder("kaspa:search").part(content_hash(&query)?).build()?;
+
+        if let Some(results) = self.cache.get_redis_only(key.redis_key()).await {
+            return Ok(results);
+        }
+        let results = self.search_uncached(&query).await;
+        if results.missing.is_empty() {
+            self.cache.set_redis_only(key.redis_key(), &results, SEARCH_TTL_SECS).await;
+        }
+        Ok(results)
+    }
+
+    async fn search_uncached(&self, query: &str) -> SearchResults {
+        let domain_only = query.ends_with(KNS_DOMAIN_SUFFIX);
+        let ticker_like = !domain_only && query.chars().all(|c| c.is_ascii_alphanumeric());
+        let domain = normalize_kns_domain(query).ok();
+
+        let (collection, domain) = tokio::join!(
+            async {
+                if ticker_like {
+                    Some(self.get_krc721_collection_info(query).await)
+                } else {
+                    None
+                }
+            },
+            async {
+                match domain {
+                    Some(domain) => Some(self.get_kns_domain(&domain).await),
+                    None => None,
+                }
+            },
+        );
+
+        let mut missing = Vec::new();
+        let tokens = if domain_only {
+            Vec::new()
+        } else {
+            matching_tokens(&self.tokens_config.get_tokens(), query)
+        };
+        let collections = collection
+            .and_then(|result| search_part(result, "collection", query, &mut missing))
+            .into_iter()
+            .collect();
+        let domains = domain
+            .and_then(|result| search_part(result, "domain", query, &mut missing))
+            .into_iter()
+            .collect();
+
+        SearchResults { query: query.to_string(), tokens, collections, domains, missing }
+    }
+
     // ========================================================================
     // Token Configuration Helpers
     // ========================================================================
@@ -583,4 +1361,1407 @@ impl KaspaComService {
     pub fn is_token_configured(&self, token: &str) -> bool {
         self.tokens_config.has_token(token)
     }
+
+    // ========================================================================
+    // Bulk Fan-out
+    // ========================================================================
+
+    /// Run `fetch` for every item, bounded by the shared bulk semaphore
+    ///
+    /// All bulk requests queue against the same permits, so concurrent bulk
+    /// calls can't saturate the upstream and slow down single-item traffic.
+    /// Results are returned in input order. The number of fetches holding a
+    /// permit is exported as the `kaspacom_bulk_in_flight` gauge.
+    pub async fn fetch_bulk<I, T, F, Fut>(&self, items: Vec<I>, fetch: F) -> Vec<Result<T>>
+    where
+        F: Fn(I) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let fetches = items.into_iter().map(|item| {
+            let semaphore = self.bulk_semaphore.clone();
+            let fut = fetch(item);
+            async move {
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Bulk semaphore closed: {}", e))?;
+                let _in_flight = BulkInFlight::enter();
+                fut.await
+            }
+        });
+
+        futures::future::join_all(fetches).await
+    }
+
+    // ========================================================================
+    // Cache Warming
+    // ========================================================================
+
+    /// Pre-fetch token info, floor price, and trade stats for every configured token
+    ///
+    /// Entries that are still fresh in Parquet are skipped so warming doesn't
+    /// spend rate-limit budget needlessly, and the run stops early once the
+    /// rate limiter is exhausted. Returns the number of keys warmed.
+    pub async fn warm_cache(&self) -> usize {
+        let mut warmed = 0;
+        let mut skipped = 0;
+
+        for token in self.get_configured_tokens() {
+            let ticker = normalize_ticker(&token);
+            let targets = [
+                (
+                    cache_categories::TOKEN_INFO,
+                    CacheKey::builder("kaspa:token_info").ticker(&ticker),
+                    self.cache.ttls().cold_parquet_secs,
+                ),
+                (
+                    cache_categories::FLOOR_PRICES,
+                    CacheKey::builder("kaspa:floor_price").ticker(&ticker),
+                    self.cache.ttls().hot_parquet_secs,
+                ),
+                (
+                    cache_categories::TRADE_STATS,
+                    CacheKey::builder("kaspa:trade_stats")
+                        .part(WARM_TRADE_STATS_TIME_FRAME)
+                        .ticker(&ticker),
+                    self.cache.ttls().warm_parquet_secs,
+                ),
+            ];
+
+            for (category, key, parquet_ttl) in targets {
+                let parquet_key = match key.build() {
+                    Ok(key) => key.parquet_key().to_string(),
+                    Err(e) => {
+                        warn!("Skipping warm-up of {}/{}: {}", category, ticker, e);
+                        continue;
+                    }
+                };
+                if self.cache.is_fresh(category, &parquet_key, parquet_ttl) {
+                    skipped += 1;
+                    continue;
+                }
+
+                if !self.cache.has_rate_limit_budget(category).await {
+                    warn!(
+                        "Rate limit budget exhausted, stopping cache warming early ({} keys warmed, {} fresh)",
+                        warmed, skipped
+                    );
+                    return warmed;
+                }
+
+                let result = match category {
+                    cache_categories::TOKEN_INFO => self.get_token_info(&ticker).await.map(|_| ()),
+                    cache_categories::FLOOR_PRICES => {
+                        self.get_floor_prices(Some(&ticker)).await.map(|_| ())
+                    }
+                    _ => self
+                        .get_trade_stats(WARM_TRADE_STATS_TIME_FRAME, Some(&ticker))
+                        .await
+                        .map(|_| ()),
+                };
+
+                match result {
+                    Ok(()) => warmed += 1,
+                    Err(e) => warn!("Failed to warm {}/{}: {}", category, parquet_key, e),
+                }
+            }
+        }
+
+        info!("Cache warming finished: {} keys warmed, {} already fresh", warmed, skipped);
+        warmed
+    }
+}
+
+/// Time frame used when pre-fetching trade stats (matches the handler default)
+const WARM_TRADE_STATS_TIME_FRAME: &str = "6h";
+
+/// Trade-stats window used for the floor-price minimum-volume filter
+pub const MIN_VOLUME_TIME_FRAME: &str = "24h";
+
+/// Sort fields accepted by the floor-price and market-overview listings
+pub const LISTING_SORT_FIELDS: &[&str] = &["floor_price", "volume", "ticker"];
+
+/// Sort directions accepted by the listings
+pub const SORT_ORDERS: &[&str] = &["asc", "desc"];
+
+/// Field a listing is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingSortField {
+    FloorPrice,
+    /// Trade volume over [`MIN_VOLUME_TIME_FRAME`]
+    Volume,
+    Ticker,
+}
+
+/// Filtering and ordering applied to a listing after it is read from cache
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListingOptions {
+    /// Sort field; `None` keeps the upstream order
+    pub sort_by: Option<ListingSortField>,
+    pub descending: bool,
+    /// Minimum trade volume in KAS over [`MIN_VOLUME_TIME_FRAME`]
+    pub min_volume: Option<f64>,
+}
+
+impl ListingOptions {
+    /// Parse `sort_by`/`order` query values (`order` defaults to `asc`)
+    ///
+    /// Unknown values fail with [`InvalidParameter`] so handlers answer 400.
+    pub fn parse(sort_by: Option<&str>, order: Option<&str>, min_volume: Option<f64>) -> Result<Self> {
+        let sort_by = match sort_by {
+            None => None,
+            Some(field) => {
+                InvalidParameter::check("sort_by", field, LISTING_SORT_FIELDS)?;
+                Some(match field {
+                    "floor_price" => ListingSortField::FloorPrice,
+                    "volume" => ListingSortField::Volume,
+                    _ => ListingSortField::Ticker,
+                })
+            }
+        };
+        let order = order.unwrap_or("asc");
+        InvalidParameter::check("order", order, SORT_ORDERS)?;
+
+        Ok(Self {
+            sort_by,
+            descending: order == "desc",
+            min_volume,
+        })
+    }
+
+    /// Whether trade volumes are needed to apply these options
+    fn needs_volume(&self) -> bool {
+        self.min_volume.is_some() || self.sort_by == Some(ListingSortField::Volume)
+    }
+}
+
+/// Sort fields accepted by the KNS listed-orders endpoint
+pub const KNS_LISTING_SORT_FIELDS: &[&str] = &["price", "created_at"];
+
+/// Field KNS listed orders are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnsSortField {
+    Price,
+    CreatedAt,
+}
+
+/// Ordering and offset/limit slicing applied to KNS listed orders after
+/// they are read from cache
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KnsListingOptions {
+    /// Sort field; `None` keeps the upstream order
+    pub sort_by: Option<KnsSortField>,
+    pub descending: bool,
+    /// Orders skipped after sorting
+    pub offset: usize,
+    /// Maximum orders returned; `None` returns the rest
+    pub limit: Option<usize>,
+}
+
+impl KnsListingOptions {
+    /// Parse `sort_by`/`order` query values (`order` defaults to `asc`)
+    ///
+    /// Unknown values fail with [`InvalidParameter`] so handlers answer 400.
+    pub fn parse(sort_by: Option<&str>, order: Option<&str>, offset: Option<usize>, limit: Option<usize>) -> Result<Self> {
+        let sort_by = match sort_by {
+            None => None,
+            Some(field) => {
+                InvalidParameter::check("sort_by", field, KNS_LISTING_SORT_FIELDS)?;
+                Some(match field {
+                    "price" => KnsSortField::Price,
+                    _ => KnsSortField::CreatedAt,
+                })
+            }
+        };
+        let order = order.unwrap_or("asc");
+        InvalidParameter::check("order", order, SORT_ORDERS)?;
+
+        Ok(Self {
+            sort_by,
+            descending: order == "desc",
+            offset: offset.unwrap_or(0),
+            limit,
+        })
+    }
+
+    /// Sort (ties keep their upstream order), then skip `offset` and take `limit`
+    fn apply(&self, mut orders: Vec<KnsOrder>) -> Vec<KnsOrder> {
+        if let Some(field) = self.sort_by {
+            orders.sort_by(|a, b| {
+                let ordering = match field {
+                    KnsSortField::Price => a.price.total_cmp(&b.price),
+                    KnsSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                };
+                if self.descending { ordering.reverse() } else { ordering }
+            });
+        }
+        orders
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+/// A row that can be filtered and sorted by [`ListingOptions`]
+pub trait Listing {
+    fn ticker(&self) -> &str;
+    fn floor_price(&self) -> Option<f64>;
+}
+
+impl Listing for FloorPriceEntry {
+    fn ticker(&self) -> &str {
+        &self.ticker
+    }
+
+    fn floor_price(&self) -> Option<f64> {
+        Some(self.floor_price)
+    }
+}
+
+impl Listing for MarketOverviewRow {
+    fn ticker(&self) -> &str {
+        &self.ticker
+    }
+
+    fn floor_price(&self) -> Option<f64> {
+        self.floor_price
+    }
+}
+
+/// Rank tokens by rarity, rarest first, and say where the ranks came from
+///
+/// Upstream ranks are kept when every token has one. Otherwise each token
+/// scores the sum of `1 / rarity` over its traits (rarer traits weigh
+/// more, independent of whether rarity is a fraction or a percentage) and
+/// equal scores share a rank.
+fn rank_by_rarity(tokens: Vec<NftToken>) -> (Vec<NftRarityEntry>, &'static str) {
+    let traits = |token: &NftToken| -> BTreeMap<String, String> {
+        token
+            .traits
+            .iter()
+            .flatten()
+            .map(|(trait_type, t)| (trait_type.clone(), t.value.clone()))
+            .collect()
+    };
+
+    if !tokens.is_empty() && tokens.iter().all(|t| t.rarity_rank.is_some()) {
+        let mut entries: Vec<NftRarityEntry> = tokens
+            .iter()
+            .map(|token| NftRarityEntry {
+                token_id: token.token_id,
+                rarity_rank: token.rarity_rank.unwrap_or_default(),
+                traits: traits(token),
+            })
+            .collect();
+        entries.sort_by_key(|e| (e.rarity_rank, e.token_id));
+        return (entries, "upstream");
+    }
+
+    let mut scored: Vec<(f64, NftRarityEntry)> = tokens
+        .iter()
+        .map(|token| {
+            let score = token
+                .traits
+                .iter()
+                .flatten()
+                .filter(|(_, t)| t.rarity > 0.0)
+                .map(|(_, t)| 1.0 / t.rarity)
+                .sum();
+            let entry = NftRarityEntry { token_id: token.token_id, rarity_rank: 0, traits: traits(token) };
+            (score, entry)
+        })
+        .collect();
+    scored.sort_by(|(a, x), (b, y)| b.total_cmp(a).then(x.token_id.cmp(&y.token_id)));
+
+    let mut entries: Vec<NftRarityEntry> = Vec::with_capacity(scored.len());
+    let mut previous: Option<f64> = None;
+    for (position, (score, mut entry)) in scored.into_iter().enumerate() {
+        entry.rarity_rank = match entries.last() {
+            Some(last) if previous == Some(score) => last.rarity_rank,
+            _ => position as i32 + 1,
+        };
+        previous = Some(score);
+        entries.push(entry);
+    }
+    (entries, "computed")
+}
+
+/// Per trait type, its values with their share of `total_tokens`, most
+/// common first
+fn trait_percentages(
+    counts: BTreeMap<String, HashMap<String, u64>>,
+    total_tokens: u64,
+) -> BTreeMap<String, Vec<NftTraitCount>> {
+    counts
+        .into_iter()
+        .map(|(trait_type, values)| {
+            let mut values: Vec<NftTraitCount> = values
+                .into_iter()
+                .map(|(value, count)| NftTraitCount {
+                    value,
+                    count,
+                    percentage: count as f64 * 100.0 / total_tokens.max(1) as f64,
+                })
+                .collect();
+            values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            (trait_type, values)
+        })
+        .collect()
+}
+
+/// A response part's value, or `None` with the part named in `missing`
+///
+/// Shared by the responses that degrade part by part instead of failing:
+/// token detail, market overview and search.
+fn detail_part<T>(result: Result<T>, part: &str, subject: &str, missing: &mut Vec<String>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("{} for {} unavailable: {}", part, subject, e);
+            missing.push(part.to_string());
+            None
+        }
+    }
+}
+
+/// Status of `domain` (already normalized) from listed and sold orders,
+/// or `None` if it appears in neither
+fn kns_domain_status(domain: &str, listed: Vec<KnsOrder>, sold: Vec<KnsOrder>) -> Option<KnsDomainStatus> {
+    let matches = |order: &KnsOrder| order.asset_id.trim().eq_ignore_ascii_case(domain);
+    let listing = listed.into_iter().filter(matches).max_by_key(|o| o.created_at);
+    let last_sale = sold
+        .into_iter()
+        .filter(matches)
+        .max_by_key(|o| o.fulfillment_timestamp.unwrap_or(o.created_at));
+    if listing.is_none() && last_sale.is_none() {
+        return None;
+    }
+    Some(KnsDomainStatus {
+        domain: domain.to_string(),
+        status: if listing.is_some() { "listed" } else { "sold" }.to_string(),
+        listing,
+        last_sale,
+    })
+}
+
+/// A search lookup's match, `None` if nothing is named by the query, or
+/// `None` named in `missing` if the lookup failed
+fn search_part<T>(result: Result<T>, name: &str, query: &str, missing: &mut Vec<String>) -> Option<T> {
+    match result {
+        Err(e) if matches!(ApiError::find(&e), Some(ApiError::NotFound(_) | ApiError::Validation(_))) => None,
+        result => detail_part(result, name, query, missing),
+    }
+}
+
+/// Configured tickers containing `query` (lowercase), exact match first,
+/// then prefixes, then other substrings, each group alphabetical
+fn matching_tokens(tokens: &[String], query: &str) -> Vec<String> {
+    let mut matches: Vec<(u8, String)> = tokens
+        .iter()
+        .filter_map(|token| {
+            let lowered = token.to_lowercase();
+            let rank = if lowered == query {
+                0
+            } else if lowered.starts_with(query) {
+                1
+            } else if lowered.contains(query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, normalize_ticker(token)))
+        })
+        .collect();
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches.into_iter().take(MAX_SEARCH_TOKENS).map(|(_, ticker)| ticker).collect()
+}
+
+/// Trade volume in KAS per normalized ticker
+fn volume_by_ticker(stats: &TradeStatsResponse) -> HashMap<String, f64> {
+    stats
+        .tokens
+        .iter()
+        .map(|t| (normalize_ticker(&t.ticker), t.total_volume_kas))
+        .collect()
+}
+
+/// Apply a [`ListingOptions`] min-volume filter and sort to a listing
+///
+/// `volumes` is `None` when they couldn't be fetched: the min-volume filter
+/// is then skipped rather than dropping every entry, and a volume sort keeps
+/// the original order.
+fn apply_listing_options<T: Listing>(
+    items: Vec<T>,
+    options: &ListingOptions,
+    volumes: Option<&HashMap<String, f64>>,
+) -> Vec<T> {
+    let no_volumes = HashMap::new();
+    let mut items = match (options.min_volume, volumes) {
+        (Some(min_volume), Some(volumes)) => filter_by_min_volume(items, volumes, min_volume),
+        _ => items,
+    };
+    if let Some(field) = options.sort_by {
+        sort_listing(&mut items, field, options.descending, volumes.unwrap_or(&no_volumes));
+    }
+    items
+}
+
+/// Drop entries whose ticker traded less than `min_volume` KAS.
+/// Tickers missing from the trade stats have no volume.
+fn filter_by_min_volume<T: Listing>(items: Vec<T>, volumes: &HashMap<String, f64>, min_volume: f64) -> Vec<T> {
+    items
+        .into_iter()
+        .filter(|item| {
+            volumes
+                .get(&normalize_ticker(item.ticker()))
+                .is_some_and(|volume| *volume >= min_volume)
+        })
+        .collect()
+}
+
+/// Sort a listing in place; entries without a value for `field` go last
+/// in either direction, and ties keep their original order.
+fn sort_listing<T: Listing>(items: &mut [T], field: ListingSortField, descending: bool, volumes: &HashMap<String, f64>) {
+    let directed = |ordering: std::cmp::Ordering| if descending { ordering.reverse() } else { ordering };
+    let by_value = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => directed(a.total_cmp(&b)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    };
+    let volume = |item: &T| volumes.get(&normalize_ticker(item.ticker())).copied();
+
+    match field {
+        ListingSortField::Ticker => {
+            items.sort_by(|a, b| directed(normalize_ticker(a.ticker()).cmp(&normalize_ticker(b.ticker()))))
+        }
+        ListingSortField::FloorPrice => items.sort_by(|a, b| by_value(a.floor_price(), b.floor_price())),
+        ListingSortField::Volume => items.sort_by(|a, b| by_value(volume(a), volume(b))),
+    }
+}
+
+/// Cache key for a KRC721 tokens query, or `None` for uncacheable filters
+///
+/// `body` is the upstream request for `filter` (ticker and paging already
+/// normalized); it is hashed in canonical form, with object keys sorted,
+/// so logically identical filters share an entry whatever their key order.
+/// `owner` filters are one wallet each and would only fragment the cache.
+fn krc721_tokens_cache_key(filter: &NftTokenFilter, body: &Value) -> Result<Option<CacheKey>> {
+    if filter.owner.is_some() {
+        return Ok(None);
+    }
+    CacheKey::builder("kaspa:krc721")
+        .part("tokens")
+        .part(content_hash(body)?)
+        .build()
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::TokenTradeStats;
+    use crate::infrastructure::kaspacom_client::mock_server;
+
+    /// Cache over the Kaspa.com stand-in at `upstream`, with `redis` in front
+    /// of Parquet under `cache_dir`; the client doesn't retry, so upstream
+    /// errors surface at once
+    fn mock_cache(
+        upstream: &str,
+        redis: Arc<dyn crate::domain::CacheRepository>,
+        cache_dir: &std::path::Path,
+        requests_per_minute: u32,
+    ) -> CacheService {
+        use crate::infrastructure::{KaspaComClient, KaspaComClientConfig, ParquetStore, RateLimiter};
+
+        let config = KaspaComClientConfig { max_retries: 0, ..Default::default() };
+        let client = KaspaComClient::with_config(upstream, &config);
+        CacheService::new(
+            redis,
+            Arc::new(ParquetStore::new(cache_dir.to_str().unwrap())),
+            Arc::new(client),
+            Arc::new(RateLimiter::new(requests_per_minute)),
+        )
+    }
+
+    /// Serve `router` as the Kaspa.com API and build a service over it that
+    /// caches in memory and in Parquet under the returned directory
+    async fn mock_upstream(router: axum::Router) -> (KaspaComService, tempfile::TempDir) {
+        let upstream = mock_server(router).await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = mock_cache(&upstream, Arc::new(crate::infrastructure::InMemoryCache::new()), dir.path(), 60);
+        (KaspaComService::new(Arc::new(cache), TokensConfig { tokens: HashMap::new() }), dir)
+    }
+
+    fn floor(ticker: &str, price: f64) -> FloorPriceEntry {
+        FloorPriceEntry { ticker: ticker.to_string(), floor_price: price, cached_at: None }
+    }
+
+    fn stats(volumes: &[(&str, f64)]) -> TradeStatsResponse {
+        TradeStatsResponse {
+            total_trades_kaspiano: 0,
+            total_volume_kas_kaspiano: "0".to_string(),
+            total_volume_usd_kaspiano: "0".to_string(),
+            tokens: volumes
+                .iter()
+                .map(|(ticker, volume)| TokenTradeStats {
+                    ticker: ticker.to_string(),
+                    total_trades: 1,
+                    total_volume_kas: *volume,
+                    total_volume_usd: "0".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn kns_order(asset_id: &str, created_at: i64, fulfilled_at: Option<i64>) -> KnsOrder {
+        KnsOrder {
+            id: format!("{}-{}", asset_id, created_at),
+            asset_id: asset_id.to_string(),
+            price: 100.0,
+            seller_address: "kaspa:seller".to_string(),
+            buyer_address: fulfilled_at.map(|_| "kaspa:buyer".to_string()),
+            created_at,
+            status: if fulfilled_at.is_some() { "completed" } else { "listed" }.to_string(),
+            fulfillment_timestamp: fulfilled_at,
+        }
+    }
+
+    #[test]
+    fn test_kns_listed_orders_sorted_then_sliced() {
+        let mut orders = vec![kns_order("a.kas", 3, None), kns_order("b.kas", 1, None), kns_order("c.kas", 2, None)];
+        orders[0].price = 50.0;
+        orders[1].price = 300.0;
+        orders[2].price = 10.0;
+        let ids = |orders: Vec<KnsOrder>| orders.into_iter().map(|o| o.asset_id).collect::<Vec<_>>();
+
+        let options = KnsListingOptions::parse(Some("price"), None, None, None).unwrap();
+        assert_eq!(ids(options.apply(orders.clone())), ["c.kas", "a.kas", "b.kas"]);
+
+        let options = KnsListingOptions::parse(Some("created_at"), Some("desc"), None, None).unwrap();
+        assert_eq!(ids(options.apply(orders.clone())), ["a.kas", "c.kas", "b.kas"]);
+
+        let options = KnsListingOptions::parse(Some("price"), Some("desc"), Some(1), Some(1)).unwrap();
+        assert_eq!(ids(options.apply(orders.clone())), ["a.kas"]);
+
+        let options = KnsListingOptions::parse(None, None, Some(2), None).unwrap();
+        assert_eq!(ids(options.apply(orders.clone())), ["c.kas"]);
+
+        let options = KnsListingOptions::parse(None, None, Some(5), Some(10)).unwrap();
+        assert!(options.apply(orders).is_empty());
+    }
+
+    #[test]
+    fn test_kns_listing_options_reject_unknown_values() {
+        let err = KnsListingOptions::parse(Some("seller"), None, None, None).unwrap_err();
+        assert!(err.downcast_ref::<InvalidParameter>().is_some());
+        let err = KnsListingOptions::parse(Some("price"), Some("up"), None, None).unwrap_err();
+        assert!(err.downcast_ref::<InvalidParameter>().is_some());
+        assert_eq!(KnsListingOptions::parse(None, None, None, None).unwrap(), KnsListingOptions::default());
+    }
+
+    #[test]
+    fn test_kns_domain_status() {
+        let listed = vec![kns_order("Alice.kas", 5, None), kns_order("bob.kas", 6, None)];
+        let sold = vec![
+            kns_order("alice.kas", 1, Some(2)),
+            kns_order("alice.kas", 3, Some(4)),
+            kns_order("carol.kas", 1, Some(9)),
+        ];
+
+        let alice = kns_domain_status("alice.kas", listed.clone(), sold.clone()).unwrap();
+        assert_eq!(alice.status, "listed");
+        assert_eq!(alice.listing.unwrap().created_at, 5);
+        assert_eq!(alice.last_sale.unwrap().fulfillment_timestamp, Some(4));
+
+        let carol = kns_domain_status("carol.kas", listed.clone(), sold.clone()).unwrap();
+        assert_eq!(carol.status, "sold");
+        assert!(carol.listing.is_none());
+
+        assert!(kns_domain_status("dave.kas", listed, sold).is_none());
+    }
+
+    #[test]
+    fn test_filter_by_min_volume() {
+        let floors = vec![floor("NACHO", 0.1), floor("slow", 0.2), floor("DUST", 0.3), floor("GHOST", 0.4)];
+        let stats = stats(&[("nacho", 5000.0), ("SLOW", 1000.0), ("DUST", 0.5)]);
+
+        let kept: Vec<String> = filter_by_min_volume(floors, &volume_by_ticker(&stats), 1000.0)
+            .into_iter()
+            .map(|e| e.ticker)
+            .collect();
+
+        // DUST is below the threshold and GHOST has no trades at all
+        assert_eq!(kept, vec!["NACHO", "slow"]);
+    }
+
+    fn sorted(field: &str, order: &str) -> Vec<String> {
+        let mut floors = vec![floor("slow", 0.2), floor("NACHO", 0.3), floor("GHOST", 0.1), floor("dust", 0.4)];
+        let volumes = volume_by_ticker(&stats(&[("NACHO", 5000.0), ("SLOW", 1000.0), ("DUST", 3000.0)]));
+        let options = ListingOptions::parse(Some(field), Some(order), None).unwrap();
+        sort_listing(&mut floors, options.sort_by.unwrap(), options.descending, &volumes);
+        floors.into_iter().map(|e| e.ticker).collect()
+    }
+
+    #[test]
+    fn test_sort_by_floor_price() {
+        assert_eq!(sorted("floor_price", "asc"), vec!["GHOST", "slow", "NACHO", "dust"]);
+        assert_eq!(sorted("floor_price", "desc"), vec!["dust", "NACHO", "slow", "GHOST"]);
+    }
+
+    #[test]
+    fn test_sort_by_volume_puts_untraded_last() {
+        assert_eq!(sorted("volume", "asc"), vec!["slow", "dust", "NACHO", "GHOST"]);
+        assert_eq!(sorted("volume", "desc"), vec!["NACHO", "dust", "slow", "GHOST"]);
+    }
+
+    #[test]
+    fn test_sort_by_ticker_ignores_case() {
+        assert_eq!(sorted("ticker", "asc"), vec!["dust", "GHOST", "NACHO", "slow"]);
+        assert_eq!(sorted("ticker", "desc"), vec!["slow", "NACHO", "GHOST", "dust"]);
+    }
+
+    #[test]
+    fn test_listing_options_reject_unknown_values() {
+        let err = ListingOptions::parse(Some("market_cap"), None, None).unwrap_err();
+        assert_eq!(err.downcast_ref::<InvalidParameter>().unwrap().name, "sort_by");
+        let err = ListingOptions::parse(Some("ticker"), Some("up"), None).unwrap_err();
+        assert_eq!(err.downcast_ref::<InvalidParameter>().unwrap().name, "order");
+
+        let options = ListingOptions::parse(None, None, Some(10.0)).unwrap();
+        assert_eq!(options, ListingOptions { sort_by: None, descending: false, min_volume: Some(10.0) });
+        assert!(options.needs_volume());
+    }
+
+    #[test]
+    fn test_resolve_ipfs_uri_rewrites_to_gateway() {
+        let gateway = "https://gateway.example/ipfs/";
+        assert_eq!(
+            resolve_ipfs_uri("ipfs://bafybeiabc", gateway),
+            "https://gateway.example/ipfs/bafybeiabc"
+        );
+        assert_eq!(
+            resolve_ipfs_uri("ipfs://bafybeiabc/images/173.png", gateway),
+            "https://gateway.example/ipfs/bafybeiabc/images/173.png"
+        );
+        assert_eq!(
+            resolve_ipfs_uri("ipfs://ipfs/bafybeiabc/1.json", DEFAULT_IPFS_GATEWAY),
+            "https://ipfs.io/ipfs/bafybeiabc/1.json"
+        );
+    }
+
+    #[test]
+    fn test_resolve_ipfs_uri_leaves_http_urls_untouched() {
+        let url = "https://cache.krc721.stream/krc721/mainnet/optimized/KASPUNK/1";
+        assert_eq!(resolve_ipfs_uri(url, DEFAULT_IPFS_GATEWAY), url);
+        assert_eq!(resolve_ipfs_uri("", DEFAULT_IPFS_GATEWAY), "");
+    }
+
+    #[test]
+    fn test_check_freshness_reports_ok_stale_and_missing() {
+        use crate::infrastructure::{KaspaComClient, ParquetStore, RateLimiter, RedisRepository};
+
+        let dir = tempfile::tempdir().unwrap();
+        let parquet = Arc::new(ParquetStore::new(dir.path().to_str().unwrap()));
+        parquet.write_simple("floor_prices", "all", &serde_json::json!([]), 60).unwrap();
+        let cache = Arc::new(CacheService::new(
+            Arc::new(RedisRepository::new(None)),
+            parquet,
+            Arc::new(KaspaComClient::new()),
+            Arc::new(RateLimiter::new(60)),
+        ));
+
+        let target = |category: &str, key: &str, max_age_secs| FreshnessTarget {
+            category: category.to_string(),
+            key: key.to_string(),
+            max_age_secs,
+        };
+        let service = KaspaComService::new(cache, TokensConfig { tokens: HashMap::new() })
+            .with_freshness_targets(vec![
+                target("floor_prices", "all", 300),
+                target("trade_stats", "24h", 300),
+            ]);
+
+        let checks = service.check_freshness();
+        assert_eq!(checks[0].status, "ok");
+        assert!(checks[0].age_secs.is_some());
+        assert_eq!(checks[1].status, "missing");
+        assert_eq!(checks[1].age_secs, None);
+    }
+
+    #[tokio::test]
+    async fn test_krc721_tokens_limit_is_capped_upstream() {
+        use crate::domain::MAX_NFT_TOKENS_LIMIT;
+        use axum::{routing::post, Json};
+
+        // Upstream reporting the page size it was asked for (0 for none) as its only item
+        let app = axum::Router::new().route(
+            "/api/krc721/tokens",
+            post(|Json(filter): Json<serde_json::Value>| async move {
+                let limit = filter["limit"].as_i64().unwrap_or(0);
+                Json(serde_json::json!({
+                    "items": [{ "_id": "1", "tokenId": limit, "ticker": "KASPUNKS" }],
+                    "totalCount": 250
+                }))
+            }),
+        );
+        let (service, _dir) = mock_upstream(app).await;
+
+        let filter = NftTokenFilter { page: Some(2), limit: Some(10_000), ..Default::default() };
+        let response = service.get_krc721_tokens(&filter).await.unwrap();
+
+        assert_eq!(response.items[0].token_id, i64::from(MAX_NFT_TOKENS_LIMIT));
+        let paging = (response.page, response.limit, response.total_pages);
+        assert_eq!(paging, (2, Some(MAX_NFT_TOKENS_LIMIT), Some(3)));
+        assert!(response.limit_capped);
+
+        // Without a limit none is sent, and the page count is unknown
+        let response = service.get_krc721_tokens(&NftTokenFilter::default()).await.unwrap();
+        assert_eq!(response.items[0].token_id, 0);
+        assert_eq!((response.page, response.limit, response.total_pages), (1, None, None));
+        assert!(!response.limit_capped);
+    }
+
+    #[tokio::test]
+    async fn test_equivalent_krc721_filters_share_a_cache_entry() {
+        use axum::{routing::post, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream_calls = calls.clone();
+        let app = axum::Router::new().route(
+            "/api/krc721/tokens",
+            post(move || {
+                upstream_calls.fetch_add(1, Ordering::SeqCst);
+                async { Json(serde_json::json!({ "items": [], "totalCount": 0 })) }
+            }),
+        );
+        let (service, _dir) = mock_upstream(app).await;
+        let filter = |json: &str| serde_json::from_str::<NftTokenFilter>(json).unwrap();
+
+        for json in [
+            r#"{"ticker": "kaspunks", "isListed": true, "page": 1, "traits": {"hat": "red", "eyes": "blue"}}"#,
+            r#"{"traits": {"eyes": "blue", "hat": "red"}, "page": 1, "isListed": true, "ticker": "KASPUNKS"}"#,
+        ] {
+            service.get_krc721_tokens(&filter(json)).await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A different filter is a different entry, and owner filters aren't cached
+        service.get_krc721_tokens(&filter(r#"{"ticker": "KASPUNKS", "page": 2}"#)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        for _ in 0..2 {
+            service.get_krc721_tokens(&filter(r#"{"owner": "kaspa:abc"}"#)).await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    fn nft(token_id: i64, rarity_rank: Option<i32>, traits: &[(&str, &str, f64)]) -> NftToken {
+        serde_json::from_value(serde_json::json!({
+            "_id": token_id.to_string(),
+            "tokenId": token_id,
+            "ticker": "PUNK",
+            "rarityRank": rarity_rank,
+            "traits": traits
+                .iter()
+                .map(|(trait_type, value, rarity)| (trait_type.to_string(), serde_json::json!({ "value": value, "rarity": rarity })))
+                .collect::<serde_json::Map<String, serde_json::Value>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rank_by_rarity_prefers_upstream_ranks() {
+        let (entries, source) = rank_by_rarity(vec![nft(1, Some(2), &[]), nft(2, Some(1), &[("hat", "red", 50.0)])]);
+        assert_eq!(source, "upstream");
+        assert_eq!(entries.iter().map(|e| (e.token_id, e.rarity_rank)).collect::<Vec<_>>(), vec![(2, 1), (1, 2)]);
+        assert_eq!(entries[0].traits.get("hat").map(String::as_str), Some("red"));
+    }
+
+    #[test]
+    fn test_rank_by_rarity_computes_ranks_from_traits() {
+        // One missing upstream rank means all ranks are computed
+        let (entries, source) = rank_by_rarity(vec![
+            nft(1, Some(1), &[("hat", "red", 50.0)]),
+            nft(2, None, &[("hat", "gold", 1.0)]),
+            nft(3, None, &[("hat", "red", 50.0)]),
+            nft(4, None, &[]),
+        ]);
+        assert_eq!(source, "computed");
+        assert_eq!(
+            entries.iter().map(|e| (e.token_id, e.rarity_rank)).collect::<Vec<_>>(),
+            vec![(2, 1), (1, 2), (3, 2), (4, 4)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_krc721_rarity_scans_every_page() {
+        use axum::{routing::{get, post}, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // 150 minted tokens of 200, served 100 per page; token N has rank N
+        let pages = Arc::new(AtomicUsize::new(0));
+        let page_calls = pages.clone();
+        let app = axum::Router::new()
+            .route(
+                "/krc721/{ticker}",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "ticker": "PUNK", "totalSupply": 200, "totalMinted": 150, "totalHolders": 10
+                    }))
+                }),
+            )
+            .route(
+                "/api/krc721/tokens",
+                post(move |Json(filter): Json<serde_json::Value>| {
+                    page_calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let page = filter["page"].as_i64().unwrap();
+                        let limit = filter["limit"].as_i64().unwrap();
+                        let items: Vec<_> = ((page - 1) * limit + 1..=(page * limit).min(150))
+                            .map(|id| nft(id, Some(id as i32), &[]))
+                            .collect();
+                        Json(serde_json::json!({ "items": items, "totalCount": 150 }))
+                    }
+                }),
+            );
+        let (service, _dir) = mock_upstream(app).await;
+
+        let rarity = service.get_krc721_rarity("punk").await.unwrap();
+        assert_eq!(rarity.ticker, "PUNK");
+        assert!(rarity.minting);
+        assert_eq!(rarity.rank_source, "upstream");
+        assert_eq!(rarity.tokens.len(), 150);
+        assert_eq!(rarity.tokens[149].token_id, 150);
+        assert_eq!(pages.load(Ordering::SeqCst), 2);
+
+        // The ranking itself is cached
+        service.get_krc721_rarity("PUNK").await.unwrap();
+        assert_eq!(pages.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_krc721_scans_are_single_flight_and_budgeted() {
+        use crate::infrastructure::InMemoryCache;
+        use axum::{routing::{get, post}, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // 250 finished tokens, 3 pages
+        let pages = Arc::new(AtomicUsize::new(0));
+        let page_calls = pages.clone();
+        let app = axum::Router::new()
+            .route(
+                "/krc721/{ticker}",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "ticker": "PUNK", "totalSupply": 250, "totalMinted": 250, "totalHolders": 10
+                    }))
+                }),
+            )
+            .route(
+                "/api/krc721/tokens",
+                post(move |Json(filter): Json<serde_json::Value>| {
+                    page_calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                        let page = filter["page"].as_i64().unwrap();
+                        let limit = filter["limit"].as_i64().unwrap();
+                        let items: Vec<_> = ((page - 1) * limit + 1..=(page * limit).min(250))
+                            .map(|id| nft(id, Some(id as i32), &[]))
+                            .collect();
+                        Json(serde_json::json!({ "items": items, "totalCount": 250 }))
+                    }
+                }),
+            );
+        let upstream = mock_server(app).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let service = |requests_per_minute| {
+            let cache = mock_cache(&upstream, Arc::new(InMemoryCache::new()), dir.path(), requests_per_minute);
+            KaspaComService::new(Arc::new(cache), TokensConfig { tokens: HashMap::new() })
+        };
+
+        // After the collection info and the first page, a budget of three
+        // calls leaves one, too few for the remaining two pages
+        let starved = service(3);
+        let err = starved.get_krc721_rarity("punk").await.unwrap_err();
+        assert!(err.downcast_ref::<crate::infrastructure::RateLimitExceeded>().is_some(), "{}", err);
+        assert_eq!(pages.swap(0, Ordering::SeqCst), 1);
+
+        // Concurrent misses scan the collection once
+        let shared = service(60);
+        let (first, second) = tokio::join!(shared.get_krc721_rarity("punk"), shared.get_krc721_rarity("PUNK"));
+        assert_eq!(first.unwrap().tokens.len(), 250);
+        assert_eq!(second.unwrap().tokens.len(), 250);
+        // The first page is still cached from the starved attempt
+        assert_eq!(pages.load(Ordering::SeqCst), 2);
+        assert!(shared.collection_scans.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_trait_percentages_sort_most_common_first() {
+        let counts = BTreeMap::from([(
+            "hat".to_string(),
+            HashMap::from([("red".to_string(), 1), ("blue".to_string(), 3)]),
+        )]);
+        let traits = trait_percentages(counts, 4);
+        let hats: Vec<_> = traits["hat"].iter().map(|t| (t.value.as_str(), t.count, t.percentage)).collect();
+        assert_eq!(hats, vec![("blue", 3, 75.0), ("red", 1, 25.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_krc721_trait_distribution_counts_across_pages() {
+        use axum::{routing::{get, post}, Json};
+
+        // 120 finished tokens: every third has a gold hat, the rest red
+        let app = axum::Router::new()
+            .route(
+                "/krc721/{ticker}",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "ticker": "PUNK", "totalSupply": 120, "totalMinted": 120, "totalHolders": 10
+                    }))
+                }),
+            )
+            .route(
+                "/api/krc721/tokens",
+                post(|Json(filter): Json<serde_json::Value>| async move {
+                    let page = filter["page"].as_i64().unwrap();
+                    let limit = filter["limit"].as_i64().unwrap();
+                    let items: Vec<_> = ((page - 1) * limit + 1..=(page * limit).min(120))
+                        .map(|id| nft(id, None, &[("hat", if id % 3 == 0 { "gold" } else { "red" }, 1.0)]))
+                        .collect();
+                    Json(serde_json::json!({ "items": items, "totalCount": 120 }))
+                }),
+            );
+        let (service, _dir) = mock_upstream(app).await;
+
+        let distribution = service.get_krc721_trait_distribution("punk").await.unwrap();
+        assert_eq!(distribution.total_tokens, 120);
+        assert!(!distribution.minting);
+        let hats: Vec<_> = distribution.traits["hat"].iter().map(|t| (t.value.as_str(), t.count)).collect();
+        assert_eq!(hats, vec![("red", 80), ("gold", 40)]);
+    }
+
+    #[tokio::test]
+    async fn test_nft_metadata_is_retried_and_cached() {
+        use crate::infrastructure::{InMemoryCache, KaspaComClient, KaspaComClientConfig, ParquetStore, RateLimiter};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // krc721.stream stand-in that drops the first two connections
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let metadata_host = format!("http://{}", listener.local_addr().unwrap());
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    continue;
+                }
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let body = r#"{"image": "ipfs://bafybeiabc/173.png", "name": "Turtle #173"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = KaspaComClient::with_config(
+            "http://127.0.0.1:9",
+            &KaspaComClientConfig { max_retries: 3, base_backoff_ms: 1, ..Default::default() },
+        )
+        .with_metadata_base_url(&metadata_host);
+        let cache = Arc::new(CacheService::new(
+            Arc::new(InMemoryCache::new()),
+            Arc::new(ParquetStore::new(dir.path().to_str().unwrap())),
+            Arc::new(client),
+            Arc::new(RateLimiter::new(60)),
+        ));
+        let service = KaspaComService::new(cache.clone(), TokensConfig { tokens: HashMap::new() });
+
+        let metadata = service.get_nft_metadata("turtle", 173).await.unwrap();
+        assert_eq!(metadata.name, "Turtle #173");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Persisted to Parquet and served from cache afterwards
+        let key = CacheKey::builder("kaspa:krc721")
+            .part("metadata")
+            .ticker("TURTLE")
+            .part(173)
+            .build()
+            .unwrap();
+        assert!(cache.entry_age_secs(cache_categories::KRC721, key.parquet_key()).is_some());
+        service.get_nft_metadata("TURTLE", 173).await.unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_token_detail_names_failed_parts() {
+        use axum::{http::StatusCode, routing::get, Json};
+
+        // Token info and floor price load; sold orders and history are down
+        let app = axum::Router::new()
+            .route(
+                "/api/token-info/{ticker}",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "ticker": "NACHO", "totalSupply": 1, "totalMintTimes": 1, "totalMinted": 1,
+                        "totalHolders": 1, "mintLimit": 1, "state": "finished",
+                        "price": 0.6, "marketCap": 1000.0
+                    }))
+                }),
+            )
+            .route(
+                "/api/floor-price",
+                get(|| async { Json(serde_json::json!([{ "ticker": "NACHO", "floor_price": 0.5 }])) }),
+            )
+            .route("/api/sold-orders", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route("/api/historical-data", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }));
+        let (service, _dir) = mock_upstream(app).await;
+
+        let detail = service.get_token_detail("nacho", "24h").await.unwrap();
+
+        assert_eq!(detail.ticker, "NACHO");
+        assert_eq!(detail.info.map(|i| i.price), Some(0.6));
+        assert_eq!(detail.floor_price, Some(0.5));
+        assert!(detail.recent_trades.is_none());
+        assert!(detail.history.is_none());
+        assert_eq!(detail.missing, vec!["recent_trades", "history"]);
+    }
+
+    #[tokio::test]
+    async fn test_market_overview_keeps_partial_rows() {
+        use axum::{extract::Path, http::StatusCode, routing::get, Json};
+
+        // Upstream with floor prices and token info for NACHO only; logos are down
+        let app = axum::Router::new()
+            .route(
+                "/api/floor-price",
+                get(|| async { Json(serde_json::json!([{ "ticker": "NACHO", "floor_price": 0.5 }])) }),
+            )
+            .route("/api/tokens-logos", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route(
+                "/api/token-info/{ticker}",
+                get(|Path(ticker): Path<String>| async move {
+                    if ticker != "NACHO" {
+                        return Err(StatusCode::NOT_FOUND);
+                    }
+                    Ok(Json(serde_json::json!({
+                        "ticker": "NACHO", "totalSupply": 1, "totalMintTimes": 1, "totalMinted": 1,
+                        "totalHolders": 1, "mintLimit": 1, "state": "finished",
+                        "price": 0.6, "marketCap": 1000.0
+                    })))
+                }),
+            );
+        let (service, _dir) = mock_upstream(app).await;
+
+        let rows = service
+            .get_market_overview(
+                Some(vec!["nacho".into(), "GHOST".into(), "NACHO".into()]),
+                &ListingOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].ticker, "NACHO");
+        assert_eq!(rows[0].floor_price, Some(0.5));
+        assert_eq!(rows[0].price, Some(0.6));
+        assert_eq!(rows[0].market_cap, Some(1000.0));
+        assert_eq!(rows[0].missing, vec!["logo"]);
+        assert_eq!(rows[1].ticker, "GHOST");
+        assert_eq!(rows[1].floor_price, None);
+        assert_eq!(rows[1].missing, vec!["logo", "token_info"]);
+
+        // Trade stats are down too: rows are kept, unfiltered, with volume missing
+        let by_volume = ListingOptions::parse(Some("volume"), Some("desc"), Some(1.0)).unwrap();
+        let rows = service.get_market_overview(Some(vec!["NACHO".into()]), &by_volume).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].price, Some(0.6));
+        assert_eq!(rows[0].missing, vec!["logo", "volume"]);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_overrides_are_used() {
+        use crate::application::cache_service::TtlConfig;
+        use crate::infrastructure::InMemoryCache;
+
+        let app = axum::Router::new().fallback(|| async {
+            axum::Json(serde_json::json!([{ "ticker": "NACHO", "floor_price": 0.5 }]))
+        });
+        let upstream = mock_server(app).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(InMemoryCache::new());
+        let cache = Arc::new(
+            mock_cache(&upstream, redis.clone(), dir.path(), 60)
+                .with_ttls(TtlConfig { hot_redis_secs: 90, ..TtlConfig::default() }),
+        );
+        let service = KaspaComService::new(cache, TokensConfig { tokens: HashMap::new() });
+
+        service.get_floor_prices(Some("nacho")).await.unwrap();
+        assert_eq!(redis.ttl_of("kaspa:floor_price:NACHO"), Some(90));
+    }
+
+    #[tokio::test]
+    async fn test_trade_stats_survive_dead_redis() {
+        use crate::infrastructure::{ParquetStore, RedisPoolConfig, RedisRepository};
+
+        // Upstream serving trade stats for cache misses
+        let body = serde_json::to_value(stats(&[("NACHO", 42.0)])).unwrap();
+        let app = axum::Router::new().fallback(move || async move { axum::Json(body) });
+        let upstream = mock_server(app).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let parquet = Arc::new(ParquetStore::new(dir.path().to_str().unwrap()));
+        let seeded = serde_json::to_value(stats(&[("DUST", 1.0)])).unwrap();
+        parquet.write_simple("trade_stats", "24h", &seeded, ttl::WARM_PARQUET_SECS).unwrap();
+
+        let dead_redis = RedisRepository::with_config(
+            Some("redis://127.0.0.1:1".to_string()),
+            &RedisPoolConfig { connection_timeout_ms: 200, ..RedisPoolConfig::default() },
+        );
+        let cache = Arc::new(mock_cache(&upstream, Arc::new(dead_redis), dir.path(), 60));
+        let service = KaspaComService::new(cache, TokensConfig { tokens: HashMap::new() });
+
+        // Served from Parquet
+        let from_parquet = service.get_trade_stats("24h", None).await.unwrap();
+        assert_eq!(from_parquet.tokens[0].ticker, "DUST");
+
+        // Fetched from the API on a miss
+        let from_api = service.get_trade_stats("6h", None).await.unwrap();
+        assert_eq!(from_api.tokens[0].ticker, "NACHO");
+    }
+
+    /// Service over an upstream with the KRC721 collection BITCOIN and the
+    /// listed KNS domain alice.kas; also returns the number of collection
+    /// lookups made
+    async fn search_service(
+        tokens: &[&str],
+    ) -> (
+        KaspaComService,
+        Arc<std::sync::atomic::AtomicUsize>,
+        Arc<crate::infrastructure::InMemoryCache>,
+        tempfile::TempDir,
+    ) {
+        use crate::domain::TokenExchanges;
+        use crate::infrastructure::InMemoryCache;
+        use axum::{extract::Path, http::StatusCode, routing::get, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let collection_lookups = Arc::new(AtomicUsize::new(0));
+        let lookups = collection_lookups.clone();
+        let app = axum::Router::new()
+            .route(
+                "/krc721/{ticker}",
+                get(move |Path(ticker): Path<String>| {
+                    lookups.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        match ticker.as_str() {
+                            "BITCOIN" => {}
+                            "BROKEN" => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+                            _ => return Err(StatusCode::NOT_FOUND),
+                        }
+                        Ok(Json(serde_json::json!({
+                            "ticker": "BITCOIN", "totalSupply": 100, "totalMinted": 100, "totalHolders": 10
+                        })))
+                    }
+                }),
+            )
+            .route(
+                "/api/kns/listed-orders",
+                get(|| async {
+                    Json(serde_json::json!({ "orders": [{
+                        "_id": "1", "assetId": "alice.kas", "price": 250.0,
+                        "sellerAddress": "kaspa:seller", "createdAt": 5, "status": "listed"
+                    }] }))
+                }),
+            )
+            .route("/api/kns/sold-orders", get(|| async { Json(serde_json::json!([])) }));
+        let upstream = mock_server(app).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let redis = Arc::new(InMemoryCache::new());
+        let cache = Arc::new(mock_cache(&upstream, redis.clone(), dir.path(), 60));
+        let tokens = tokens
+            .iter()
+            .map(|token| (token.to_string(), TokenExchanges { exchanges: Vec::new() }))
+            .collect();
+        (KaspaComService::new(cache, TokensConfig { tokens }), collection_lookups, redis, dir)
+    }
+
+    #[tokio::test]
+    async fn test_search_matches_configured_tokens() {
+        let (service, _, _, _dir) = search_service(&["kaspa", "nachokat", "nacho", "ghost", "supernacho"]).await;
+
+        let results = service.search(" Nacho ").await.unwrap();
+
+        assert_eq!(results.query, "nacho");
+        assert_eq!(results.tokens, ["NACHO", "NACHOKAT", "SUPERNACHO"]);
+        assert!(results.collections.is_empty());
+        assert!(results.domains.is_empty());
+        assert!(results.missing.is_empty(), "{:?}", results.missing);
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_collection() {
+        let (service, lookups, redis, _dir) = search_service(&["kaspa"]).await;
+
+        let results = service.search("bitcoin").await.unwrap();
+
+        assert!(results.tokens.is_empty());
+        assert_eq!(results.collections.len(), 1);
+        assert_eq!(results.collections[0].ticker, "BITCOIN");
+        assert!(results.domains.is_empty());
+
+        // Served from cache the second time, from Redis only
+        service.search("BITCOIN").await.unwrap();
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let key = CacheKey::builder("kaspa:search")
+            .part(content_hash("bitcoin").unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(redis.ttl_of(key.redis_key()), Some(SEARCH_TTL_SECS));
+    }
+
+    #[tokio::test]
+    async fn test_search_with_missing_lookups_is_not_cached() {
+        let (service, lookups, _, _dir) = search_service(&["kaspa"]).await;
+
+        let results = service.search("broken").await.unwrap();
+        assert!(results.collections.is_empty());
+        assert_eq!(results.missing, vec!["collection"]);
+
+        service.search("broken").await.unwrap();
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_domain_and_kas_suffix_skips_other_lookups() {
+        let (service, lookups, _, _dir) = search_service(&["alicecoin"]).await;
+
+        let results = service.search("Alice.KAS").await.unwrap();
+        assert_eq!(results.domains.len(), 1);
+        assert_eq!(results.domains[0].domain, "alice.kas");
+        assert_eq!(results.domains[0].status, "listed");
+        assert!(results.tokens.is_empty());
+        assert!(results.collections.is_empty());
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // Without the suffix every category is searched
+        let results = service.search("alice").await.unwrap();
+        assert_eq!(results.domains[0].domain, "alice.kas");
+        assert_eq!(results.tokens, ["ALICECOIN"]);
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pinning_uncached_reference_data_fetches_it_first() {
+        use axum::routing::get;
+
+        let app = axum::Router::new().route(
+            "/api/tokens-logos",
+            get(|| async {
+                axum::Json(serde_json::json!([{ "ticker": "NACHO", "logo": "https://x/nacho.png" }]))
+            }),
+        );
+        let (service, _dir) = mock_upstream(app).await;
+
+        assert!(service.pin_cache_entry(cache_categories::LOGOS, "NACHO", true).await.unwrap());
+        assert!(service.cache.is_fresh(cache_categories::LOGOS, "NACHO", u64::MAX));
+
+        // Keys that can't be rebuilt from their name still need a request first
+        assert!(!service.pin_cache_entry(cache_categories::ORDERS, "NACHO", true).await.unwrap());
+        // Unpinning never fetches
+        assert!(!service.pin_cache_entry(cache_categories::LOGOS, "all", false).await.unwrap());
+    }
+
+    /// Service warming `tokens` against an upstream whose token info always
+    /// fails, counting upstream requests
+    async fn warming_service(
+        tokens: &[&str],
+        requests_per_minute: u32,
+    ) -> (KaspaComService, Arc<std::sync::atomic::AtomicUsize>, tempfile::TempDir) {
+        use crate::domain::TokenExchanges;
+        use axum::{http::StatusCode, routing::get, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let (floor_hits, stats_hits, info_hits) = (requests.clone(), requests.clone(), requests.clone());
+        let app = axum::Router::new()
+            .route(
+                "/api/floor-price",
+                get(move || {
+                    floor_hits.fetch_add(1, Ordering::SeqCst);
+                    async { Json(vec![floor("NACHO", 0.5)]) }
+                }),
+            )
+            .route(
+                "/api/trade-stats",
+                get(move || {
+                    stats_hits.fetch_add(1, Ordering::SeqCst);
+                    async { Json(stats(&[("NACHO", 10.0)])) }
+                }),
+            )
+            .route(
+                "/api/token-info/{ticker}",
+                get(move || {
+                    info_hits.fetch_add(1, Ordering::SeqCst);
+                    async { StatusCode::INTERNAL_SERVER_ERROR }
+                }),
+            );
+        let upstream = mock_server(app).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = mock_cache(
+            &upstream,
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            dir.path(),
+            requests_per_minute,
+        );
+        let tokens = tokens
+            .iter()
+            .map(|token| (token.to_string(), TokenExchanges { exchanges: Vec::new() }))
+            .collect();
+        (KaspaComService::new(Arc::new(cache), TokensConfig { tokens }), requests, dir)
+    }
+
+    #[tokio::test]
+    async fn test_warm_cache_fetches_each_configured_token_and_skips_fresh_keys() {
+        use std::sync::atomic::Ordering;
+
+        let (service, requests, _dir) = warming_service(&["nacho", "kaspa"], 60).await;
+
+        // Floor price and trade stats warm; failing token info doesn't stop the run
+        assert_eq!(service.warm_cache().await, 4);
+        assert_eq!(requests.load(Ordering::SeqCst), 6);
+        assert!(service.cache.is_fresh(
+            cache_categories::FLOOR_PRICES,
+            CacheKey::builder("kaspa:floor_price").ticker("KASPA").build().unwrap().parquet_key(),
+            u64::MAX,
+        ));
+
+        // Only the keys that failed are fetched again
+        assert_eq!(service.warm_cache().await, 0);
+        assert_eq!(requests.load(Ordering::SeqCst), 8);
+    }
+
+    #[tokio::test]
+    async fn test_warm_cache_stops_when_rate_limit_budget_runs_out() {
+        use std::sync::atomic::Ordering;
+
+        let (service, requests, _dir) = warming_service(&["nacho", "kaspa"], 2).await;
+
+        // Token info and floor price for the first token use up the budget
+        assert_eq!(service.warm_cache().await, 1);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,265 @@
+//! Shared fan-out for live-update features (SSE, GraphQL subscriptions,
+//! WebSocket feeds).
+//!
+//! One background poller per feed pushes updates into a bounded broadcast
+//! channel, and every connected client holds a [`Subscription`] to it. The
+//! poller never waits on consumers: when a client falls more than
+//! `broadcast_capacity` updates behind, its oldest unread updates are dropped
+//! and it receives a single [`FeedEvent::Lagged`] with the number skipped
+//! before resuming from the oldest update still buffered. Clients can use
+//! that to resync (e.g. refetch a snapshot) instead of silently missing data.
+//!
+//! The number of connected subscribers is exported as the
+//! `live_feed_subscribers{feed}` gauge.
+
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// Default number of updates buffered per feed
+pub const DEFAULT_BROADCAST_CAPACITY: usize = 256;
+/// Default seconds between polls of the upstream source
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+/// Default cap on subscribers per feed
+pub const DEFAULT_MAX_SUBSCRIBERS_PER_FEED: usize = 100;
+
+/// Capacity, polling and connection settings shared by live-update features
+#[derive(Deserialize, Debug, Clone)]
+pub struct LiveFeedConfig {
+    /// Updates buffered per feed before slow subscribers start lagging
+    #[serde(default = "default_broadcast_capacity")]
+    pub broadcast_capacity: usize,
+    /// Seconds between polls of the upstream source
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Connections allowed per feed before new ones are refused
+    #[serde(default = "default_max_subscribers_per_feed")]
+    pub max_subscribers_per_feed: usize,
+}
+
+impl Default for LiveFeedConfig {
+    fn default() -> Self {
+        Self {
+            broadcast_capacity: default_broadcast_capacity(),
+            poll_interval_secs: default_poll_interval_secs(),
+            max_subscribers_per_feed: default_max_subscribers_per_feed(),
+        }
+    }
+}
+
+impl LiveFeedConfig {
+    /// Poll interval as a duration (at least one second)
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs.max(1))
+    }
+}
+
+fn default_broadcast_capacity() -> usize {
+    DEFAULT_BROADCAST_CAPACITY
+}
+
+fn default_poll_interval_secs() -> u64 {
+    DEFAULT_POLL_INTERVAL_SECS
+}
+
+fn default_max_subscribers_per_feed() -> usize {
+    DEFAULT_MAX_SUBSCRIBERS_PER_FEED
+}
+
+/// What a subscriber receives
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedEvent<T> {
+    /// A new update from the poller
+    Update(T),
+    /// The subscriber fell behind and this many updates were dropped
+    Lagged { skipped: u64 },
+}
+
+/// A named broadcast feed with a background poller
+#[derive(Clone)]
+pub struct LiveFeed<T> {
+    name: Arc<str>,
+    sender: broadcast::Sender<T>,
+    subscribers: Arc<AtomicUsize>,
+}
+
+impl<T: Clone + Send + 'static> LiveFeed<T> {
+    /// Create a feed buffering up to `capacity` updates
+    pub fn new(name: impl Into<Arc<str>>, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            name: name.into(),
+            sender,
+            subscribers: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Create a feed from the shared live-feed configuration
+    pub fn from_config(name: impl Into<Arc<str>>, config: &LiveFeedConfig) -> Self {
+        Self::new(name, config.broadcast_capacity)
+    }
+
+    /// Number of currently connected subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.load(Ordering::Relaxed)
+    }
+
+    /// Connect a new subscriber
+    pub fn subscribe(&self) -> Subscription<T> {
+        let receiver = self.sender.subscribe();
+        let count = self.subscribers.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!("live_feed_subscribers", "feed" => self.name.to_string()).set(count as f64);
+        Subscription {
+            name: self.name.clone(),
+            receiver,
+            subscribers: self.subscribers.clone(),
+        }
+    }
+
+    /// Publish an update to all current subscribers
+    ///
+    /// Never blocks; with no subscribers the update is discarded.
+    pub fn publish(&self, update: T) {
+        let _ = self.sender.send(update);
+    }
+
+    /// Poll `source` every `interval` and publish each result.
+    ///
+    /// Polls are skipped while nobody is subscribed so idle feeds don't
+    /// spend upstream rate-limit budget. Errors are logged and the poller
+    /// keeps running.
+    pub fn spawn_poller<F, Fut>(&self, interval: Duration, source: F) -> JoinHandle<()>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<T>> + Send,
+    {
+        self.spawn_poller_until(interval, CancellationToken::new(), source)
+    }
+
+    /// [`Self::spawn_poller`], stopping once `shutdown` is cancelled
+    pub fn spawn_poller_until<F, Fut>(
+        &self,
+        interval: Duration,
+        shutdown: CancellationToken,
+        mut source: F,
+    ) -> JoinHandle<()>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<T>> + Send,
+    {
+        let feed = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                if feed.subscriber_count() == 0 {
+                    continue;
+                }
+                match source().await {
+                    Ok(update) => feed.publish(update),
+                    Err(e) => warn!("Live feed '{}' poll failed: {}", feed.name, e),
+                }
+            }
+        })
+    }
+}
+
+/// A connected subscriber; dropping it disconnects
+pub struct Subscription<T> {
+    name: Arc<str>,
+    receiver: broadcast::Receiver<T>,
+    subscribers: Arc<AtomicUsize>,
+}
+
+impl<T: Clone> Subscription<T> {
+    /// Wait for the next event; `None` once the feed is gone
+    pub async fn next(&mut self) -> Option<FeedEvent<T>> {
+        match self.receiver.recv().await {
+            Ok(update) => Some(FeedEvent::Update(update)),
+            Err(RecvError::Lagged(skipped)) => {
+                debug!("Live feed '{}' subscriber lagged by {} updates", self.name, skipped);
+                metrics::counter!("live_feed_lagged_total", "feed" => self.name.to_string()).increment(skipped);
+                Some(FeedEvent::Lagged { skipped })
+            }
+            Err(RecvError::Closed) => None,
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let count = self.subscribers.fetch_sub(1, Ordering::Relaxed).saturating_sub(1);
+        metrics::gauge!("live_feed_subscribers", "feed" => self.name.to_string()).set(count as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_subscriber_gets_lagged_then_newest_updates() {
+        let feed = LiveFeed::new("test", 2);
+        let mut sub = feed.subscribe();
+
+        for i in 0..5 {
+            feed.publish(i);
+        }
+
+        // The three oldest updates were dropped
+        assert_eq!(sub.next().await, Some(FeedEvent::Lagged { skipped: 3 }));
+        assert_eq!(sub.next().await, Some(FeedEvent::Update(3)));
+        assert_eq!(sub.next().await, Some(FeedEvent::Update(4)));
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_count_tracks_drops() {
+        let feed: LiveFeed<u32> = LiveFeed::new("test", 4);
+        let a = feed.subscribe();
+        let b = feed.subscribe();
+        assert_eq!(feed.subscriber_count(), 2);
+
+        drop(a);
+        assert_eq!(feed.subscriber_count(), 1);
+        drop(b);
+        assert_eq!(feed.subscriber_count(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_poller_only_polls_with_subscribers() {
+        let feed = LiveFeed::new("test", 4);
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = polls.clone();
+        let poller = feed.spawn_poller(Duration::from_secs(1), move || {
+            let n = counter.fetch_add(1, Ordering::Relaxed);
+            async move { Ok(n) }
+        });
+
+        tokio::time::sleep(Duration::from_millis(3500)).await;
+        assert_eq!(polls.load(Ordering::Relaxed), 0);
+
+        let mut sub = feed.subscribe();
+        assert_eq!(sub.next().await, Some(FeedEvent::Update(0)));
+        poller.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_poller_stops_on_shutdown() {
+        let feed = LiveFeed::new("test", 4);
+        let shutdown = CancellationToken::new();
+        let poller = feed.spawn_poller_until(Duration::from_secs(1), shutdown.clone(), || async { Ok(0) });
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(5), poller).await.unwrap().unwrap();
+    }
+}
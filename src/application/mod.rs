@@ -1,12 +1,17 @@
+pub mod cache_key;
 pub mod cache_service;
 pub mod exchange_index;
 pub mod kaspacom_service;
+pub mod live_feed;
+pub mod price_ticks;
 pub mod service;
 pub mod ticker_service;
 
+pub use cache_key::CacheKey;
 pub use cache_service::CacheService;
-pub use exchange_index::ExchangeIndex;
+pub use exchange_index::{ExchangeIndex, ExchangeIndexStatus};
 pub use kaspacom_service::KaspaComService;
+pub use price_ticks::PriceTickFeeds;
 pub use service::ContentService;
 pub use ticker_service::TickerService;
 
@@ -0,0 +1,256 @@
+//! Live aggregate-price ticks per token, for WebSocket clients.
+//!
+//! Each token gets one [`LiveFeed`] whose poller calls
+//! [`TickerService::get_ticker_stats`] every `poll_interval_secs`, so the
+//! number of connected sockets doesn't multiply upstream or cache reads.
+//! Feeds are created on first subscription and removed, poller and all,
+//! when the last subscriber leaves. At most `max_subscribers_per_feed`
+//! sockets may follow one token.
+
+use crate::application::live_feed::{FeedEvent, LiveFeed, LiveFeedConfig, Subscription};
+use crate::application::ticker_service::{TickerStatsResponse, TickerService};
+use crate::domain::normalize_token;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+/// Stats range the ticks are computed over
+pub const PRICE_TICK_RANGE: &str = "today";
+
+/// Latest aggregate price of a token
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PriceTick {
+    pub token: String,
+    /// Average price across exchanges
+    pub price: Option<f64>,
+    /// Volume-weighted average price
+    pub vwap: Option<f64>,
+    /// Total volume across all exchanges
+    pub total_volume_24h: Option<f64>,
+    pub exchange_count: usize,
+    /// When the underlying stats were computed (ISO 8601)
+    pub timestamp: String,
+}
+
+impl PriceTick {
+    /// Whether `other` reports a different price than `self`
+    pub fn price_changed(&self, other: &PriceTick) -> bool {
+        self.price != other.price || self.vwap != other.vwap
+    }
+}
+
+impl From<TickerStatsResponse> for PriceTick {
+    fn from(stats: TickerStatsResponse) -> Self {
+        Self {
+            token: stats.token,
+            price: stats.aggregate.avg_price,
+            vwap: stats.aggregate.vwap,
+            total_volume_24h: stats.aggregate.total_volume_24h,
+            exchange_count: stats.aggregate.exchange_count,
+            timestamp: stats.timestamp,
+        }
+    }
+}
+
+/// A token's feed and the task polling it
+struct TokenFeed {
+    feed: LiveFeed<PriceTick>,
+    poller: JoinHandle<()>,
+}
+
+type FeedMap = Arc<Mutex<HashMap<String, TokenFeed>>>;
+
+/// Price-tick feeds keyed by normalized token
+pub struct PriceTickFeeds {
+    ticker_service: Arc<TickerService>,
+    config: LiveFeedConfig,
+    feeds: FeedMap,
+    /// Stops every poller when cancelled
+    shutdown: CancellationToken,
+}
+
+impl PriceTickFeeds {
+    pub fn new(ticker_service: Arc<TickerService>, config: LiveFeedConfig) -> Self {
+        Self {
+            ticker_service,
+            config,
+            feeds: Arc::default(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Stop all pollers once `shutdown` is cancelled
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Current tick for `token`; fails if the token has no stats
+    pub async fn latest(&self, token: &str) -> anyhow::Result<PriceTick> {
+        self.ticker_service
+            .get_ticker_stats(token.to_string(), PRICE_TICK_RANGE.to_string())
+            .await
+            .map(PriceTick::from)
+    }
+
+    /// Subscribe to `token`'s ticks, or `None` if its feed is full
+    ///
+    /// Only call this for tokens [`Self::latest`] succeeded for, so feeds
+    /// (and their pollers) exist only for tokens that have data.
+    pub fn subscribe(&self, token: &str) -> Option<PriceTickSubscription> {
+        let token = normalize_token(token);
+        let mut feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = feeds.entry(token.clone()).or_insert_with(|| {
+            // Feed names label the subscriber metrics; bounded by tokens with data
+            let feed = LiveFeed::from_config(format!("price_ticks:{}", token), &self.config);
+            let service = self.ticker_service.clone();
+            let token = token.clone();
+            let poller = feed.spawn_poller_until(self.config.poll_interval(), self.shutdown.clone(), move || {
+                let service = service.clone();
+                let token = token.clone();
+                async move {
+                    service
+                        .get_ticker_stats(token, PRICE_TICK_RANGE.to_string())
+                        .await
+                        .map(PriceTick::from)
+                }
+            });
+            TokenFeed { feed, poller }
+        });
+
+        if entry.feed.subscriber_count() >= self.config.max_subscribers_per_feed {
+            return None;
+        }
+        Some(PriceTickSubscription {
+            inner: Some(entry.feed.subscribe()),
+            token,
+            feeds: self.feeds.clone(),
+        })
+    }
+
+    /// Number of tokens with a feed (and a poller)
+    pub fn feed_count(&self) -> usize {
+        self.feeds.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+/// A subscriber to one token's ticks; the last one to drop removes the
+/// feed and stops its poller
+pub struct PriceTickSubscription {
+    inner: Option<Subscription<PriceTick>>,
+    token: String,
+    feeds: FeedMap,
+}
+
+impl PriceTickSubscription {
+    /// Wait for the next event; `None` once the feed is gone
+    pub async fn next(&mut self) -> Option<FeedEvent<PriceTick>> {
+        self.inner.as_mut()?.next().await
+    }
+}
+
+impl Drop for PriceTickSubscription {
+    fn drop(&mut self) {
+        let mut feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+        // Unsubscribe under the lock so a concurrent subscribe can't join
+        // a feed that's about to be removed
+        self.inner.take();
+        if feeds.get(&self.token).is_some_and(|entry| entry.feed.subscriber_count() == 0) {
+            if let Some(entry) = feeds.remove(&self.token) {
+                entry.poller.abort();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::RepoConfig;
+    use crate::infrastructure::{InMemoryCache, LocalFileRepository};
+
+    fn feeds(max_subscribers_per_feed: usize) -> PriceTickFeeds {
+        let ticker_service = TickerService::new(
+            Arc::new(LocalFileRepository::new(std::env::temp_dir())),
+            Arc::new(InMemoryCache::new()),
+            RepoConfig {
+                source: "github".to_string(),
+                owner: "KaspaDev".to_string(),
+                repo: "Kaspa-Exchange-Data".to_string(),
+            },
+        );
+        PriceTickFeeds::new(
+            Arc::new(ticker_service),
+            LiveFeedConfig { max_subscribers_per_feed, ..LiveFeedConfig::default() },
+        )
+    }
+
+    fn tick(price: Option<f64>, timestamp: &str) -> PriceTick {
+        PriceTick {
+            token: "kaspa".to_string(),
+            price,
+            vwap: price,
+            total_volume_24h: Some(1.0),
+            exchange_count: 1,
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_price_changed_ignores_timestamp() {
+        assert!(!tick(Some(0.1), "a").price_changed(&tick(Some(0.1), "b")));
+        assert!(tick(Some(0.1), "a").price_changed(&tick(Some(0.2), "a")));
+        assert!(tick(Some(0.1), "a").price_changed(&tick(None, "a")));
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_per_token_are_capped() {
+        let feeds = feeds(2);
+        let a = feeds.subscribe("kaspa").unwrap();
+        let _b = feeds.subscribe("KASPA").unwrap();
+        assert!(feeds.subscribe("kaspa").is_none());
+
+        // Other tokens have their own budget, and leaving frees a slot
+        assert!(feeds.subscribe("nacho").is_some());
+        drop(a);
+        assert!(feeds.subscribe("kaspa").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_last_subscriber_leaving_stops_the_poller() {
+        let feeds = feeds(2);
+        let a = feeds.subscribe("kaspa").unwrap();
+        let b = feeds.subscribe("kaspa").unwrap();
+        let poller = feeds.feeds.lock().unwrap()["kaspa"].poller.abort_handle();
+
+        drop(a);
+        assert_eq!(feeds.feed_count(), 1);
+        drop(b);
+        assert_eq!(feeds.feed_count(), 0);
+        wait_finished(&poller).await;
+    }
+
+    async fn wait_finished(task: &tokio::task::AbortHandle) {
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while !task.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pollers_stop_on_shutdown() {
+        let shutdown = CancellationToken::new();
+        let feeds = feeds(2).with_shutdown(shutdown.clone());
+        let _sub = feeds.subscribe("kaspa").unwrap();
+        let poller = feeds.feeds.lock().unwrap()["kaspa"].poller.abort_handle();
+
+        shutdown.cancel();
+        wait_finished(&poller).await;
+    }
+}
@@ -1,4 +1,6 @@
-use crate::domain::{CacheRepository, Content, ContentRepository, ContentType, RepoConfig};
+use crate::application::kaspacom_service::DEFAULT_BULK_CONCURRENCY;
+use crate::domain::{ApiError, CacheRepository, Content, ContentRepository, ContentType, RepoConfig};
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
 use chrono::NaiveDate;
 use futures::StreamExt;
@@ -6,11 +8,109 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{error, info};
 
+/// How long directory listings are cached (1 minute)
+const LISTING_TTL_SECS: u64 = 60;
+
+/// How long file contents are cached; keys include the blob SHA (1 day)
+const FILE_TTL_SECS: u64 = 24 * 3600;
+
+/// Redis-backed cache in front of a content repository.
+///
+/// Listings are cached briefly under their path. File contents are cached
+/// for longer under path and blob SHA, with the SHA read from the (cached)
+/// listing of the file's directory, so an edited file is refetched as soon
+/// as that listing expires. Files missing from that listing are not found
+/// without asking upstream; raw file fetches, and files whose directory
+/// can't be listed, pass straight through. Failures are never cached.
+pub struct CachedContentRepository {
+    inner: Arc<dyn ContentRepository>,
+    cache_repo: Arc<dyn CacheRepository>,
+}
+
+impl CachedContentRepository {
+    pub fn new(inner: Arc<dyn ContentRepository>, cache_repo: Arc<dyn CacheRepository>) -> Self {
+        Self { inner, cache_repo }
+    }
+
+    fn listing_key(config: &RepoConfig, path: &str) -> String {
+        format!("v1:gh:ls:{}:{}:{}:{}", config.source, config.owner, config.repo, path)
+    }
+
+    fn file_key(config: &RepoConfig, path: &str, sha: &str) -> String {
+        format!("v1:gh:file:{}:{}:{}:{}:{}", config.source, config.owner, config.repo, path, sha)
+    }
+
+    /// Cached value under `key`, counting the lookup as a hit or miss
+    async fn cached<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let hit = match self.cache_repo.get(key).await {
+            Ok(Some(cached)) => serde_json::from_str(&cached).ok(),
+            _ => None,
+        };
+        let operation = if hit.is_some() { "hit" } else { "miss" };
+        metrics::counter!("content_cache_operations_total", "operation" => operation).increment(1);
+        hit
+    }
+
+    async fn store<T: Serialize>(&self, key: &str, value: &T, ttl: u64) {
+        if let Ok(json) = serde_json::to_string(value) {
+            let _ = self.cache_repo.set(key, &json, ttl).await;
+        }
+    }
+
+    /// SHA of the file at `path` from its directory's listing, or `None`
+    /// when the directory can't be listed
+    async fn file_sha(&self, config: &RepoConfig, path: &str) -> Option<anyhow::Result<String>> {
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let listing = self.list_directory(config, parent).await.ok()?;
+        let sha = listing
+            .into_iter()
+            .find(|item| item.name == name && item.item_type == ContentType::File)
+            .and_then(|item| item.sha);
+        Some(sha.ok_or_else(|| ApiError::NotFound(format!("File not found: {}", path)).into()))
+    }
+}
+
+#[async_trait]
+impl ContentRepository for CachedContentRepository {
+    async fn get_content(&self, config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+        let path = path.trim_matches('/');
+        let Some(sha) = self.file_sha(config, path).await else {
+            return self.inner.get_content(config, path).await;
+        };
+        let sha = sha?;
+
+        let key = Self::file_key(config, path, &sha);
+        if let Some(content) = self.cached::<Content>(&key).await {
+            return Ok(content);
+        }
+        let content = self.inner.get_content(config, path).await?;
+        self.store(&key, &content, FILE_TTL_SECS).await;
+        Ok(content)
+    }
+
+    async fn list_directory(&self, config: &RepoConfig, path: &str) -> anyhow::Result<Vec<Content>> {
+        let path = path.trim_matches('/');
+        let key = Self::listing_key(config, path);
+        if let Some(items) = self.cached::<Vec<Content>>(&key).await {
+            return Ok(items);
+        }
+        let items = self.inner.list_directory(config, path).await?;
+        self.store(&key, &items, LISTING_TTL_SECS).await;
+        Ok(items)
+    }
+
+    async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+        self.inner.get_raw_file(url).await
+    }
+}
+
 #[derive(Clone)]
 pub struct ContentService {
     content_repo: Arc<dyn ContentRepository>,
     cache_repo: Arc<dyn CacheRepository>,
     allowed_repos: Vec<RepoConfig>,
+    /// Files of an aggregated page fetched concurrently
+    fetch_concurrency: usize,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -32,18 +132,33 @@ pub struct AggregatedResult {
 }
 
 impl ContentService {
+    /// Create the service; `content_repo` is wrapped in a
+    /// [`CachedContentRepository`] backed by `cache_repo`.
     pub fn new(
         content_repo: Arc<dyn ContentRepository>,
         cache_repo: Arc<dyn CacheRepository>,
         allowed_repos: Vec<RepoConfig>,
     ) -> Self {
         Self {
-            content_repo,
+            content_repo: Arc::new(CachedContentRepository::new(content_repo, cache_repo.clone())),
             cache_repo,
             allowed_repos,
+            fetch_concurrency: DEFAULT_BULK_CONCURRENCY,
         }
     }
 
+    /// Cap the files an aggregated page fetches concurrently (default:
+    /// [`DEFAULT_BULK_CONCURRENCY`]); values below 1 are treated as 1
+    pub fn with_fetch_concurrency(mut self, limit: usize) -> Self {
+        self.fetch_concurrency = limit.max(1);
+        self
+    }
+
+    /// The cached content repository, for other services reading the same repos
+    pub fn repository(&self) -> Arc<dyn ContentRepository> {
+        self.content_repo.clone()
+    }
+
     fn validate_access(&self, source: &str, owner: &str, repo: &str) -> bool {
         self.allowed_repos
             .iter()
@@ -102,7 +217,8 @@ impl ContentService {
         let c_repo = self.content_repo.clone();
 
         let result = if options.aggregate {
-            Self::process_aggregation(c_repo, repo_config, path, options).await?
+            Self::process_aggregation(c_repo, repo_config, path, options, self.fetch_concurrency)
+                .await?
         } else {
             Self::process_standard(c_repo, repo_config, path).await?
         };
@@ -166,6 +282,7 @@ impl ContentService {
         config: RepoConfig,
         path: String,
         opts: AggregateOptions,
+        fetch_concurrency: usize,
     ) -> anyhow::Result<serde_json::Value> {
         // 1. List files
         let mut items = content_repo.list_directory(&config, &path).await?;
@@ -253,7 +370,7 @@ impl ContentService {
                     }
                 }
             })
-            .buffer_unordered(10)
+            .buffer_unordered(fetch_concurrency)
             .collect::<Vec<_>>()
             .await;
 
@@ -268,3 +385,121 @@ impl ContentService {
         })?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::InMemoryCache;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// One directory holding `rates.json`, whose SHA tests can change
+    #[derive(Default)]
+    struct CountingRepo {
+        sha: Mutex<String>,
+        listings: AtomicUsize,
+        files: AtomicUsize,
+    }
+
+    fn file(name: &str, sha: &str) -> Content {
+        Content {
+            name: name.to_string(),
+            path: format!("data/{}", name),
+            item_type: ContentType::File,
+            sha: Some(sha.to_string()),
+            content: Some(general_purpose::STANDARD.encode(format!("{{\"sha\":\"{}\"}}", sha))),
+            encoding: Some("base64".to_string()),
+            html_url: None,
+            download_url: None,
+            url: String::new(),
+        }
+    }
+
+    #[async_trait]
+    impl ContentRepository for CountingRepo {
+        async fn get_content(&self, _config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+            self.files.fetch_add(1, Ordering::SeqCst);
+            match path {
+                "data/rates.json" => Ok(file("rates.json", &self.sha.lock().unwrap())),
+                _ => anyhow::bail!("Not found: {}", path),
+            }
+        }
+
+        async fn list_directory(&self, _config: &RepoConfig, path: &str) -> anyhow::Result<Vec<Content>> {
+            self.listings.fetch_add(1, Ordering::SeqCst);
+            match path {
+                "data" => Ok(vec![file("rates.json", &self.sha.lock().unwrap())]),
+                _ => anyhow::bail!("Directory not found: {}", path),
+            }
+        }
+
+        async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+            anyhow::bail!("Not found: {}", url)
+        }
+    }
+
+    fn config() -> RepoConfig {
+        RepoConfig {
+            source: "github".to_string(),
+            owner: "KaspaDev".to_string(),
+            repo: "Kaspa-Exchange-Data".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_listing_is_served_from_cache() {
+        let upstream = Arc::new(CountingRepo::default());
+        let cache = Arc::new(InMemoryCache::new());
+        let repo = CachedContentRepository::new(upstream.clone(), cache.clone());
+
+        let first = repo.list_directory(&config(), "data").await.unwrap();
+        let second = repo.list_directory(&config(), "/data/").await.unwrap();
+        assert_eq!(upstream.listings.load(Ordering::SeqCst), 1);
+        assert_eq!(first[0].name, second[0].name);
+        assert_eq!(
+            cache.ttl_of("v1:gh:ls:github:KaspaDev:Kaspa-Exchange-Data:data"),
+            Some(LISTING_TTL_SECS)
+        );
+
+        // Failures reach the caller and are retried next time
+        assert!(repo.list_directory(&config(), "missing").await.is_err());
+        assert!(repo.list_directory(&config(), "missing").await.is_err());
+        assert_eq!(upstream.listings.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_file_content_is_cached_per_sha() {
+        let upstream = Arc::new(CountingRepo::default());
+        *upstream.sha.lock().unwrap() = "aaa".to_string();
+        let cache = Arc::new(InMemoryCache::new());
+        let repo = CachedContentRepository::new(upstream.clone(), cache.clone());
+
+        repo.get_content(&config(), "data/rates.json").await.unwrap();
+        let cached = repo.get_content(&config(), "data/rates.json").await.unwrap();
+        assert_eq!(cached.sha.as_deref(), Some("aaa"));
+        assert_eq!(upstream.files.load(Ordering::SeqCst), 1);
+        assert_eq!(upstream.listings.load(Ordering::SeqCst), 1);
+
+        // Once the listing expires, a new SHA means new content
+        *upstream.sha.lock().unwrap() = "bbb".to_string();
+        cache.delete("v1:gh:ls:github:KaspaDev:Kaspa-Exchange-Data:data").await.unwrap();
+        let updated = repo.get_content(&config(), "data/rates.json").await.unwrap();
+        assert_eq!(updated.sha.as_deref(), Some("bbb"));
+        assert_eq!(upstream.files.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_file_missing_from_listing_is_not_found() {
+        let upstream = Arc::new(CountingRepo::default());
+        *upstream.sha.lock().unwrap() = "aaa".to_string();
+        let repo = CachedContentRepository::new(upstream.clone(), Arc::new(InMemoryCache::new()));
+
+        let err = repo.get_content(&config(), "data/other.json").await.unwrap_err();
+        assert!(matches!(ApiError::find(&err), Some(ApiError::NotFound(_))), "{}", err);
+        assert_eq!(upstream.files.load(Ordering::SeqCst), 0);
+
+        // Directories that can't be listed still pass through
+        assert!(repo.get_content(&config(), "missing/rates.json").await.is_err());
+        assert_eq!(upstream.files.load(Ordering::SeqCst), 1);
+    }
+}
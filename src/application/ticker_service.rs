@@ -3,14 +3,20 @@
 //! Provides convenience methods for accessing aggregated token statistics
 //! across all exchanges without requiring directory navigation.
 
-use crate::application::ExchangeIndex;
-use crate::domain::{CacheRepository, ContentRepository, ContentType, RepoConfig};
+use crate::application::cache_service::{is_tracking_response_max_age, record_response_max_age};
+use crate::application::{CacheKey, ExchangeIndex};
+use crate::domain::{
+    normalize_exchange, normalize_token, validate_path_segment, ApiError, CacheRepository, Content,
+    ContentRepository, ContentType, RepoConfig,
+};
 use base64::{engine::general_purpose, Engine as _};
-use chrono::{Duration, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use futures::StreamExt;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
-use tracing::{info, warn};
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
 use utoipa::ToSchema;
 
 /// Response structure for ticker stats endpoint.
@@ -45,12 +51,60 @@ pub struct ExchangeStats {
     pub change_pct: Option<f64>,
     /// Number of data points in range
     pub data_points: usize,
+    /// Whether the exchange is known to report trustworthy prices
+    #[serde(default = "default_reliable")]
+    pub reliable: bool,
+    /// Known reporting delay of the exchange in seconds
+    #[serde(default)]
+    pub delay_secs: u64,
+    /// Weight of this exchange's price in the aggregate `avg_price`
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Time of the last data point in milliseconds since the epoch
+    #[serde(skip)]
+    pub last_trade_ms: Option<i64>,
+}
+
+fn default_reliable() -> bool {
+    true
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Known data-quality properties of an exchange.
+///
+/// Exchanges without an entry are treated as reliable with no delay and
+/// full weight.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeMeta {
+    /// Whether the exchange reports real, timely prices
+    #[serde(default = "default_reliable")]
+    pub reliable: bool,
+    /// Known reporting delay in seconds
+    #[serde(default)]
+    pub delay_secs: u64,
+    /// Weight of the exchange's price in the aggregate average (default 1.0;
+    /// 0 leaves it out)
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+impl Default for ExchangeMeta {
+    fn default() -> Self {
+        Self {
+            reliable: true,
+            delay_secs: 0,
+            weight: default_weight(),
+        }
+    }
 }
 
 /// Aggregated statistics across all exchanges.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AggregateStats {
-    /// Average price across exchanges
+    /// Average price across exchanges, weighted by each exchange's `weight`
     pub avg_price: Option<f64>,
     /// Total volume across all exchanges
     pub total_volume_24h: Option<f64>,
@@ -58,6 +112,58 @@ pub struct AggregateStats {
     pub vwap: Option<f64>,
     /// Number of active exchanges
     pub exchange_count: usize,
+    /// Number of exchanges excluded from the averages as price outliers
+    #[serde(default)]
+    pub outliers_excluded: usize,
+    /// Exchanges excluded as price outliers, by name
+    #[serde(default)]
+    pub filtered_out: Vec<String>,
+}
+
+/// Rules for leaving outlying exchange prices out of aggregate averages.
+///
+/// Both rules measure distance from the median last price and need at
+/// least three prices; an exchange flagged by either is excluded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutlierRules {
+    /// Exclude prices more than this many median absolute deviations away
+    pub mad_threshold: Option<f64>,
+    /// Exclude prices deviating more than this percent from the median
+    pub max_deviation_pct: Option<f64>,
+    /// Leave excluded exchanges out of `vwap` too, not just `avg_price`
+    pub apply_to_vwap: bool,
+}
+
+impl Default for OutlierRules {
+    fn default() -> Self {
+        Self {
+            mad_threshold: None,
+            max_deviation_pct: None,
+            apply_to_vwap: true,
+        }
+    }
+}
+
+impl OutlierRules {
+    /// Whether any rule is enabled
+    fn enabled(&self) -> bool {
+        self.mad_threshold.is_some() || self.max_deviation_pct.is_some()
+    }
+}
+
+/// Current price of a token, without the per-exchange breakdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LatestPriceResponse {
+    /// Token symbol/name
+    pub token: String,
+    /// Current price
+    pub price: Option<f64>,
+    /// `aggregate` when taken from cached stats (average across exchanges),
+    /// otherwise the exchange whose last price this is
+    pub source: String,
+    /// When the price was observed (ISO 8601): the stats time for
+    /// `aggregate`, otherwise the exchange's last trade
+    pub timestamp: String,
 }
 
 /// Response structure for ticker history endpoint.
@@ -71,6 +177,12 @@ pub struct TickerHistoryResponse {
     pub resolution: String,
     /// OHLCV data points
     pub data: Vec<OhlcvPoint>,
+    /// Simple moving average of closes, parallel to `data` (null during warm-up)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sma: Option<Vec<Option<f64>>>,
+    /// Exponential moving average of closes, parallel to `data` (null during warm-up)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ema: Option<Vec<Option<f64>>>,
 }
 
 /// Response structure for simplified timeseries endpoint.
@@ -148,6 +260,12 @@ pub struct ExchangeTokenRow {
     pub change_pct: Option<f64>,
     /// Number of data points in range
     pub data_points: usize,
+    /// Whether the exchange is known to report trustworthy prices
+    #[serde(default = "default_reliable")]
+    pub reliable: bool,
+    /// Known reporting delay of the exchange in seconds
+    #[serde(default)]
+    pub delay_secs: u64,
 }
 
 /// Simple timeseries data point for easy chart consumption.
@@ -174,6 +292,245 @@ pub struct OhlcvPoint {
     pub close: f64,
     /// Volume
     pub volume: f64,
+    /// Volume-weighted average price over the bucket (close when no volume traded)
+    #[serde(default)]
+    pub vwap: f64,
+}
+
+/// Result for one token of a batch stats request: either `stats` or `error`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TickerStatsBatchEntry {
+    /// Stats for the token, when it could be fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TickerStatsResponse>,
+    /// Why the token could not be fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A single point of a rebased comparison series.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NormalizedPoint {
+    /// Unix timestamp (seconds)
+    pub timestamp: i64,
+    /// Close price relative to the first shared bucket (100 = unchanged)
+    pub value: f64,
+}
+
+/// One token's rebased series in a comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ComparisonSeries {
+    /// Token symbol/name
+    pub token: String,
+    /// Points on the buckets shared by all compared tokens
+    pub data: Vec<NormalizedPoint>,
+}
+
+/// Response structure for the token comparison endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ComparisonResponse {
+    /// Range requested
+    pub range: String,
+    /// Data resolution
+    pub resolution: String,
+    /// First shared bucket, where every series equals 100
+    pub base_timestamp: Option<i64>,
+    /// One series per token, in request order
+    pub series: Vec<ComparisonSeries>,
+}
+
+/// Query parameters for the token comparison endpoint.
+#[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
+pub struct ComparisonQuery {
+    /// Comma-separated tokens to compare (2 to 5)
+    #[param(example = "kaspa,nacho")]
+    pub tokens: String,
+    /// Lookback range: today, 7d, 30d (default: 7d)
+    #[param(default = "7d", example = "30d")]
+    pub range: Option<String>,
+    /// Data resolution: 1m, 5m, 15m, 30m, 1h, 4h, 1d, 1w, 1M (default: 1h)
+    #[param(default = "1h", example = "1d")]
+    pub resolution: Option<String>,
+}
+
+impl ComparisonQuery {
+    /// Requested tokens, trimmed, with empty entries dropped
+    pub fn tokens(&self) -> Vec<String> {
+        self.tokens
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Requested range, or `7d` when absent
+    pub fn range(&self) -> &str {
+        self.range.as_deref().unwrap_or("7d")
+    }
+
+    /// Requested resolution, or `1h` when absent
+    pub fn resolution(&self) -> &str {
+        self.resolution.as_deref().unwrap_or("1h")
+    }
+}
+
+/// A token traded on an exchange.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct TickerPair {
+    /// Token identifier
+    #[schema(example = "kaspa")]
+    pub token: String,
+    /// Exchange identifier
+    #[schema(example = "mexc")]
+    pub exchange: String,
+}
+
+/// Query parameters for the ticker pairs endpoint.
+#[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
+pub struct PairsQuery {
+    /// Only return pairs on this exchange (case-insensitive)
+    #[param(example = "mexc")]
+    pub exchange: Option<String>,
+}
+
+/// Query parameters for the ticker search endpoint.
+#[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
+pub struct TickerSearchQuery {
+    /// Text to match against token names (case-insensitive)
+    #[param(example = "kas")]
+    pub q: String,
+    /// Maximum number of results (default and cap: the configured maximum)
+    #[param(example = 10)]
+    pub limit: Option<usize>,
+}
+
+/// Tokens matching a search, prefix matches first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TickerSearchResponse {
+    /// The search text as received
+    #[schema(example = "kas")]
+    pub query: String,
+    /// Matching token names, best match first
+    pub tickers: Vec<String>,
+    /// Number of tickers returned
+    pub count: usize,
+}
+
+/// Default cap on ticker search results
+pub const DEFAULT_MAX_SEARCH_RESULTS: usize = 20;
+
+/// Query parameters for the volume leaderboard endpoint.
+#[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
+pub struct LeaderboardQuery {
+    /// Lookback range: today, 7d, 30d (default: today)
+    #[param(default = "today", example = "today")]
+    pub range: Option<String>,
+    /// Number of tokens returned (default 20, capped at 100)
+    #[param(example = 20)]
+    pub limit: Option<usize>,
+}
+
+impl LeaderboardQuery {
+    /// Requested range, or `today` when absent
+    pub fn range(&self) -> &str {
+        self.range.as_deref().unwrap_or("today")
+    }
+}
+
+/// One token's place on the volume leaderboard.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LeaderboardEntry {
+    /// 1-based position, highest volume first
+    pub rank: usize,
+    pub token: String,
+    /// 24h volume summed across exchanges
+    pub total_volume_24h: f64,
+    /// Weighted average price across exchanges
+    pub avg_price: Option<f64>,
+    /// Exchanges contributing to the volume
+    pub exchange_count: usize,
+}
+
+/// Tokens ranked by cross-exchange 24h volume.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LeaderboardResponse {
+    /// Range the stats were read for
+    pub range: String,
+    /// When the ranking was computed (ISO 8601)
+    pub timestamp: String,
+    /// Tokens looked at, including those without volume or that failed
+    pub tokens_scanned: usize,
+    /// Top tokens, highest volume first
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// Default number of tokens on the volume leaderboard
+pub const DEFAULT_LEADERBOARD_LIMIT: usize = 20;
+
+/// Most tokens one leaderboard request returns
+pub const MAX_LEADERBOARD_LIMIT: usize = 100;
+
+/// How long a computed leaderboard is cached (seconds)
+pub const LEADERBOARD_TTL_SECS: u64 = 600;
+
+/// Maximum number of tokens in one comparison.
+pub const MAX_COMPARE_TOKENS: usize = 5;
+
+/// Maximum number of tokens in one batch stats request.
+pub const MAX_BATCH_TOKENS: usize = 50;
+
+/// Lookback ranges accepted by the ticker and exchange endpoints.
+pub const SUPPORTED_RANGES: &[&str] = &["today", "7d", "30d"];
+
+/// A query parameter value outside its supported set.
+///
+/// Returned (inside `anyhow::Error`) instead of silently falling back to a
+/// default, so API layers can downcast it and answer with 400.
+#[derive(Debug, Clone)]
+pub struct InvalidParameter {
+    pub name: &'static str,
+    pub value: String,
+    pub expected: &'static [&'static str],
+}
+
+impl std::fmt::Display for InvalidParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unsupported {} '{}' (expected one of: {})",
+            self.name,
+            self.value,
+            self.expected.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidParameter {}
+
+impl From<&InvalidParameter> for crate::domain::ApiError {
+    fn from(invalid: &InvalidParameter) -> Self {
+        Self::Validation(invalid.to_string())
+    }
+}
+
+impl InvalidParameter {
+    /// Ensure `value` is one of `expected`
+    pub(crate) fn check(
+        name: &'static str,
+        value: &str,
+        expected: &'static [&'static str],
+    ) -> Result<(), Self> {
+        if expected.contains(&value) {
+            Ok(())
+        } else {
+            Err(Self {
+                name,
+                value: value.to_string(),
+                expected,
+            })
+        }
+    }
 }
 
 /// Query parameters for ticker stats endpoint.
@@ -184,17 +541,76 @@ pub struct TickerStatsQuery {
     pub range: Option<String>,
 }
 
+impl TickerStatsQuery {
+    /// Requested range, or `today` when absent
+    pub fn range(&self) -> &str {
+        self.range.as_deref().unwrap_or("today")
+    }
+}
+
+/// 1970-01-05, the first Monday after the epoch, in Unix seconds
+const FIRST_MONDAY_SECS: i64 = 4 * 86400;
+
+/// Resolutions accepted by [`TickerService::get_ticker_history`].
+pub const SUPPORTED_RESOLUTIONS: &[&str] =
+    &["1m", "5m", "15m", "30m", "1h", "4h", "1d", "1w", "1M"];
+
 /// Query parameters for ticker history endpoint.
 #[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
 pub struct TickerHistoryQuery {
     /// Lookback range: today, 7d, 30d (default: 7d)
     #[param(default = "7d", example = "7d")]
     pub range: Option<String>,
-    /// Data resolution: 1m, 5m, 15m, 30m, 1h, 4h, 1d (default: 1h)
+    /// Data resolution: 1m, 5m, 15m, 30m, 1h, 4h, 1d, 1w (Monday-aligned), 1M
+    /// (calendar month) (default: 1h)
     #[param(default = "1h", example = "1h")]
     pub resolution: Option<String>,
 }
 
+impl TickerHistoryQuery {
+    /// Requested range, or `7d` when absent
+    pub fn range(&self) -> &str {
+        self.range.as_deref().unwrap_or("7d")
+    }
+
+    /// Requested resolution, or `1h` when absent
+    pub fn resolution(&self) -> &str {
+        self.resolution.as_deref().unwrap_or("1h")
+    }
+}
+
+/// Longest moving-average window accepted by [`OverlayQuery`].
+pub const MAX_OVERLAY_WINDOW: usize = 500;
+
+/// Moving-average overlays for the ticker history endpoint.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+pub struct OverlayQuery {
+    /// Simple moving average window over bucket closes (1-500)
+    #[param(example = 20)]
+    pub sma: Option<usize>,
+    /// Exponential moving average window over bucket closes (1-500)
+    #[param(example = 12)]
+    pub ema: Option<usize>,
+}
+
+impl OverlayQuery {
+    /// Ensure every requested window is between 1 and [`MAX_OVERLAY_WINDOW`]
+    fn validate(&self) -> anyhow::Result<()> {
+        for (name, window) in [("sma", self.sma), ("ema", self.ema)] {
+            if let Some(window) = window {
+                if !(1..=MAX_OVERLAY_WINDOW).contains(&window) {
+                    return Err(ApiError::Validation(format!(
+                        "{} window must be between 1 and {}, got {}",
+                        name, MAX_OVERLAY_WINDOW, window
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Query parameters for exchange detail endpoint.
 #[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
 pub struct ExchangeDetailQuery {
@@ -203,6 +619,177 @@ pub struct ExchangeDetailQuery {
     pub range: Option<String>,
 }
 
+impl ExchangeDetailQuery {
+    /// Requested range, or `today` when absent
+    pub fn range(&self) -> &str {
+        self.range.as_deref().unwrap_or("today")
+    }
+}
+
+/// How long latest prices are cached (seconds)
+pub const LATEST_PRICE_TTL_SECS: u64 = 30;
+
+/// Exchanges tried for a latest price before giving up
+const LATEST_PRICE_MAX_EXCHANGES: usize = 3;
+
+/// Default number of repository calls in flight at once, across all requests
+pub const DEFAULT_REPO_CONCURRENCY: usize = 10;
+
+/// Default days before today searched for an exchange's latest data file
+pub const DEFAULT_STATS_LOOKBACK_DAYS: i64 = 2;
+
+/// Upper bound on the stats lookback, each day costing one repository read
+pub const MAX_STATS_LOOKBACK_DAYS: i64 = 14;
+
+/// Where a token's data lives
+struct TokenLocation {
+    repo: Arc<dyn ContentRepository>,
+    config: RepoConfig,
+    exchange_dirs: Vec<Content>,
+}
+
+/// How long a path missing from both the local mirror and GitHub is
+/// remembered before GitHub is asked again (seconds)
+pub const GITHUB_MISS_TTL_SECS: u64 = 60;
+
+/// Paths GitHub recently reported missing, keyed by repo and path
+#[derive(Default)]
+struct GithubMisses {
+    entries: std::sync::Mutex<HashMap<String, std::time::Instant>>,
+}
+
+impl GithubMisses {
+    fn key(config: &RepoConfig, path: &str) -> String {
+        format!("{}/{}:{}", config.owner, config.repo, path)
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some(at) if at.elapsed().as_secs() < GITHUB_MISS_TTL_SECS => true,
+            Some(_) => {
+                entries.remove(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&self, key: String) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, at| at.elapsed().as_secs() < GITHUB_MISS_TTL_SECS);
+        entries.insert(key, std::time::Instant::now());
+    }
+}
+
+/// Local mirror that reads paths it doesn't have (yet) from GitHub.
+///
+/// Only not-found errors fall back; anything else from the local volume
+/// is returned as is. Paths GitHub doesn't have either are remembered for
+/// [`GITHUB_MISS_TTL_SECS`], so repeated requests for unknown tokens don't
+/// each cost GitHub calls.
+struct LocalWithFallback {
+    local: Arc<dyn ContentRepository>,
+    remote: Arc<dyn ContentRepository>,
+    misses: Arc<GithubMisses>,
+}
+
+impl LocalWithFallback {
+    /// Run `fetch` against GitHub unless `key` recently missed there
+    async fn remote<T, F>(&self, key: String, path: &str, fetch: F) -> anyhow::Result<T>
+    where
+        F: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        if self.misses.contains(&key) {
+            debug!("{} recently missing from GitHub", path);
+            return Err(ApiError::NotFound(format!("Not found: {}", path)).into());
+        }
+        let result = fetch.await;
+        if let Err(e) = &result {
+            if is_not_found(e) {
+                self.misses.insert(key);
+            }
+        }
+        result
+    }
+}
+
+fn is_not_found(e: &anyhow::Error) -> bool {
+    matches!(ApiError::find(e), Some(ApiError::NotFound(_)))
+}
+
+#[async_trait::async_trait]
+impl ContentRepository for LocalWithFallback {
+    async fn get_content(&self, config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+        match self.local.get_content(config, path).await {
+            Err(e) if is_not_found(&e) => {
+                debug!("{} missing locally, reading from GitHub", path);
+                let key = GithubMisses::key(config, path);
+                self.remote(key, path, self.remote.get_content(config, path))
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    async fn list_directory(
+        &self,
+        config: &RepoConfig,
+        path: &str,
+    ) -> anyhow::Result<Vec<Content>> {
+        match self.local.list_directory(config, path).await {
+            Err(e) if is_not_found(&e) => {
+                debug!("{} missing locally, listing from GitHub", path);
+                let key = format!("{}/", GithubMisses::key(config, path));
+                self.remote(key, path, self.remote.list_directory(config, path))
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+        // Listings that fell back carry GitHub URLs
+        if url.starts_with("file://") {
+            self.local.get_raw_file(url).await
+        } else {
+            self.remote.get_raw_file(url).await
+        }
+    }
+}
+
+/// Repository whose calls each hold a permit from a shared semaphore.
+///
+/// Every repository the service hands out is wrapped with the same
+/// semaphore, so nested fan-outs (tokens, then exchanges, then days) and
+/// concurrent requests together never run more calls than it has permits.
+struct PermitLimited {
+    inner: Arc<dyn ContentRepository>,
+    permits: Arc<Semaphore>,
+}
+
+#[async_trait::async_trait]
+impl ContentRepository for PermitLimited {
+    async fn get_content(&self, config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+        let _permit = self.permits.acquire().await?;
+        self.inner.get_content(config, path).await
+    }
+
+    async fn list_directory(
+        &self,
+        config: &RepoConfig,
+        path: &str,
+    ) -> anyhow::Result<Vec<Content>> {
+        let _permit = self.permits.acquire().await?;
+        self.inner.list_directory(config, path).await
+    }
+
+    async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+        let _permit = self.permits.acquire().await?;
+        self.inner.get_raw_file(url).await
+    }
+}
+
 /// Service for ticker-focused operations.
 #[derive(Clone)]
 pub struct TickerService {
@@ -210,7 +797,27 @@ pub struct TickerService {
     local_repo: Option<Arc<dyn ContentRepository>>,
     cache_repo: Arc<dyn CacheRepository>,
     default_repo: RepoConfig,
+    /// Other allowed repos tried, in order, for tokens missing from the default
+    fallback_repos: Vec<RepoConfig>,
     exchange_index: Option<Arc<ExchangeIndex>>,
+    /// Outlier exclusion for aggregate prices (all rules off by default)
+    outlier_rules: OutlierRules,
+    /// Data-quality metadata keyed by lowercase exchange name
+    exchange_meta: HashMap<String, ExchangeMeta>,
+    /// Permits for repository calls, shared by every request
+    repo_permits: Arc<Semaphore>,
+    /// Held while a leaderboard is computed, so concurrent misses compute once
+    leaderboard_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Read paths missing from the local mirror from GitHub instead
+    github_fallback: bool,
+    /// Paths the GitHub fallback recently found missing
+    github_misses: Arc<GithubMisses>,
+    /// Derive `change_pct` from first and last price rather than `percentage`
+    change_from_history: bool,
+    /// Days before today searched for an exchange's latest data file
+    stats_lookback_days: i64,
+    /// Most results a ticker search returns
+    max_search_results: usize,
 }
 
 impl TickerService {
@@ -224,7 +831,17 @@ impl TickerService {
             local_repo: None,
             cache_repo,
             default_repo,
+            fallback_repos: Vec::new(),
             exchange_index: None,
+            outlier_rules: OutlierRules::default(),
+            exchange_meta: HashMap::new(),
+            repo_permits: Arc::new(Semaphore::new(DEFAULT_REPO_CONCURRENCY)),
+            leaderboard_lock: Arc::default(),
+            github_fallback: false,
+            github_misses: Arc::default(),
+            change_from_history: false,
+            stats_lookback_days: DEFAULT_STATS_LOOKBACK_DAYS,
+            max_search_results: DEFAULT_MAX_SEARCH_RESULTS,
         }
     }
 
@@ -241,16 +858,223 @@ impl TickerService {
             local_repo,
             cache_repo,
             default_repo,
+            fallback_repos: Vec::new(),
             exchange_index,
+            outlier_rules: OutlierRules::default(),
+            exchange_meta: HashMap::new(),
+            repo_permits: Arc::new(Semaphore::new(DEFAULT_REPO_CONCURRENCY)),
+            leaderboard_lock: Arc::default(),
+            github_fallback: false,
+            github_misses: Arc::default(),
+            change_from_history: false,
+            stats_lookback_days: DEFAULT_STATS_LOOKBACK_DAYS,
+            max_search_results: DEFAULT_MAX_SEARCH_RESULTS,
+        }
+    }
+
+    /// Route tokens missing from the default repo to other allowed repos.
+    ///
+    /// `allowed_repos` is the whitelist from config; entries equal to the
+    /// default repo are skipped. Repos are tried in the given order and only
+    /// ever through the GitHub repository, since the local mirror holds the
+    /// default repo alone.
+    pub fn with_allowed_repos(
+        mut self,
+        allowed_repos: impl IntoIterator<Item = RepoConfig>,
+    ) -> Self {
+        let default_repo = &self.default_repo;
+        self.fallback_repos = allowed_repos
+            .into_iter()
+            .filter(|repo| repo != default_repo)
+            .collect();
+        self
+    }
+
+    /// Enable outlier rejection in aggregate prices.
+    ///
+    /// Exchanges flagged by `rules` are left out of `avg_price` (and of
+    /// `vwap` when `apply_to_vwap` is set) and listed in `filtered_out`.
+    pub fn with_outlier_rules(mut self, rules: OutlierRules) -> Self {
+        self.outlier_rules = rules;
+        self
+    }
+
+    /// Cap the repository calls the service runs concurrently.
+    ///
+    /// The limit is service-wide: every listing and file read, from any
+    /// request and at any nesting level (tokens, their exchanges, the daily
+    /// files of each), waits for one of `limit` shared permits. Values below
+    /// 1 are treated as 1.
+    pub fn with_repo_concurrency(mut self, limit: usize) -> Self {
+        self.repo_permits = Arc::new(Semaphore::new(limit.max(1)));
+        self
+    }
+
+    /// Whether paths missing from the local mirror are read from GitHub.
+    ///
+    /// Off by default; turn it on so a partially synced volume still
+    /// serves every token. Has no effect without a local mirror.
+    pub fn with_github_fallback(mut self, enabled: bool) -> Self {
+        self.github_fallback = enabled;
+        self
+    }
+
+    /// Search up to `days` days before today for an exchange's data file.
+    ///
+    /// Stats come from the newest daily file with data points, so a sync gap
+    /// longer than the lookback yields empty stats. Ranges longer than
+    /// `days` search their whole span. Capped at [`MAX_STATS_LOOKBACK_DAYS`].
+    pub fn with_stats_lookback_days(mut self, days: i64) -> Self {
+        self.stats_lookback_days = days.clamp(0, MAX_STATS_LOOKBACK_DAYS);
+        self
+    }
+
+    /// Days before today to search for a `start..=end` range
+    fn stats_lookback(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        (end - start)
+            .num_days()
+            .max(self.stats_lookback_days)
+            .min(MAX_STATS_LOOKBACK_DAYS)
+    }
+
+    /// Cap the results of [`Self::search_tickers`]. Values below 1 are
+    /// treated as 1.
+    pub fn with_max_search_results(mut self, max: usize) -> Self {
+        self.max_search_results = max.max(1);
+        self
+    }
+
+    /// Compute `change_pct` from the data points instead of the raw field.
+    ///
+    /// The `percentage` field of the latest data point is not always a 24h
+    /// change. When enabled, `change_pct` is `(last - first) / first * 100`
+    /// over the requested range's data points (every day file of a 7d or 30d
+    /// range), with `percentage` used only when that can't be computed. When
+    /// disabled the computed value, over the latest day only, is still used
+    /// for data without a `percentage` field.
+    pub fn with_change_from_history(mut self, enabled: bool) -> Self {
+        self.change_from_history = enabled;
+        self
+    }
+
+    /// Attach per-exchange data-quality metadata (reliability, delay).
+    pub fn with_exchange_meta(mut self, exchange_meta: HashMap<String, ExchangeMeta>) -> Self {
+        self.exchange_meta = exchange_meta
+            .into_iter()
+            .map(|(exchange, meta)| {
+                // Negative or NaN weights count as 0
+                let weight = meta.weight.max(0.0);
+                (
+                    normalize_exchange(&exchange),
+                    ExchangeMeta { weight, ..meta },
+                )
+            })
+            .collect();
+        self
+    }
+
+    /// Cached response under `cache_key`, if present and readable.
+    ///
+    /// A hit's remaining TTL is reported as the response's max-age, feeding
+    /// `Cache-Control` on the HTTP routes; it is read in the same round trip
+    /// as the value, and not at all outside an HTTP request.
+    async fn cache_get<T: DeserializeOwned>(&self, cache_key: &str) -> Option<T> {
+        if !is_tracking_response_max_age() {
+            let cached = self.cache_repo.get(cache_key).await.ok()??;
+            return serde_json::from_str(&cached).ok();
+        }
+        let (cached, remaining) = self.cache_repo.get_with_ttl(cache_key).await.ok()??;
+        let response = serde_json::from_str(&cached).ok()?;
+        if let Some(remaining) = remaining {
+            record_response_max_age(remaining);
+        }
+        Some(response)
+    }
+
+    /// Cache `response` under `cache_key` for `ttl_secs`, which is also
+    /// reported as the response's max-age.
+    async fn cache_set<T: Serialize>(&self, cache_key: &str, response: &T, ttl_secs: u64) {
+        if let Ok(json) = serde_json::to_string(response) {
+            let _ = self.cache_repo.set(cache_key, &json, ttl_secs).await;
+            record_response_max_age(ttl_secs);
         }
     }
 
+    /// Metadata for an exchange, defaulting to reliable with no delay
+    fn meta_for(&self, exchange: &str) -> ExchangeMeta {
+        self.exchange_meta
+            .get(&normalize_exchange(exchange))
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Get the repository to use (local if available, otherwise GitHub).
+    ///
+    /// With GitHub fallback enabled, paths missing locally are read from
+    /// GitHub.
     fn get_repo(&self) -> Arc<dyn ContentRepository> {
-        self.local_repo
-            .as_ref()
-            .cloned()
-            .unwrap_or_else(|| self.content_repo.clone())
+        let repo: Arc<dyn ContentRepository> = match &self.local_repo {
+            Some(local) if self.github_fallback => Arc::new(LocalWithFallback {
+                local: local.clone(),
+                remote: self.content_repo.clone(),
+                misses: self.github_misses.clone(),
+            }),
+            Some(local) => local.clone(),
+            None => self.content_repo.clone(),
+        };
+        self.permit_limited(repo)
+    }
+
+    /// `repo` with each call holding one of the service's repository permits
+    fn permit_limited(&self, repo: Arc<dyn ContentRepository>) -> Arc<dyn ContentRepository> {
+        Arc::new(PermitLimited {
+            inner: repo,
+            permits: self.repo_permits.clone(),
+        })
+    }
+
+    /// Find the repo holding `token`'s exchange directories.
+    ///
+    /// Checks the default repo first (through the local mirror when there
+    /// is one), then each fallback repo. Only whitelisted repos are ever
+    /// queried, and an empty token directory counts as missing.
+    async fn locate_token(&self, token: &str) -> anyhow::Result<TokenLocation> {
+        let token_path = format!("data/{}", token);
+        let candidates = std::iter::once((self.get_repo(), &self.default_repo)).chain(
+            self.fallback_repos
+                .iter()
+                .map(|config| (self.permit_limited(self.content_repo.clone()), config)),
+        );
+
+        let mut last_error = None;
+        let mut found_empty = false;
+        for (repo, config) in candidates {
+            match repo.list_directory(config, &token_path).await {
+                Ok(items) => {
+                    let exchange_dirs: Vec<_> = items
+                        .into_iter()
+                        .filter(|e| e.item_type == ContentType::Dir)
+                        .collect();
+                    if !exchange_dirs.is_empty() {
+                        if *config != self.default_repo {
+                            debug!("Token {} found in {}/{}", token, config.owner, config.repo);
+                        }
+                        return Ok(TokenLocation {
+                            repo,
+                            config: config.clone(),
+                            exchange_dirs,
+                        });
+                    }
+                    found_empty = true;
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match last_error {
+            Some(e) if !found_empty => Err(e),
+            _ => Err(ApiError::NotFound(format!("No exchanges found for token: {}", token)).into()),
+        }
     }
 
     /// Get current stats for a token across all exchanges.
@@ -259,67 +1083,82 @@ impl TickerService {
         token: String,
         range: String,
     ) -> anyhow::Result<TickerStatsResponse> {
-        let cache_key = format!("v1:ticker:{}:stats:{}", token, range);
+        InvalidParameter::check("range", &range, SUPPORTED_RANGES)?;
+        // Responses echo the token as requested; paths and keys use its
+        // canonical form
+        let requested = token;
+        let token = normalize_token(&requested);
+        validate_path_segment("token", &token)?;
+
+        let key = CacheKey::builder("v1:ticker")
+            .ticker(&token)
+            .part("stats")
+            .part(&range)
+            .build()?;
+        let cache_key = key.redis_key();
 
         // Check cache first
-        if let Ok(Some(cached)) = self.cache_repo.get(&cache_key).await {
-            if let Ok(response) = serde_json::from_str::<TickerStatsResponse>(&cached) {
-                info!("Cache HIT: {}", cache_key);
-                metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
-                return Ok(response);
-            }
+        if let Some(response) = self.cache_get::<TickerStatsResponse>(cache_key).await {
+            info!("Cache HIT: {}", cache_key);
+            metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+            return Ok(TickerStatsResponse {
+                token: requested,
+                ..response
+            });
         }
         metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
 
         // Discover exchanges for this token
-        let repo = self.get_repo();
-        let token_path = format!("data/{}", token.to_lowercase());
-        let exchanges = repo
-            .list_directory(&self.default_repo, &token_path)
-            .await?;
-
-        let exchange_dirs: Vec<_> = exchanges
-            .into_iter()
-            .filter(|e| e.item_type == ContentType::Dir)
-            .collect();
-
-        if exchange_dirs.is_empty() {
-            anyhow::bail!("No exchanges found for token: {}", token);
-        }
+        let TokenLocation {
+            repo,
+            config: repo_config,
+            exchange_dirs,
+        } = self.locate_token(&token).await?;
 
         // Calculate date range
-        let (start_date, end_date) = Self::calculate_date_range(&range);
+        let (start_date, end_date) = Self::calculate_date_range(&range)?;
 
         // Fetch stats from each exchange concurrently
         let repo_clone = repo.clone();
+        let lookback_days = self.stats_lookback(start_date, end_date);
+        let change_since = self.change_from_history.then_some(start_date);
         let mut exchange_stats = Vec::new();
-        let fetches = futures::stream::iter(exchange_dirs)
-            .map(|exchange| {
-                let repo = repo_clone.clone();
-                let config = self.default_repo.clone();
-                let token = token.clone();
-                let start = start_date;
-                let end = end_date;
-                async move {
-                    Self::fetch_exchange_stats(repo, config, token, exchange.name, start, end).await
-                }
-            })
-            .buffer_unordered(10)
-            .collect::<Vec<_>>()
-            .await;
+        let fetches = futures::future::join_all(exchange_dirs.into_iter().map(|exchange| {
+            let repo = repo_clone.clone();
+            let config = repo_config.clone();
+            let token = token.clone();
+            async move {
+                Self::fetch_exchange_stats(
+                    repo,
+                    config,
+                    token,
+                    exchange.name,
+                    lookback_days,
+                    change_since,
+                )
+                .await
+            }
+        }))
+        .await;
 
         for result in fetches {
             match result {
-                Ok(stats) => exchange_stats.push(stats),
+                Ok(mut stats) => {
+                    let meta = self.meta_for(&stats.exchange);
+                    stats.reliable = meta.reliable;
+                    stats.delay_secs = meta.delay_secs;
+                    stats.weight = meta.weight;
+                    exchange_stats.push(stats);
+                }
                 Err(e) => warn!("Failed to fetch exchange stats: {}", e),
             }
         }
 
         // Calculate aggregate stats
-        let aggregate = Self::calculate_aggregate(&exchange_stats);
+        let aggregate = Self::calculate_aggregate(&exchange_stats, &self.outlier_rules);
 
         let response = TickerStatsResponse {
-            token: token.clone(),
+            token: requested,
             timestamp: Utc::now().to_rfc3339(),
             range: range.clone(),
             exchanges: exchange_stats,
@@ -327,13 +1166,273 @@ impl TickerService {
         };
 
         // Cache result (5 min TTL)
-        if let Ok(json) = serde_json::to_string(&response) {
-            let _ = self.cache_repo.set(&cache_key, &json, 300).await;
+        self.cache_set(cache_key, &response, 300).await;
+
+        Ok(response)
+    }
+
+    /// Get the current price of a token without computing full stats.
+    ///
+    /// Uses the `today` aggregate average when [`Self::get_ticker_stats`]
+    /// has it cached. Otherwise reads one exchange's latest file, trying reliable,
+    /// low-delay exchanges first and stopping at the first with a price.
+    /// Cached for [`LATEST_PRICE_TTL_SECS`].
+    pub async fn get_latest_price(&self, token: String) -> anyhow::Result<LatestPriceResponse> {
+        let requested = token;
+        let token = normalize_token(&requested);
+        validate_path_segment("token", &token)?;
+
+        let key = CacheKey::builder("v1:ticker")
+            .ticker(&token)
+            .part("latest")
+            .build()?;
+        let cache_key = key.redis_key();
+        if let Some(response) = self.cache_get::<LatestPriceResponse>(cache_key).await {
+            metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+            return Ok(LatestPriceResponse {
+                token: requested,
+                ..response
+            });
+        }
+        metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
+
+        let stats_key = CacheKey::builder("v1:ticker")
+            .ticker(&token)
+            .part("stats")
+            .part("today")
+            .build()?;
+        let cached_stats = match self.cache_repo.get(stats_key.redis_key()).await {
+            Ok(Some(cached)) => serde_json::from_str::<TickerStatsResponse>(&cached).ok(),
+            _ => None,
+        };
+
+        let response = match cached_stats {
+            Some(stats) if stats.aggregate.avg_price.is_some() => LatestPriceResponse {
+                token: token.clone(),
+                price: stats.aggregate.avg_price,
+                source: "aggregate".to_string(),
+                timestamp: stats.timestamp,
+            },
+            _ => self.latest_exchange_price(&token).await?,
+        };
+
+        self.cache_set(cache_key, &response, LATEST_PRICE_TTL_SECS)
+            .await;
+        Ok(LatestPriceResponse {
+            token: requested,
+            ..response
+        })
+    }
+
+    /// Last price from the first exchange that has one, best exchanges first
+    async fn latest_exchange_price(&self, token: &str) -> anyhow::Result<LatestPriceResponse> {
+        let TokenLocation {
+            repo,
+            config,
+            mut exchange_dirs,
+        } = self.locate_token(token).await?;
+        exchange_dirs.sort_by_cached_key(|dir| {
+            let meta = self.meta_for(&dir.name);
+            (!meta.reliable, meta.delay_secs, dir.name.clone())
+        });
+
+        let (start, end) = Self::calculate_date_range("today")?;
+        for dir in exchange_dirs.into_iter().take(LATEST_PRICE_MAX_EXCHANGES) {
+            let fetched = Self::fetch_exchange_stats(
+                repo.clone(),
+                config.clone(),
+                token.to_string(),
+                dir.name,
+                self.stats_lookback(start, end),
+                None,
+            )
+            .await;
+            match fetched {
+                Ok(ExchangeStats {
+                    exchange,
+                    last: Some(price),
+                    last_trade_ms,
+                    ..
+                }) => {
+                    // The file's last trade, so an old price doesn't look live
+                    let traded_at = last_trade_ms
+                        .and_then(DateTime::from_timestamp_millis)
+                        .unwrap_or_else(Utc::now);
+                    return Ok(LatestPriceResponse {
+                        token: token.to_string(),
+                        price: Some(price),
+                        source: exchange,
+                        timestamp: traded_at.to_rfc3339(),
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to fetch latest price: {}", e),
+            }
+        }
+        anyhow::bail!("No current price for token: {}", token)
+    }
+
+    /// Get current stats for several tokens at once.
+    ///
+    /// Tokens are fetched concurrently through [`Self::get_ticker_stats`], so
+    /// tokens already in the cache are cheap. A failing token gets an error
+    /// entry instead of failing the whole batch; an unsupported range or an
+    /// oversized batch fails up front. Duplicate tokens are fetched once.
+    pub async fn get_ticker_stats_batch(
+        &self,
+        tokens: Vec<String>,
+        range: String,
+    ) -> anyhow::Result<BTreeMap<String, TickerStatsBatchEntry>> {
+        InvalidParameter::check("range", &range, SUPPORTED_RANGES)?;
+        if tokens.len() > MAX_BATCH_TOKENS {
+            return Err(ApiError::Validation(format!(
+                "Batch of {} tokens exceeds the limit of {}",
+                tokens.len(),
+                MAX_BATCH_TOKENS
+            ))
+            .into());
         }
 
+        // Spellings of one token share a cache entry, so they're fetched once
+        let unique: BTreeSet<String> = tokens.into_iter().collect();
+        let results = futures::future::join_all(unique.into_iter().map(|token| {
+            let range = range.clone();
+            async move {
+                let result = self.get_ticker_stats(token.clone(), range).await;
+                (token, result)
+            }
+        }))
+        .await;
+
+        Ok(results
+            .into_iter()
+            .map(|(token, result)| {
+                let entry = match result {
+                    Ok(stats) => TickerStatsBatchEntry {
+                        stats: Some(stats),
+                        error: None,
+                    },
+                    Err(e) => {
+                        warn!("Batch stats failed for {}: {}", token, e);
+                        TickerStatsBatchEntry {
+                            stats: None,
+                            error: Some(e.to_string()),
+                        }
+                    }
+                };
+                (token, entry)
+            })
+            .collect())
+    }
+
+    /// Rank tokens by 24h volume summed across their exchanges.
+    ///
+    /// Tokens come from the exchange index (or
+    /// [`Self::get_available_tickers`] without one) and each one's
+    /// aggregate is read through [`Self::get_ticker_stats`], their repository
+    /// calls sharing the service's permits. Tokens without volume or whose
+    /// stats fail are left out. The full ranking is cached for
+    /// [`LEADERBOARD_TTL_SECS`] per range, so every `limit` shares it, and
+    /// concurrent misses wait for one computation rather than each running
+    /// their own.
+    pub async fn get_volume_leaderboard(
+        &self,
+        range: String,
+        limit: Option<usize>,
+    ) -> anyhow::Result<LeaderboardResponse> {
+        InvalidParameter::check("range", &range, SUPPORTED_RANGES)?;
+        let limit = limit
+            .unwrap_or(DEFAULT_LEADERBOARD_LIMIT)
+            .clamp(1, MAX_LEADERBOARD_LIMIT);
+
+        let key = CacheKey::builder("v1:ticker")
+            .part("leaderboard")
+            .part(&range)
+            .build()?;
+        let cache_key = key.redis_key();
+        let mut response = match self.cache_get::<LeaderboardResponse>(cache_key).await {
+            Some(response) => {
+                metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+                response
+            }
+            None => {
+                metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
+                let _computing = self.leaderboard_lock.lock().await;
+                // Filled while we waited, by the request holding the lock
+                match self.cache_get::<LeaderboardResponse>(cache_key).await {
+                    Some(response) => response,
+                    None => {
+                        let response = self.compute_volume_leaderboard(&range).await?;
+                        self.cache_set(cache_key, &response, LEADERBOARD_TTL_SECS)
+                            .await;
+                        response
+                    }
+                }
+            }
+        };
+        response.entries.truncate(limit);
         Ok(response)
     }
 
+    /// Full leaderboard for `range`, uncached
+    async fn compute_volume_leaderboard(&self, range: &str) -> anyhow::Result<LeaderboardResponse> {
+        let tokens = match &self.exchange_index {
+            Some(index) if index.has_rebuilt() => index.get_all_tokens().await,
+            _ => self
+                .get_available_tickers()
+                .await?
+                .tickers
+                .into_iter()
+                .collect(),
+        };
+        let tokens_scanned = tokens.len();
+
+        let results = futures::future::join_all(tokens.into_iter().map(|token| async move {
+            (
+                token.clone(),
+                self.get_ticker_stats(token, range.to_string()).await,
+            )
+        }))
+        .await;
+
+        let mut ranked: Vec<LeaderboardEntry> = results
+            .into_iter()
+            .filter_map(|(token, result)| match result {
+                Ok(stats) => {
+                    let aggregate = stats.aggregate;
+                    Some(LeaderboardEntry {
+                        rank: 0,
+                        token: stats.token,
+                        total_volume_24h: aggregate
+                            .total_volume_24h
+                            .filter(|volume| *volume > 0.0)?,
+                        avg_price: aggregate.avg_price,
+                        exchange_count: aggregate.exchange_count,
+                    })
+                }
+                Err(e) => {
+                    warn!("Leaderboard stats failed for {}: {}", token, e);
+                    None
+                }
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.total_volume_24h
+                .total_cmp(&a.total_volume_24h)
+                .then_with(|| a.token.cmp(&b.token))
+        });
+        for (position, entry) in ranked.iter_mut().enumerate() {
+            entry.rank = position + 1;
+        }
+
+        Ok(LeaderboardResponse {
+            range: range.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            tokens_scanned,
+            entries: ranked,
+        })
+    }
+
     /// Get historical data for a token (for charting).
     pub async fn get_ticker_history(
         &self,
@@ -341,35 +1440,39 @@ impl TickerService {
         range: String,
         resolution: String,
     ) -> anyhow::Result<TickerHistoryResponse> {
-        let cache_key = format!("v1:ticker:{}:history:{}:{}", token, range, resolution);
+        InvalidParameter::check("range", &range, SUPPORTED_RANGES)?;
+        InvalidParameter::check("resolution", &resolution, SUPPORTED_RESOLUTIONS)?;
+        let requested = token;
+        let token = normalize_token(&requested);
+        validate_path_segment("token", &token)?;
+
+        let key = CacheKey::builder("v1:ticker")
+            .ticker(&token)
+            .part("history")
+            .part(&range)
+            .part(&resolution)
+            .build()?;
+        let cache_key = key.redis_key();
 
         // Check cache first
-        if let Ok(Some(cached)) = self.cache_repo.get(&cache_key).await {
-            if let Ok(response) = serde_json::from_str::<TickerHistoryResponse>(&cached) {
-                info!("Cache HIT: {}", cache_key);
-                metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
-                return Ok(response);
-            }
+        if let Some(response) = self.cache_get::<TickerHistoryResponse>(cache_key).await {
+            info!("Cache HIT: {}", cache_key);
+            metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+            return Ok(TickerHistoryResponse {
+                token: requested,
+                ..response
+            });
         }
         metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
 
         // Discover exchanges for this token
-        let repo = self.get_repo();
-        let token_path = format!("data/{}", token.to_lowercase());
-        let exchanges = repo
-            .list_directory(&self.default_repo, &token_path)
-            .await?;
-
-        let exchange_dirs: Vec<_> = exchanges
-            .into_iter()
-            .filter(|e| e.item_type == ContentType::Dir)
-            .collect();
-
-        if exchange_dirs.is_empty() {
-            anyhow::bail!("No exchanges found for token: {}", token);
-        }
+        let TokenLocation {
+            repo,
+            config: repo_config,
+            exchange_dirs,
+        } = self.locate_token(&token).await?;
 
-        let (start_date, end_date) = Self::calculate_date_range(&range);
+        let (start_date, end_date) = Self::calculate_date_range(&range)?;
 
         // Collect raw data from exchanges - try up to 10 to find ones with data
         let repo_clone = repo.clone();
@@ -382,10 +1485,10 @@ impl TickerService {
             if exchanges_with_data >= MAX_EXCHANGES {
                 break;
             }
-            
+
             match Self::fetch_exchange_raw_data(
                 repo_clone.clone(),
-                self.default_repo.clone(),
+                repo_config.clone(),
                 token.clone(),
                 exchange.name.clone(),
                 start_date,
@@ -395,7 +1498,11 @@ impl TickerService {
             {
                 Ok(data) => {
                     if !data.is_empty() {
-                        info!("Found {} data points from {} for history", data.len(), exchange.name);
+                        info!(
+                            "Found {} data points from {} for history",
+                            data.len(),
+                            exchange.name
+                        );
                         all_data.extend(data);
                         exchanges_with_data += 1;
                     }
@@ -404,57 +1511,217 @@ impl TickerService {
             }
         }
 
-        info!("Total raw data points collected: {} for {} history", all_data.len(), token);
+        info!(
+            "Total raw data points collected: {} for {} history",
+            all_data.len(),
+            token
+        );
 
         // Aggregate into OHLCV based on resolution
         let ohlcv_data = Self::aggregate_to_ohlcv(&all_data, &resolution);
-        
-        info!("OHLCV data points after aggregation: {} for {} (resolution: {})", ohlcv_data.len(), token, resolution);
+
+        info!(
+            "OHLCV data points after aggregation: {} for {} (resolution: {})",
+            ohlcv_data.len(),
+            token,
+            resolution
+        );
 
         let response = TickerHistoryResponse {
-            token: token.clone(),
+            token: requested,
             range: range.clone(),
             resolution: resolution.clone(),
             data: ohlcv_data,
+            sma: None,
+            ema: None,
         };
 
         // Cache result (5 min TTL)
-        if let Ok(json) = serde_json::to_string(&response) {
-            let _ = self.cache_repo.set(&cache_key, &json, 300).await;
-        }
+        self.cache_set(cache_key, &response, 300).await;
 
         Ok(response)
     }
 
-    fn calculate_date_range(range: &str) -> (NaiveDate, NaiveDate) {
+    /// Get historical data for a token with moving-average overlays.
+    ///
+    /// The overlays are computed over bucket closes of
+    /// [`Self::get_ticker_history`] (and its cache) and returned as arrays
+    /// parallel to `data`; buckets before a window fills are `null`.
+    pub async fn get_ticker_history_with_overlays(
+        &self,
+        token: String,
+        range: String,
+        resolution: String,
+        overlays: &OverlayQuery,
+    ) -> anyhow::Result<TickerHistoryResponse> {
+        overlays.validate()?;
+        let mut history = self.get_ticker_history(token, range, resolution).await?;
+
+        let closes: Vec<f64> = history.data.iter().map(|p| p.close).collect();
+        history.sma = overlays
+            .sma
+            .map(|window| Self::simple_moving_average(&closes, window));
+        history.ema = overlays
+            .ema
+            .map(|window| Self::exponential_moving_average(&closes, window));
+        Ok(history)
+    }
+
+    /// Mean of the last `window` values at each index, `None` until the window fills
+    fn simple_moving_average(values: &[f64], window: usize) -> Vec<Option<f64>> {
+        let mut sum = 0.0;
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                sum += value;
+                if i >= window {
+                    sum -= values[i - window];
+                }
+                (i + 1 >= window).then(|| sum / window as f64)
+            })
+            .collect()
+    }
+
+    /// EMA with smoothing `2 / (window + 1)`, seeded with the SMA of the first window
+    fn exponential_moving_average(values: &[f64], window: usize) -> Vec<Option<f64>> {
+        let alpha = 2.0 / (window as f64 + 1.0);
+        let mut ema: Option<f64> = None;
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                if i + 1 == window {
+                    ema = Some(values[..window].iter().sum::<f64>() / window as f64);
+                } else if let Some(previous) = ema {
+                    ema = Some(alpha * value + (1.0 - alpha) * previous);
+                }
+                ema
+            })
+            .collect()
+    }
+
+    /// Compare several tokens' price series on a common scale.
+    ///
+    /// Each token's OHLCV history comes from [`Self::get_ticker_history`]
+    /// (and its cache). Series are aligned on the buckets present for every
+    /// token and rebased so each starts at 100 on the first shared bucket.
+    pub async fn get_comparison(
+        &self,
+        tokens: Vec<String>,
+        range: String,
+        resolution: String,
+    ) -> anyhow::Result<ComparisonResponse> {
+        InvalidParameter::check("range", &range, SUPPORTED_RANGES)?;
+        InvalidParameter::check("resolution", &resolution, SUPPORTED_RESOLUTIONS)?;
+        if !(2..=MAX_COMPARE_TOKENS).contains(&tokens.len()) {
+            anyhow::bail!(
+                "Comparison needs between 2 and {} tokens, got {}",
+                MAX_COMPARE_TOKENS,
+                tokens.len()
+            );
+        }
+
+        let histories = futures::future::try_join_all(tokens.iter().map(|token| {
+            self.get_ticker_history(token.clone(), range.clone(), resolution.clone())
+        }))
+        .await?;
+
+        let (base_timestamp, series) = Self::rebase_on_common_buckets(
+            histories
+                .into_iter()
+                .map(|history| (history.token, history.data))
+                .collect(),
+        );
+
+        Ok(ComparisonResponse {
+            range,
+            resolution,
+            base_timestamp,
+            series,
+        })
+    }
+
+    /// Keep only buckets present in every series and rebase closes to 100
+    /// at the first shared bucket, which is returned alongside.
+    ///
+    /// A series whose base close is not positive can't be rebased and is
+    /// returned with no points.
+    fn rebase_on_common_buckets(
+        series: Vec<(String, Vec<OhlcvPoint>)>,
+    ) -> (Option<i64>, Vec<ComparisonSeries>) {
+        let mut common: Option<BTreeSet<i64>> = None;
+        for (_, points) in &series {
+            let timestamps: BTreeSet<i64> = points.iter().map(|p| p.timestamp).collect();
+            common = Some(match common {
+                None => timestamps,
+                Some(c) => c.intersection(&timestamps).copied().collect(),
+            });
+        }
+        let common = common.unwrap_or_default();
+        let base_timestamp = common.first().copied();
+
+        let series = series
+            .into_iter()
+            .map(|(token, points)| {
+                let mut closes: Vec<(i64, f64)> = points
+                    .into_iter()
+                    .filter(|p| common.contains(&p.timestamp))
+                    .map(|p| (p.timestamp, p.close))
+                    .collect();
+                closes.sort_by_key(|(ts, _)| *ts);
+                closes.dedup_by_key(|(ts, _)| *ts);
+
+                let data = match closes.first() {
+                    Some(&(_, base)) if base > 0.0 => closes
+                        .into_iter()
+                        .map(|(timestamp, close)| NormalizedPoint {
+                            timestamp,
+                            value: close / base * 100.0,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                ComparisonSeries { token, data }
+            })
+            .collect();
+        (base_timestamp, series)
+    }
+
+    fn calculate_date_range(range: &str) -> Result<(NaiveDate, NaiveDate), InvalidParameter> {
+        InvalidParameter::check("range", range, SUPPORTED_RANGES)?;
         let today = Utc::now().date_naive();
         let start = match range {
-            "today" => today,
             "7d" => today - Duration::days(7),
             "30d" => today - Duration::days(30),
             _ => today,
         };
-        (start, today)
+        Ok((start, today))
     }
 
+    /// Stats from an exchange's latest non-empty day file within `lookback_days`
+    ///
+    /// With `change_since`, `change_pct` is computed from the data points
+    /// (see [`Self::with_change_from_history`]); when that date is before the
+    /// day found, the days in between are read too, so the change spans the whole range rather than the one day.
     async fn fetch_exchange_stats(
         repo: Arc<dyn ContentRepository>,
         config: RepoConfig,
         token: String,
         exchange: String,
-        _start_date: NaiveDate,
-        _end_date: NaiveDate,
+        lookback_days: i64,
+        change_since: Option<NaiveDate>,
     ) -> anyhow::Result<ExchangeStats> {
-        // Try to get data file - try today first, then fall back to previous days
+        // Try today first, then step back until a file with data points is found
         let today = Utc::now().date_naive();
-        let days_to_try = [today, today - Duration::days(1), today - Duration::days(2)];
+        let days_to_try = (0..=lookback_days).map(|days_back| today - Duration::days(days_back));
 
         for date in days_to_try {
             let year = date.format("%Y");
             let month = date.format("%m");
             let date_path = format!(
                 "data/{}/{}/{}/{}/{}-raw.json",
-                token.to_lowercase(),
+                normalize_token(&token),
                 exchange,
                 year,
                 month,
@@ -470,9 +1737,37 @@ impl TickerService {
                             let clean = raw.replace('\n', "");
                             if let Ok(bytes) = general_purpose::STANDARD.decode(&clean) {
                                 if let Ok(s) = String::from_utf8(bytes) {
-                                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&s) {
-                                        info!("Found data for {} from {} for date {}", token, exchange, date);
-                                        return Self::parse_exchange_stats(&exchange, &json);
+                                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&s)
+                                    {
+                                        let mut stats = Self::parse_exchange_stats(
+                                            &exchange,
+                                            &json,
+                                            change_since.is_some(),
+                                        )?;
+                                        if stats.data_points == 0 {
+                                            continue;
+                                        }
+                                        info!(
+                                            "Found data for {} from {} for date {}",
+                                            token, exchange, date
+                                        );
+                                        if let Some(since) =
+                                            change_since.filter(|since| *since < date)
+                                        {
+                                            let series = Self::fetch_exchange_raw_data(
+                                                repo.clone(),
+                                                config.clone(),
+                                                token.clone(),
+                                                exchange.clone(),
+                                                since,
+                                                date,
+                                            )
+                                            .await?;
+                                            if let Some(change) = Self::change_over(&series) {
+                                                stats.change_pct = Some(change);
+                                            }
+                                        }
+                                        return Ok(stats);
                                     }
                                 }
                             }
@@ -495,12 +1790,17 @@ impl TickerService {
             volume_24h: None,
             change_pct: None,
             data_points: 0,
+            reliable: true,
+            delay_secs: 0,
+            weight: default_weight(),
+            last_trade_ms: None,
         })
     }
 
     fn parse_exchange_stats(
         exchange: &str,
         json: &serde_json::Value,
+        change_from_history: bool,
     ) -> anyhow::Result<ExchangeStats> {
         let data = json.get("data").and_then(|d| d.as_array());
 
@@ -514,6 +1814,10 @@ impl TickerService {
                     volume_24h: None,
                     change_pct: None,
                     data_points: 0,
+                    reliable: true,
+                    delay_secs: 0,
+                    weight: default_weight(),
+                    last_trade_ms: None,
                 });
             }
 
@@ -523,7 +1827,10 @@ impl TickerService {
             // Calculate high/low across all data points
             let mut high: Option<f64> = None;
             let mut low: Option<f64> = None;
-            let mut total_volume: f64 = 0.0;
+            let total_volume = Self::segmented_volume(
+                arr.iter()
+                    .filter_map(|p| p.get("quoteVolume").and_then(|v| v.as_f64())),
+            );
 
             for point in arr {
                 if let Some(h) = point.get("high").and_then(|v| v.as_f64()) {
@@ -532,19 +1839,28 @@ impl TickerService {
                 if let Some(l) = point.get("low").and_then(|v| v.as_f64()) {
                     low = Some(low.map_or(l, |curr| curr.min(l)));
                 }
-                if let Some(v) = point.get("quoteVolume").and_then(|v| v.as_f64()) {
-                    total_volume = v; // Use latest quoteVolume as it's cumulative
-                }
             }
 
+            let reported_change = latest.get("percentage").and_then(|v| v.as_f64());
+            let computed_change = Self::change_over(arr);
+            let change_pct = if change_from_history {
+                computed_change.or(reported_change)
+            } else {
+                reported_change.or(computed_change)
+            };
+
             Ok(ExchangeStats {
                 exchange: exchange.to_string(),
                 last: latest.get("last").and_then(|v| v.as_f64()),
                 high,
                 low,
                 volume_24h: Some(total_volume),
-                change_pct: latest.get("percentage").and_then(|v| v.as_f64()),
+                change_pct,
                 data_points: arr.len(),
+                reliable: true,
+                delay_secs: 0,
+                weight: default_weight(),
+                last_trade_ms: latest.get("timestamp").and_then(|v| v.as_i64()),
             })
         } else {
             Ok(ExchangeStats {
@@ -555,40 +1871,83 @@ impl TickerService {
                 volume_24h: None,
                 change_pct: None,
                 data_points: 0,
+                reliable: true,
+                delay_secs: 0,
+                weight: default_weight(),
+                last_trade_ms: None,
             })
         }
     }
 
-    fn calculate_aggregate(exchanges: &[ExchangeStats]) -> AggregateStats {
-        let active_exchanges: Vec<_> = exchanges
+    /// Percent change from the first to the last priced data point.
+    ///
+    /// `None` with fewer than two priced points or a zero opening price.
+    fn change_over(data: &[serde_json::Value]) -> Option<f64> {
+        let mut prices = data
             .iter()
-            .filter(|e| e.last.is_some())
-            .collect();
+            .filter_map(|p| p.get("last").and_then(|v| v.as_f64()));
+        let first = prices.next()?;
+        let last = prices.next_back()?;
+        if first == 0.0 {
+            return None;
+        }
+        Some((last - first) / first * 100.0)
+    }
+
+    fn calculate_aggregate(exchanges: &[ExchangeStats], rules: &OutlierRules) -> AggregateStats {
+        let all_active: Vec<_> = exchanges.iter().filter(|e| e.last.is_some()).collect();
 
-        if active_exchanges.is_empty() {
+        if all_active.is_empty() {
             return AggregateStats {
                 avg_price: None,
                 total_volume_24h: None,
                 vwap: None,
                 exchange_count: 0,
+                outliers_excluded: 0,
+                filtered_out: Vec::new(),
             };
         }
 
-        let sum_price: f64 = active_exchanges
-            .iter()
-            .filter_map(|e| e.last)
-            .sum();
-        let avg_price = sum_price / active_exchanges.len() as f64;
+        // Volume is summed across every active exchange; only prices are filtered
+        let total_volume: f64 = all_active.iter().filter_map(|e| e.volume_24h).sum();
 
-        let total_volume: f64 = active_exchanges
-            .iter()
-            .filter_map(|e| e.volume_24h)
-            .sum();
+        let flags = Self::outlier_flags(&all_active, rules);
+        let mut active_exchanges = Vec::new();
+        let mut filtered_out = Vec::new();
+        for (e, outlier) in all_active.iter().zip(flags) {
+            if outlier {
+                filtered_out.push(e.exchange.clone());
+            } else {
+                active_exchanges.push(*e);
+            }
+        }
+        if !filtered_out.is_empty() {
+            debug!(
+                "Excluded outlier exchange prices: {}",
+                filtered_out.join(", ")
+            );
+        }
+
+        // Weighted mean; exchanges weighted 0 still count towards volume
+        let mut weighted_price = 0.0;
+        let mut weight_sum = 0.0;
+        for e in &active_exchanges {
+            if let Some(price) = e.last {
+                weighted_price += price * e.weight;
+                weight_sum += e.weight;
+            }
+        }
+        let avg_price = (weight_sum > 0.0).then(|| weighted_price / weight_sum);
 
         // Calculate VWAP (volume-weighted average price)
+        let vwap_exchanges = if rules.apply_to_vwap {
+            &active_exchanges
+        } else {
+            &all_active
+        };
         let mut weighted_sum = 0.0;
         let mut volume_sum = 0.0;
-        for e in &active_exchanges {
+        for e in vwap_exchanges {
             if let (Some(price), Some(vol)) = (e.last, e.volume_24h) {
                 weighted_sum += price * vol;
                 volume_sum += vol;
@@ -601,11 +1960,75 @@ impl TickerService {
         };
 
         AggregateStats {
-            avg_price: Some(avg_price),
+            avg_price,
             total_volume_24h: Some(total_volume),
             vwap,
             exchange_count: active_exchanges.len(),
+            outliers_excluded: filtered_out.len(),
+            filtered_out,
+        }
+    }
+
+    /// Which of `active` (all with a last price) are outliers under `rules`
+    ///
+    /// Flags nothing when every price would be flagged, e.g. two clusters
+    /// far either side of an even-count median.
+    fn outlier_flags(active: &[&ExchangeStats], rules: &OutlierRules) -> Vec<bool> {
+        let none = vec![false; active.len()];
+        if !rules.enabled() {
+            return none;
+        }
+        let prices: Vec<f64> = active.iter().filter_map(|e| e.last).collect();
+        let Some((median, mad)) = Self::median_and_mad(&prices) else {
+            return none;
+        };
+
+        let flags: Vec<bool> = prices
+            .iter()
+            .map(|price| {
+                let deviation = (price - median).abs();
+                // A zero MAD means most exchanges agree exactly; nothing to scale by
+                let by_mad = rules
+                    .mad_threshold
+                    .is_some_and(|t| mad > 0.0 && deviation > t * mad);
+                let by_pct = rules
+                    .max_deviation_pct
+                    .is_some_and(|pct| median != 0.0 && deviation / median.abs() * 100.0 > pct);
+                by_mad || by_pct
+            })
+            .collect();
+        if flags.iter().all(|&flagged| flagged) {
+            return none;
+        }
+        flags
+    }
+
+    /// Median and median absolute deviation of a set of values.
+    ///
+    /// Returns None for fewer than three values, where an outlier can't be
+    /// told apart from the rest.
+    fn median_and_mad(values: &[f64]) -> Option<(f64, f64)> {
+        fn median(sorted: &[f64]) -> f64 {
+            let mid = sorted.len() / 2;
+            if sorted.len().is_multiple_of(2) {
+                (sorted[mid - 1] + sorted[mid]) / 2.0
+            } else {
+                sorted[mid]
+            }
+        }
+
+        if values.len() < 3 {
+            return None;
         }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let med = median(&sorted);
+
+        let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - med).abs()).collect();
+        deviations.sort_by(|a, b| a.total_cmp(b));
+
+        Some((med, median(&deviations)))
     }
 
     async fn fetch_exchange_raw_data(
@@ -616,96 +2039,124 @@ impl TickerService {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> anyhow::Result<Vec<serde_json::Value>> {
+        info!(
+            "Fetching raw data for {}/{} from {} to {}",
+            token, exchange, start_date, end_date
+        );
+
+        let date_paths: Vec<String> = start_date
+            .iter_days()
+            .take_while(|date| *date <= end_date)
+            .map(|date| {
+                format!(
+                    "data/{}/{}/{}/{}/{}-raw.json",
+                    normalize_token(&token),
+                    exchange,
+                    date.format("%Y"),
+                    date.format("%m"),
+                    date.format("%Y-%m-%d")
+                )
+            })
+            .collect();
+
+        // Fetch the days concurrently, keeping them in date order
+        let days = futures::future::join_all(date_paths.iter().map(|date_path| {
+            let repo = repo.clone();
+            let config = config.clone();
+            async move { Self::fetch_day_raw_data(repo, &config, date_path).await }
+        }))
+        .await;
+        let all_data: Vec<serde_json::Value> = days.into_iter().flatten().collect();
+
+        info!(
+            "Total data points collected for {}/{}: {}",
+            token,
+            exchange,
+            all_data.len()
+        );
+        Ok(all_data)
+    }
+
+    /// Data points of one daily raw file, empty when missing or unreadable
+    async fn fetch_day_raw_data(
+        repo: Arc<dyn ContentRepository>,
+        config: &RepoConfig,
+        date_path: &str,
+    ) -> Vec<serde_json::Value> {
         let mut all_data = Vec::new();
-        let mut current = start_date;
-        
-        info!("Fetching raw data for {}/{} from {} to {}", token, exchange, start_date, end_date);
 
-        while current <= end_date {
-            let year = current.format("%Y");
-            let month = current.format("%m");
-            let date_path = format!(
-                "data/{}/{}/{}/{}/{}-raw.json",
-                token.to_lowercase(),
-                exchange,
-                year,
-                month,
-                current.format("%Y-%m-%d")
-            );
-            
-            info!("Trying to fetch: {}", date_path);
-
-            if let Ok(content) = repo.get_content(&config, &date_path).await {
-                // Try to use get_raw_file if URL is available (more efficient for local files)
-                let file_url = content.download_url.as_ref().or_else(|| Some(&content.url));
-                if let Some(url) = file_url {
-                    if url.starts_with("file://") {
-                        match repo.get_raw_file(url).await {
-                            Ok(json) => {
-                                // Already parsed JSON from get_raw_file
-                                if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-                                    if !data.is_empty() {
-                                        all_data.extend(data.clone());
-                                    }
+        info!("Trying to fetch: {}", date_path);
+
+        if let Ok(content) = repo.get_content(config, date_path).await {
+            // Try to use get_raw_file if URL is available (more efficient for local files)
+            let file_url = content.download_url.as_ref().or(Some(&content.url));
+            if let Some(url) = file_url {
+                if url.starts_with("file://") {
+                    match repo.get_raw_file(url).await {
+                        Ok(json) => {
+                            // Already parsed JSON from get_raw_file
+                            if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+                                if !data.is_empty() {
+                                    all_data.extend(data.clone());
                                 }
-                                continue; // Successfully processed, continue to next file
-                            }
-                            Err(e) => {
-                                warn!("Failed to read raw file from {}: {}", url, e);
-                                // Fall through to base64 decode method
                             }
+                            return all_data;
+                        }
+                        Err(e) => {
+                            warn!("Failed to read raw file from {}: {}", url, e);
+                            // Fall through to base64 decode method
                         }
                     }
                 }
+            }
 
-                // Fallback: decode base64 content (GitHub API or LocalFileRepository)
-                if let (Some(raw), Some(enc)) = (content.content, content.encoding) {
-                    if enc == "base64" {
-                        let clean = raw.replace('\n', "");
-                        if let Ok(bytes) = general_purpose::STANDARD.decode(&clean) {
-                            if let Ok(s) = String::from_utf8(bytes) {
-                                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&s) {
-                                    if let Some(data) = json.get("data").and_then(|d| d.as_array())
-                                    {
-                                        if !data.is_empty() {
-                                            info!("Successfully loaded {} data points from {}", data.len(), date_path);
-                                            all_data.extend(data.clone());
-                                        } else {
-                                            warn!("File {} exists but data array is empty", date_path);
-                                        }
+            // Fallback: decode base64 content (GitHub API or LocalFileRepository)
+            if let (Some(raw), Some(enc)) = (content.content, content.encoding) {
+                if enc == "base64" {
+                    let clean = raw.replace('\n', "");
+                    if let Ok(bytes) = general_purpose::STANDARD.decode(&clean) {
+                        if let Ok(s) = String::from_utf8(bytes) {
+                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&s) {
+                                if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+                                    if !data.is_empty() {
+                                        info!(
+                                            "Successfully loaded {} data points from {}",
+                                            data.len(),
+                                            date_path
+                                        );
+                                        all_data.extend(data.clone());
                                     } else {
-                                        warn!("File {} exists but no 'data' array found", date_path);
+                                        warn!("File {} exists but data array is empty", date_path);
                                     }
                                 } else {
-                                    warn!("File {} exists but failed to parse as JSON", date_path);
+                                    warn!("File {} exists but no 'data' array found", date_path);
                                 }
                             } else {
-                                warn!("File {} exists but failed to decode UTF-8", date_path);
+                                warn!("File {} exists but failed to parse as JSON", date_path);
                             }
                         } else {
-                            warn!("File {} exists but failed to decode base64", date_path);
+                            warn!("File {} exists but failed to decode UTF-8", date_path);
                         }
+                    } else {
+                        warn!("File {} exists but failed to decode base64", date_path);
                     }
-                } else {
-                    warn!("File {} not found or has no content", date_path);
                 }
             } else {
-                warn!("Failed to get content for {}: file not found", date_path);
+                warn!("File {} not found or has no content", date_path);
             }
-
-            current += Duration::days(1);
+        } else {
+            warn!("Failed to get content for {}: file not found", date_path);
         }
-        
-        info!("Total data points collected for {}/{}: {}", token, exchange, all_data.len());
 
-        Ok(all_data)
+        all_data
     }
 
-    fn aggregate_to_ohlcv(data: &[serde_json::Value], resolution: &str) -> Vec<OhlcvPoint> {
-        if data.is_empty() {
-            return vec![];
-        }
-
+    /// Start of the bucket (Unix seconds) containing `ts_secs`.
+    ///
+    /// Fixed-width resolutions divide from the epoch, except `1w`, which
+    /// starts on Monday 00:00 UTC (the epoch fell on a Thursday). `1M` buckets
+    /// by calendar month (UTC), so each bucket starts on the 1st at 00:00.
+    fn bucket_start(ts_secs: i64, resolution: &str) -> i64 {
         let interval_secs: i64 = match resolution {
             "1m" => 60,
             "5m" => 300,
@@ -714,8 +2165,26 @@ impl TickerService {
             "1h" => 3600,
             "4h" => 14400,
             "1d" => 86400,
+            "1w" => {
+                return (ts_secs - FIRST_MONDAY_SECS).div_euclid(604800) * 604800
+                    + FIRST_MONDAY_SECS;
+            }
+            "1M" => {
+                return chrono::DateTime::from_timestamp(ts_secs, 0)
+                    .and_then(|dt| NaiveDate::from_ymd_opt(dt.year(), dt.month(), 1))
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|dt| dt.and_utc().timestamp())
+                    .unwrap_or(ts_secs);
+            }
             _ => 3600, // Default to 1h
         };
+        ts_secs.div_euclid(interval_secs) * interval_secs
+    }
+
+    fn aggregate_to_ohlcv(data: &[serde_json::Value], resolution: &str) -> Vec<OhlcvPoint> {
+        if data.is_empty() {
+            return vec![];
+        }
 
         // Group data points by time bucket
         let mut buckets: std::collections::BTreeMap<i64, Vec<&serde_json::Value>> =
@@ -724,12 +2193,15 @@ impl TickerService {
         for point in data {
             if let Some(ts) = point.get("timestamp").and_then(|v| v.as_i64()) {
                 // Convert milliseconds to seconds and bucket
-                let ts_secs = ts / 1000;
-                let bucket = (ts_secs / interval_secs) * interval_secs;
+                let bucket = Self::bucket_start(ts / 1000, resolution);
                 buckets.entry(bucket).or_default().push(point);
             }
         }
 
+        // Cumulative quoteVolume of the previous point, carried across buckets
+        // so the first point of a bucket still gets its traded volume
+        let mut prev_cumulative: Option<f64> = None;
+
         // Convert buckets to OHLCV
         buckets
             .into_iter()
@@ -738,7 +2210,11 @@ impl TickerService {
                 let mut high = f64::MIN;
                 let mut low = f64::MAX;
                 let mut close = 0.0;
-                let mut volume = 0.0;
+                let volume = Self::segmented_volume(
+                    points
+                        .iter()
+                        .filter_map(|p| p.get("quoteVolume").and_then(|v| v.as_f64())),
+                );
 
                 if let Some(first) = points.first() {
                     open = first.get("last").and_then(|v| v.as_f64()).unwrap_or(0.0);
@@ -747,16 +2223,26 @@ impl TickerService {
                     close = last.get("last").and_then(|v| v.as_f64()).unwrap_or(0.0);
                 }
 
+                let mut price_volume = 0.0;
+                let mut traded_volume = 0.0;
+
                 for p in &points {
+                    if let Some(cumulative) = p.get("quoteVolume").and_then(|v| v.as_f64()) {
+                        let traded = Self::traded_since(prev_cumulative, cumulative);
+                        prev_cumulative = Some(cumulative);
+                        if let (Some(price), true) =
+                            (p.get("last").and_then(|v| v.as_f64()), traded > 0.0)
+                        {
+                            price_volume += price * traded;
+                            traded_volume += traded;
+                        }
+                    }
                     if let Some(h) = p.get("high").and_then(|v| v.as_f64()) {
                         high = high.max(h);
                     }
                     if let Some(l) = p.get("low").and_then(|v| v.as_f64()) {
                         low = low.min(l);
                     }
-                    if let Some(v) = p.get("quoteVolume").and_then(|v| v.as_f64()) {
-                        volume = v; // Use latest as it's cumulative
-                    }
                 }
 
                 // Fix edge cases
@@ -767,6 +2253,12 @@ impl TickerService {
                     low = close;
                 }
 
+                let vwap = if traded_volume > 0.0 {
+                    price_volume / traded_volume
+                } else {
+                    close
+                };
+
                 OhlcvPoint {
                     timestamp,
                     open,
@@ -774,45 +2266,74 @@ impl TickerService {
                     low,
                     close,
                     volume,
+                    vwap,
                 }
             })
             .collect()
     }
 
+    /// Total volume from a sequence of cumulative `quoteVolume` readings.
+    ///
+    /// The running total restarts with each daily `*-raw.json` file, so a
+    /// decrease between consecutive readings marks a new segment. The total
+    /// is the sum of each segment's peak (its last reading before a reset).
+    fn segmented_volume(readings: impl IntoIterator<Item = f64>) -> f64 {
+        let mut total = 0.0;
+        let mut prev: Option<f64> = None;
+        for reading in readings {
+            if let Some(p) = prev.filter(|&p| reading < p) {
+                total += p;
+            }
+            prev = Some(reading);
+        }
+        total + prev.unwrap_or(0.0)
+    }
+
+    /// Volume traded between two cumulative `quoteVolume` readings.
+    ///
+    /// `quoteVolume` is a running total that exchanges reset daily, so a
+    /// reading lower than the previous one is treated as a reset and the new
+    /// reading is taken as the volume traded since. The very first reading
+    /// has no baseline and contributes nothing.
+    fn traded_since(prev_cumulative: Option<f64>, cumulative: f64) -> f64 {
+        match prev_cumulative {
+            Some(prev) if cumulative >= prev => cumulative - prev,
+            Some(_) => cumulative,
+            None => 0.0,
+        }
+    }
+
     /// Get list of available tickers/tokens.
-    /// 
+    ///
     /// Returns all tokens that have data available in the repository.
     /// This is useful for discovering which tickers can be queried.
-    /// 
+    ///
     /// # Returns
-    /// 
+    ///
     /// AvailableTickersResponse with a list of ticker names.
-    /// 
+    ///
     /// # Example
-    /// 
-    /// ```rust,no_run
+    ///
+    /// ```rust,ignore
     /// let tickers = ticker_service.get_available_tickers().await?;
     /// // Returns: AvailableTickersResponse { tickers: vec!["kaspa", "slow", "nacho"], count: 3 }
     /// ```
     pub async fn get_available_tickers(&self) -> anyhow::Result<AvailableTickersResponse> {
-        let cache_key = "v1:tickers:list";
+        let key = CacheKey::builder("v1:tickers").part("list").build()?;
+        let cache_key = key.redis_key();
 
         // Check cache first (cache for 1 hour since this changes infrequently)
-        if let Ok(Some(cached)) = self.cache_repo.get(cache_key).await {
-            if let Ok(response) = serde_json::from_str::<AvailableTickersResponse>(&cached) {
-                info!("Cache HIT: {}", cache_key);
-                metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
-                return Ok(response);
-            }
+        if let Some(response) = self.cache_get::<AvailableTickersResponse>(cache_key).await {
+            info!("Cache HIT: {}", cache_key);
+            metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+            return Ok(response);
         }
         metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
 
         // List the data directory to discover tokens
         let repo = self.get_repo();
         let data_dir = "data";
-        let items = repo
-            .list_directory(&self.default_repo, data_dir)
-            .await?;
+        let items = repo.list_directory(&self.default_repo, data_dir).await?;
 
         // Filter for directories (tokens) only
         let tickers: Vec<String> = items
@@ -827,43 +2348,100 @@ impl TickerService {
         };
 
         // Cache result (1 hour TTL)
-        if let Ok(json) = serde_json::to_string(&response) {
-            let _ = self.cache_repo.set(cache_key, &json, 3600).await;
-        }
+        self.cache_set(cache_key, &response, 3600).await;
 
         Ok(response)
     }
 
-    /// Get simplified timeseries data for easy chart consumption.
-    /// 
-    /// Returns price data as simple timestamp/price pairs,
-    /// which is easier to consume for most charting libraries.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `token` - Token name (e.g., "kaspa")
-    /// * `range` - Time range: "today", "7d", or "30d"
-    /// * `resolution` - Data resolution: "1m", "5m", "15m", "30m", "1h", "4h", or "1d"
-    /// 
-    /// # Returns
-    /// 
-    /// TimeseriesResponse with data points containing timestamp and price.
-    /// 
-    /// # Example
-    /// 
-    /// ```rust,no_run
-    /// // Get hourly data for the last 7 days
-    /// let timeseries = ticker_service.get_timeseries("kaspa", "7d", "1h").await?;
-    /// // Each point has: { timestamp: 1704067200, price: 0.04512 }
-    /// ```
-    pub async fn get_timeseries(
+    /// Search token names for search-as-you-type UIs.
+    ///
+    /// Matches `query` case-insensitively as a prefix or substring of each
+    /// token from the exchange index (or [`Self::get_available_tickers`]
+    /// without one). Prefix matches rank first, then shorter names, then
+    /// alphabetical order. `limit` defaults to, and is capped at, the
+    /// configured maximum.
+    pub async fn search_tickers(
         &self,
-        token: String,
-        range: String,
+        query: &str,
+        limit: Option<usize>,
+    ) -> anyhow::Result<TickerSearchResponse> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Err(
+                ApiError::Validation("Search query 'q' must not be empty".to_string()).into(),
+            );
+        }
+        let limit = limit
+            .unwrap_or(self.max_search_results)
+            .clamp(1, self.max_search_results);
+
+        let tokens = match &self.exchange_index {
+            Some(index) if index.has_rebuilt() => index.get_all_tokens().await,
+            _ => self.get_available_tickers().await?.tickers,
+        };
+
+        let mut matches: Vec<(bool, String)> = tokens
+            .into_iter()
+            .filter_map(|token| {
+                let name = token.to_lowercase();
+                if name.starts_with(&needle) {
+                    Some((false, token))
+                } else if name.contains(&needle) {
+                    Some((true, token))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        matches.sort_by_cached_key(|(substring_only, token)| {
+            (*substring_only, token.len(), token.to_lowercase())
+        });
+        matches.dedup_by(|a, b| a.1.eq_ignore_ascii_case(&b.1));
+
+        let tickers: Vec<String> = matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, token)| token)
+            .collect();
+        Ok(TickerSearchResponse {
+            query: query.to_string(),
+            count: tickers.len(),
+            tickers,
+        })
+    }
+
+    /// Get simplified timeseries data for easy chart consumption.
+    ///
+    /// Returns price data as simple timestamp/price pairs,
+    /// which is easier to consume for most charting libraries.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Token name (e.g., "kaspa")
+    /// * `range` - Time range: "today", "7d", or "30d"
+    /// * `resolution` - Data resolution: "1m", "5m", "15m", "30m", "1h", "4h", "1d", "1w", or "1M"
+    ///
+    /// # Returns
+    ///
+    /// TimeseriesResponse with data points containing timestamp and price.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Get hourly data for the last 7 days
+    /// let timeseries = ticker_service.get_timeseries("kaspa", "7d", "1h").await?;
+    /// // Each point has: { timestamp: 1704067200, price: 0.04512 }
+    /// ```
+    pub async fn get_timeseries(
+        &self,
+        token: String,
+        range: String,
         resolution: String,
     ) -> anyhow::Result<TimeseriesResponse> {
-        let history = self.get_ticker_history(token.clone(), range.clone(), resolution.clone()).await?;
-        
+        let history = self
+            .get_ticker_history(token, range.clone(), resolution.clone())
+            .await?;
+
         let data: Vec<TimeseriesPoint> = history
             .data
             .into_iter()
@@ -874,7 +2452,7 @@ impl TickerService {
             .collect();
 
         Ok(TimeseriesResponse {
-            token,
+            token: history.token,
             range,
             resolution,
             data,
@@ -882,7 +2460,7 @@ impl TickerService {
     }
 
     /// Get timeseries data with all OHLCV values for advanced charts.
-    /// 
+    ///
     /// Similar to `get_timeseries` but returns full OHLCV data points.
     /// Use this when you need high/low/open/close for candlestick or advanced charts.
     pub async fn get_timeseries_ohlcv(
@@ -896,18 +2474,18 @@ impl TickerService {
     }
 
     /// Get list of exchanges with their associated KRC20 tokens.
-    /// 
+    ///
     /// Returns all exchanges that have data available, with a list of tokens
     /// that are available on each exchange. This is useful for discovering
     /// which exchanges support which tokens.
-    /// 
+    ///
     /// # Returns
-    /// 
+    ///
     /// ExchangesResponse with a list of exchanges and their tokens.
-    /// 
+    ///
     /// # Example
-    /// 
-    /// ```rust,no_run
+    ///
+    /// ```rust,ignore
     /// let exchanges = ticker_service.get_exchanges().await?;
     /// // Returns: ExchangesResponse {
     /// //   exchanges: vec![
@@ -918,15 +2496,14 @@ impl TickerService {
     /// // }
     /// ```
     pub async fn get_exchanges(&self) -> anyhow::Result<ExchangesResponse> {
-        let cache_key = "v1:exchanges:list";
+        let key = CacheKey::builder("v1:exchanges").part("list").build()?;
+        let cache_key = key.redis_key();
 
         // Check cache first (cache for 1 hour since this changes infrequently)
-        if let Ok(Some(cached)) = self.cache_repo.get(cache_key).await {
-            if let Ok(response) = serde_json::from_str::<ExchangesResponse>(&cached) {
-                info!("Cache HIT: {}", cache_key);
-                metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
-                return Ok(response);
-            }
+        if let Some(response) = self.cache_get::<ExchangesResponse>(cache_key).await {
+            info!("Cache HIT: {}", cache_key);
+            metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+            return Ok(response);
         }
         metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
 
@@ -953,9 +2530,7 @@ impl TickerService {
                 };
 
                 // Cache result (1 hour TTL)
-                if let Ok(json) = serde_json::to_string(&response) {
-                    let _ = self.cache_repo.set(cache_key, &json, 3600).await;
-                }
+                self.cache_set(cache_key, &response, 3600).await;
 
                 return Ok(response);
             }
@@ -977,24 +2552,12 @@ impl TickerService {
         let mut exchange_map: std::collections::HashMap<String, Vec<String>> =
             std::collections::HashMap::new();
 
-        // For each token, discover its exchanges
-        for token in token_dirs {
-            let token_path = format!("data/{}", token);
-            match repo.list_directory(&self.default_repo, &token_path).await {
-                Ok(exchange_items) => {
-                    for exchange_item in exchange_items {
-                        if exchange_item.item_type == ContentType::Dir {
-                            let exchange_name = exchange_item.name;
-                            exchange_map
-                                .entry(exchange_name.clone())
-                                .or_insert_with(Vec::new)
-                                .push(token.clone());
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to list exchanges for token {}: {}", token, e);
-                }
+        for (token, exchange_names) in self.list_token_exchanges(&repo, token_dirs).await {
+            for exchange_name in exchange_names {
+                exchange_map
+                    .entry(exchange_name)
+                    .or_default()
+                    .push(token.clone());
             }
         }
 
@@ -1020,31 +2583,96 @@ impl TickerService {
         };
 
         // Cache result (1 hour TTL)
-        if let Ok(json) = serde_json::to_string(&response) {
-            let _ = self.cache_repo.set(cache_key, &json, 3600).await;
-        }
+        self.cache_set(cache_key, &response, 3600).await;
 
         Ok(response)
     }
 
+    /// List each token's exchange directories in the default repo.
+    ///
+    /// Listings run concurrently, bounded by the repository permits, and
+    /// come back in completion order. Tokens whose listing fails are logged
+    /// and left out.
+    async fn list_token_exchanges(
+        &self,
+        repo: &Arc<dyn ContentRepository>,
+        tokens: Vec<String>,
+    ) -> Vec<(String, Vec<String>)> {
+        tokens
+            .into_iter()
+            .map(|token| async move {
+                let token_path = format!("data/{}", token);
+                let listing = repo.list_directory(&self.default_repo, &token_path).await;
+                (token, listing)
+            })
+            .collect::<futures::stream::FuturesUnordered<_>>()
+            .filter_map(|(token, listing)| async move {
+                match listing {
+                    Ok(items) => {
+                        let exchanges = items
+                            .into_iter()
+                            .filter(|item| item.item_type == ContentType::Dir)
+                            .map(|item| item.name)
+                            .collect();
+                        Some((token, exchanges))
+                    }
+                    Err(e) => {
+                        warn!("Failed to list exchanges for token {}: {}", token, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+            .await
+    }
+
+    /// List every token/exchange pair, sorted by token then exchange.
+    ///
+    /// Flattens [`Self::get_exchanges`], so it shares that listing's index
+    /// lookup, repository fallback and 1 hour cache. `exchange` keeps only
+    /// pairs on that exchange; an unknown exchange yields an empty list.
+    pub async fn get_pairs(&self, exchange: Option<&str>) -> anyhow::Result<Vec<TickerPair>> {
+        let exchange = exchange.map(normalize_exchange);
+        let listing = self.get_exchanges().await?;
+
+        let mut pairs: Vec<TickerPair> = listing
+            .exchanges
+            .into_iter()
+            .filter(|info| {
+                exchange
+                    .as_deref()
+                    .is_none_or(|wanted| normalize_exchange(&info.exchange) == wanted)
+            })
+            .flat_map(|info| {
+                let exchange = info.exchange;
+                info.tokens.into_iter().map(move |token| TickerPair {
+                    token,
+                    exchange: exchange.clone(),
+                })
+            })
+            .collect();
+        pairs.sort();
+        Ok(pairs)
+    }
+
     /// Get detailed information about a specific exchange with all its tokens and statistics.
-    /// 
+    ///
     /// Returns all tokens available on the specified exchange with their current
     /// statistics (price, volume, change, etc.). This is useful for exchange
     /// detail pages that need to display all tokens on a specific exchange.
-    /// 
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `exchange` - Exchange identifier (e.g., "ascendex", "binance")
     /// * `range` - Time range: "today", "7d", or "30d"
-    /// 
+    ///
     /// # Returns
-    /// 
+    ///
     /// ExchangeDetailResponse with exchange info and list of tokens with stats.
-    /// 
+    ///
     /// # Example
-    /// 
-    /// ```rust,no_run
+    ///
+    /// ```rust,ignore
     /// let detail = ticker_service.get_exchange_detail("ascendex", "today").await?;
     /// // Returns: ExchangeDetailResponse {
     /// //   exchange: "ascendex",
@@ -1060,15 +2688,22 @@ impl TickerService {
         exchange: String,
         range: String,
     ) -> anyhow::Result<ExchangeDetailResponse> {
-        let cache_key = format!("v1:exchange:{}:detail:{}", exchange, range);
+        InvalidParameter::check("range", &range, SUPPORTED_RANGES)?;
+        let exchange = normalize_exchange(&exchange);
+        validate_path_segment("exchange", &exchange)?;
+
+        let key = CacheKey::builder("v1:exchange")
+            .part(&exchange)
+            .part("detail")
+            .part(&range)
+            .build()?;
+        let cache_key = key.redis_key();
 
         // Check cache first
-        if let Ok(Some(cached)) = self.cache_repo.get(&cache_key).await {
-            if let Ok(response) = serde_json::from_str::<ExchangeDetailResponse>(&cached) {
-                info!("Cache HIT: {}", cache_key);
-                metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
-                return Ok(response);
-            }
+        if let Some(response) = self.cache_get::<ExchangeDetailResponse>(cache_key).await {
+            info!("Cache HIT: {}", cache_key);
+            metrics::counter!("cache_operations_total", "operation" => "hit").increment(1);
+            return Ok(response);
         }
         metrics::counter!("cache_operations_total", "operation" => "miss").increment(1);
 
@@ -1100,26 +2735,19 @@ impl TickerService {
                 .map(|item| item.name)
                 .collect();
 
-            // For each token, check if it has this exchange
-            let mut found_tokens = Vec::new();
-            for token in token_dirs {
-                let token_path = format!("data/{}", token);
-                match repo.list_directory(&self.default_repo, &token_path).await {
-                    Ok(exchange_items) => {
-                        for exchange_item in exchange_items {
-                            if exchange_item.item_type == ContentType::Dir
-                                && exchange_item.name.to_lowercase() == exchange.to_lowercase()
-                            {
-                                found_tokens.push(token);
-                                break; // Found the exchange for this token, move to next token
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Failed to list exchanges for token {}: {}", token, e);
-                    }
-                }
-            }
+            // Keep the tokens listed on this exchange
+            let mut found_tokens: Vec<String> = self
+                .list_token_exchanges(&repo, token_dirs)
+                .await
+                .into_iter()
+                .filter(|(_, exchange_names)| {
+                    exchange_names
+                        .iter()
+                        .any(|name| normalize_exchange(name) == exchange)
+                })
+                .map(|(token, _)| token)
+                .collect();
+            found_tokens.sort();
 
             if found_tokens.is_empty() {
                 anyhow::bail!("Exchange not found: {}", exchange);
@@ -1130,29 +2758,30 @@ impl TickerService {
         };
 
         // Calculate date range
-        let (start_date, end_date) = Self::calculate_date_range(&range);
+        let (start_date, end_date) = Self::calculate_date_range(&range)?;
 
         // Fetch stats for each token on this exchange concurrently
         let repo = self.get_repo();
+        let meta = self.meta_for(&exchange);
+        let lookback_days = self.stats_lookback(start_date, end_date);
+        let change_since = self.change_from_history.then_some(start_date);
         let mut token_rows = Vec::new();
-        let fetches: Vec<anyhow::Result<ExchangeTokenRow>> = futures::stream::iter(tokens_with_exchange)
-            .map(|token| {
+        let fetches: Vec<anyhow::Result<ExchangeTokenRow>> =
+            futures::future::join_all(tokens_with_exchange.into_iter().map(|token| {
                 let repo = repo.clone();
                 let config = self.default_repo.clone();
                 let exchange_name = exchange.clone();
-                let start = start_date;
-                let end = end_date;
                 async move {
                     let stats = Self::fetch_exchange_stats(
                         repo,
                         config,
                         token.clone(),
                         exchange_name,
-                        start,
-                        end,
+                        lookback_days,
+                        change_since,
                     )
                     .await?;
-                    
+
                     // Convert ExchangeStats to ExchangeTokenRow
                     Ok(ExchangeTokenRow {
                         token,
@@ -1162,11 +2791,11 @@ impl TickerService {
                         volume_24h: stats.volume_24h,
                         change_pct: stats.change_pct,
                         data_points: stats.data_points,
+                        reliable: meta.reliable,
+                        delay_secs: meta.delay_secs,
                     })
                 }
-            })
-            .buffer_unordered(10)
-            .collect::<Vec<anyhow::Result<ExchangeTokenRow>>>()
+            }))
             .await;
 
         for result in fetches {
@@ -1193,10 +2822,1511 @@ impl TickerService {
         };
 
         // Cache result (5 min TTL)
-        if let Ok(json) = serde_json::to_string(&response) {
-            let _ = self.cache_repo.set(&cache_key, &json, 300).await;
-        }
+        self.cache_set(cache_key, &response, 300).await;
 
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(exchange: &str, last: f64, volume: f64) -> ExchangeStats {
+        ExchangeStats {
+            exchange: exchange.to_string(),
+            last: Some(last),
+            high: None,
+            low: None,
+            volume_24h: Some(volume),
+            change_pct: None,
+            data_points: 1,
+            reliable: true,
+            delay_secs: 0,
+            weight: default_weight(),
+            last_trade_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_calculate_aggregate_excludes_outliers_when_enabled() {
+        let exchanges = vec![
+            stats("a", 0.100, 10.0),
+            stats("b", 0.102, 10.0),
+            stats("c", 0.098, 10.0),
+            stats("d", 0.101, 10.0),
+            stats("stale", 0.500, 10.0),
+        ];
+
+        let plain = TickerService::calculate_aggregate(&exchanges, &OutlierRules::default());
+        assert_eq!(plain.exchange_count, 5);
+        assert_eq!(plain.outliers_excluded, 0);
+
+        let mad = OutlierRules {
+            mad_threshold: Some(3.0),
+            ..OutlierRules::default()
+        };
+        let filtered = TickerService::calculate_aggregate(&exchanges, &mad);
+        assert_eq!(filtered.exchange_count, 4);
+        assert_eq!(filtered.outliers_excluded, 1);
+        assert!((filtered.avg_price.unwrap() - 0.10025).abs() < 1e-9);
+        assert_eq!(filtered.total_volume_24h, Some(50.0));
+    }
+
+    #[test]
+    fn test_down_weighted_exchange_moves_average_less() {
+        let mut exchanges = vec![
+            stats("a", 1.0, 10.0),
+            stats("b", 1.0, 10.0),
+            stats("thin", 2.0, 1.0),
+        ];
+        let equal = TickerService::calculate_aggregate(&exchanges, &OutlierRules::default());
+        assert!((equal.avg_price.unwrap() - 4.0 / 3.0).abs() < 1e-9);
+
+        exchanges[2].weight = 0.25;
+        let weighted = TickerService::calculate_aggregate(&exchanges, &OutlierRules::default());
+        assert!((weighted.avg_price.unwrap() - 2.5 / 2.25).abs() < 1e-9);
+        // VWAP and volume already account for size and are unchanged
+        assert_eq!(weighted.vwap, equal.vwap);
+        assert_eq!(weighted.total_volume_24h, equal.total_volume_24h);
+
+        for e in &mut exchanges {
+            e.weight = 0.0;
+        }
+        let unweighted = TickerService::calculate_aggregate(&exchanges, &OutlierRules::default());
+        assert_eq!(unweighted.avg_price, None);
+        assert_eq!(unweighted.exchange_count, 3);
+    }
+
+    #[test]
+    fn test_exchange_weights_default_to_one_and_are_never_negative() {
+        let meta: HashMap<String, ExchangeMeta> = serde_yaml::from_str(
+            "thin: { weight: 0.2 }
+broken: { weight: -3 }
+mexc: {}
+",
+        )
+        .unwrap();
+        let service = offline_service().with_exchange_meta(meta);
+
+        assert_eq!(service.meta_for("thin").weight, 0.2);
+        assert_eq!(service.meta_for("broken").weight, 0.0);
+        assert_eq!(service.meta_for("mexc").weight, 1.0);
+        assert_eq!(service.meta_for("ascendex").weight, 1.0);
+    }
+
+    #[test]
+    fn test_percent_rule_drops_exchange_far_from_median() {
+        let exchanges = vec![
+            stats("a", 0.100, 10.0),
+            stats("b", 0.102, 10.0),
+            stats("c", 0.099, 10.0),
+            stats("broken", 1.000, 10.0), // 10x off
+        ];
+        let pct = OutlierRules {
+            max_deviation_pct: Some(20.0),
+            ..OutlierRules::default()
+        };
+
+        let plain = TickerService::calculate_aggregate(&exchanges, &OutlierRules::default());
+        assert!(plain.avg_price.unwrap() > 0.3);
+        assert!(plain.filtered_out.is_empty());
+
+        let filtered = TickerService::calculate_aggregate(&exchanges, &pct);
+        assert_eq!(filtered.filtered_out, ["broken"]);
+        assert_eq!(filtered.outliers_excluded, 1);
+        assert_eq!(filtered.exchange_count, 3);
+        assert!((filtered.avg_price.unwrap() - 0.301 / 3.0).abs() < 1e-9);
+        assert!((filtered.vwap.unwrap() - 0.301 / 3.0).abs() < 1e-9);
+        assert_eq!(filtered.total_volume_24h, Some(40.0));
+
+        // The VWAP can keep every exchange while the average drops the outlier
+        let avg_only = TickerService::calculate_aggregate(
+            &exchanges,
+            &OutlierRules {
+                apply_to_vwap: false,
+                ..pct
+            },
+        );
+        assert_eq!(avg_only.avg_price, filtered.avg_price);
+        assert_eq!(avg_only.vwap, plain.vwap);
+    }
+
+    #[test]
+    fn test_percent_rule_never_drops_every_exchange() {
+        // The even-count median sits between the clusters, far from all four
+        let exchanges = vec![
+            stats("a", 1.0, 1.0),
+            stats("b", 1.0, 1.0),
+            stats("c", 3.0, 1.0),
+            stats("d", 3.0, 1.0),
+        ];
+        let pct = OutlierRules {
+            max_deviation_pct: Some(10.0),
+            ..OutlierRules::default()
+        };
+        let aggregate = TickerService::calculate_aggregate(&exchanges, &pct);
+        assert!(aggregate.filtered_out.is_empty());
+        assert_eq!(aggregate.avg_price, Some(2.0));
+    }
+
+    fn tick(ts_secs: i64, last: f64, high: f64, low: f64) -> serde_json::Value {
+        serde_json::json!({
+            "timestamp": ts_secs * 1000,
+            "last": last,
+            "high": high,
+            "low": low,
+            "quoteVolume": last * 10.0,
+        })
+    }
+
+    fn ts(y: i32, m: u32, d: u32, h: u32) -> i64 {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn test_monthly_buckets_follow_calendar_months() {
+        let data = vec![
+            tick(ts(2025, 1, 15, 0), 1.0, 1.5, 0.9),
+            tick(ts(2025, 1, 31, 23), 2.0, 2.5, 1.8),
+            tick(ts(2025, 2, 1, 0), 3.0, 3.2, 2.9),
+            tick(ts(2025, 2, 28, 12), 2.5, 4.0, 2.0),
+            tick(ts(2025, 3, 1, 0), 5.0, 5.0, 5.0),
+        ];
+
+        let points = TickerService::aggregate_to_ohlcv(&data, "1M");
+
+        let starts: Vec<i64> = points.iter().map(|p| p.timestamp).collect();
+        assert_eq!(
+            starts,
+            vec![ts(2025, 1, 1, 0), ts(2025, 2, 1, 0), ts(2025, 3, 1, 0)]
+        );
+
+        let feb = &points[1];
+        assert_eq!(feb.open, 3.0);
+        assert_eq!(feb.close, 2.5);
+        assert_eq!(feb.high, 4.0);
+        assert_eq!(feb.low, 2.0);
+        // quoteVolume drops 30 -> 25, which reads as a reset: both peaks count
+        assert_eq!(feb.volume, 55.0);
+    }
+
+    #[test]
+    fn test_weekly_buckets_start_on_monday() {
+        let base = ts(2025, 1, 6, 0); // a Monday
+        let data = vec![
+            tick(base, 1.0, 1.0, 1.0),
+            tick(base + 604799, 2.0, 3.0, 0.5),
+            tick(base + 604800, 4.0, 4.0, 4.0),
+        ];
+
+        let points = TickerService::aggregate_to_ohlcv(&data, "1w");
+
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, base);
+        assert_eq!(points[0].open, 1.0);
+        assert_eq!(points[0].close, 2.0);
+        assert_eq!(points[0].high, 3.0);
+        assert_eq!(points[0].low, 0.5);
+        assert_eq!(points[1].timestamp, base + 604800);
+
+        // Midweek and Sunday-night points fall into the week's Monday
+        assert_eq!(TickerService::bucket_start(ts(2025, 1, 9, 15), "1w"), base);
+        assert_eq!(TickerService::bucket_start(ts(2025, 1, 12, 23), "1w"), base);
+        assert_eq!(TickerService::bucket_start(ts(1969, 12, 31, 0), "1w"), ts(1969, 12, 29, 0));
+    }
+
+    #[test]
+    fn test_monthly_and_minute_resolutions_differ() {
+        assert_eq!(
+            TickerService::bucket_start(ts(2025, 2, 14, 7) + 59, "1m"),
+            ts(2025, 2, 14, 7)
+        );
+        assert_eq!(
+            TickerService::bucket_start(ts(2025, 2, 14, 7), "1M"),
+            ts(2025, 2, 1, 0)
+        );
+    }
+
+    fn offline_service() -> TickerService {
+        let dir = std::env::temp_dir();
+        TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(&dir)),
+            Arc::new(crate::infrastructure::RedisRepository::new(None)),
+            RepoConfig {
+                source: "github".to_string(),
+                owner: "KaspaDev".to_string(),
+                repo: "Kaspa-Exchange-Data".to_string(),
+            },
+        )
+    }
+
+    fn invalid_parameter(err: anyhow::Error) -> InvalidParameter {
+        err.downcast::<InvalidParameter>()
+            .expect("expected InvalidParameter")
+    }
+
+    #[test]
+    fn test_absent_parameters_use_defaults() {
+        let stats: TickerStatsQuery = serde_json::from_str("{}").unwrap();
+        let history: TickerHistoryQuery = serde_json::from_str("{}").unwrap();
+        let detail: ExchangeDetailQuery = serde_json::from_str("{}").unwrap();
+
+        assert_eq!(stats.range(), "today");
+        assert_eq!(history.range(), "7d");
+        assert_eq!(history.resolution(), "1h");
+        assert_eq!(detail.range(), "today");
+        assert!(TickerService::calculate_date_range(stats.range()).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_range_is_rejected_by_date_range() {
+        let err = TickerService::calculate_date_range("7days").unwrap_err();
+        assert_eq!(err.name, "range");
+        assert_eq!(err.value, "7days");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_parameters_return_errors() {
+        let service = offline_service();
+
+        let err = service
+            .get_ticker_stats("slow".to_string(), "7days".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(invalid_parameter(err).name, "range");
+
+        let err = service
+            .get_ticker_history("slow".to_string(), "7d".to_string(), "2h".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(invalid_parameter(err).name, "resolution");
+
+        let err = service
+            .get_exchange_detail("ascendex".to_string(), "week".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(invalid_parameter(err).value, "week");
+
+        let overlays = OverlayQuery {
+            sma: Some(0),
+            ema: None,
+        };
+        let err = service
+            .get_ticker_history_with_overlays(
+                "slow".to_string(),
+                "7d".to_string(),
+                "1h".to_string(),
+                &overlays,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(ApiError::find(&err), Some(ApiError::Validation(_))),
+            "{}",
+            err
+        );
+    }
+
+    fn assert_series_close(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len());
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            match (a, e) {
+                (Some(a), Some(e)) => assert!((a - e).abs() < 1e-9, "index {}: {} != {}", i, a, e),
+                _ => assert_eq!(a, e, "index {}", i),
+            }
+        }
+    }
+
+    #[test]
+    fn test_simple_moving_average() {
+        let closes = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_series_close(
+            &TickerService::simple_moving_average(&closes, 3),
+            &[None, None, Some(2.0), Some(3.0), Some(4.0), Some(5.0)],
+        );
+        assert_series_close(
+            &TickerService::simple_moving_average(&closes, 1),
+            &closes.map(Some),
+        );
+    }
+
+    #[test]
+    fn test_exponential_moving_average() {
+        // alpha = 2 / (3 + 1) = 0.5, seeded with SMA(2, 4, 6) = 4
+        let closes = [2.0, 4.0, 6.0, 8.0, 4.0, 10.0];
+        assert_series_close(
+            &TickerService::exponential_moving_average(&closes, 3),
+            &[None, None, Some(4.0), Some(6.0), Some(5.0), Some(7.5)],
+        );
+    }
+
+    #[test]
+    fn test_window_longer_than_series_is_all_null() {
+        let closes = [1.0, 2.0];
+        assert_eq!(
+            TickerService::simple_moving_average(&closes, 5),
+            vec![None, None]
+        );
+        assert_eq!(
+            TickerService::exponential_moving_average(&closes, 5),
+            vec![None, None]
+        );
+    }
+
+    fn trade(ts_secs: i64, last: f64, cumulative_volume: f64) -> serde_json::Value {
+        serde_json::json!({
+            "timestamp": ts_secs * 1000,
+            "last": last,
+            "quoteVolume": cumulative_volume,
+        })
+    }
+
+    #[test]
+    fn test_vwap_uses_volume_deltas() {
+        let data = vec![
+            trade(0, 1.0, 100.0),   // baseline only
+            trade(60, 2.0, 110.0),  // 10 traded at 2.0
+            trade(120, 4.0, 140.0), // 30 traded at 4.0
+        ];
+
+        let points = TickerService::aggregate_to_ohlcv(&data, "1h");
+
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].vwap, (2.0 * 10.0 + 4.0 * 30.0) / 40.0);
+    }
+
+    #[test]
+    fn test_vwap_carries_baseline_across_buckets_and_handles_reset() {
+        let data = vec![
+            trade(3500, 1.0, 500.0),
+            trade(3600, 3.0, 520.0), // 20 traded, first point of the next bucket
+            trade(3660, 5.0, 5.0),   // daily reset: 5 traded since
+            trade(7200, 7.0, 5.0),   // nothing traded in this bucket
+        ];
+
+        let points = TickerService::aggregate_to_ohlcv(&data, "1h");
+
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].vwap, points[0].close);
+        assert_eq!(points[1].vwap, (3.0 * 20.0 + 5.0 * 5.0) / 25.0);
+        assert_eq!(points[2].vwap, 7.0);
+    }
+
+    fn close(timestamp: i64, close: f64) -> OhlcvPoint {
+        OhlcvPoint {
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            vwap: close,
+        }
+    }
+
+    #[test]
+    fn test_rebase_on_common_buckets() {
+        let (base_timestamp, series) = TickerService::rebase_on_common_buckets(vec![
+            (
+                "kaspa".to_string(),
+                vec![
+                    close(100, 0.2),
+                    close(200, 0.1),
+                    close(300, 0.3),
+                    close(400, 0.4),
+                ],
+            ),
+            (
+                "nacho".to_string(),
+                vec![close(200, 5.0), close(300, 7.5), close(400, 2.5)],
+            ),
+            ("slow".to_string(), vec![close(200, 1.0), close(400, 1.5)]),
+        ]);
+
+        // Only buckets 200 and 400 are present in all three series
+        assert_eq!(base_timestamp, Some(200));
+        let points = |i: usize| -> Vec<(i64, f64)> {
+            series[i]
+                .data
+                .iter()
+                .map(|p| (p.timestamp, p.value))
+                .collect()
+        };
+        assert_eq!(series[0].token, "kaspa");
+        assert_eq!(points(0), vec![(200, 100.0), (400, 400.0)]);
+        assert_eq!(points(1), vec![(200, 100.0), (400, 50.0)]);
+        assert_eq!(points(2), vec![(200, 100.0), (400, 150.0)]);
+    }
+
+    #[test]
+    fn test_rebase_skips_series_without_positive_base() {
+        let (base_timestamp, series) = TickerService::rebase_on_common_buckets(vec![
+            ("kaspa".to_string(), vec![close(100, 0.0), close(200, 1.0)]),
+            ("nacho".to_string(), vec![close(100, 2.0), close(200, 3.0)]),
+        ]);
+        // The shared start doesn't depend on the first series rebasing
+        assert_eq!(base_timestamp, Some(100));
+        assert!(series[0].data.is_empty());
+        assert_eq!(series[1].data.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stats_batch_reports_per_token_errors() {
+        let service = offline_service();
+
+        let batch = service
+            .get_ticker_stats_batch(
+                vec![
+                    "no-such-token-a".to_string(),
+                    "no-such-token-b".to_string(),
+                    "no-such-token-a".to_string(),
+                ],
+                "today".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch
+            .values()
+            .all(|entry| entry.stats.is_none() && entry.error.is_some()));
+
+        let err = service
+            .get_ticker_stats_batch(vec!["slow".to_string()], "7days".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(invalid_parameter(err).name, "range");
+
+        let oversized = vec!["slow".to_string(); MAX_BATCH_TOKENS + 1];
+        assert!(service
+            .get_ticker_stats_batch(oversized, "today".to_string())
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_exchange_meta_defaults_to_reliable() {
+        let meta: HashMap<String, ExchangeMeta> =
+            serde_yaml::from_str("Binance: { reliable: false, delay_secs: 300 }\nmexc: {}\n")
+                .unwrap();
+        let service = offline_service().with_exchange_meta(meta);
+
+        assert_eq!(
+            service.meta_for("binance"),
+            ExchangeMeta {
+                reliable: false,
+                delay_secs: 300,
+                weight: 1.0,
+            }
+        );
+        assert_eq!(service.meta_for("mexc"), ExchangeMeta::default());
+        assert_eq!(service.meta_for("ascendex"), ExchangeMeta::default());
+    }
+
+    #[test]
+    fn test_cached_stats_without_flags_deserialize_as_reliable() {
+        let stats: ExchangeStats = serde_json::from_value(serde_json::json!({
+            "exchange": "ascendex",
+            "last": 1.0,
+            "high": null,
+            "low": null,
+            "volume_24h": null,
+            "change_pct": null,
+            "data_points": 1
+        }))
+        .unwrap();
+
+        assert!(stats.reliable);
+        assert_eq!(stats.delay_secs, 0);
+    }
+
+    #[test]
+    fn test_volume_sums_peaks_across_daily_resets() {
+        let day = 86400;
+        let data = vec![
+            trade(0, 1.0, 100.0),
+            trade(3600, 1.0, 250.0), // day 1 peak
+            trade(day, 1.0, 0.0),    // day 2 restarts
+            trade(day + 3600, 1.0, 40.0),
+            trade(day + 7200, 1.0, 90.0), // day 2 peak
+        ];
+
+        let stats = TickerService::parse_exchange_stats(
+            "ascendex",
+            &serde_json::json!({ "data": data }),
+            false,
+        )
+        .unwrap();
+        assert_eq!(stats.volume_24h, Some(340.0));
+
+        let points = TickerService::aggregate_to_ohlcv(&data, "1w");
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].volume, 340.0);
+    }
+
+    #[test]
+    fn test_change_pct_from_first_and_last_price() {
+        let mut open = trade(0, 0.080, 10.0);
+        let mut close = trade(3600, 0.092, 20.0);
+        // Per-point change, not the change over the day
+        open["percentage"] = serde_json::json!(0.5);
+        close["percentage"] = serde_json::json!(1.2);
+        let json = serde_json::json!({ "data": [open, trade(1800, 0.070, 15.0), close] });
+
+        let raw = TickerService::parse_exchange_stats("mexc", &json, false).unwrap();
+        assert_eq!(raw.change_pct, Some(1.2));
+
+        let computed = TickerService::parse_exchange_stats("mexc", &json, true).unwrap();
+        assert!((computed.change_pct.unwrap() - 15.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_change_pct_spans_the_requested_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        let opened = now - Duration::days(5);
+        write_day(
+            dir.path(),
+            "kaspa",
+            "mexc",
+            opened.date_naive(),
+            vec![trade(opened.timestamp(), 0.080, 10.0)],
+        );
+        let mut close = trade(now.timestamp(), 0.100, 30.0);
+        close["percentage"] = serde_json::json!(1.2);
+        let today = vec![trade(now.timestamp() - 60, 0.090, 20.0), close];
+        write_day(dir.path(), "kaspa", "mexc", now.date_naive(), today);
+        let service = |from_history| {
+            TickerService::new(
+                Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+                Arc::new(crate::infrastructure::InMemoryCache::new()),
+                offline_service().default_repo,
+            )
+            .with_change_from_history(from_history)
+        };
+        let change = |service: TickerService, range: &'static str| async move {
+            let stats = service
+                .get_ticker_stats("kaspa".to_string(), range.to_string())
+                .await
+                .unwrap();
+            stats.exchanges[0].change_pct.unwrap()
+        };
+
+        // From the range's first point five days ago, not today's first
+        assert!((change(service(true), "7d").await - 25.0).abs() < 1e-9);
+        assert!((change(service(true), "today").await - 100.0 / 9.0).abs() < 1e-9);
+        assert_eq!(change(service(false), "7d").await, 1.2);
+    }
+
+    #[test]
+    fn test_change_pct_falls_back_between_sources() {
+        // No `percentage` field: the computed change is used either way
+        let falling = serde_json::json!({ "data": [trade(0, 2.0, 1.0), trade(60, 1.5, 2.0)] });
+        for from_history in [false, true] {
+            let stats =
+                TickerService::parse_exchange_stats("mexc", &falling, from_history).unwrap();
+            assert_eq!(stats.change_pct, Some(-25.0));
+        }
+
+        // A single point can't give a change, so the raw field is kept
+        let mut only = trade(0, 2.0, 1.0);
+        only["percentage"] = serde_json::json!(3.0);
+        let single = serde_json::json!({ "data": [only] });
+        let stats = TickerService::parse_exchange_stats("mexc", &single, true).unwrap();
+        assert_eq!(stats.change_pct, Some(3.0));
+
+        let zero_open = serde_json::json!({ "data": [trade(0, 0.0, 1.0), trade(60, 1.0, 2.0)] });
+        let stats = TickerService::parse_exchange_stats("mexc", &zero_open, true).unwrap();
+        assert_eq!(stats.change_pct, None);
+    }
+
+    #[tokio::test]
+    async fn test_history_reads_each_local_day_file_once() {
+        use crate::infrastructure::{InMemoryCache, LocalFileRepository};
+
+        // Local files are read through file:// URLs; the date loop used to
+        // stay on the first day it read that way
+        let dir = tempfile::tempdir().unwrap();
+        let today = Utc::now().date_naive();
+        for days_ago in [1, 2] {
+            let day = today - Duration::days(days_ago);
+            let day_dir = dir.path().join(format!("kaspa/mexc/{}", day.format("%Y/%m")));
+            std::fs::create_dir_all(&day_dir).unwrap();
+            let noon = day.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp_millis();
+            let trade = serde_json::json!({ "timestamp": noon, "last": 1.0, "quoteVolume": 10.0 });
+            std::fs::write(
+                day_dir.join(format!("{}-raw.json", day.format("%Y-%m-%d"))),
+                serde_json::json!({ "data": [trade] }).to_string(),
+            )
+            .unwrap();
+        }
+        let service = TickerService::new(
+            Arc::new(LocalFileRepository::new(dir.path())),
+            Arc::new(InMemoryCache::new()),
+            offline_service().default_repo,
+        );
+
+        let history = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            service.get_ticker_history("kaspa".to_string(), "7d".to_string(), "1d".to_string()),
+        )
+        .await
+        .expect("history never finished")
+        .unwrap();
+        assert_eq!(history.data.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ticker_case_variants_share_cache_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("kaspa/mexc")).unwrap();
+        let cache = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            cache.clone(),
+            offline_service().default_repo,
+        );
+
+        let upper = service
+            .get_ticker_stats("KASPA".to_string(), "today".to_string())
+            .await
+            .unwrap();
+        assert_eq!(upper.token, "KASPA");
+        assert_eq!(cache.ttl_of("v1:ticker:KASPA:stats:today"), Some(300));
+
+        // With the data gone, only the shared cache entry can answer
+        std::fs::remove_dir_all(dir.path().join("kaspa")).unwrap();
+        let lower = service
+            .get_ticker_stats("kaspa".to_string(), "today".to_string())
+            .await
+            .unwrap();
+        assert_eq!(lower.timestamp, upper.timestamp);
+        assert_eq!(lower.token, "kaspa");
+
+        let batch = service
+            .get_ticker_stats_batch(
+                vec!["Kaspa".to_string(), "KASPA".to_string()],
+                "today".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(batch.keys().collect::<Vec<_>>(), ["KASPA", "Kaspa"]);
+        assert_eq!(batch["Kaspa"].stats.as_ref().unwrap().token, "Kaspa");
+    }
+
+    /// Token directories per repo name; records which repos were listed
+    struct RepoStub {
+        tokens: HashMap<&'static str, Vec<&'static str>>,
+        listed: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ContentRepository for RepoStub {
+        async fn get_content(&self, _config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+            anyhow::bail!("Not found: {}", path)
+        }
+
+        async fn list_directory(
+            &self,
+            config: &RepoConfig,
+            path: &str,
+        ) -> anyhow::Result<Vec<Content>> {
+            self.listed.lock().unwrap().push(config.repo.clone());
+            let token = path.trim_start_matches("data/");
+            if !self
+                .tokens
+                .get(config.repo.as_str())
+                .is_some_and(|t| t.contains(&token))
+            {
+                anyhow::bail!("Directory not found: {}", path);
+            }
+            Ok(vec![Content {
+                name: "mexc".to_string(),
+                path: format!("{}/mexc", path),
+                item_type: ContentType::Dir,
+                sha: None,
+                content: None,
+                encoding: None,
+                html_url: None,
+                download_url: None,
+                url: String::new(),
+            }])
+        }
+
+        async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+            anyhow::bail!("Not found: {}", url)
+        }
+    }
+
+    fn repo(name: &str) -> RepoConfig {
+        RepoConfig {
+            source: "github".to_string(),
+            owner: "KaspaDev".to_string(),
+            repo: name.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_missing_from_default_repo_is_found_in_fallback() {
+        let stub = Arc::new(RepoStub {
+            tokens: HashMap::from([("cex-data", vec!["kaspa"]), ("dex-data", vec!["nacho"])]),
+            listed: Default::default(),
+        });
+        let service = TickerService::new(
+            stub.clone(),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+        )
+        .with_allowed_repos([repo("cex-data"), repo("dex-data")]);
+
+        let location = service.locate_token("nacho").await.unwrap();
+        assert_eq!(location.config, repo("dex-data"));
+        assert_eq!(location.exchange_dirs[0].name, "mexc");
+        let stats = service
+            .get_ticker_stats("NACHO".to_string(), "today".to_string())
+            .await
+            .unwrap();
+        assert_eq!(stats.token, "NACHO");
+
+        // The default repo still wins when it has the token
+        assert_eq!(
+            service.locate_token("kaspa").await.unwrap().config,
+            repo("cex-data")
+        );
+
+        // Only whitelisted repos are listed, and a token in none of them fails
+        let err = service.locate_token("other").await.err().unwrap();
+        assert!(err.to_string().contains("Directory not found"), "{}", err);
+        assert!(stub
+            .listed
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|r| r == "cex-data" || r == "dex-data"));
+    }
+
+    #[tokio::test]
+    async fn test_without_fallback_repos_only_default_is_listed() {
+        let stub = Arc::new(RepoStub {
+            tokens: HashMap::from([("dex-data", vec!["nacho"])]),
+            listed: Default::default(),
+        });
+        let service = TickerService::new(
+            stub.clone(),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+        )
+        .with_allowed_repos([repo("cex-data")]);
+
+        assert!(service.locate_token("nacho").await.is_err());
+        assert_eq!(*stub.listed.lock().unwrap(), ["cex-data"]);
+    }
+
+    #[tokio::test]
+    async fn test_traversal_names_are_rejected_before_any_lookup() {
+        let stub = Arc::new(RepoStub {
+            tokens: HashMap::from([("cex-data", vec!["kaspa"])]),
+            listed: Default::default(),
+        });
+        let service = TickerService::new(
+            stub.clone(),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+        );
+        let is_validation =
+            |err: anyhow::Error| matches!(ApiError::find(&err), Some(ApiError::Validation(_)));
+
+        for token in ["../../secret", "kaspa/../../etc", "..", "kas\u{7f}pa"] {
+            let stats = service
+                .get_ticker_stats(token.to_string(), "today".to_string())
+                .await;
+            assert!(is_validation(stats.err().unwrap()), "{:?}", token);
+            let history = service
+                .get_ticker_history(token.to_string(), "today".to_string(), "1h".to_string())
+                .await;
+            assert!(is_validation(history.err().unwrap()), "{:?}", token);
+        }
+        let detail = service
+            .get_exchange_detail("../mexc".to_string(), "today".to_string())
+            .await;
+        assert!(is_validation(detail.err().unwrap()));
+        assert!(stub.listed.lock().unwrap().is_empty());
+
+        // Ordinary names still reach the repository
+        assert!(service
+            .get_ticker_stats("KASPA".to_string(), "today".to_string())
+            .await
+            .is_ok());
+        assert_eq!(*stub.listed.lock().unwrap(), ["cex-data"]);
+    }
+
+    /// Lists `tokens` token directories, each holding one exchange, and
+    /// tracks how many token listings are in flight at once
+    struct ConcurrencyProbe {
+        tokens: usize,
+        listings: std::sync::atomic::AtomicUsize,
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    fn dir(name: String) -> Content {
+        Content {
+            path: format!("data/{}", name),
+            name,
+            item_type: ContentType::Dir,
+            sha: None,
+            content: None,
+            encoding: None,
+            html_url: None,
+            download_url: None,
+            url: String::new(),
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ContentRepository for ConcurrencyProbe {
+        async fn get_content(&self, _config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+            anyhow::bail!("Not found: {}", path)
+        }
+
+        async fn list_directory(
+            &self,
+            _config: &RepoConfig,
+            path: &str,
+        ) -> anyhow::Result<Vec<Content>> {
+            use std::sync::atomic::Ordering;
+            if path == "data" {
+                return Ok((0..self.tokens)
+                    .map(|i| dir(format!("token{}", i)))
+                    .collect());
+            }
+            self.listings.fetch_add(1, Ordering::SeqCst);
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(vec![dir("mexc".to_string())])
+        }
+
+        async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+            anyhow::bail!("Not found: {}", url)
+        }
+    }
+
+    /// Daily raw files whose reads overlap, earlier days answering slower
+    struct DailyFiles {
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ContentRepository for DailyFiles {
+        async fn get_content(&self, _config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
+            use std::sync::atomic::Ordering;
+            let file_name = path.rsplit('/').next().unwrap();
+            let date = NaiveDate::parse_from_str(&file_name[..10], "%Y-%m-%d").unwrap();
+            let ts = date.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp();
+
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(
+                5 + (31 - date.day() as u64) * 2,
+            ))
+            .await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let body = serde_json::json!({ "data": [trade(ts, date.day() as f64, 1.0)] });
+            Ok(Content {
+                content: Some(general_purpose::STANDARD.encode(body.to_string())),
+                encoding: Some("base64".to_string()),
+                ..dir(file_name.to_string())
+            })
+        }
+
+        async fn list_directory(
+            &self,
+            _config: &RepoConfig,
+            path: &str,
+        ) -> anyhow::Result<Vec<Content>> {
+            anyhow::bail!("Not found: {}", path)
+        }
+
+        async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+            anyhow::bail!("Not found: {}", url)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_raw_data_days_fetched_concurrently_in_date_order() {
+        let files = Arc::new(DailyFiles {
+            in_flight: Default::default(),
+            max_in_flight: Default::default(),
+        });
+        let start = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+
+        let limited = Arc::new(PermitLimited {
+            inner: files.clone(),
+            permits: Arc::new(Semaphore::new(4)),
+        });
+        let data = TickerService::fetch_exchange_raw_data(
+            limited,
+            repo("cex-data"),
+            "kaspa".to_string(),
+            "mexc".to_string(),
+            start,
+            end,
+        )
+        .await
+        .unwrap();
+
+        let max_in_flight = files
+            .max_in_flight
+            .load(std::sync::atomic::Ordering::SeqCst);
+        assert!(
+            (2..=4).contains(&max_in_flight),
+            "max in flight: {}",
+            max_in_flight
+        );
+        let days: Vec<f64> = data.iter().map(|p| p["last"].as_f64().unwrap()).collect();
+        assert_eq!(days, (1..=30).map(f64::from).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_exchange_discovery_respects_repo_concurrency() {
+        let probe = Arc::new(ConcurrencyProbe {
+            tokens: 24,
+            listings: Default::default(),
+            in_flight: Default::default(),
+            max_in_flight: Default::default(),
+        });
+        let service = TickerService::new(
+            probe.clone(),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+        )
+        .with_repo_concurrency(4);
+
+        let listing = service.get_exchanges().await.unwrap();
+        assert_eq!(listing.exchanges.len(), 1);
+        assert_eq!(listing.exchanges[0].token_count, 24);
+        assert_eq!(listing.exchanges[0].tokens[..2], ["token0", "token1"]);
+        let max = probe
+            .max_in_flight
+            .load(std::sync::atomic::Ordering::SeqCst);
+        assert!((2..=4).contains(&max), "max in flight: {}", max);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_leaderboard_misses_compute_once_within_repo_concurrency() {
+        use std::sync::atomic::Ordering;
+        let probe = Arc::new(ConcurrencyProbe {
+            tokens: 12,
+            listings: Default::default(),
+            in_flight: Default::default(),
+            max_in_flight: Default::default(),
+        });
+        let service = TickerService::new(
+            probe.clone(),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+        )
+        .with_repo_concurrency(3);
+
+        let (first, second) = tokio::join!(
+            service.get_volume_leaderboard("today".to_string(), None),
+            service.get_volume_leaderboard("today".to_string(), None),
+        );
+        assert!(first.is_ok() && second.is_ok());
+
+        // Each token's directory is listed by one computation only, and the
+        // nested exchange reads never push past the shared limit
+        assert_eq!(probe.listings.load(Ordering::SeqCst), 12);
+        let max = probe.max_in_flight.load(Ordering::SeqCst);
+        assert!((1..=3).contains(&max), "max in flight: {}", max);
+    }
+
+    fn local_first(
+        dir: &std::path::Path,
+        stub: Arc<RepoStub>,
+        github_fallback: bool,
+    ) -> TickerService {
+        TickerService::with_local(
+            stub,
+            Some(Arc::new(crate::infrastructure::LocalFileRepository::new(
+                dir,
+            ))),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+            None,
+        )
+        .with_github_fallback(github_fallback)
+    }
+
+    #[tokio::test]
+    async fn test_paths_missing_locally_are_read_from_github() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("kaspa/ascendex")).unwrap();
+        let stub = Arc::new(RepoStub {
+            tokens: HashMap::from([("cex-data", vec!["kaspa", "nacho"])]),
+            listed: Default::default(),
+        });
+        let service = local_first(dir.path(), stub.clone(), true);
+
+        // Local hit: GitHub is never asked
+        let local = service.locate_token("kaspa").await.unwrap();
+        assert_eq!(local.exchange_dirs[0].name, "ascendex");
+        assert!(stub.listed.lock().unwrap().is_empty());
+
+        // Local miss, GitHub hit
+        let remote = service.locate_token("nacho").await.unwrap();
+        assert_eq!(remote.exchange_dirs[0].name, "mexc");
+        assert_eq!(*stub.listed.lock().unwrap(), ["cex-data"]);
+
+        // Missing from both
+        let err = service.locate_token("other").await.err().unwrap();
+        assert!(
+            err.to_string().contains("Directory not found: data/other"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_github_fallback_can_be_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let stub = Arc::new(RepoStub {
+            tokens: HashMap::from([("cex-data", vec!["nacho"])]),
+            listed: Default::default(),
+        });
+        let service = local_first(dir.path(), stub.clone(), false);
+
+        let err = service.locate_token("nacho").await.err().unwrap();
+        assert!(
+            matches!(ApiError::find(&err), Some(ApiError::NotFound(_))),
+            "{}",
+            err
+        );
+        assert!(stub.listed.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_github_misses_are_remembered() {
+        struct MissingRemote(std::sync::atomic::AtomicUsize);
+
+        #[async_trait::async_trait]
+        impl ContentRepository for MissingRemote {
+            async fn get_content(
+                &self,
+                _config: &RepoConfig,
+                path: &str,
+            ) -> anyhow::Result<Content> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Err(ApiError::NotFound(format!("Not found: {}", path)).into())
+            }
+
+            async fn list_directory(
+                &self,
+                _config: &RepoConfig,
+                path: &str,
+            ) -> anyhow::Result<Vec<Content>> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Err(ApiError::NotFound(format!("Directory not found: {}", path)).into())
+            }
+
+            async fn get_raw_file(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+                anyhow::bail!("Not found: {}", url)
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let remote = Arc::new(MissingRemote(Default::default()));
+        let service = TickerService::with_local(
+            remote.clone(),
+            Some(Arc::new(crate::infrastructure::LocalFileRepository::new(
+                dir.path(),
+            ))),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            repo("cex-data"),
+            None,
+        )
+        .with_github_fallback(true);
+
+        for _ in 0..3 {
+            let err = service.locate_token("other").await.err().unwrap();
+            assert!(
+                matches!(ApiError::find(&err), Some(ApiError::NotFound(_))),
+                "{}",
+                err
+            );
+        }
+        assert_eq!(remote.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    /// Write today's raw file for `token` on `exchange` with one trade
+    fn write_day(
+        dir: &std::path::Path,
+        token: &str,
+        exchange: &str,
+        date: NaiveDate,
+        data: Vec<serde_json::Value>,
+    ) {
+        let month_dir = dir
+            .join(token)
+            .join(exchange)
+            .join(date.format("%Y").to_string())
+            .join(date.format("%m").to_string());
+        std::fs::create_dir_all(&month_dir).unwrap();
+        let body = serde_json::json!({ "data": data });
+        std::fs::write(
+            month_dir.join(format!("{}-raw.json", date.format("%Y-%m-%d"))),
+            body.to_string(),
+        )
+        .unwrap();
+    }
+
+    fn write_today(dir: &std::path::Path, token: &str, exchange: &str, last: f64) {
+        let today = Utc::now().date_naive();
+        write_day(
+            dir,
+            token,
+            exchange,
+            today,
+            vec![trade(Utc::now().timestamp(), last, 100.0)],
+        );
+    }
+
+    async fn search_service(tokens: &[&str]) -> (tempfile::TempDir, TickerService) {
+        let dir = tempfile::tempdir().unwrap();
+        for token in tokens {
+            std::fs::create_dir_all(dir.path().join(token).join("mexc")).unwrap();
+        }
+        let index = Arc::new(ExchangeIndex::new(dir.path()));
+        index.rebuild().await.unwrap();
+        let service = TickerService::with_local(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            None,
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            offline_service().default_repo,
+            Some(index),
+        );
+        (dir, service)
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_prefix_matches_first() {
+        let (_dir, service) =
+            search_service(&["akaspa", "bkas", "kasper", "kaspa", "nacho", "slow"]).await;
+
+        let found = service.search_tickers("kas", None).await.unwrap();
+        assert_eq!(found.tickers, ["kaspa", "kasper", "bkas", "akaspa"]);
+        assert_eq!(found.count, 4);
+
+        let first = service.search_tickers("kas", Some(1)).await.unwrap();
+        assert_eq!(first.tickers, ["kaspa"]);
+
+        let capped = service
+            .with_max_search_results(2)
+            .search_tickers("kas", Some(50))
+            .await
+            .unwrap();
+        assert_eq!(capped.tickers, ["kaspa", "kasper"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_ignores_case() {
+        let (_dir, service) = search_service(&["NACHO", "kaspa", "Ghoad"]).await;
+
+        let found = service.search_tickers("NaC", None).await.unwrap();
+        assert_eq!(found.tickers, ["NACHO"]);
+        assert_eq!(found.query, "NaC");
+        assert_eq!(
+            service
+                .search_tickers(" KASPA ", None)
+                .await
+                .unwrap()
+                .tickers,
+            ["kaspa"]
+        );
+        assert_eq!(
+            service.search_tickers("HOA", None).await.unwrap().tickers,
+            ["Ghoad"]
+        );
+        assert!(service
+            .search_tickers("xyz", None)
+            .await
+            .unwrap()
+            .tickers
+            .is_empty());
+
+        let err = service.search_tickers("  ", None).await.unwrap_err();
+        assert!(matches!(
+            ApiError::find(&err),
+            Some(ApiError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_ranks_by_total_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = Utc::now().date_naive();
+        let now = Utc::now().timestamp();
+        // kaspa: 100 + 400 across two exchanges; nacho: 900 on one; slow: 50
+        write_day(
+            dir.path(),
+            "kaspa",
+            "mexc",
+            today,
+            vec![trade(now, 0.1, 100.0)],
+        );
+        write_day(
+            dir.path(),
+            "kaspa",
+            "gate",
+            today,
+            vec![trade(now, 0.1, 400.0)],
+        );
+        write_day(
+            dir.path(),
+            "nacho",
+            "mexc",
+            today,
+            vec![trade(now, 0.5, 900.0)],
+        );
+        write_day(
+            dir.path(),
+            "slow",
+            "mexc",
+            today,
+            vec![trade(now, 2.0, 50.0)],
+        );
+        std::fs::create_dir_all(dir.path().join("ghost").join("mexc")).unwrap();
+        let index = Arc::new(ExchangeIndex::new(dir.path()));
+        index.rebuild().await.unwrap();
+        let service = TickerService::with_local(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            None,
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            offline_service().default_repo,
+            Some(index),
+        );
+
+        let board = service
+            .get_volume_leaderboard("today".to_string(), None)
+            .await
+            .unwrap();
+        let ranking: Vec<(usize, &str, f64)> = board
+            .entries
+            .iter()
+            .map(|e| (e.rank, e.token.as_str(), e.total_volume_24h))
+            .collect();
+        assert_eq!(
+            ranking,
+            [(1, "nacho", 900.0), (2, "kaspa", 500.0), (3, "slow", 50.0)]
+        );
+        assert_eq!(board.entries[1].exchange_count, 2);
+        assert_eq!(board.tokens_scanned, 4);
+
+        // Later limits are served from the cached ranking
+        std::fs::remove_dir_all(dir.path().join("nacho")).unwrap();
+        let top = service
+            .get_volume_leaderboard("today".to_string(), Some(1))
+            .await
+            .unwrap();
+        assert_eq!(top.entries.len(), 1);
+        assert_eq!(top.entries[0].token, "nacho");
+
+        let err = service
+            .get_volume_leaderboard("1y".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<InvalidParameter>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cached_stats_report_remaining_ttl() {
+        use crate::application::cache_service::track_response_max_age;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_today(dir.path(), "kaspa", "mexc", 0.1);
+        let service = TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            offline_service().default_repo,
+        );
+        let stats = || service.get_ticker_stats("kaspa".to_string(), "today".to_string());
+
+        let (miss, max_age) = track_response_max_age(stats()).await;
+        assert!(miss.is_ok());
+        assert_eq!(max_age, Some(300));
+
+        let (hit, max_age) = track_response_max_age(stats()).await;
+        assert!(hit.is_ok());
+        assert!(
+            max_age.is_some_and(|secs| (298..=300).contains(&secs)),
+            "{:?}",
+            max_age
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_found_past_sync_gap() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = Utc::now().date_naive();
+        write_day(
+            dir.path(),
+            "kaspa",
+            "mexc",
+            today - Duration::days(4),
+            vec![trade(0, 0.12, 50.0)],
+        );
+        // An empty file for today doesn't stop the search
+        write_day(dir.path(), "kaspa", "mexc", today, vec![]);
+        let service = |lookback_days| {
+            TickerService::new(
+                Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+                Arc::new(crate::infrastructure::InMemoryCache::new()),
+                offline_service().default_repo,
+            )
+            .with_stats_lookback_days(lookback_days)
+        };
+        let last_price = |stats: TickerStatsResponse| stats.exchanges[0].last;
+
+        let default = service(DEFAULT_STATS_LOOKBACK_DAYS);
+        let stats = default
+            .get_ticker_stats("kaspa".to_string(), "today".to_string())
+            .await
+            .unwrap();
+        assert_eq!(last_price(stats), None);
+        // A week's range searches the whole week
+        let stats = default
+            .get_ticker_stats("kaspa".to_string(), "7d".to_string())
+            .await
+            .unwrap();
+        assert_eq!(last_price(stats), Some(0.12));
+
+        let deeper = service(4);
+        let stats = deeper
+            .get_ticker_stats("kaspa".to_string(), "today".to_string())
+            .await
+            .unwrap();
+        assert_eq!(last_price(stats), Some(0.12));
+
+        assert_eq!(service(1000).stats_lookback_days, MAX_STATS_LOOKBACK_DAYS);
+    }
+
+    #[tokio::test]
+    async fn test_latest_price_prefers_reliable_exchange() {
+        let dir = tempfile::tempdir().unwrap();
+        write_today(dir.path(), "kaspa", "ascendex", 0.5);
+        write_today(dir.path(), "kaspa", "mexc", 0.1);
+        let cache = Arc::new(crate::infrastructure::InMemoryCache::new());
+        let service = TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            cache.clone(),
+            offline_service().default_repo,
+        )
+        .with_exchange_meta(HashMap::from([(
+            "ascendex".to_string(),
+            ExchangeMeta {
+                reliable: false,
+                ..ExchangeMeta::default()
+            },
+        )]));
+
+        let latest = service.get_latest_price("KASPA".to_string()).await.unwrap();
+        assert_eq!(latest.source, "mexc");
+        assert_eq!(latest.price, Some(0.1));
+        assert_eq!(
+            cache.ttl_of("v1:ticker:KASPA:latest"),
+            Some(LATEST_PRICE_TTL_SECS)
+        );
+
+        assert!(service
+            .get_latest_price("missing".to_string())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_latest_price_is_stamped_with_its_last_trade() {
+        let dir = tempfile::tempdir().unwrap();
+        let traded_at = Utc::now() - Duration::days(3);
+        let day = traded_at.date_naive();
+        write_day(
+            dir.path(),
+            "kaspa",
+            "mexc",
+            day,
+            vec![trade(traded_at.timestamp(), 0.1, 100.0)],
+        );
+        let service = TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            offline_service().default_repo,
+        )
+        .with_stats_lookback_days(4);
+
+        let latest = service.get_latest_price("kaspa".to_string()).await.unwrap();
+        assert_eq!(latest.price, Some(0.1));
+        let stamped = DateTime::parse_from_rfc3339(&latest.timestamp).unwrap();
+        assert_eq!(stamped.timestamp(), traded_at.timestamp());
+    }
+
+    #[tokio::test]
+    async fn test_latest_price_reuses_cached_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        write_today(dir.path(), "kaspa", "mexc", 0.1);
+        write_today(dir.path(), "kaspa", "ascendex", 0.2);
+        let service = TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            offline_service().default_repo,
+        );
+
+        let stats = service
+            .get_ticker_stats("kaspa".to_string(), "today".to_string())
+            .await
+            .unwrap();
+        let latest = service.get_latest_price("kaspa".to_string()).await.unwrap();
+        assert_eq!(latest.source, "aggregate");
+        assert_eq!(latest.price, stats.aggregate.avg_price);
+        assert_eq!(latest.timestamp, stats.timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_pairs_flatten_exchanges_and_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["kaspa/mexc", "kaspa/ascendex", "slow/mexc"] {
+            std::fs::create_dir_all(dir.path().join(path)).unwrap();
+        }
+        let service = TickerService::new(
+            Arc::new(crate::infrastructure::LocalFileRepository::new(dir.path())),
+            Arc::new(crate::infrastructure::InMemoryCache::new()),
+            offline_service().default_repo,
+        );
+        let pair = |token: &str, exchange: &str| TickerPair {
+            token: token.to_string(),
+            exchange: exchange.to_string(),
+        };
+
+        assert_eq!(
+            service.get_pairs(None).await.unwrap(),
+            [
+                pair("kaspa", "ascendex"),
+                pair("kaspa", "mexc"),
+                pair("slow", "mexc")
+            ]
+        );
+        assert_eq!(
+            service.get_pairs(Some(" MEXC ")).await.unwrap(),
+            [pair("kaspa", "mexc"), pair("slow", "mexc")]
+        );
+        assert!(service.get_pairs(Some("binance")).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+//! Error classification shared by services and the API layer.
+//!
+//! Services return `anyhow::Result`, but failures that callers need to tell
+//! apart are raised as an [`ApiError`] inside the `anyhow::Error` (possibly
+//! wrapped in context). The API layer recovers it with [`ApiError::find`]
+//! and maps each kind to a fixed status code and machine-readable `code`,
+//! so handlers never inspect error messages.
+
+use std::fmt;
+
+/// A classified failure with a stable machine-readable code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The requested resource doesn't exist (here or upstream)
+    NotFound(String),
+    /// A rate limit was hit; retry after the given number of seconds
+    RateLimited {
+        message: String,
+        retry_after_secs: u64,
+    },
+    /// The upstream API failed or returned something unusable
+    Upstream(String),
+    /// The request itself is invalid
+    Validation(String),
+    /// Anything else
+    Internal(String),
+}
+
+impl ApiError {
+    /// Stable code clients can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::RateLimited { .. } => "RATE_LIMITED",
+            Self::Upstream(_) => "UPSTREAM_ERROR",
+            Self::Validation(_) => "VALIDATION_ERROR",
+            Self::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Human-readable description
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(m)
+            | Self::RateLimited { message: m, .. }
+            | Self::Upstream(m)
+            | Self::Validation(m)
+            | Self::Internal(m) => m,
+        }
+    }
+
+    /// Classify a non-success upstream HTTP status
+    pub fn from_upstream_status(status: u16, message: String) -> Self {
+        match status {
+            404 => Self::NotFound(message),
+            _ => Self::Upstream(message),
+        }
+    }
+
+    /// The first `ApiError` in an error's chain, if any
+    pub fn find(err: &anyhow::Error) -> Option<&ApiError> {
+        err.chain()
+            .find_map(|cause| cause.downcast_ref::<ApiError>())
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_find_through_context() {
+        let err: anyhow::Result<()> =
+            Err(ApiError::from_upstream_status(404, "no such token".into()).into());
+        let err = err.context("Failed to fetch token info").unwrap_err();
+
+        let found = ApiError::find(&err).unwrap();
+        assert_eq!(found.code(), "NOT_FOUND");
+        assert_eq!(found.message(), "no such token");
+
+        assert_eq!(ApiError::find(&anyhow::anyhow!("plain")), None);
+        assert_eq!(
+            ApiError::from_upstream_status(502, String::new()).code(),
+            "UPSTREAM_ERROR"
+        );
+    }
+}
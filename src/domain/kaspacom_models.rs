@@ -4,8 +4,9 @@
 //! and are designed to be compatible with both JSON serialization and Parquet storage.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
 
 // ============================================================================
 // KRC20 Token Models
@@ -163,6 +164,16 @@ pub struct TokenLogo {
     pub logo: String,
 }
 
+/// Logo image bytes proxied from the URL in [`TokenLogo::logo`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoImage {
+    /// `Content-Type` reported by the upstream host (always `image/*`)
+    pub content_type: String,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
 /// Open orders response from `/api/open-orders`
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OpenOrdersResponse {
@@ -247,24 +258,67 @@ pub struct NftCollectionStats {
     pub total_volume_usd: String,
 }
 
+/// Largest page size forwarded for an [`NftTokenFilter`]; larger `limit`s are lowered to it
+pub const MAX_NFT_TOKENS_LIMIT: i32 = 100;
+
 /// NFT token filter for POST `/api/krc721/tokens`
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+///
+/// Fields the gateway doesn't know are kept in `extra` and forwarded to the
+/// upstream unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_price_range"))]
 pub struct NftTokenFilter {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 50))]
     pub ticker: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 100))]
     pub owner: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub is_listed: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0.0))]
     pub min_price: Option<f64>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0.0))]
     pub max_price: Option<f64>,
-    #[serde(default)]
+    /// Page number, starting at 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
-    #[serde(default)]
+    /// Tokens per page, lowered to [`MAX_NFT_TOKENS_LIMIT`] if above it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
     pub limit: Option<i32>,
+    /// Other upstream filter fields, passed through as-is
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NftTokenFilter {
+    /// The requested `limit` capped at [`MAX_NFT_TOKENS_LIMIT`], and whether it was lowered
+    ///
+    /// No limit is made up when none was requested, so the upstream keeps
+    /// its own default page size.
+    pub fn capped_limit(&self) -> (Option<i32>, bool) {
+        match self.limit {
+            Some(limit) if limit > MAX_NFT_TOKENS_LIMIT => (Some(MAX_NFT_TOKENS_LIMIT), true),
+            limit => (limit, false),
+        }
+    }
+}
+
+fn validate_price_range(filter: &NftTokenFilter) -> Result<(), ValidationError> {
+    match (filter.min_price, filter.max_price) {
+        (Some(min), Some(max)) if min > max => {
+            let mut error = ValidationError::new("price_range");
+            error.message = Some("minPrice must not exceed maxPrice".into());
+            Err(error)
+        }
+        _ => Ok(()),
+    }
 }
 
 /// NFT tokens response from POST `/api/krc721/tokens`
@@ -273,6 +327,32 @@ pub struct NftTokenFilter {
 pub struct NftTokensResponse {
     pub items: Vec<NftToken>,
     pub total_count: i64,
+    /// Page returned, starting at 1
+    #[serde(default)]
+    pub page: i32,
+    /// Page size forwarded upstream; absent when the request set none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    /// Pages available at `limit`; absent when the request set no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<i64>,
+    /// Whether the requested `limit` was above the maximum and lowered to it
+    #[serde(default)]
+    pub limit_capped: bool,
+}
+
+impl NftTokensResponse {
+    /// Attach the effective paging and derive `total_pages` from `limit`
+    pub fn with_paging(mut self, page: i32, limit: Option<i32>, limit_capped: bool) -> Self {
+        self.page = page;
+        self.limit = limit;
+        self.total_pages = limit.map(|limit| {
+            let limit = i64::from(limit.max(1));
+            (self.total_count.max(0) + limit - 1) / limit
+        });
+        self.limit_capped = limit_capped;
+        self
+    }
 }
 
 /// Individual NFT token
@@ -308,6 +388,60 @@ pub struct NftTrait {
     pub rarity: f64,
 }
 
+/// Largest collection the gateway scans in full (rarity ranking, trait
+/// distribution)
+pub const MAX_COLLECTION_SCAN_TOKENS: i64 = 10_000;
+
+/// One token's place in its collection's rarity ranking
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NftRarityEntry {
+    pub token_id: i64,
+    /// 1 is the rarest; tokens with equal scores share a rank
+    pub rarity_rank: i32,
+    /// Trait type to value
+    pub traits: BTreeMap<String, String>,
+}
+
+/// Rarity-ranked tokens of a KRC721 collection
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NftRarityResponse {
+    pub ticker: String,
+    /// Tokens ranked, rarest first
+    pub tokens: Vec<NftRarityEntry>,
+    /// Whether the collection is still minting; ranks may change as
+    /// tokens are added
+    pub minting: bool,
+    /// "upstream" when every token carried a `rarityRank`, otherwise
+    /// "computed" from the traits' rarity
+    pub rank_source: String,
+}
+
+/// How many tokens of a collection have one trait value
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NftTraitCount {
+    pub value: String,
+    pub count: u64,
+    /// Share of the collection's tokens with this value (0-100)
+    pub percentage: f64,
+}
+
+/// Trait value counts across a KRC721 collection
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NftTraitDistribution {
+    pub ticker: String,
+    /// Tokens scanned; percentages are relative to this
+    pub total_tokens: u64,
+    /// Whether the collection is still minting; counts may change as
+    /// tokens are added
+    pub minting: bool,
+    /// Trait type to its values, most common first
+    pub traits: BTreeMap<String, Vec<NftTraitCount>>,
+}
+
 // ============================================================================
 // KNS Domain Models
 // ============================================================================
@@ -335,6 +469,22 @@ pub struct KnsListedOrdersResponse {
     pub orders: Vec<KnsOrder>,
 }
 
+/// Marketplace status of one KNS domain
+///
+/// Derived from the listed orders and recent sales; `listing` is the open
+/// order if the domain is for sale and `lastSale` the most recent sale in
+/// the lookback window.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KnsDomainStatus {
+    /// Normalized domain, e.g. `mywallet.kas`
+    pub domain: String,
+    /// `listed` if there is an open order, otherwise `sold`
+    pub status: String,
+    pub listing: Option<KnsOrder>,
+    pub last_sale: Option<KnsOrder>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct KnsTradeStatsResponse {
@@ -371,6 +521,147 @@ where
     }
 }
 
+// ============================================================================
+// Pagination
+// ============================================================================
+
+/// Largest page of sold orders a single request may ask for
+pub const MAX_ORDERS_PAGE_LIMIT: usize = 1000;
+
+/// A page of results ordered newest-first
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor to pass as `after` to fetch the next page
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    /// Newest `createdAt` across all pages; pass it as `since` on the next
+    /// poll to only receive newer orders
+    pub latest_timestamp: Option<i64>,
+}
+
+/// Orders that can be paged by recency
+pub trait Paginated {
+    /// Creation timestamp used as the primary sort key
+    fn created_at(&self) -> i64;
+    /// Unique ID used to break ties between equal timestamps
+    fn order_id(&self) -> &str;
+}
+
+impl Paginated for SoldOrder {
+    fn created_at(&self) -> i64 {
+        self.created_at
+    }
+    fn order_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Paginated for NftOrder {
+    fn created_at(&self) -> i64 {
+        self.created_at
+    }
+    fn order_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Keep only items created strictly after `since`; `None` keeps everything
+pub fn created_since<T: Paginated>(mut items: Vec<T>, since: Option<i64>) -> Vec<T> {
+    if let Some(since) = since {
+        items.retain(|item| item.created_at() > since);
+    }
+    items
+}
+
+/// Opaque pagination cursor identifying the last item of a page
+///
+/// Encodes `created_at` and the order ID, so a cursor keeps pointing at the
+/// same position even when newer orders arrive between requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCursor {
+    pub created_at: i64,
+    pub id: String,
+}
+
+impl OrderCursor {
+    /// Encode as a URL-safe opaque string
+    pub fn encode(&self) -> String {
+        use base64::{engine::general_purpose, Engine as _};
+        general_purpose::URL_SAFE_NO_PAD.encode(format!("{}:{}", self.created_at, self.id))
+    }
+
+    /// Decode a cursor produced by [`OrderCursor::encode`]
+    pub fn decode(cursor: &str) -> anyhow::Result<Self> {
+        use base64::{engine::general_purpose, Engine as _};
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| anyhow::anyhow!("Invalid cursor"))?;
+        let raw = String::from_utf8(bytes).map_err(|_| anyhow::anyhow!("Invalid cursor"))?;
+        let (created_at, id) = raw
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Invalid cursor"))?;
+        Ok(Self {
+            created_at: created_at.parse().map_err(|_| anyhow::anyhow!("Invalid cursor"))?,
+            id: id.to_string(),
+        })
+    }
+
+    fn of<T: Paginated>(item: &T) -> Self {
+        Self {
+            created_at: item.created_at(),
+            id: item.order_id().to_string(),
+        }
+    }
+
+    /// Whether an item sorts strictly after this cursor (newest-first order)
+    fn precedes<T: Paginated>(&self, item: &T) -> bool {
+        (item.created_at(), item.order_id()) < (self.created_at, self.id.as_str())
+    }
+}
+
+impl<T: Paginated> Page<T> {
+    /// Sort items newest-first (ties broken by ID) and take the page after `after`
+    ///
+    /// With no `limit`, all remaining items are returned in one page.
+    pub fn paginate(mut items: Vec<T>, limit: Option<usize>, after: Option<&OrderCursor>) -> Self {
+        items.sort_by(|a, b| {
+            (b.created_at(), b.order_id()).cmp(&(a.created_at(), a.order_id()))
+        });
+        let latest_timestamp = items.first().map(|item| item.created_at());
+
+        if let Some(cursor) = after {
+            items.retain(|item| cursor.precedes(item));
+        }
+
+        let has_more = limit.is_some_and(|limit| items.len() > limit);
+        if let Some(limit) = limit {
+            items.truncate(limit);
+        }
+
+        let next_cursor = if has_more {
+            items.last().map(|item| OrderCursor::of(item).encode())
+        } else {
+            None
+        };
+
+        Self {
+            items,
+            next_cursor,
+            has_more,
+            latest_timestamp,
+        }
+    }
+
+    /// Report at least `since` as `latest_timestamp`, so a poll that found
+    /// no newer orders hands the client's own `since` back
+    pub fn polled_since(mut self, since: Option<i64>) -> Self {
+        self.latest_timestamp = self.latest_timestamp.max(since);
+        self
+    }
+}
+
 // ============================================================================
 // Token Configuration
 // ============================================================================
@@ -389,10 +680,29 @@ pub struct TokenExchanges {
 
 impl TokensConfig {
     /// Load configuration from a JSON file
+    ///
+    /// Parsing is tolerant at the token level: malformed token entries are
+    /// logged and skipped so one bad entry doesn't drop the whole list. The
+    /// file itself must still be valid JSON with a top-level `tokens` object.
     pub fn load(path: &str) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let config: Self = serde_json::from_str(&content)?;
-        Ok(config)
+        let root: serde_json::Value = serde_json::from_str(&content)?;
+        let entries = root
+            .get("tokens")
+            .and_then(|t| t.as_object())
+            .ok_or_else(|| anyhow::anyhow!("missing or invalid \"tokens\" object"))?;
+
+        let mut tokens = HashMap::with_capacity(entries.len());
+        for (name, entry) in entries {
+            match serde_json::from_value::<TokenExchanges>(entry.clone()) {
+                Ok(exchanges) => {
+                    tokens.insert(name.clone(), exchanges);
+                }
+                Err(e) => tracing::warn!("Skipping malformed token entry '{}' in {}: {}", name, path, e),
+            }
+        }
+
+        Ok(Self { tokens })
     }
 
     /// Get all token names (original case from config)
@@ -402,7 +712,7 @@ impl TokensConfig {
 
     /// Get the uppercase ticker for API calls
     pub fn get_ticker(token: &str) -> String {
-        token.to_uppercase()
+        super::normalize_ticker(token)
     }
 
     /// Get exchanges for a token (case-insensitive lookup)
@@ -467,7 +777,7 @@ impl CacheMetadata {
 /// NFT metadata from krc721.stream `/krc721/mainnet/metadata/{ticker}/{tokenId}`
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct NftMetadata {
-    /// IPFS image URL (e.g., "ipfs://bafybei...")
+    /// Image URL; upstream `ipfs://` URIs are resolved to the configured gateway
     pub image: String,
     /// NFT name (e.g., "Bitcoin the Turtle #173")
     pub name: String,
@@ -496,7 +806,7 @@ pub struct Krc721CollectionInfo {
     pub total_holders: i64,
     #[serde(default)]
     pub price: f64,
-    /// Base IPFS URI for the collection
+    /// Base URI for the collection, resolved like [`NftMetadata::image`]
     #[serde(default)]
     pub buri: Option<String>,
     /// Deployer address
@@ -544,3 +854,168 @@ pub struct CollectionHolder {
     pub owner: String,
     pub count: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn order(id: &str, created_at: i64) -> NftOrder {
+        NftOrder {
+            id: id.to_string(),
+            ticker: "KASPUNKS".to_string(),
+            token_id: id.to_string(),
+            price: 1.0,
+            seller_address: "kaspa:seller".to_string(),
+            buyer_address: None,
+            created_at,
+            status: "completed".to_string(),
+            fulfillment_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_since_is_exclusive_and_latest_timestamp_tracks_newest() {
+        let orders = vec![order("a", 100), order("b", 300), order("c", 200), order("d", 200)];
+
+        let page = Page::paginate(created_since(orders.clone(), None), Some(1), None).polled_since(None);
+        assert_eq!(page.latest_timestamp, Some(300));
+
+        let newer = created_since(orders.clone(), Some(200));
+        let page = Page::paginate(newer, None, None).polled_since(Some(200));
+        assert_eq!(page.items.iter().map(|o| o.id.as_str()).collect::<Vec<_>>(), ["b"]);
+        assert_eq!(page.latest_timestamp, Some(300));
+
+        let page = Page::paginate(created_since(orders.clone(), Some(300)), None, None).polled_since(Some(300));
+        assert!(page.items.is_empty());
+        assert_eq!(page.latest_timestamp, Some(300));
+
+        let page = Page::paginate(created_since(orders, Some(199)), None, None).polled_since(Some(199));
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.latest_timestamp, Some(300));
+
+        let empty: Vec<NftOrder> = Vec::new();
+        assert_eq!(Page::paginate(empty, None, None).polled_since(None).latest_timestamp, None);
+    }
+
+    #[test]
+    fn test_page_cursor_walks_without_gaps_or_duplicates() {
+        let orders = vec![
+            order("a", 100),
+            order("b", 300),
+            order("c", 200),
+            order("d", 200),
+            order("e", 50),
+        ];
+
+        let mut seen = Vec::new();
+        let mut after: Option<OrderCursor> = None;
+        loop {
+            let page = Page::paginate(orders.clone(), Some(2), after.as_ref());
+            seen.extend(page.items.iter().map(|o| o.id.clone()));
+            match page.next_cursor {
+                Some(cursor) => after = Some(OrderCursor::decode(&cursor).unwrap()),
+                None => {
+                    assert!(!page.has_more);
+                    break;
+                }
+            }
+        }
+
+        assert_eq!(seen, vec!["b", "d", "c", "a", "e"]);
+        assert!(OrderCursor::decode("not a cursor!").is_err());
+    }
+
+    #[test]
+    fn test_tokens_config_skips_malformed_entries() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"{{
+                "tokens": {{
+                    "Kaspa": {{ "exchanges": ["binance", "mexc"] }},
+                    "Broken": {{ "exchanges": "not-a-list" }},
+                    "Nacho": {{ "exchanges": ["kaspacom"] }},
+                    "SLOW": {{ "exchanges": [] }}
+                }}
+            }}"#
+        )
+        .unwrap();
+
+        let config = TokensConfig::load(file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(config.tokens.len(), 3);
+        assert!(!config.tokens.contains_key("Broken"));
+        assert_eq!(config.get_exchanges("kaspa").unwrap(), &vec!["binance".to_string(), "mexc".to_string()]);
+        assert!(config.tokens.contains_key("Nacho"));
+        assert!(config.tokens.contains_key("SLOW"));
+    }
+
+    #[test]
+    fn test_nft_token_filter_forwards_unknown_fields() {
+        let filter: NftTokenFilter = serde_json::from_value(serde_json::json!({
+            "ticker": "KASPUNKS",
+            "limit": 20,
+            "sortBy": "price",
+            "traits": { "hat": "red" }
+        }))
+        .unwrap();
+        assert!(filter.validate().is_ok());
+
+        // Absent fields stay absent rather than being sent as null
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            serde_json::json!({
+                "ticker": "KASPUNKS",
+                "limit": 20,
+                "sortBy": "price",
+                "traits": { "hat": "red" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_nft_token_filter_rejects_out_of_range_values() {
+        let invalid = |filter: NftTokenFilter| filter.validate().unwrap_err().to_string();
+
+        let error = invalid(NftTokenFilter { limit: Some(0), ..Default::default() });
+        assert!(error.contains("limit"), "{}", error);
+        let error = invalid(NftTokenFilter { page: Some(0), ..Default::default() });
+        assert!(error.contains("page"), "{}", error);
+        let error = invalid(NftTokenFilter { min_price: Some(5.0), max_price: Some(1.0), ..Default::default() });
+        assert!(error.contains("minPrice must not exceed maxPrice"), "{}", error);
+
+        // Oversized pages are capped by the service rather than rejected
+        assert!(NftTokenFilter { page: Some(1), limit: Some(10_000), ..Default::default() }
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_nft_limit_is_capped_but_not_defaulted() {
+        let filter = |limit| NftTokenFilter { limit, ..Default::default() };
+        assert_eq!(filter(None).capped_limit(), (None, false));
+        assert_eq!(filter(Some(50)).capped_limit(), (Some(50), false));
+        assert_eq!(filter(Some(MAX_NFT_TOKENS_LIMIT)).capped_limit(), (Some(MAX_NFT_TOKENS_LIMIT), false));
+        assert_eq!(filter(Some(10_000)).capped_limit(), (Some(MAX_NFT_TOKENS_LIMIT), true));
+    }
+
+    #[test]
+    fn test_nft_total_pages_rounds_up() {
+        let response = |total_count| NftTokensResponse {
+            items: Vec::new(),
+            total_count,
+            page: 0,
+            limit: None,
+            total_pages: None,
+            limit_capped: false,
+        };
+        assert_eq!(response(0).with_paging(1, Some(20), false).total_pages, Some(0));
+        assert_eq!(response(20).with_paging(1, Some(20), false).total_pages, Some(1));
+        assert_eq!(response(21).with_paging(2, Some(20), false).total_pages, Some(2));
+        assert_eq!(response(21).with_paging(1, None, false).total_pages, None);
+
+        let capped = response(1001).with_paging(4, Some(100), true);
+        assert_eq!((capped.page, capped.limit, capped.total_pages, capped.limit_capped), (4, Some(100), Some(11), true));
+    }
+}
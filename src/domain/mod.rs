@@ -7,8 +7,15 @@
 //! - Value objects and types used throughout the application
 //! - Kaspa.com API models for marketplace data
 
+pub mod error;
 pub mod kaspacom_models;
+pub mod ticker;
+pub use error::ApiError;
 pub use kaspacom_models::*;
+pub use ticker::{
+    normalize_exchange, normalize_kns_domain, normalize_ticker, normalize_token,
+    validate_path_segment, KNS_DOMAIN_SUFFIX,
+};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -22,7 +29,7 @@ use std::fmt::Debug;
 /// # Examples
 ///
 /// ```
-/// use gatewayapi::domain::RepoConfig;
+/// use krcbot_kaspacom_gatewayapi::domain::RepoConfig;
 ///
 /// let config = RepoConfig {
 ///     source: "github".to_string(),
@@ -51,6 +58,7 @@ pub struct RepoConfig {
 /// - `name`: The filename or directory name
 /// - `path`: The full path within the repository
 /// - `item_type`: Whether this is a file, directory, or unknown type
+/// - `sha`: Git object SHA, when the source reports one
 /// - `content`: Base64-encoded file content (for files only)
 /// - `encoding`: Content encoding type (typically "base64" for files)
 /// - `html_url`: Browser-viewable URL (optional)
@@ -64,6 +72,9 @@ pub struct Content {
     pub path: String,
     /// Type of content (file, directory, or unknown)
     pub item_type: ContentType,
+    /// Git object SHA (GitHub only); changes whenever the content does
+    #[serde(default)]
+    pub sha: Option<String>,
 
     // File-specific fields
     /// Base64-encoded file content (present for files only)
@@ -105,7 +116,7 @@ impl From<String> for ContentType {
     /// # Examples
     ///
     /// ```
-    /// use gatewayapi::domain::ContentType;
+    /// use krcbot_kaspacom_gatewayapi::domain::ContentType;
     ///
     /// assert_eq!(ContentType::from("file".to_string()), ContentType::File);
     /// assert_eq!(ContentType::from("dir".to_string()), ContentType::Dir);
@@ -237,4 +248,39 @@ pub trait CacheRepository: Send + Sync {
     /// - Returns error if cache connection fails
     /// - Returns error if the value cannot be stored
     async fn set(&self, key: &str, value: &str, ttl_seconds: u64) -> anyhow::Result<()>;
+
+    /// Remove a cached value by key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Cache key to remove
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` whether or not the key existed, or an error if the
+    /// cache operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - Returns error if the delete command fails
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Seconds until a cached value expires.
+    ///
+    /// Returns `Ok(None)` if the key doesn't exist, has no expiry, or the
+    /// backend can't tell (the default).
+    async fn ttl(&self, _key: &str) -> anyhow::Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// A cached value together with its remaining TTL (as [`Self::ttl`]).
+    ///
+    /// The default makes two calls; backends that can should answer both
+    /// in one round trip.
+    async fn get_with_ttl(&self, key: &str) -> anyhow::Result<Option<(String, Option<u64>)>> {
+        let Some(value) = self.get(key).await? else {
+            return Ok(None);
+        };
+        Ok(Some((value, self.ttl(key).await?)))
+    }
 }
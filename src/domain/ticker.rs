@@ -0,0 +1,143 @@
+//! Canonical forms for token tickers and exchange names.
+//!
+//! Every layer that keys, compares or looks up a ticker goes through these
+//! helpers, so `KASPA`, `kaspa` and ` Kaspa ` resolve to the same cache
+//! entries and data paths.
+
+use crate::domain::ApiError;
+
+/// Canonical ticker: trimmed and uppercased.
+///
+/// This is the form used by the Kaspa.com API and in cache keys.
+///
+/// # Examples
+///
+/// ```
+/// use krcbot_kaspacom_gatewayapi::domain::normalize_ticker;
+///
+/// assert_eq!(normalize_ticker(" nacho "), "NACHO");
+/// ```
+pub fn normalize_ticker(ticker: &str) -> String {
+    ticker.trim().to_uppercase()
+}
+
+/// Canonical token name: trimmed and lowercased.
+///
+/// This is the directory name used in the exchange data repository
+/// (`data/<token>/<exchange>/...`). Ticker responses still echo the token
+/// as requested.
+///
+/// # Examples
+///
+/// ```
+/// use krcbot_kaspacom_gatewayapi::domain::normalize_token;
+///
+/// assert_eq!(normalize_token("KASPA"), "kaspa");
+/// ```
+pub fn normalize_token(token: &str) -> String {
+    token.trim().to_lowercase()
+}
+
+/// Canonical exchange name: trimmed and lowercased, matching the exchange
+/// directory names in the data repository.
+pub fn normalize_exchange(exchange: &str) -> String {
+    exchange.trim().to_lowercase()
+}
+
+/// KNS top-level domain suffix
+pub const KNS_DOMAIN_SUFFIX: &str = ".kas";
+
+/// Canonical KNS domain name: trimmed, lowercased, with the `.kas` suffix.
+///
+/// Lookups accept the name with or without the suffix, so `Alice`,
+/// `alice.kas` and `ALICE.KAS` all resolve to `alice.kas`, the form used
+/// as the asset id in KNS orders. Names that are empty or contain
+/// whitespace, path or query separators, or control characters are
+/// rejected.
+///
+/// # Examples
+///
+/// ```
+/// use krcbot_kaspacom_gatewayapi::domain::normalize_kns_domain;
+///
+/// assert_eq!(normalize_kns_domain(" MyWallet ").unwrap(), "mywallet.kas");
+/// assert_eq!(normalize_kns_domain("mywallet.KAS").unwrap(), "mywallet.kas");
+/// ```
+pub fn normalize_kns_domain(name: &str) -> Result<String, ApiError> {
+    let lowered = name.trim().to_lowercase();
+    let label = lowered.strip_suffix(KNS_DOMAIN_SUFFIX).unwrap_or(&lowered);
+    let invalid = label.is_empty()
+        || label.starts_with('.')
+        || label.ends_with('.')
+        || label.contains(['/', '\\', '?', '#'])
+        || label.chars().any(|c| c.is_whitespace() || c.is_control());
+    if invalid {
+        return Err(ApiError::Validation(format!("Invalid KNS domain '{}'", name.escape_debug())));
+    }
+    Ok(format!("{}{}", label, KNS_DOMAIN_SUFFIX))
+}
+
+/// Ensure a token or exchange name is safe to use as one path segment.
+///
+/// Names come from request paths and are spliced into repository paths
+/// (`data/<token>/<exchange>/...`), so anything that could climb out of
+/// the data directory or split into several segments is rejected: empty
+/// names, `.` and `..`, path separators and control characters.
+///
+/// # Examples
+///
+/// ```
+/// use krcbot_kaspacom_gatewayapi::domain::validate_path_segment;
+///
+/// assert!(validate_path_segment("token", "kaspa").is_ok());
+/// assert!(validate_path_segment("token", "../../secret").is_err());
+/// ```
+pub fn validate_path_segment(kind: &str, name: &str) -> Result<(), ApiError> {
+    let invalid = name.is_empty()
+        || name == "."
+        || name.contains("..")
+        || name.contains(['/', '\\'])
+        || name.chars().any(char::is_control);
+    if invalid {
+        return Err(ApiError::Validation(format!("Invalid {} name '{}'", kind, name.escape_debug())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticker_and_token_forms_agree() {
+        for input in ["kaspa", "KASPA", "Kaspa", " kaspa\t"] {
+            assert_eq!(normalize_ticker(input), "KASPA");
+            assert_eq!(normalize_token(input), "kaspa");
+        }
+        assert_eq!(normalize_ticker(&normalize_token("sLoW")), normalize_ticker("SLOW"));
+        assert_eq!(normalize_exchange(" MEXC "), "mexc");
+    }
+
+    #[test]
+    fn test_kns_domain_suffix_is_normalized() {
+        for input in ["alice", "alice.kas", "ALICE.KAS", " Alice.Kas\t", "alice.KAS"] {
+            assert_eq!(normalize_kns_domain(input).unwrap(), "alice.kas", "{:?}", input);
+        }
+        assert_eq!(normalize_kns_domain("sub.alice").unwrap(), "sub.alice.kas");
+        for input in ["", ".kas", "  .KAS ", "ali ce", "alice/.kas", "a?b", "alice.", "al\0ice"] {
+            let err = normalize_kns_domain(input).unwrap_err();
+            assert_eq!(err.code(), "VALIDATION_ERROR", "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_path_segments_cannot_escape_the_data_directory() {
+        for name in ["kaspa", "nacho", "kas-pa_2", "kaspa.v2", "mexc"] {
+            assert!(validate_path_segment("token", name).is_ok(), "{}", name);
+        }
+        for name in ["", ".", "..", "../../secret", "kaspa/mexc", "..\\secret", "kas\npa", "kaspa\0"] {
+            let err = validate_path_segment("token", name).unwrap_err();
+            assert_eq!(err.code(), "VALIDATION_ERROR", "{:?}", name);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Circuit breaker for kaspa.com upstream calls.
+//!
+//! After `failure_threshold` consecutive upstream failures the breaker
+//! opens and calls fail fast for `cooldown_secs` instead of each waiting
+//! out the client's retries. The first call after the cooldown goes through
+//! as a probe (half-open): success closes the breaker, failure reopens it
+//! for another cooldown. Other calls keep failing fast while the probe is
+//! in flight; a probe that never reports back (e.g. a cancelled request) is
+//! replaced after one cooldown.
+
+use crate::infrastructure::rate_limiter::{Clock, SystemClock};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Thresholds for [`CircuitBreaker`]
+#[derive(Deserialize, Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive upstream failures that open the breaker; 0 disables it
+    /// (default: 5)
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds the breaker stays open before probing (default: 30)
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            cooldown_secs: default_cooldown_secs(),
+        }
+    }
+}
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_cooldown_secs() -> u64 {
+    30
+}
+
+/// Breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Calls go through
+    Closed,
+    /// Calls fail fast until the cooldown ends
+    Open,
+    /// One probe call decides whether to close or reopen
+    HalfOpen,
+}
+
+impl BreakerState {
+    /// Value of the `kaspacom_circuit_state` gauge
+    fn gauge_value(self) -> f64 {
+        match self {
+            Self::Closed => 0.0,
+            Self::HalfOpen => 1.0,
+            Self::Open => 2.0,
+        }
+    }
+}
+
+/// Snapshot of the breaker for the cache stats endpoint
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CircuitBreakerStatus {
+    pub state: BreakerState,
+    /// Upstream failures since the last success
+    pub consecutive_failures: u32,
+    pub failure_threshold: u32,
+    pub cooldown_secs: u64,
+    /// Seconds until the next probe is allowed, while open
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    /// When the breaker last opened
+    opened_at: Option<Instant>,
+    /// When the current half-open probe was let through
+    probe_started_at: Option<Instant>,
+}
+
+/// Consecutive-failure circuit breaker
+pub struct CircuitBreaker {
+    clock: Arc<dyn Clock>,
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// Create a breaker driven by the given clock
+    pub fn with_clock(config: CircuitBreakerConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            config,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started_at: None,
+            }),
+        }
+    }
+
+    fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.config.cooldown_secs)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Ask to make an upstream call
+    ///
+    /// Returns the seconds until the next probe when the call must fail
+    /// fast. Moves an open breaker whose cooldown has ended to half-open and
+    /// lets this call through as the probe.
+    pub fn try_acquire(&self) -> Result<(), u64> {
+        if self.config.failure_threshold == 0 {
+            return Ok(());
+        }
+        let now = self.clock.now();
+        let mut inner = self.lock();
+
+        let blocked_since = match inner.state {
+            BreakerState::Closed => return Ok(()),
+            BreakerState::Open => inner.opened_at,
+            BreakerState::HalfOpen => inner.probe_started_at,
+        };
+        let elapsed = blocked_since.map_or(Duration::MAX, |since| now.saturating_duration_since(since));
+        if elapsed >= self.cooldown() {
+            if inner.state == BreakerState::Open {
+                info!("kaspa.com circuit breaker half-open, probing upstream");
+                Self::set_state(&mut inner, BreakerState::HalfOpen);
+            }
+            inner.probe_started_at = Some(now);
+            return Ok(());
+        }
+
+        metrics::counter!("kaspacom_circuit_rejected_total").increment(1);
+        Err((self.cooldown() - elapsed).as_secs_f64().ceil().max(1.0) as u64)
+    }
+
+    /// Report a successful upstream call, closing the breaker
+    pub fn record_success(&self) {
+        let mut inner = self.lock();
+        if inner.state != BreakerState::Closed {
+            info!("kaspa.com circuit breaker closed, upstream recovered");
+            Self::set_state(&mut inner, BreakerState::Closed);
+        }
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_started_at = None;
+    }
+
+    /// Report a failed upstream call, opening the breaker at the threshold
+    /// or when a half-open probe fails
+    pub fn record_failure(&self) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let now = self.clock.now();
+        let mut inner = self.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+
+        let open = match inner.state {
+            BreakerState::Closed => inner.consecutive_failures >= self.config.failure_threshold,
+            BreakerState::HalfOpen => true,
+            // A call let through before the breaker opened
+            BreakerState::Open => false,
+        };
+        if open {
+            warn!(
+                "kaspa.com circuit breaker open after {} consecutive failures, failing fast for {}s",
+                inner.consecutive_failures, self.config.cooldown_secs
+            );
+            Self::set_state(&mut inner, BreakerState::Open);
+            inner.opened_at = Some(now);
+            inner.probe_started_at = None;
+        }
+    }
+
+    /// Snapshot of the breaker; also refreshes the `kaspacom_circuit_state`
+    /// gauge (0 closed, 1 half-open, 2 open)
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let now = self.clock.now();
+        let inner = self.lock();
+        metrics::gauge!("kaspacom_circuit_state").set(inner.state.gauge_value());
+
+        let retry_after_secs = match (inner.state, inner.opened_at) {
+            (BreakerState::Open, Some(opened_at)) => Some(
+                self.cooldown()
+                    .saturating_sub(now.saturating_duration_since(opened_at))
+                    .as_secs(),
+            ),
+            _ => None,
+        };
+        CircuitBreakerStatus {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            failure_threshold: self.config.failure_threshold,
+            cooldown_secs: self.config.cooldown_secs,
+            retry_after_secs,
+        }
+    }
+
+    fn set_state(inner: &mut BreakerInner, state: BreakerState) {
+        inner.state = state;
+        metrics::gauge!("kaspacom_circuit_state").set(state.gauge_value());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rate_limiter::MockClock;
+
+    fn breaker(failure_threshold: u32) -> (CircuitBreaker, Arc<MockClock>) {
+        let clock = MockClock::new();
+        let config = CircuitBreakerConfig {
+            failure_threshold,
+            cooldown_secs: 30,
+        };
+        (CircuitBreaker::with_clock(config, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let (breaker, _) = breaker(3);
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert!(breaker.try_acquire().is_ok());
+
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Open);
+        assert_eq!(breaker.try_acquire(), Err(30));
+        assert_eq!(breaker.status().retry_after_secs, Some(30));
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_or_reopens() {
+        let (breaker, clock) = breaker(1);
+        breaker.record_failure();
+
+        clock.advance(Duration::from_secs(29));
+        assert_eq!(breaker.try_acquire(), Err(1));
+
+        // Probe fails: open for another full cooldown
+        clock.advance(Duration::from_secs(1));
+        assert!(breaker.try_acquire().is_ok());
+        assert_eq!(breaker.status().state, BreakerState::HalfOpen);
+        assert!(breaker.try_acquire().is_err(), "only one probe at a time");
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Open);
+        assert_eq!(breaker.try_acquire(), Err(30));
+
+        // Probe succeeds: closed
+        clock.advance(Duration::from_secs(30));
+        assert!(breaker.try_acquire().is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 0);
+        assert!(breaker.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn test_abandoned_probe_is_replaced_after_cooldown() {
+        let (breaker, clock) = breaker(1);
+        breaker.record_failure();
+        clock.advance(Duration::from_secs(30));
+        assert!(breaker.try_acquire().is_ok());
+
+        // The probe never reports back
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(breaker.try_acquire(), Err(20));
+        clock.advance(Duration::from_secs(20));
+        assert!(breaker.try_acquire().is_ok());
+        assert_eq!(breaker.status().state, BreakerState::HalfOpen);
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_breaker() {
+        let (breaker, _) = breaker(0);
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert!(breaker.try_acquire().is_ok());
+    }
+}
@@ -18,15 +18,22 @@
 //! - Automatically retries on 429/403 status codes with exponential backoff
 //! - Respects `Retry-After` header when provided
 //!
+//! # Conditional Requests
+//!
+//! When an ETag cache is configured, response ETags and bodies are stored per
+//! URL and sent back as `If-None-Match`. GitHub answers unchanged resources
+//! with `304 Not Modified`, which doesn't count against the rate limit, and
+//! the previously cached body is returned instead.
+//!
 //! # Examples
 //!
 //! ```no_run
-//! use gatewayapi::infrastructure::GitHubRepository;
-//! use gatewayapi::domain::{ContentRepository, RepoConfig};
+//! use krcbot_kaspacom_gatewayapi::infrastructure::GitHubRepository;
+//! use krcbot_kaspacom_gatewayapi::domain::{ContentRepository, RepoConfig};
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
-//!     let token = std::env::var("GITHUB_TOKEN")?;
+//!     let token = std::env::var("GITHUB_TOKEN").ok();
 //!     let repo = GitHubRepository::new(token);
 //!     
 //!     let config = RepoConfig {
@@ -41,13 +48,25 @@
 //! }
 //! ```
 
-use crate::domain::{Content, ContentRepository, ContentType, RepoConfig};
+use crate::domain::{CacheRepository, Content, ContentRepository, ContentType, RepoConfig};
+use crate::infrastructure::telemetry;
 use async_trait::async_trait;
-use reqwest::{Client, Response};
-use serde::Deserialize;
+use reqwest::{header, Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// How long stored ETags and bodies are kept (7 days)
+const ETAG_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// ETag and body of a previous successful response
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    etag: String,
+    body: String,
+}
 
 /// GitHub API client with automatic rate limit handling and retry logic.
 ///
@@ -61,6 +80,8 @@ pub struct GitHubRepository {
     /// If None, requests are made without authentication (60 req/hour limit for public repos)
     /// If Some, requests use authentication (5,000 req/hour limit)
     token: Option<String>,
+    /// Store for ETags and bodies used for conditional requests (optional)
+    etag_cache: Option<Arc<dyn CacheRepository>>,
 }
 
 impl GitHubRepository {
@@ -82,7 +103,7 @@ impl GitHubRepository {
     /// # Examples
     ///
     /// ```
-    /// use gatewayapi::infrastructure::GitHubRepository;
+    /// use krcbot_kaspacom_gatewayapi::infrastructure::GitHubRepository;
     ///
     /// // With token (recommended for higher rate limits)
     /// let token = std::env::var("GITHUB_TOKEN").ok();
@@ -98,7 +119,104 @@ impl GitHubRepository {
             .build()
             .expect("Failed to build HTTP client");
 
-        Self { client, token }
+        Self {
+            client,
+            token,
+            etag_cache: None,
+        }
+    }
+
+    /// Enable conditional requests, storing ETags and bodies in the given cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - Cache used to store the last ETag and body per URL (e.g. Redis)
+    pub fn with_etag_cache(mut self, cache: Arc<dyn CacheRepository>) -> Self {
+        self.etag_cache = Some(cache);
+        self
+    }
+
+    /// Build a GET request with the standard headers and optional authentication.
+    fn request(&self, url: &str, accept: &str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .get(url)
+            .header("Accept", accept)
+            .header("User-Agent", "GitRows-API-Proxy");
+
+        // Add Authorization header only if token is provided
+        if let Some(ref token) = self.token {
+            request = request.header("Authorization", format!("token {}", token));
+        }
+
+        telemetry::inject_trace_context(request)
+    }
+
+    /// Fetch a URL's body, using a conditional request when an ETag is cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL to fetch
+    /// * `accept` - Value for the `Accept` header
+    /// * `error_label` - Prefix for the error message on non-success status
+    ///
+    /// # Returns
+    ///
+    /// The response body, or the previously cached body on `304 Not Modified`.
+    #[tracing::instrument(name = "github_request", skip(self, accept, error_label))]
+    async fn fetch_body(&self, url: &str, accept: &str, error_label: &str) -> anyhow::Result<String> {
+        let etag_key = format!("github:etag:{}", url);
+        let cached = match &self.etag_cache {
+            Some(cache) => cache
+                .get(&etag_key)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|raw| serde_json::from_str::<CachedResponse>(&raw).ok()),
+            None => None,
+        };
+
+        let resp = self
+            .execute_with_retry(|| {
+                let mut request = self.request(url, accept);
+                if let Some(ref cached) = cached {
+                    request = request.header(header::IF_NONE_MATCH, &cached.etag);
+                }
+                request.send()
+            })
+            .await?;
+
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                debug!("GitHub 304 Not Modified, using cached body: {}", url);
+                return Ok(cached.body);
+            }
+        }
+
+        if !resp.status().is_success() {
+            anyhow::bail!("{}: {}", error_label, resp.status());
+        }
+
+        let etag = resp
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body = resp.text().await?;
+
+        if let (Some(cache), Some(etag)) = (&self.etag_cache, etag) {
+            let entry = CachedResponse {
+                etag,
+                body: body.clone(),
+            };
+            if let Ok(raw) = serde_json::to_string(&entry) {
+                if let Err(e) = cache.set(&etag_key, &raw, ETAG_TTL_SECS).await {
+                    warn!("Failed to store GitHub ETag for {}: {}", url, e);
+                }
+            }
+        }
+
+        Ok(body)
     }
 
     /// Check and log rate limit information from response headers.
@@ -215,6 +333,7 @@ struct GitHubItemDto {
     path: String,
     #[serde(rename = "type")]
     item_type: String,
+    sha: Option<String>,
     url: String,
     content: Option<String>,
     encoding: Option<String>,
@@ -228,6 +347,7 @@ impl From<GitHubItemDto> for Content {
             name: dto.name,
             path: dto.path,
             item_type: ContentType::from(dto.item_type),
+            sha: dto.sha,
             content: dto.content,
             encoding: dto.encoding,
             html_url: dto.html_url,
@@ -246,27 +366,11 @@ impl ContentRepository for GitHubRepository {
             config.owner, config.repo, clean_path
         );
 
-        let resp = self
-            .execute_with_retry(|| {
-                let mut request = self.client
-                    .get(&url)
-                    .header("Accept", "application/vnd.github.v3+json")
-                    .header("User-Agent", "GitRows-API-Proxy");
-                
-                // Add Authorization header only if token is provided
-                if let Some(ref token) = self.token {
-                    request = request.header("Authorization", format!("token {}", token));
-                }
-                
-                request.send()
-            })
+        let body = self
+            .fetch_body(&url, "application/vnd.github.v3+json", "GitHub API Error")
             .await?;
 
-        if !resp.status().is_success() {
-            anyhow::bail!("GitHub API Error: {}", resp.status());
-        }
-
-        let dto: GitHubItemDto = resp.json().await?;
+        let dto: GitHubItemDto = serde_json::from_str(&body)?;
         Ok(Content::from(dto))
     }
 
@@ -288,27 +392,11 @@ impl ContentRepository for GitHubRepository {
         loop {
             let url = format!("{}?per_page={}&page={}", base_url, PER_PAGE, page);
 
-            let resp = self
-                .execute_with_retry(|| {
-                    let mut request = self.client
-                        .get(&url)
-                        .header("Accept", "application/vnd.github.v3+json")
-                        .header("User-Agent", "GitRows-API-Proxy");
-                    
-                    // Add Authorization header only if token is provided
-                    if let Some(ref token) = self.token {
-                        request = request.header("Authorization", format!("token {}", token));
-                    }
-                    
-                    request.send()
-                })
+            let body = self
+                .fetch_body(&url, "application/vnd.github.v3+json", "GitHub API Error")
                 .await?;
 
-            if !resp.status().is_success() {
-                anyhow::bail!("GitHub API Error: {}", resp.status());
-            }
-
-            let dtos: Vec<GitHubItemDto> = resp.json().await?;
+            let dtos: Vec<GitHubItemDto> = serde_json::from_str(&body)?;
             
             // If we got no items, we've reached the end
             if dtos.is_empty() {
@@ -337,27 +425,67 @@ impl ContentRepository for GitHubRepository {
     }
 
     async fn get_raw_file(&self, url: &str) -> anyhow::Result<Value> {
-        let resp = self
-            .execute_with_retry(|| {
-                let mut request = self.client
-                    .get(url)
-                    .header("Accept", "application/vnd.github.v3.raw")
-                    .header("User-Agent", "GitRows-API-Proxy");
-                
-                // Add Authorization header only if token is provided
-                if let Some(ref token) = self.token {
-                    request = request.header("Authorization", format!("token {}", token));
-                }
-                
-                request.send()
-            })
+        let body = self
+            .fetch_body(url, "application/vnd.github.v3.raw", "GitHub Fetch Error")
             .await?;
 
-        if !resp.status().is_success() {
-            anyhow::bail!("GitHub Fetch Error: {}", resp.status());
-        }
-
-        let val: Value = resp.json().await?;
+        let val: Value = serde_json::from_str(&body)?;
         Ok(val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::InMemoryCache;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serve `body` with ETag `"v1"`, answering a matching `If-None-Match`
+    /// with 304; returns the base URL and the count of each response
+    async fn etag_server() -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let (full, not_modified) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let (full_count, not_modified_count) = (full.clone(), not_modified.clone());
+        let app = axum::Router::new().fallback(move |headers: axum::http::HeaderMap| {
+            let (full, not_modified) = (full_count.clone(), not_modified_count.clone());
+            async move {
+                if headers.get(header::IF_NONE_MATCH).is_some_and(|v| v == "\"v1\"") {
+                    not_modified.fetch_add(1, Ordering::SeqCst);
+                    return (StatusCode::NOT_MODIFIED, [(header::ETAG, "\"v1\"")], "");
+                }
+                full.fetch_add(1, Ordering::SeqCst);
+                (StatusCode::OK, [(header::ETAG, "\"v1\"")], "body")
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/file", addr), full, not_modified)
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_resource_is_served_from_the_etag_cache() {
+        let (url, full, not_modified) = etag_server().await;
+        let cache = Arc::new(InMemoryCache::new());
+        let repo = GitHubRepository::new(None).with_etag_cache(cache.clone());
+
+        assert_eq!(repo.fetch_body(&url, "*/*", "GitHub error").await.unwrap(), "body");
+        assert!(cache.get(&format!("github:etag:{}", url)).await.unwrap().is_some());
+
+        // The second request is conditional and answered from the cache
+        assert_eq!(repo.fetch_body(&url, "*/*", "GitHub error").await.unwrap(), "body");
+        assert_eq!(full.load(Ordering::SeqCst), 1);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_unconditional_without_an_etag_cache() {
+        let (url, full, not_modified) = etag_server().await;
+        let repo = GitHubRepository::new(None);
+
+        for _ in 0..2 {
+            assert_eq!(repo.fetch_body(&url, "*/*", "GitHub error").await.unwrap(), "body");
+        }
+        assert_eq!(full.load(Ordering::SeqCst), 2);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 0);
+    }
+}
@@ -3,22 +3,63 @@
 //! This client is used for fetching data from the remote API when cache misses occur.
 //! It handles ticker normalization (uppercase), retry logic, and error handling.
 
+use crate::domain::ApiError;
+use crate::infrastructure::telemetry;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::{Client, Method, StatusCode};
+use serde::Deserialize;
 use serde_json::Value;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio_retry::strategy::{jitter, ExponentialBackoff};
 use tokio_retry::Retry;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Base URL for Kaspa.com API
 const BASE_URL: &str = "https://api.kaspa.com";
 
-/// Request timeout in seconds
-const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Base URL for NFT metadata served by the krc721.stream cache
+const METADATA_BASE_URL: &str = "https://cache.krc721.stream";
+
+/// Characters of an upstream error body kept in the error message
+const MAX_ERROR_BODY_CHARS: usize = 200;
+
+/// Timeout, retry and backoff settings for [`KaspaComClient`]
+#[derive(Deserialize, Debug, Clone)]
+pub struct KaspaComClientConfig {
+    /// Retries after a failed connection; 0 sends each request once
+    /// (default: 3)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+    /// Per-request timeout in seconds (default: 30)
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Base of the exponential backoff between retries, in milliseconds;
+    /// delays are jittered (default: 100)
+    #[serde(default = "default_base_backoff_ms")]
+    pub base_backoff_ms: u64,
+}
+
+impl Default for KaspaComClientConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            request_timeout_secs: default_request_timeout_secs(),
+            base_backoff_ms: default_base_backoff_ms(),
+        }
+    }
+}
+
+fn default_max_retries() -> usize {
+    3
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
 
-/// Maximum retry attempts
-const MAX_RETRIES: usize = 3;
+fn default_base_backoff_ms() -> u64 {
+    100
+}
 
 /// Kaspa.com L1 Marketplace API Client
 ///
@@ -27,7 +68,16 @@ const MAX_RETRIES: usize = 3;
 #[derive(Clone)]
 pub struct KaspaComClient {
     client: Client,
+    /// Client for [`Self::fetch_image`]; never follows redirects, so an
+    /// external URL can't bounce requests onto internal hosts
+    image_client: Client,
     base_url: String,
+    /// Host of [`Self::fetch_nft_metadata`]; not sent the API token
+    metadata_base_url: String,
+    max_retries: usize,
+    base_backoff_ms: u64,
+    /// Sent as `Authorization: Bearer` on every API request when set
+    api_token: Option<String>,
 }
 
 impl KaspaComClient {
@@ -38,98 +88,183 @@ impl KaspaComClient {
 
     /// Create a new client with a custom base URL (for testing)
     pub fn with_base_url(base_url: &str) -> Self {
+        Self::with_config(base_url, &KaspaComClientConfig::default())
+    }
+
+    /// Create a client with explicit timeout and retry settings
+    pub fn with_config(base_url: &str, config: &KaspaComClientConfig) -> Self {
         let client = Client::builder()
-            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .user_agent("KaspaDevCacheProxy/1.0")
+            .build()
+            .expect("Failed to create HTTP client");
+        let image_client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
             .user_agent("KaspaDevCacheProxy/1.0")
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .expect("Failed to create HTTP client");
 
         Self {
             client,
+            image_client,
             base_url: base_url.to_string(),
+            metadata_base_url: METADATA_BASE_URL.to_string(),
+            max_retries: config.max_retries,
+            base_backoff_ms: config.base_backoff_ms,
+            api_token: None,
         }
     }
 
+    /// Authenticate API requests with a kaspa.com token (e.g. from
+    /// `KASPACOM_TOKEN`); `None` or an empty token leaves requests
+    /// unauthenticated
+    pub fn with_api_token(mut self, api_token: Option<String>) -> Self {
+        self.api_token = api_token.filter(|token| !token.trim().is_empty());
+        self
+    }
+
+    /// Fetch NFT metadata from a custom host instead of krc721.stream (for testing)
+    pub fn with_metadata_base_url(mut self, metadata_base_url: &str) -> Self {
+        self.metadata_base_url = metadata_base_url.to_string();
+        self
+    }
+
+    /// Whether API requests carry an `Authorization` header
+    pub fn is_authenticated(&self) -> bool {
+        self.api_token.is_some()
+    }
+
+    /// Create a client for the production API with the given settings
+    pub fn from_config(config: &KaspaComClientConfig) -> Self {
+        Self::with_config(BASE_URL, config)
+    }
+
     /// Normalize ticker to uppercase for API compatibility.
     ///
     /// The Kaspa.com API requires uppercase tickers. This method ensures
-    /// all ticker parameters are properly formatted.
+    /// all ticker parameters are properly formatted. Delegates to
+    /// [`crate::domain::normalize_ticker`].
     pub fn normalize_ticker(ticker: &str) -> String {
-        ticker.to_uppercase()
+        crate::domain::normalize_ticker(ticker)
     }
 
     /// Internal method to make a GET request with retry logic
     async fn get(&self, path: &str) -> Result<Value> {
-        let url = format!("{}{}", self.base_url, path);
-        debug!("Fetching from Kaspa.com API: {}", url);
+        debug!("Fetching from Kaspa.com API: {}{}", self.base_url, path);
+        self.request(Method::GET, path, None).await
+    }
+
+    /// Internal method to make a POST request with retry logic
+    async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        debug!("POST to Kaspa.com API: {}{}", self.base_url, path);
+        self.request(Method::POST, path, Some(body)).await
+    }
 
-        let retry_strategy = ExponentialBackoff::from_millis(100)
+    /// Send a request, retrying connection failures, and parse the JSON body
+    ///
+    /// Records `kaspacom_upstream_duration_ms` (retries and body included)
+    /// and `kaspacom_upstream_requests_total` by `endpoint`, the latter also
+    /// by `status` class (`2xx`..`5xx`, or `error` when no response
+    /// arrived). Retried attempts count towards
+    /// `kaspacom_upstream_retries_total`.
+    async fn request(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value> {
+        self.send(
+            &self.base_url,
+            self.api_token.as_deref(),
+            method,
+            path,
+            body,
+        )
+        .await
+    }
+
+    /// [`Self::request`] against any host, with an optional bearer token
+    #[tracing::instrument(name = "kaspacom_request", skip(self, api_token, body), fields(method = %method))]
+    async fn send(
+        &self,
+        base_url: &str,
+        api_token: Option<&str>,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Value> {
+        let url = format!("{}{}", base_url, path);
+        let endpoint = endpoint_label(path);
+        let started = Instant::now();
+
+        let retry_strategy = ExponentialBackoff::from_millis(self.base_backoff_ms)
             .map(jitter)
-            .take(MAX_RETRIES);
-
-        let response = Retry::spawn(retry_strategy, || async {
-            self.client
-                .get(&url)
-                .header("Accept", "application/json")
-                .send()
-                .await
+            .take(self.max_retries);
+
+        let mut attempts = 0u64;
+        let sent = Retry::start(retry_strategy, || {
+            attempts += 1;
+            let mut request = self
+                .client
+                .request(method.clone(), &url)
+                .header("Accept", "application/json");
+            if let Some(token) = api_token {
+                request = request.bearer_auth(token);
+            }
+            request = telemetry::inject_trace_context(request);
+            match body {
+                Some(body) => request.json(body),
+                None => request,
+            }
+            .send()
         })
-        .await
-        .with_context(|| format!("Failed to fetch from {}", url))?;
+        .await;
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_body = response.text().await.unwrap_or_default();
-            anyhow::bail!(
-                "API request failed with status {}: {}",
-                status,
-                error_body
-            );
+        if attempts > 1 {
+            metrics::counter!("kaspacom_upstream_retries_total", "endpoint" => endpoint.clone())
+                .increment(attempts - 1);
         }
 
-        let json: Value = response
-            .json()
-            .await
-            .with_context(|| format!("Failed to parse JSON from {}", url))?;
+        let response = match sent {
+            Ok(response) => response,
+            Err(e) => {
+                record_upstream_request(endpoint, "error", started);
+                let action = if method == Method::POST {
+                    "POST to"
+                } else {
+                    "fetch from"
+                };
+                return Err(
+                    ApiError::Upstream(format!("Failed to {} {}: {}", action, url, e)).into(),
+                );
+            }
+        };
 
-        Ok(json)
+        let status = response.status();
+        let result = Self::read_json(response, &url).await;
+        record_upstream_request(endpoint, status_class(status), started);
+        result
     }
 
-    /// Internal method to make a POST request with retry logic
-    async fn post(&self, path: &str, body: &Value) -> Result<Value> {
-        let url = format!("{}{}", self.base_url, path);
-        debug!("POST to Kaspa.com API: {}", url);
-
-        let retry_strategy = ExponentialBackoff::from_millis(100)
-            .map(jitter)
-            .take(MAX_RETRIES);
-
-        let response = Retry::spawn(retry_strategy, || async {
-            self.client
-                .post(&url)
-                .header("Accept", "application/json")
-                .header("Content-Type", "application/json")
-                .json(body)
-                .send()
-                .await
-        })
-        .await
-        .with_context(|| format!("Failed to POST to {}", url))?;
-
+    /// Map an error status to [`ApiError`], otherwise parse the body as JSON
+    async fn read_json(response: reqwest::Response, url: &str) -> Result<Value> {
         let status = response.status();
         if !status.is_success() {
+            // The message reaches clients as `details`, so only a prefix of
+            // the upstream body is kept; the full body is logged
             let error_body = response.text().await.unwrap_or_default();
-            anyhow::bail!(
-                "API request failed with status {}: {}",
-                status,
-                error_body
-            );
+            warn!("{} returned {}: {}", url, status, error_body);
+            return Err(ApiError::from_upstream_status(
+                status.as_u16(),
+                format!(
+                    "API request failed with status {}: {}",
+                    status,
+                    truncate_chars(&error_body, MAX_ERROR_BODY_CHARS)
+                ),
+            )
+            .into());
         }
 
         let json: Value = response
             .json()
             .await
-            .with_context(|| format!("Failed to parse JSON from {}", url))?;
+            .map_err(|e| ApiError::Upstream(format!("Failed to parse JSON from {}: {}", url, e)))?;
 
         Ok(json)
     }
@@ -141,11 +276,7 @@ impl KaspaComClient {
     /// Fetch trade statistics for KRC20 tokens
     ///
     /// GET /api/trade-stats?timeFrame=6h&ticker=TICKER
-    pub async fn fetch_trade_stats(
-        &self,
-        time_frame: &str,
-        ticker: Option<&str>,
-    ) -> Result<Value> {
+    pub async fn fetch_trade_stats(&self, time_frame: &str, ticker: Option<&str>) -> Result<Value> {
         let mut path = format!("/api/trade-stats?timeFrame={}", time_frame);
         if let Some(t) = ticker {
             path.push_str(&format!("&ticker={}", Self::normalize_ticker(t)));
@@ -296,7 +427,10 @@ impl KaspaComClient {
     /// GET /api/krc721/listed-orders?ticker=TICKER
     pub async fn fetch_krc721_listed_orders(&self, ticker: Option<&str>) -> Result<Value> {
         let path = match ticker {
-            Some(t) => format!("/api/krc721/listed-orders?ticker={}", Self::normalize_ticker(t)),
+            Some(t) => format!(
+                "/api/krc721/listed-orders?ticker={}",
+                Self::normalize_ticker(t)
+            ),
             None => "/api/krc721/listed-orders".to_string(),
         };
         info!("Fetching KRC721 listed orders: {}", path);
@@ -333,7 +467,10 @@ impl KaspaComClient {
     /// GET /api/krc721/floor-price?ticker=TICKER
     pub async fn fetch_krc721_floor_prices(&self, ticker: Option<&str>) -> Result<Value> {
         let path = match ticker {
-            Some(t) => format!("/api/krc721/floor-price?ticker={}", Self::normalize_ticker(t)),
+            Some(t) => format!(
+                "/api/krc721/floor-price?ticker={}",
+                Self::normalize_ticker(t)
+            ),
             None => "/api/krc721/floor-price".to_string(),
         };
         info!("Fetching KRC721 floor prices: {}", path);
@@ -403,34 +540,90 @@ impl KaspaComClient {
 
     /// Fetch NFT metadata from krc721.stream cache
     ///
-    /// GET https://cache.krc721.stream/krc721/mainnet/metadata/{ticker}/{tokenId}
+    /// GET `https://cache.krc721.stream/krc721/mainnet/metadata/{ticker}/{tokenId}`,
+    /// retried like API requests but without the API token
     pub async fn fetch_nft_metadata(&self, ticker: &str, token_id: i64) -> Result<Value> {
-        let url = format!(
-            "https://cache.krc721.stream/krc721/mainnet/metadata/{}/{}",
+        let path = format!(
+            "/krc721/mainnet/metadata/{}/{}",
             Self::normalize_ticker(ticker),
             token_id
         );
-        info!("Fetching NFT metadata from krc721.stream: {}", url);
+        info!("Fetching NFT metadata from krc721.stream: {}", path);
+        self.send(&self.metadata_base_url, None, Method::GET, &path, None)
+            .await
+    }
 
-        let response = self.client
-            .get(&url)
-            .header("Accept", "application/json")
+    /// Fetch an image from an external host (e.g. a token logo URL)
+    ///
+    /// Only `http`/`https` URLs are fetched and redirects are not
+    /// followed. The response must be `image/*` other than SVG (which can
+    /// carry scripts) and at most `max_bytes` long; the body is read chunk by chunk and abandoned as soon as it
+    /// exceeds the limit, with or without a `Content-Length`. Returns `{contentType, data}` with the
+    /// bytes base64-encoded so the result can be cached like API responses.
+    pub async fn fetch_image(&self, url: &str, max_bytes: usize) -> Result<Value> {
+        let parsed =
+            reqwest::Url::parse(url).with_context(|| format!("Invalid image URL {}", url))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            anyhow::bail!("Unsupported image URL scheme: {}", parsed.scheme());
+        }
+        info!("Fetching image: {}", url);
+
+        let mut response = self
+            .image_client
+            .get(parsed)
             .send()
             .await
-            .with_context(|| format!("Failed to fetch NFT metadata from {}", url))?;
+            .with_context(|| format!("Failed to fetch image from {}", url))?;
 
         let status = response.status();
         if !status.is_success() {
-            let error_body = response.text().await.unwrap_or_default();
-            anyhow::bail!("NFT metadata request failed with status {}: {}", status, error_body);
+            return Err(ApiError::from_upstream_status(
+                status.as_u16(),
+                format!("Image request failed with status {}", status),
+            )
+            .into());
         }
 
-        let json: Value = response
-            .json()
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        if !content_type.starts_with("image/") {
+            anyhow::bail!(
+                "Upstream returned non-image content type '{}'",
+                content_type
+            );
+        }
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        if essence.eq_ignore_ascii_case("image/svg+xml") {
+            anyhow::bail!("SVG images are not proxied");
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len as usize > max_bytes)
+        {
+            anyhow::bail!("Image exceeds {} bytes", max_bytes);
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .with_context(|| format!("Failed to parse NFT metadata JSON from {}", url))?;
+            .with_context(|| format!("Failed to read image body from {}", url))?
+        {
+            if bytes.len() + chunk.len() > max_bytes {
+                anyhow::bail!("Image exceeds {} bytes", max_bytes);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
 
-        Ok(json)
+        use base64::{engine::general_purpose, Engine as _};
+        Ok(serde_json::json!({
+            "contentType": content_type,
+            "data": general_purpose::STANDARD.encode(&bytes),
+        }))
     }
 
     /// Get optimized NFT image URL from krc721.stream CDN
@@ -451,6 +644,58 @@ impl Default for KaspaComClient {
     }
 }
 
+/// Metrics label for a request path
+///
+/// Drops the query string and replaces the ticker in path-parameter
+/// endpoints with `{ticker}`, so label cardinality stays bounded.
+fn endpoint_label(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or(path);
+    if path.starts_with("/krc721/mainnet/metadata/") {
+        return "/krc721/mainnet/metadata/{ticker}/{tokenId}".to_string();
+    }
+    for prefix in ["/api/token-info/", "/krc721/"] {
+        if path.len() > prefix.len() && path.starts_with(prefix) {
+            return format!("{}{{ticker}}", prefix);
+        }
+    }
+    path.to_string()
+}
+
+/// Status class label (`2xx`, `4xx`, ...) for a response status
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+/// At most `max` characters of `s`, marked with `...` when cut
+fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+fn record_upstream_request(endpoint: String, status: &'static str, started: Instant) {
+    metrics::histogram!("kaspacom_upstream_duration_ms", "endpoint" => endpoint.clone())
+        .record(started.elapsed().as_secs_f64() * 1000.0);
+    metrics::counter!("kaspacom_upstream_requests_total", "endpoint" => endpoint, "status" => status)
+        .increment(1);
+}
+
+/// Serve `app` on a local port as an upstream stand-in and return its base URL
+#[cfg(test)]
+pub(crate) async fn mock_server(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -476,14 +721,287 @@ mod tests {
     fn test_normalize_ticker_edge_cases() {
         // Test empty string
         assert_eq!(KaspaComClient::normalize_ticker(""), "");
-        
+
         // Test mixed case
         assert_eq!(KaspaComClient::normalize_ticker("sLoW"), "SLOW");
-        
+
         // Test with numbers
         assert_eq!(KaspaComClient::normalize_ticker("token123"), "TOKEN123");
-        
+
         // Test already uppercase
         assert_eq!(KaspaComClient::normalize_ticker("KASPA"), "KASPA");
     }
+
+    #[tokio::test]
+    async fn test_fetch_image_rejects_non_http_urls() {
+        let client = KaspaComClient::new();
+        for url in [
+            "file:///etc/passwd",
+            "gopher://localhost:6379/_PING",
+            "not a url",
+        ] {
+            assert!(
+                client.fetch_image(url, 1024).await.is_err(),
+                "{} should be rejected",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_image_stops_reading_chunked_oversized_body() {
+        use axum::{body::Body, http::header, routing::get};
+        use futures::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // 1024 chunks of 64 KiB (64 MiB) with no Content-Length; count
+        // chunks pulled, which socket buffering keeps well below the total
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = pulled.clone();
+        let chunked = move |chunk_count: usize, chunk_size: usize| {
+            let counter = counter.clone();
+            async move {
+                let chunks = futures::stream::iter(0..chunk_count).map(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, std::io::Error>(vec![0u8; chunk_size])
+                });
+                (
+                    [(header::CONTENT_TYPE, "image/png")],
+                    Body::from_stream(chunks),
+                )
+            }
+        };
+        let large = chunked.clone();
+        let app = axum::Router::new()
+            .route("/large.png", get(move || large(1024, 64 * 1024)))
+            .route("/small.png", get(move || chunked(4, 256)));
+        let base = mock_server(app).await;
+
+        let client = KaspaComClient::new();
+        let err = client
+            .fetch_image(&format!("{}/large.png", base), 4096)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds 4096 bytes"), "{}", err);
+        assert!(pulled.load(Ordering::SeqCst) < 1024, "whole body was read");
+
+        let ok = client
+            .fetch_image(&format!("{}/small.png", base), 4096)
+            .await
+            .unwrap();
+        assert_eq!(ok["contentType"], "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_image_does_not_follow_redirects() {
+        use axum::{http::header, response::Redirect, routing::get};
+
+        let internal = mock_server(axum::Router::new().route(
+            "/secret.png",
+            get(|| async { ([(header::CONTENT_TYPE, "image/png")], "internal") }),
+        ))
+        .await;
+        let target = format!("{}/secret.png", internal);
+        let app = axum::Router::new().route(
+            "/logo.png",
+            get(move || async move { Redirect::temporary(&target) }),
+        );
+        let base = mock_server(app).await;
+
+        let err = KaspaComClient::new()
+            .fetch_image(&format!("{}/logo.png", base), 1024)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("307"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_image_rejects_svg() {
+        use axum::{http::header, routing::get};
+
+        let app = axum::Router::new().route(
+            "/logo.svg",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "image/svg+xml; charset=utf-8")],
+                    "<svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>",
+                )
+            }),
+        );
+        let base = mock_server(app).await;
+
+        let err = KaspaComClient::new()
+            .fetch_image(&format!("{}/logo.svg", base), 1024)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("SVG"), "{}", err);
+    }
+
+    #[test]
+    fn test_endpoint_label_bounds_cardinality() {
+        assert_eq!(
+            endpoint_label("/api/trade-stats?timeFrame=6h&ticker=NACHO"),
+            "/api/trade-stats"
+        );
+        assert_eq!(
+            endpoint_label("/api/token-info/NACHO"),
+            "/api/token-info/{ticker}"
+        );
+        assert_eq!(endpoint_label("/krc721/BITCOIN"), "/krc721/{ticker}");
+        assert_eq!(
+            endpoint_label("/krc721/mainnet/metadata/BITCOIN/173"),
+            "/krc721/mainnet/metadata/{ticker}/{tokenId}"
+        );
+        assert_eq!(
+            endpoint_label("/api/krc721/mint?ticker=X"),
+            "/api/krc721/mint"
+        );
+        assert_eq!(status_class(StatusCode::OK), "2xx");
+        assert_eq!(status_class(StatusCode::NOT_FOUND), "4xx");
+        assert_eq!(status_class(StatusCode::BAD_GATEWAY), "5xx");
+    }
+
+    #[test]
+    fn test_upstream_requests_are_instrumented() {
+        // The mock upstream runs on its own runtime; requests run on this
+        // thread so the local recorder sees their metrics
+        let server = tokio::runtime::Runtime::new().unwrap();
+        let app = axum::Router::new()
+            .route(
+                "/api/trade-stats",
+                axum::routing::get(|| async { axum::Json(serde_json::json!({})) }),
+            )
+            .fallback(|| async { (axum::http::StatusCode::NOT_FOUND, "not found") });
+        let upstream = server.block_on(mock_server(app));
+
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let client = KaspaComClient::with_base_url(&upstream);
+                client.fetch_trade_stats("6h", Some("nacho")).await.unwrap();
+                client.fetch_trade_stats("1d", None).await.unwrap();
+                assert!(client.fetch_token_info("nacho").await.is_err());
+            });
+        });
+
+        let rendered = handle.render();
+        assert!(
+            rendered.contains(
+                r#"kaspacom_upstream_requests_total{endpoint="/api/trade-stats",status="2xx"} 2"#
+            ),
+            "{}",
+            rendered
+        );
+        assert!(rendered.contains(
+            r#"kaspacom_upstream_requests_total{endpoint="/api/token-info/{ticker}",status="4xx"} 1"#
+        ), "{}", rendered);
+        assert!(
+            rendered
+                .contains(r#"kaspacom_upstream_duration_ms_count{endpoint="/api/trade-stats"} 2"#),
+            "{}",
+            rendered
+        );
+        assert!(
+            !rendered.contains("kaspacom_upstream_retries_total"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_token_sent_as_bearer_header() {
+        let app = axum::Router::new().route(
+            "/api/trade-stats",
+            axum::routing::any(|headers: axum::http::HeaderMap| async move {
+                let auth = headers
+                    .get(axum::http::header::AUTHORIZATION)
+                    .map(|v| v.to_str().unwrap().to_string());
+                axum::Json(serde_json::json!({ "authorization": auth }))
+            }),
+        );
+        let upstream = mock_server(app).await;
+
+        let anonymous =
+            KaspaComClient::with_base_url(&upstream).with_api_token(Some(" ".to_string()));
+        assert!(!anonymous.is_authenticated());
+        let body = anonymous.fetch_trade_stats("6h", None).await.unwrap();
+        assert!(body["authorization"].is_null());
+
+        let client =
+            KaspaComClient::with_base_url(&upstream).with_api_token(Some("secret".to_string()));
+        assert!(client.is_authenticated());
+        let body = client.fetch_trade_stats("6h", None).await.unwrap();
+        assert_eq!(body["authorization"], "Bearer secret");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_is_truncated() {
+        let body = "é".repeat(5000);
+        let app = axum::Router::new()
+            .fallback(move || async move { (axum::http::StatusCode::BAD_REQUEST, body) });
+        let upstream = mock_server(app).await;
+
+        let err = KaspaComClient::with_base_url(&upstream)
+            .fetch_trade_stats("6h", None)
+            .await
+            .unwrap_err();
+        let message = ApiError::find(&err).unwrap().message().to_string();
+        assert!(message.starts_with("API request failed with status 400 Bad Request: é"));
+        assert!(message.ends_with("..."));
+        assert!(
+            message.chars().count() < MAX_ERROR_BODY_CHARS + 60,
+            "{}",
+            message
+        );
+    }
+
+    /// Upstream that drops every connection; returns its URL and the number
+    /// of connections accepted
+    async fn dropping_upstream() -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let attempts = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = attempts.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                drop(socket);
+            }
+        });
+        (format!("http://{}", addr), attempts)
+    }
+
+    #[tokio::test]
+    async fn test_retry_count_is_configurable() {
+        use std::sync::atomic::Ordering;
+
+        let (upstream, attempts) = dropping_upstream().await;
+        let no_retries = KaspaComClientConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let client = KaspaComClient::with_config(&upstream, &no_retries);
+        assert!(client.fetch_trade_stats("6h", None).await.is_err());
+        assert_eq!(
+            attempts.load(Ordering::SeqCst),
+            1,
+            "max_retries=0 must not retry"
+        );
+
+        let (upstream, attempts) = dropping_upstream().await;
+        let two_retries = KaspaComClientConfig {
+            max_retries: 2,
+            base_backoff_ms: 1,
+            ..Default::default()
+        };
+        let client = KaspaComClient::with_config(&upstream, &two_retries);
+        assert!(client.fetch_trade_stats("6h", None).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
 }
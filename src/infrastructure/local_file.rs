@@ -3,16 +3,26 @@
 //! Reads data directly from the local filesystem, useful when data is mounted
 //! as a volume (e.g., in Docker). Falls back gracefully when files don't exist.
 
-use crate::domain::{Content, ContentRepository, ContentType, RepoConfig};
+use crate::domain::{ApiError, Content, ContentRepository, ContentType, RepoConfig};
 use async_trait::async_trait;
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde_json::Value;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
-use tracing::warn;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Default quiet period before watched changes are reported
+pub const DEFAULT_WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
 
 /// Local filesystem repository that reads from a base directory.
 pub struct LocalFileRepository {
     base_path: PathBuf,
+    /// Keeps the filesystem watcher alive for repositories built with `with_watch`
+    _watcher: Option<RecommendedWatcher>,
 }
 
 impl LocalFileRepository {
@@ -25,16 +35,75 @@ impl LocalFileRepository {
     /// # Examples
     ///
     /// ```
-    /// use gatewayapi::infrastructure::LocalFileRepository;
+    /// use krcbot_kaspacom_gatewayapi::infrastructure::LocalFileRepository;
     ///
     /// let repo = LocalFileRepository::new("/app/data");
     /// ```
     pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
         Self {
             base_path: base_path.as_ref().to_path_buf(),
+            _watcher: None,
         }
     }
 
+    /// Create a repository that watches the base directory for changes.
+    ///
+    /// Files or directories created, removed or renamed anywhere under the
+    /// base path are collected until no further event arrives for
+    /// `debounce`, then `on_change` is called once with the names of the
+    /// affected top-level token directories. This keeps a bulk data sync from
+    /// triggering one callback per file.
+    ///
+    /// Must be called from within a Tokio runtime. If the base path is not
+    /// available or the watcher cannot be started, this behaves like
+    /// [`LocalFileRepository::new`].
+    pub fn with_watch<P, F>(base_path: P, debounce: Duration, on_change: F) -> Self
+    where
+        P: AsRef<Path>,
+        F: Fn(HashSet<String>) + Send + 'static,
+    {
+        let mut repo = Self::new(base_path);
+        if !repo.is_available() {
+            debug!("Not watching {:?}: path is not available", repo.base_path);
+            return repo;
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel::<PathBuf>();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) if is_structural_change(&event.kind) => {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Filesystem watch error: {}", e),
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&repo.base_path, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match watcher {
+            Ok(watcher) => repo._watcher = Some(watcher),
+            Err(e) => {
+                warn!("Failed to watch {:?}: {}", repo.base_path, e);
+                return repo;
+            }
+        }
+        info!("Watching {:?} for changes", repo.base_path);
+
+        // Both spellings of the base path, since some platforms report
+        // canonicalized event paths
+        let bases: Vec<PathBuf> = std::iter::once(repo.base_path.clone())
+            .chain(repo.base_path.canonicalize().ok())
+            .collect();
+        // Ends when the repository (and so the watcher and sender) is dropped
+        tokio::spawn(report_debounced(rx, bases, debounce, on_change));
+
+        repo
+    }
+
     /// Check if the base path exists and is accessible.
     pub fn is_available(&self) -> bool {
         self.base_path.exists() && self.base_path.is_dir()
@@ -108,6 +177,7 @@ impl LocalFileRepository {
                 name,
                 path: format!("data/{}", relative_path),
                 item_type,
+                sha: None,
                 content: None,
                 encoding: None,
                 html_url: None,
@@ -145,6 +215,7 @@ impl LocalFileRepository {
             name: file_name,
             path: format!("data/{}", relative_path),
             item_type: ContentType::File,
+            sha: None,
             content: Some(encoded),
             encoding: Some("base64".to_string()),
             html_url: None,
@@ -154,13 +225,29 @@ impl LocalFileRepository {
     }
 }
 
+/// Creations, removals and renames change directory listings; content
+/// edits and metadata changes do not.
+fn is_structural_change(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    )
+}
+
+/// First path component below the base directory, skipping hidden entries
+fn token_of(bases: &[PathBuf], path: &Path) -> Option<String> {
+    let relative = bases.iter().find_map(|base| path.strip_prefix(base).ok())?;
+    let token = relative.components().next()?.as_os_str().to_string_lossy().to_string();
+    (!token.starts_with('.')).then_some(token)
+}
+
 #[async_trait]
 impl ContentRepository for LocalFileRepository {
     async fn get_content(&self, _config: &RepoConfig, path: &str) -> anyhow::Result<Content> {
         let file_path = self.resolve_path(path);
 
         if !file_path.exists() {
-            anyhow::bail!("File not found: {}", file_path.display());
+            return Err(ApiError::NotFound(format!("File not found: {}", file_path.display())).into());
         }
 
         if file_path.is_dir() {
@@ -179,7 +266,7 @@ impl ContentRepository for LocalFileRepository {
         let dir_path = self.resolve_path(path);
 
         if !dir_path.exists() {
-            anyhow::bail!("Directory not found: {}", dir_path.display());
+            return Err(ApiError::NotFound(format!("Directory not found: {}", dir_path.display())).into());
         }
 
         if !dir_path.is_dir() {
@@ -219,3 +306,89 @@ impl ContentRepository for LocalFileRepository {
     }
 }
 
+
+/// Report the token directories of changed paths from `rx`, one call per
+/// burst of changes separated by at least `debounce` of quiet
+async fn report_debounced<F>(
+    mut rx: mpsc::UnboundedReceiver<PathBuf>,
+    bases: Vec<PathBuf>,
+    debounce: Duration,
+    on_change: F,
+) where
+    F: Fn(HashSet<String>),
+{
+    while let Some(first) = rx.recv().await {
+        let mut tokens = HashSet::new();
+        tokens.extend(token_of(&bases, &first));
+        while let Ok(Some(path)) = tokio::time::timeout(debounce, rx.recv()).await {
+            tokens.extend(token_of(&bases, &path));
+        }
+        if !tokens.is_empty() {
+            debug!("Local data changed for tokens: {:?}", tokens);
+            on_change(tokens);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_reports_changed_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _repo = LocalFileRepository::with_watch(dir.path(), Duration::from_millis(50), move |tokens| {
+            let _ = tx.send(tokens);
+        });
+
+        std::fs::create_dir_all(dir.path().join("NACHO").join("mexc")).unwrap();
+        std::fs::create_dir_all(dir.path().join("SLOW").join("xeggex")).unwrap();
+        std::fs::write(dir.path().join("NACHO").join("mexc").join("2025-01-01.json"), "{}").unwrap();
+
+        // Event delivery timing varies, so collect reports until both show up
+        let expected = HashSet::from(["NACHO".to_string(), "SLOW".to_string()]);
+        let mut seen = HashSet::new();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !seen.is_superset(&expected) {
+                seen.extend(rx.recv().await.unwrap());
+            }
+        })
+        .await
+        .expect("no change notification for both tokens");
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_changes_are_debounced_per_burst() {
+        let base = PathBuf::from("/data");
+        let (paths, rx) = mpsc::unbounded_channel();
+        let (tx, mut reports) = mpsc::unbounded_channel();
+        tokio::spawn(report_debounced(rx, vec![base.clone()], Duration::from_millis(300), move |tokens| {
+            let _ = tx.send(tokens);
+        }));
+
+        // One burst: each change arrives within the quiet period of the last
+        for path in ["NACHO/mexc", "SLOW/xeggex", "NACHO/mexc/2025-01-01.json"] {
+            paths.send(base.join(path)).unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        let burst = reports.recv().await.unwrap();
+        assert_eq!(burst, HashSet::from(["NACHO".to_string(), "SLOW".to_string()]));
+        assert!(reports.try_recv().is_err());
+
+        // A later change is its own report
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        paths.send(base.join("KASPY/mexc")).unwrap();
+        assert_eq!(reports.recv().await.unwrap(), HashSet::from(["KASPY".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_watch_is_noop_for_missing_path() {
+        let repo = LocalFileRepository::with_watch("/nonexistent/local-data", DEFAULT_WATCH_DEBOUNCE, |_| {
+            panic!("no events expected")
+        });
+        assert!(!repo.is_available());
+        assert!(repo._watcher.is_none());
+    }
+}
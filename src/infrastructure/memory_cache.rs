@@ -0,0 +1,85 @@
+use crate::domain::CacheRepository;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Process-local [`CacheRepository`] with per-key expiry.
+///
+/// Stands in for Redis in tests and single-instance setups; entries are
+/// not shared between processes and are lost on restart.
+#[derive(Default)]
+pub struct InMemoryCache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+struct Entry {
+    value: String,
+    ttl_seconds: u64,
+    expires_at: Instant,
+}
+
+impl InMemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// TTL the live entry under `key` was stored with
+    pub fn ttl_of(&self, key: &str) -> Option<u64> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.ttl_seconds)
+    }
+}
+
+#[async_trait]
+impl CacheRepository for InMemoryCache {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Ok(Some(entry.value.clone())),
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl_seconds: u64) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(
+            key.to_string(),
+            Entry {
+                value: value.to_string(),
+                ttl_seconds,
+                expires_at: Instant::now() + Duration::from_secs(ttl_seconds),
+            },
+        );
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(key);
+        Ok(())
+    }
+
+    async fn ttl(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries
+            .get(key)
+            .and_then(|entry| entry.expires_at.checked_duration_since(Instant::now()))
+            .map(|remaining| remaining.as_secs()))
+    }
+
+    async fn get_with_ttl(&self, key: &str) -> anyhow::Result<Option<(String, Option<u64>)>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.get(key).and_then(|entry| {
+            let remaining = entry.expires_at.checked_duration_since(Instant::now())?;
+            Some((entry.value.clone(), Some(remaining.as_secs())))
+        }))
+    }
+}
@@ -1,14 +1,23 @@
+pub mod circuit_breaker;
 pub mod github;
 pub mod kaspacom_client;
 pub mod local_file;
+pub mod memory_cache;
 pub mod parquet_store;
 pub mod rate_limiter;
 pub mod redis;
+#[cfg(feature = "datafusion")]
+pub mod sql_query;
+pub mod telemetry;
 
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStatus};
 pub use github::GitHubRepository;
-pub use kaspacom_client::KaspaComClient;
-pub use rate_limiter::RateLimiter;
+pub use kaspacom_client::{KaspaComClient, KaspaComClientConfig};
+pub use rate_limiter::{RateLimitExceeded, RateLimiter};
 pub use local_file::LocalFileRepository;
-pub use parquet_store::{categories as cache_categories, CacheStats, CategoryStats, ParquetStore};
-pub use redis::RedisRepository;
+pub use memory_cache::InMemoryCache;
+pub use parquet_store::{categories as cache_categories, CacheStats, CategoryStats, JsonBatches, ParquetStore};
+pub use redis::{RedisPoolConfig, RedisRepository};
+#[cfg(feature = "datafusion")]
+pub use sql_query::{ParquetSqlEngine, SqlQueryError, SqlQueryResult};
 
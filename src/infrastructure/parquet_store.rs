@@ -4,12 +4,13 @@
 //! using the Parquet columnar format for compression and fast reads.
 
 use anyhow::{Context, Result};
-use arrow::array::{ArrayRef, RecordBatch, StringArray};
+use arrow::array::{Array, ArrayRef, RecordBatch, StringArray};
 use arrow::datatypes::{DataType, Field, Schema};
-use arrow_json::reader::ReaderBuilder;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use arrow_json::reader::{infer_json_schema_from_iterator, ReaderBuilder};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
 use parquet::arrow::ArrowWriter;
 use parquet::basic::Compression;
+use parquet::file::metadata::KeyValue;
 use parquet::file::properties::WriterProperties;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
@@ -21,6 +22,89 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 use utoipa::ToSchema;
 
+/// Parquet key-value metadata naming an entry's layout
+const LAYOUT_METADATA_KEY: &str = "kaspa_cache_layout";
+/// Layout written by [`ParquetStore::write_json`]: one row per record
+const RECORDS_LAYOUT: &str = "records";
+/// Rows per record batch in the records layout
+const RECORDS_BATCH_SIZE: usize = 1024;
+
+/// Records of a cache entry, yielded a batch at a time
+///
+/// Entries in the records layout are read from Parquet one row group batch
+/// (at most [`RECORDS_BATCH_SIZE`] records) per call to `next`, so the whole
+/// entry is never held at once. Other entries are already in memory and
+/// come out as a single batch.
+pub struct JsonBatches(JsonBatchSource);
+
+enum JsonBatchSource {
+    Records(ParquetRecordBatchReader),
+    Value(Option<Value>),
+}
+
+impl JsonBatches {
+    /// Batches of an in-memory payload: an array's items, or the value itself
+    pub fn from_value(value: Value) -> Self {
+        Self(JsonBatchSource::Value(Some(value)))
+    }
+}
+
+impl Iterator for JsonBatches {
+    type Item = Result<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            JsonBatchSource::Records(reader) => reader.next().map(|batch| {
+                match records_to_json(&[&batch?])? {
+                    Value::Array(records) => Ok(records),
+                    other => Ok(vec![other]),
+                }
+            }),
+            JsonBatchSource::Value(value) => value.take().map(|value| match value {
+                Value::Array(items) => Ok(items),
+                other => Ok(vec![other]),
+            }),
+        }
+    }
+}
+
+/// Convert record batches back to a JSON array of records
+fn records_to_json(batches: &[&RecordBatch]) -> Result<Value> {
+    let mut writer = arrow_json::ArrayWriter::new(Vec::new());
+    writer.write_batches(batches)?;
+    writer.finish()?;
+    let buf = writer.into_inner();
+    if buf.is_empty() {
+        return Ok(Value::Array(Vec::new()));
+    }
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+/// Whether a Parquet file was written in the records layout
+fn is_records_layout(builder: &ParquetRecordBatchReaderBuilder<File>) -> bool {
+    builder
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .is_some_and(|kv| {
+            kv.iter()
+                .any(|entry| entry.key == LAYOUT_METADATA_KEY && entry.value.as_deref() == Some(RECORDS_LAYOUT))
+        })
+}
+
+/// Whether a JSON number in a `Float64` column reads back unchanged
+fn fits_f64(value: &Value) -> bool {
+    const MAX_EXACT: u64 = 1 << f64::MANTISSA_DIGITS;
+    match value {
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.unsigned_abs() <= MAX_EXACT,
+            (None, Some(u)) => u <= MAX_EXACT,
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
 /// Cache categories for organizing data
 pub mod categories {
     pub const TOKEN_INFO: &str = "tokens";
@@ -32,6 +116,22 @@ pub mod categories {
     pub const LOGOS: &str = "logos";
     pub const KRC721: &str = "krc721";
     pub const KNS: &str = "kns";
+
+    /// All known categories
+    pub const ALL: [&str; 9] = [
+        TOKEN_INFO,
+        TRADE_STATS,
+        FLOOR_PRICES,
+        HISTORICAL,
+        ORDERS,
+        HOT_MINTS,
+        LOGOS,
+        KRC721,
+        KNS,
+    ];
+
+    /// Categories of array-shaped responses, stored as one row per record
+    pub const COLUMNAR: [&str; 2] = [FLOOR_PRICES, ORDERS];
 }
 
 /// Parquet-based local cache storage
@@ -39,9 +139,15 @@ pub mod categories {
 /// Stores cached API responses as Parquet files organized by category.
 /// Each cached entry also has a corresponding metadata JSON file to track
 /// cache timestamps and TTL.
+///
+/// An optional secondary base path can be configured as a read-only
+/// fallback (e.g. the previous cache volume during a migration). Reads
+/// check the primary path first, then the secondary; writes and deletes
+/// only ever touch the primary path.
 #[derive(Clone)]
 pub struct ParquetStore {
     base_path: PathBuf,
+    secondary_path: Option<PathBuf>,
 }
 
 impl ParquetStore {
@@ -54,19 +160,41 @@ impl ParquetStore {
             warn!("Failed to create cache directory {}: {}", base_path, e);
         }
 
-        Self { base_path: path }
+        Self { base_path: path, secondary_path: None }
+    }
+
+    /// Set a read-only secondary base path used as a fallback for reads
+    pub fn with_secondary_path(mut self, secondary_path: Option<&str>) -> Self {
+        self.secondary_path = secondary_path.map(PathBuf::from);
+        self
     }
 
     /// Get the Parquet file path for a cached entry
     fn parquet_path(&self, category: &str, key: &str) -> PathBuf {
-        let category_path = self.base_path.join(category);
-        category_path.join(format!("{}.parquet", key))
+        Self::parquet_path_in(&self.base_path, category, key)
     }
 
     /// Get the metadata JSON file path for a cached entry
     fn metadata_path(&self, category: &str, key: &str) -> PathBuf {
-        let category_path = self.base_path.join(category);
-        category_path.join(format!("{}.meta.json", key))
+        Self::metadata_path_in(&self.base_path, category, key)
+    }
+
+    fn parquet_path_in(base: &Path, category: &str, key: &str) -> PathBuf {
+        base.join(category).join(format!("{}.parquet", key))
+    }
+
+    fn metadata_path_in(base: &Path, category: &str, key: &str) -> PathBuf {
+        base.join(category).join(format!("{}.meta.json", key))
+    }
+
+    /// Resolve the base path to read an entry from
+    ///
+    /// Returns the primary path if the entry exists there, otherwise the
+    /// secondary path if the entry exists there, otherwise None.
+    fn read_base(&self, category: &str, key: &str) -> Option<&Path> {
+        std::iter::once(self.base_path.as_path())
+            .chain(self.secondary_path.as_deref())
+            .find(|base| Self::parquet_path_in(base, category, key).exists())
     }
 
     /// Ensure the category directory exists
@@ -79,26 +207,21 @@ impl ParquetStore {
 
     /// Check if a cached entry exists and is not expired
     pub fn is_valid(&self, category: &str, key: &str, max_age_secs: u64) -> bool {
-        let meta_path = self.metadata_path(category, key);
-        let parquet_path = self.parquet_path(category, key);
+        self.age_secs(category, key).is_some_and(|age| age < max_age_secs)
+    }
 
-        // Both files must exist
-        if !meta_path.exists() || !parquet_path.exists() {
-            return false;
-        }
+    /// Seconds since a cached entry was written, from its metadata
+    ///
+    /// Returns `None` if the entry or its metadata is missing or unreadable.
+    pub fn age_secs(&self, category: &str, key: &str) -> Option<u64> {
+        self.metadata(category, key).map(|meta| meta.age_secs())
+    }
 
-        // Check metadata for expiration
-        match self.read_metadata(&meta_path) {
-            Ok(meta) => {
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                let age = now.saturating_sub(meta.cached_at as u64);
-                age < max_age_secs
-            }
-            Err(_) => false,
-        }
+    /// Metadata of a cached entry, if the entry and its metadata exist
+    pub fn metadata(&self, category: &str, key: &str) -> Option<CacheMetadata> {
+        let base = self.read_base(category, key)?;
+        // Metadata must exist alongside the Parquet file
+        self.read_metadata(&Self::metadata_path_in(base, category, key)).ok()
     }
 
     /// Read cache metadata from JSON file
@@ -110,74 +233,132 @@ impl ParquetStore {
     }
 
     /// Write cache metadata to JSON file
+    ///
+    /// A pin set on the existing entry is carried over, so refreshing a
+    /// pinned entry keeps it pinned.
     fn write_metadata(&self, path: &Path, ttl_seconds: u64) -> Result<()> {
-        let meta = CacheMetadata::new(ttl_seconds);
+        let mut meta = CacheMetadata::new(ttl_seconds);
+        meta.pinned = self.read_metadata(path).is_ok_and(|existing| existing.pinned);
         let file = File::create(path)?;
         serde_json::to_writer_pretty(file, &meta)?;
         Ok(())
     }
 
-    /// Write JSON value to Parquet file
+    /// Check if a cached entry is pinned
+    ///
+    /// Pinned entries are never removed by [`Self::cleanup_expired`] and may
+    /// be served past their TTL.
+    pub fn is_pinned(&self, category: &str, key: &str) -> bool {
+        self.read_base(category, key)
+            .and_then(|base| self.read_metadata(&Self::metadata_path_in(base, category, key)).ok())
+            .is_some_and(|meta| meta.pinned)
+    }
+
+    /// Pin or unpin an existing entry in the primary cache path
     ///
-    /// This method stores arbitrary JSON as Parquet by converting it to
-    /// Arrow format and writing with compression.
+    /// Returns `false` if there is no such entry.
+    pub fn set_pinned(&self, category: &str, key: &str, pinned: bool) -> Result<bool> {
+        let meta_path = self.metadata_path(category, key);
+        if !self.parquet_path(category, key).exists() {
+            return Ok(false);
+        }
+
+        let mut meta = self
+            .read_metadata(&meta_path)
+            .with_context(|| format!("Failed to read metadata for {}/{}", category, key))?;
+        meta.pinned = pinned;
+        let file = File::create(&meta_path)?;
+        serde_json::to_writer_pretty(file, &meta)?;
+
+        info!("{} cache entry {}/{}", if pinned { "Pinned" } else { "Unpinned" }, category, key);
+        Ok(true)
+    }
+
+    /// Write an array of records as real Parquet columns
+    ///
+    /// Each top-level field becomes a column (nested objects and arrays
+    /// become struct and list columns), so Parquet can compress and query
+    /// them column by column. Payloads that aren't a non-empty array of
+    /// objects, or whose schema can't be inferred, are stored with
+    /// [`Self::write_simple`] instead. [`Self::read_json`] reads both layouts.
     pub fn write_json(&self, category: &str, key: &str, data: &Value, ttl_seconds: u64) -> Result<()> {
+        let records = match data {
+            Value::Array(records) if !records.is_empty() && records.iter().all(Value::is_object) => records,
+            _ => return self.write_simple(category, key, data, ttl_seconds),
+        };
+        if let Err(e) = self.write_records(category, key, records, ttl_seconds) {
+            debug!("Falling back to simple layout for {}/{}: {}", category, key, e);
+            return self.write_simple(category, key, data, ttl_seconds);
+        }
+        Ok(())
+    }
+
+    fn write_records(&self, category: &str, key: &str, records: &[Value], ttl_seconds: u64) -> Result<()> {
         self.ensure_category_dir(category)?;
-        
+
         let parquet_path = self.parquet_path(category, key);
         let meta_path = self.metadata_path(category, key);
 
-        // Wrap single objects in an array for Arrow compatibility
-        let json_array = match data {
-            Value::Array(arr) => format!("[{}]", arr.iter()
-                .map(|v| serde_json::to_string(v).unwrap_or_default())
-                .collect::<Vec<_>>()
-                .join(",")),
-            _ => format!("[{}]", serde_json::to_string(data)?),
-        };
-
-        // Create Arrow schema from JSON
-        let schema = self.infer_schema_from_json(data)?;
-        
-        // Convert JSON to Arrow RecordBatch
-        let cursor = std::io::Cursor::new(json_array.as_bytes());
-        let mut reader = ReaderBuilder::new(Arc::new(schema.clone()))
-            .build(cursor)?;
+        // Decode everything before touching the file, so a bad payload
+        // leaves the previous entry in place for the fallback to replace
+        let schema = Arc::new(self.infer_schema_from_json(records)?);
+        let mut decoder = ReaderBuilder::new(schema.clone())
+            .with_batch_size(RECORDS_BATCH_SIZE)
+            .build_decoder()?;
+        let mut batches = Vec::new();
+        for chunk in records.chunks(RECORDS_BATCH_SIZE) {
+            decoder.serialize(chunk)?;
+            batches.extend(decoder.flush()?);
+        }
 
-        // Create Parquet writer with compression
         let file = File::create(&parquet_path)
             .with_context(|| format!("Failed to create Parquet file: {:?}", parquet_path))?;
 
         let props = WriterProperties::builder()
             .set_compression(Compression::SNAPPY)
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                LAYOUT_METADATA_KEY.to_string(),
+                RECORDS_LAYOUT.to_string(),
+            )]))
             .build();
 
-        let mut writer = ArrowWriter::try_new(file, Arc::new(schema), Some(props))?;
-
-        // Write all batches
-        while let Some(batch) = reader.next() {
-            let batch = batch?;
-            writer.write(&batch)?;
+        let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+        for batch in &batches {
+            writer.write(batch)?;
         }
-
         writer.close()?;
 
         // Write metadata
         self.write_metadata(&meta_path, ttl_seconds)?;
 
-        debug!("Wrote cache entry: {}/{}", category, key);
+        debug!("Wrote cache entry ({} records): {}/{}", records.len(), category, key);
         Ok(())
     }
 
-    /// Infer Arrow schema from JSON value
-    fn infer_schema_from_json(&self, _data: &Value) -> Result<Schema> {
-        // For simplicity, we store complex data as a single JSON string column
-        // This allows flexible schema while still benefiting from Parquet compression
-        let fields = vec![
-            Field::new("data", DataType::Utf8, false),
-            Field::new("cached_at", DataType::Int64, false),
-        ];
-        Ok(Schema::new(fields))
+    /// Infer the Arrow schema of an array of JSON records
+    ///
+    /// A column that holds both integers and floats is widened to `Float64`
+    /// so every value fits. Integers too large for an `f64` to hold exactly
+    /// would still change, so those payloads are rejected and the caller
+    /// falls back to the simple layout.
+    fn infer_schema_from_json(&self, records: &[Value]) -> Result<Schema> {
+        let schema = infer_json_schema_from_iterator(records.iter().map(Ok))?;
+        if schema.fields().is_empty() {
+            anyhow::bail!("records have no fields");
+        }
+        for field in schema.fields() {
+            if field.data_type() != &DataType::Float64 {
+                continue;
+            }
+            let lossy = records
+                .iter()
+                .filter_map(|record| record.get(field.name()))
+                .any(|value| !fits_f64(value));
+            if lossy {
+                anyhow::bail!("column '{}' has integers an f64 can't hold exactly", field.name());
+            }
+        }
+        Ok(schema)
     }
 
     /// Write data with simple schema (JSON string + metadata)
@@ -229,35 +410,61 @@ impl ParquetStore {
     ///
     /// Returns None if the file doesn't exist or is corrupted.
     pub fn read_json(&self, category: &str, key: &str) -> Result<Option<Value>> {
-        let parquet_path = self.parquet_path(category, key);
-
-        if !parquet_path.exists() {
+        let Some(base) = self.read_base(category, key) else {
             return Ok(None);
-        }
+        };
+        let parquet_path = Self::parquet_path_in(base, category, key);
 
         let file = File::open(&parquet_path)
             .with_context(|| format!("Failed to open Parquet file: {:?}", parquet_path))?;
 
         let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+        let is_records = is_records_layout(&builder);
         let mut reader = builder.build()?;
 
-        // Read first batch
-        if let Some(batch) = reader.next() {
-            let batch = batch?;
-            
-            // Get the data column
-            if let Some(col) = batch.column_by_name("data") {
-                if let Some(string_array) = col.as_any().downcast_ref::<StringArray>() {
-                    if let Some(json_str) = string_array.value(0).into() {
-                        let value: Value = serde_json::from_str(json_str)?;
-                        debug!("Read cache entry: {}/{}", category, key);
-                        return Ok(Some(value));
-                    }
-                }
-            }
+        if is_records {
+            let batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+            let value = records_to_json(&batches.iter().collect::<Vec<_>>())?;
+            debug!("Read cache entry (records): {}/{}", category, key);
+            return Ok(Some(value));
         }
 
-        Ok(None)
+        // The simple layout holds one row; an empty file or batch has no entry
+        let Some(batch) = reader.next().transpose()? else {
+            return Ok(None);
+        };
+        let Some(string_array) = batch
+            .column_by_name("data")
+            .and_then(|col| col.as_any().downcast_ref::<StringArray>())
+        else {
+            return Ok(None);
+        };
+        if string_array.is_empty() || string_array.is_null(0) {
+            return Ok(None);
+        }
+
+        let value: Value = serde_json::from_str(string_array.value(0))?;
+        debug!("Read cache entry: {}/{}", category, key);
+        Ok(Some(value))
+    }
+
+    /// Read an entry's records a batch at a time (see [`JsonBatches`])
+    ///
+    /// Returns None if the file doesn't exist or has no entry.
+    pub fn read_json_batches(&self, category: &str, key: &str) -> Result<Option<JsonBatches>> {
+        let Some(base) = self.read_base(category, key) else {
+            return Ok(None);
+        };
+        let parquet_path = Self::parquet_path_in(base, category, key);
+
+        let file = File::open(&parquet_path)
+            .with_context(|| format!("Failed to open Parquet file: {:?}", parquet_path))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+        if is_records_layout(&builder) {
+            let reader = builder.with_batch_size(RECORDS_BATCH_SIZE).build()?;
+            return Ok(Some(JsonBatches(JsonBatchSource::Records(reader))));
+        }
+        Ok(self.read_json(category, key)?.map(JsonBatches::from_value))
     }
 
     /// Read and deserialize typed data from cache
@@ -290,7 +497,7 @@ impl ParquetStore {
             let entry = entry?;
             let path = entry.path();
             
-            if path.extension().map_or(false, |ext| ext == "parquet") {
+            if path.extension().is_some_and(|ext| ext == "parquet") {
                 if let Some(stem) = path.file_stem() {
                     keys.push(stem.to_string_lossy().to_string());
                 }
@@ -322,7 +529,7 @@ impl ParquetStore {
         let mut deleted = 0;
 
         for key in keys {
-            if !self.is_valid(category, &key, max_age_secs) {
+            if !self.is_valid(category, &key, max_age_secs) && !self.is_pinned(category, &key) {
                 self.delete(category, &key)?;
                 deleted += 1;
             }
@@ -335,25 +542,84 @@ impl ParquetStore {
         Ok(deleted)
     }
 
+    /// Delete the oldest entries until the primary path fits in `max_bytes`
+    ///
+    /// Entries are ordered by `cached_at` (falling back to file mtime when
+    /// metadata is unreadable) across all categories, oldest first. Pinned
+    /// entries are never evicted but still count towards the total, so the
+    /// cap can't be met if pinned entries alone exceed it.
+    pub fn evict_to_size(&self, max_bytes: u64) -> Result<EvictionReport> {
+        let mut entries = Vec::new();
+        for dir in fs::read_dir(&self.base_path)? {
+            let dir = dir?;
+            if !dir.file_type()?.is_dir() {
+                continue;
+            }
+            let category = dir.file_name().to_string_lossy().to_string();
+            for key in self.list_keys(&category)? {
+                let parquet_path = self.parquet_path(&category, &key);
+                let meta_path = self.metadata_path(&category, &key);
+                let Ok(parquet_meta) = fs::metadata(&parquet_path) else {
+                    continue;
+                };
+                let size = parquet_meta.len() + fs::metadata(&meta_path).map_or(0, |m| m.len());
+                let meta = self.read_metadata(&meta_path).ok();
+                let written_at = meta.as_ref().map_or_else(
+                    || {
+                        parquet_meta
+                            .modified()
+                            .ok()
+                            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                            .map_or(0, |d| d.as_secs() as i64)
+                    },
+                    |meta| meta.cached_at,
+                );
+                let pinned = meta.is_some_and(|meta| meta.pinned);
+                entries.push((written_at, category.clone(), key, size, pinned));
+            }
+        }
+
+        let mut total: u64 = entries.iter().map(|(_, _, _, size, _)| size).sum();
+        let mut report = EvictionReport::default();
+        entries.sort_by_key(|(written_at, ..)| *written_at);
+
+        for (_, category, key, size, pinned) in entries {
+            if total <= max_bytes {
+                break;
+            }
+            if pinned {
+                continue;
+            }
+            // One undeletable entry shouldn't stop the rest of the eviction
+            if let Err(e) = self.delete(&category, &key) {
+                warn!("Failed to evict cache entry {}/{}: {}", category, key, e);
+                continue;
+            }
+            total = total.saturating_sub(size);
+            report.entries_removed += 1;
+            report.bytes_reclaimed += size;
+        }
+        report.bytes_remaining = total;
+
+        if report.entries_removed > 0 {
+            info!(
+                "Evicted {} cache entries ({} bytes), {} bytes remain",
+                report.entries_removed, report.bytes_reclaimed, report.bytes_remaining
+            );
+        }
+        Ok(report)
+    }
+
     /// Get cache statistics
     pub fn get_stats(&self) -> Result<CacheStats> {
         let mut total_keys = 0;
         let mut total_size = 0u64;
         let mut category_stats = std::collections::HashMap::new();
 
-        for category in &[
-            categories::TOKEN_INFO,
-            categories::TRADE_STATS,
-            categories::FLOOR_PRICES,
-            categories::HISTORICAL,
-            categories::ORDERS,
-            categories::HOT_MINTS,
-            categories::LOGOS,
-            categories::KRC721,
-            categories::KNS,
-        ] {
+        for category in &categories::ALL {
             let keys = self.list_keys(category).unwrap_or_default();
             let mut cat_size = 0u64;
+            let pinned = keys.iter().filter(|key| self.is_pinned(category, key)).count();
             
             if !keys.is_empty() {
                 // Calculate size
@@ -371,11 +637,15 @@ impl ParquetStore {
 
             category_stats.insert(category.to_string(), CategoryStats {
                 keys: keys.len(),
+                pinned,
                 size_bytes: cat_size,
                 description: self.get_category_description(category),
                 hits: 0, // Will be set by CacheService
+                redis_hits: 0, // Will be set by CacheService
+                parquet_hits: 0, // Will be set by CacheService
                 misses: 0, // Will be set by CacheService
                 requests: 0, // Will be set by CacheService
+                hit_ratio: 0.0, // Will be set by CacheService
             });
         }
 
@@ -384,8 +654,15 @@ impl ParquetStore {
             total_size_bytes: total_size,
             categories_count: category_stats.len(),
             base_path: self.base_path.to_string_lossy().to_string(),
+            secondary_path: self
+                .secondary_path
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
             categories: category_stats,
             cache_hits: 0, // Will be set by CacheService
+            redis_hits: 0, // Will be set by CacheService
+            parquet_hits: 0, // Will be set by CacheService
+            circuit_breaker: None, // Will be set by CacheService
         })
     }
 
@@ -405,6 +682,15 @@ impl ParquetStore {
     }
 }
 
+/// Outcome of [`ParquetStore::evict_to_size`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvictionReport {
+    pub entries_removed: usize,
+    pub bytes_reclaimed: u64,
+    /// Size of the primary cache path after eviction
+    pub bytes_remaining: u64,
+}
+
 /// Cache metadata stored alongside each Parquet file
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CacheMetadata {
@@ -414,6 +700,9 @@ pub struct CacheMetadata {
     pub source: String,
     /// TTL in seconds
     pub ttl_seconds: u64,
+    /// Pinned entries survive cleanup and are served past their TTL
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 impl CacheMetadata {
@@ -422,25 +711,60 @@ impl CacheMetadata {
             cached_at: chrono::Utc::now().timestamp(),
             source: "api.kaspa.com".to_string(),
             ttl_seconds,
+            pinned: false,
         }
     }
+
+    /// Seconds since the entry was cached
+    pub fn age_secs(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        now.saturating_sub(self.cached_at as u64)
+    }
 }
 
 /// Detailed statistics for a cache category
-#[derive(Debug, Clone, serde::Serialize, ToSchema)]
+#[derive(Debug, Clone, Default, serde::Serialize, ToSchema)]
 pub struct CategoryStats {
     pub keys: usize,
+    /// Number of pinned entries in this category
+    #[serde(default)]
+    pub pinned: usize,
     pub size_bytes: u64,
     pub description: String,
-    /// Number of cache hits for this category
+    /// Number of cache hits for this category (both tiers)
     #[serde(default)]
     pub hits: u64,
+    /// Hits served by Redis (hot tier)
+    #[serde(default)]
+    pub redis_hits: u64,
+    /// Hits served by Parquet (warm tier) after a Redis miss
+    #[serde(default)]
+    pub parquet_hits: u64,
     /// Number of cache misses for this category
     #[serde(default)]
     pub misses: u64,
     /// Total number of requests for this category
     #[serde(default)]
     pub requests: u64,
+    /// Share of requests served from cache (0.0 with no requests)
+    #[serde(default)]
+    pub hit_ratio: f64,
+}
+
+impl CategoryStats {
+    /// Set the request counters and the totals derived from them
+    pub fn set_counts(&mut self, redis_hits: u64, parquet_hits: u64, misses: u64, requests: u64) {
+        let hits = redis_hits + parquet_hits;
+        self.redis_hits = redis_hits;
+        self.parquet_hits = parquet_hits;
+        self.hits = hits;
+        self.misses = misses;
+        self.requests = requests;
+        self.hit_ratio = if requests == 0 { 0.0 } else { hits as f64 / requests as f64 };
+    }
 }
 
 /// Cache statistics
@@ -450,10 +774,22 @@ pub struct CacheStats {
     pub total_size_bytes: u64,
     pub categories_count: usize,
     pub base_path: String,
+    /// Read-only fallback cache path, if configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secondary_path: Option<String>,
     pub categories: std::collections::HashMap<String, CategoryStats>,
     /// Number of requests served from cache (incremented on cache hits)
     #[serde(default)]
     pub cache_hits: u64,
+    /// Hits served by Redis across all categories
+    #[serde(default)]
+    pub redis_hits: u64,
+    /// Hits served by Parquet across all categories
+    #[serde(default)]
+    pub parquet_hits: u64,
+    /// kaspa.com circuit breaker state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<crate::infrastructure::CircuitBreakerStatus>,
 }
 
 #[cfg(test)]
@@ -502,26 +838,277 @@ mod tests {
         assert!(keys.contains(&"NACHO".to_string()));
     }
 
+    #[test]
+    fn test_secondary_path_read_through() {
+        let primary = tempdir().unwrap();
+        let secondary = tempdir().unwrap();
+
+        ParquetStore::new(secondary.path().to_str().unwrap())
+            .write_simple("tokens", "SLOW", &json!({"source": "old"}), 3600)
+            .unwrap();
+
+        let store = ParquetStore::new(primary.path().to_str().unwrap())
+            .with_secondary_path(secondary.path().to_str());
+
+        // Falls back to the secondary path on read
+        assert!(store.is_valid("tokens", "SLOW", 3600));
+        assert_eq!(store.read_json("tokens", "SLOW").unwrap().unwrap()["source"], "old");
+
+        // Writes land in the primary path, which then takes precedence
+        store.write_simple("tokens", "SLOW", &json!({"source": "new"}), 3600).unwrap();
+        assert!(primary.path().join("tokens").join("SLOW.parquet").exists());
+        assert_eq!(store.read_json("tokens", "SLOW").unwrap().unwrap()["source"], "new");
+    }
+
+    #[test]
+    fn test_pinned_entries_survive_cleanup_and_rewrites() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+
+        store.write_simple("logos", "SLOW", &json!({"a": 1}), 0).unwrap();
+        store.write_simple("logos", "NACHO", &json!({"b": 2}), 0).unwrap();
+        assert!(store.set_pinned("logos", "SLOW", true).unwrap());
+        assert!(!store.set_pinned("logos", "MISSING", true).unwrap());
+
+        // Both are expired, only the unpinned one is removed
+        assert_eq!(store.cleanup_expired("logos", 0).unwrap(), 1);
+        assert_eq!(store.list_keys("logos").unwrap(), vec!["SLOW".to_string()]);
+
+        // A refresh keeps the pin until it is explicitly removed
+        store.write_simple("logos", "SLOW", &json!({"a": 2}), 3600).unwrap();
+        assert!(store.is_pinned("logos", "SLOW"));
+        assert!(store.set_pinned("logos", "SLOW", false).unwrap());
+        assert!(!store.is_pinned("logos", "SLOW"));
+    }
+
+    #[test]
+    fn test_simple_layout_round_trip() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+
+        for payload in [json!({"ticker": "NACHO", "note": "ünïcode \"quoted\""}), json!([1, "two"]), json!(null)] {
+            store.write_simple("tokens", "NACHO", &payload, 60).unwrap();
+            assert_eq!(store.read_json("tokens", "NACHO").unwrap(), Some(payload));
+        }
+    }
+
+    #[test]
+    fn test_read_empty_parquet_file_is_a_miss() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+        store.ensure_category_dir("tokens").unwrap();
+
+        // Valid Parquet with the simple layout's schema but no rows
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("data", DataType::Utf8, false),
+            Field::new("cached_at", DataType::Int64, false),
+        ]));
+        let file = File::create(store.parquet_path("tokens", "EMPTY")).unwrap();
+        ArrowWriter::try_new(file, schema, None).unwrap().close().unwrap();
+
+        assert_eq!(store.read_json("tokens", "EMPTY").unwrap(), None);
+        assert!(store.read::<Value>("tokens", "EMPTY").unwrap().is_none());
+    }
+
+    /// A sold-orders payload shaped like the upstream API's
+    fn sold_orders(count: usize) -> Value {
+        let tickers = ["NACHO", "KASPER", "KREX", "GHOAD", "BURT"];
+        Value::Array(
+            (0..count)
+                .map(|i| {
+                    json!({
+                        "_id": format!("66f1c0ffee{:08x}", i),
+                        "ticker": tickers[i % tickers.len()],
+                        "amount": 1000 + (i * 37) % 50000,
+                        "pricePerToken": 0.00012 + (i % 97) as f64 * 0.000001,
+                        "totalPrice": 12.5 + (i % 89) as f64,
+                        "sellerAddress": format!("kaspa:qz{:060}", i % 300),
+                        "buyerAddress": if i % 10 == 0 { Value::Null } else { json!(format!("kaspa:qr{:060}", i % 700)) },
+                        "createdAt": 1_727_000_000_000i64 + i as i64 * 60_000,
+                        "status": "completed",
+                        "fulfillmentTimestamp": 1_727_000_030_000i64 + i as i64 * 60_000,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn file_size(store: &ParquetStore, category: &str, key: &str) -> u64 {
+        fs::metadata(store.parquet_path(category, key)).unwrap().len()
+    }
+
+    #[test]
+    fn test_records_layout_round_trips_and_compresses() {
+        use crate::domain::SoldOrder;
+
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+        let payload = sold_orders(5000);
+
+        store.write_simple("orders", "simple", &payload, 3600).unwrap();
+        store.write_json("orders", "records", &payload, 3600).unwrap();
+
+        let simple = file_size(&store, "orders", "simple");
+        let records = file_size(&store, "orders", "records");
+        // Typically ~40x smaller; require a clear margin, not just "smaller"
+        assert!(
+            records * 10 < simple,
+            "records layout ({records} bytes) should be under a tenth of simple ({simple} bytes)"
+        );
+
+        let orders: Vec<SoldOrder> = store.read("orders", "records").unwrap().unwrap();
+        assert_eq!(orders.len(), 5000);
+        assert_eq!(orders[1].id, "66f1c0ffee00000001");
+        assert_eq!(orders[1].amount, 1037);
+        assert_eq!(orders[0].buyer_address, None);
+        assert_eq!(orders[1].buyer_address.as_deref(), payload[1]["buyerAddress"].as_str());
+        assert_eq!(orders[4999].created_at, payload[4999]["createdAt"].as_i64().unwrap());
+    }
+
+    #[test]
+    fn test_records_are_read_a_batch_at_a_time() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+        let payload = sold_orders(2500);
+        store.write_json("orders", "records", &payload, 3600).unwrap();
+        store.write_simple("orders", "simple", &payload, 3600).unwrap();
+
+        let batches: Vec<_> = store
+            .read_json_batches("orders", "records")
+            .unwrap()
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect();
+        let sizes: Vec<_> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [RECORDS_BATCH_SIZE, RECORDS_BATCH_SIZE, 2500 - 2 * RECORDS_BATCH_SIZE]);
+        assert_eq!(batches[2].last().unwrap()["createdAt"], payload[2499]["createdAt"]);
+
+        // The simple layout is one batch
+        let simple: Vec<_> = store.read_json_batches("orders", "simple").unwrap().unwrap().collect();
+        assert_eq!(simple.len(), 1);
+        assert!(store.read_json_batches("orders", "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_irregular_payloads_fall_back_to_simple_layout() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+
+        for (key, payload) in [
+            ("object", json!({"orders": [], "cursor": null})),
+            ("empty", json!([])),
+            ("scalars", json!([1, 2, 3])),
+        ] {
+            store.write_json("orders", key, &payload, 3600).unwrap();
+            assert_eq!(store.read_json("orders", key).unwrap().unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn test_mixed_numeric_columns_are_widened() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+
+        // Integers and floats in one column share a Float64 column
+        let payload = json!([{"price": 1, "n": 7}, {"price": 1.5, "n": 8}]);
+        store.write_json("orders", "mixed", &payload, 3600).unwrap();
+        let read = store.read_json("orders", "mixed").unwrap().unwrap();
+        assert_eq!(read[0]["price"].as_f64(), Some(1.0));
+        assert_eq!(read[1]["price"].as_f64(), Some(1.5));
+        assert_eq!(read[1]["n"], 8);
+
+        // An integer a Float64 can't hold exactly keeps the simple layout
+        let payload = json!([{"price": 9_007_199_254_740_993i64}, {"price": 1.5}]);
+        store.write_json("orders", "wide", &payload, 3600).unwrap();
+        assert_eq!(store.read_json("orders", "wide").unwrap().unwrap(), payload);
+    }
+
+    /// Rewrite an entry's `cached_at` to `age_secs` ago
+    fn backdate(store: &ParquetStore, category: &str, key: &str, age_secs: i64) {
+        let path = store.metadata_path(category, key);
+        let mut meta = store.read_metadata(&path).unwrap();
+        meta.cached_at -= age_secs;
+        serde_json::to_writer(File::create(&path).unwrap(), &meta).unwrap();
+    }
+
+    #[test]
+    fn test_evict_to_size_keeps_freshest_entries() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+
+        let payload = json!({"candles": "x".repeat(4096)});
+        for (i, key) in ["OLDEST", "OLD", "NEW", "NEWEST"].iter().enumerate() {
+            store.write_simple("historical", key, &payload, 3600).unwrap();
+            backdate(&store, "historical", key, 400 - 100 * i as i64);
+        }
+        store.write_simple("logos", "PINNED", &payload, 3600).unwrap();
+        backdate(&store, "logos", "PINNED", 1000);
+        store.set_pinned("logos", "PINNED", true).unwrap();
+
+        let before = store.evict_to_size(u64::MAX).unwrap();
+        assert_eq!(before.entries_removed, 0);
+        let cap = before.bytes_remaining * 7 / 10;
+
+        let report = store.evict_to_size(cap).unwrap();
+        assert!(report.bytes_remaining <= cap);
+        assert_eq!(report.bytes_reclaimed, before.bytes_remaining - report.bytes_remaining);
+        assert_eq!(report.entries_removed, 2);
+
+        let mut kept = store.list_keys("historical").unwrap();
+        kept.sort();
+        assert_eq!(kept, vec!["NEW".to_string(), "NEWEST".to_string()]);
+        assert!(store.is_pinned("logos", "PINNED"));
+    }
+
+    #[test]
+    fn test_evict_to_size_skips_entries_it_cannot_delete() {
+        let dir = tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+
+        let payload = json!({"candles": "x".repeat(4096)});
+        for (i, key) in ["BROKEN", "OLD", "NEW"].iter().enumerate() {
+            store.write_simple("historical", key, &payload, 3600).unwrap();
+            backdate(&store, "historical", key, 300 - 100 * i as i64);
+        }
+        // A directory where the oldest entry's file should be can't be removed
+        let broken = store.parquet_path("historical", "BROKEN");
+        fs::remove_file(&broken).unwrap();
+        fs::create_dir(&broken).unwrap();
+        fs::write(broken.join("stray"), "x").unwrap();
+
+        let report = store.evict_to_size(0).unwrap();
+        assert_eq!(report.entries_removed, 2);
+        assert_eq!(store.list_keys("historical").unwrap(), vec!["BROKEN".to_string()]);
+    }
+
     #[test]
     fn test_cache_stats_serialization() {
         let mut categories = std::collections::HashMap::new();
         categories.insert("test".to_string(), CategoryStats {
             keys: 10,
+            pinned: 0,
             size_bytes: 1000,
             description: "Test".to_string(),
             hits: 0,
+            redis_hits: 0,
+            parquet_hits: 0,
             misses: 0,
             requests: 0,
+            hit_ratio: 0.0,
         });
 
         let mut cat_stats = std::collections::HashMap::new();
         cat_stats.insert("test".to_string(), CategoryStats {
             keys: 10,
+            pinned: 0,
             size_bytes: 1000,
             description: "Test".to_string(),
             hits: 0,
+            redis_hits: 0,
+            parquet_hits: 0,
             misses: 0,
             requests: 0,
+            hit_ratio: 0.0,
         });
         
         let stats = CacheStats {
@@ -529,8 +1116,12 @@ mod tests {
             total_size_bytes: 1000,
             categories_count: 1,
             base_path: "data".to_string(),
+            secondary_path: None,
             categories: cat_stats,
             cache_hits: 0,
+            redis_hits: 0,
+            parquet_hits: 0,
+            circuit_breaker: None,
         };
 
         let json = serde_json::to_string(&stats).unwrap();
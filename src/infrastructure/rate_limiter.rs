@@ -1,44 +1,192 @@
 //! Rate limiter for kaspa.com API requests.
 //!
-//! Implements a sliding window rate limiter to track and enforce
-//! request limits to the kaspa.com API.
+//! Implements a sliding-window-log rate limiter to track and enforce
+//! request limits to the kaspa.com API. Every accepted request is logged
+//! with its timestamp and only requests in the trailing 60 seconds count,
+//! so no 60-second span ever exceeds the configured limit (unlike a
+//! fixed window, which allows a double burst across a boundary).
+//!
+//! Categories (e.g. `historical`, `floor_prices`) can be given their own
+//! limit so that a burst in one category cannot starve the others.
+//! Categories without a dedicated limit share the global bucket.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Source of the current time, injectable for tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Clock backed by [`Instant::now`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when advanced manually
+#[cfg(test)]
+pub(crate) struct MockClock {
+    start: Instant,
+    offset: std::sync::Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            start: Instant::now(),
+            offset: std::sync::Mutex::new(Duration::ZERO),
+        })
+    }
+
+    pub(crate) fn advance(&self, by: Duration) {
+        *self.offset.lock().unwrap() += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + *self.offset.lock().unwrap()
+    }
+}
+
 /// Rate limiter for tracking API requests
 #[derive(Clone)]
 pub struct RateLimiter {
+    clock: Arc<dyn Clock>,
     limit: u32,
     window: Duration,
     requests: Arc<RwLock<Vec<Instant>>>,
+    /// Dedicated per-category limits (requests per window)
+    category_limits: HashMap<String, u32>,
+    /// Request log for each category with a dedicated limit
+    category_requests: Arc<RwLock<HashMap<String, Vec<Instant>>>>,
 }
 
 impl RateLimiter {
     /// Create a new rate limiter with the specified requests per minute
     pub fn new(requests_per_minute: u32) -> Self {
+        Self::with_clock(requests_per_minute, Arc::new(SystemClock))
+    }
+
+    /// Create a new rate limiter driven by the given clock
+    pub fn with_clock(requests_per_minute: u32, clock: Arc<dyn Clock>) -> Self {
         Self {
+            clock,
             limit: requests_per_minute,
             window: Duration::from_secs(60),
             requests: Arc::new(RwLock::new(Vec::new())),
+            category_limits: HashMap::new(),
+            category_requests: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    /// Check if a request is allowed and record it if so
+    /// Give categories their own requests-per-minute limit
+    ///
+    /// Categories not listed here fall back to the global bucket.
+    pub fn with_category_limits(mut self, category_limits: HashMap<String, u32>) -> Self {
+        self.category_limits = category_limits;
+        self
+    }
+
+    /// Check if a request is allowed against the global bucket and record it if so
     ///
     /// Returns true if the request is allowed, false if rate limit exceeded
     pub async fn check_and_record(&self) -> bool {
-        let now = Instant::now();
-        let window_start = now - self.window;
-
+        let now = self.clock.now();
         let mut requests = self.requests.write().await;
-        
+        Self::try_record(&mut requests, self.limit, self.window, now)
+    }
+
+    /// Check if a request for a category is allowed and record it if so
+    ///
+    /// Uses the category's dedicated bucket when one is configured,
+    /// otherwise the global bucket.
+    pub async fn check_and_record_for(&self, category: &str) -> bool {
+        let Some(&limit) = self.category_limits.get(category) else {
+            return self.check_and_record().await;
+        };
+
+        let now = self.clock.now();
+        let mut all = self.category_requests.write().await;
+        let requests = all.entry(category.to_string()).or_default();
+        Self::try_record(requests, limit, self.window, now)
+    }
+
+    /// Get the limit that applies to a category
+    pub fn limit_for(&self, category: &str) -> u32 {
+        self.category_limits
+            .get(category)
+            .copied()
+            .unwrap_or(self.limit)
+    }
+
+    /// Get the remaining budget for a category in the current window
+    pub async fn remaining_for(&self, category: &str) -> u32 {
+        let window_start = self.window_start(self.clock.now());
+        match self.category_limits.get(category) {
+            Some(&limit) => {
+                let all = self.category_requests.read().await;
+                let used = all
+                    .get(category)
+                    .map_or(0, |requests| Self::count_since(requests, window_start));
+                limit.saturating_sub(used)
+            }
+            None => {
+                let requests = self.requests.read().await;
+                self.limit
+                    .saturating_sub(Self::count_since(&requests, window_start))
+            }
+        }
+    }
+
+    /// Seconds until a request for a category would be accepted again
+    ///
+    /// This is when the oldest request in the category's window expires,
+    /// rounded up so callers never retry a moment too early.
+    pub async fn retry_after_for(&self, category: &str) -> u64 {
+        let now = self.clock.now();
+        let window_start = self.window_start(now);
+        let oldest = match self.category_limits.get(category) {
+            Some(_) => {
+                let all = self.category_requests.read().await;
+                all.get(category)
+                    .and_then(|requests| Self::oldest_since(requests, window_start))
+            }
+            None => Self::oldest_since(&self.requests.read().await, window_start),
+        };
+        oldest.map_or(0, |oldest| {
+            let wait = (oldest + self.window).saturating_duration_since(now);
+            wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+        })
+    }
+
+    /// Oldest request recorded after the window start
+    fn oldest_since(requests: &[Instant], window_start: Instant) -> Option<Instant> {
+        requests
+            .iter()
+            .copied()
+            .filter(|&time| time > window_start)
+            .min()
+    }
+
+    /// Drop expired entries from a request log and record a new request if under the limit
+    fn try_record(requests: &mut Vec<Instant>, limit: u32, window: Duration, now: Instant) -> bool {
+        let window_start = now.checked_sub(window).unwrap_or(now);
+
         // Remove requests outside the current window
         requests.retain(|&time| time > window_start);
 
         // Check if we're under the limit
-        if requests.len() < self.limit as usize {
+        if requests.len() < limit as usize {
             requests.push(now);
             true
         } else {
@@ -46,40 +194,127 @@ impl RateLimiter {
         }
     }
 
+    /// Start of the sliding window ending at `now`
+    fn window_start(&self, now: Instant) -> Instant {
+        now.checked_sub(self.window).unwrap_or(now)
+    }
+
+    /// Count requests recorded after the window start
+    fn count_since(requests: &[Instant], window_start: Instant) -> u32 {
+        requests.iter().filter(|&&time| time > window_start).count() as u32
+    }
+
     /// Get current rate limit statistics
     pub async fn get_stats(&self) -> RateLimitStats {
-        let now = Instant::now();
-        let window_start = now - self.window;
+        let now = self.clock.now();
+        let window_start = self.window_start(now);
 
         let requests = self.requests.read().await;
-        
+
         // Count requests in current window
-        let used = requests.iter().filter(|&&time| time > window_start).count() as u32;
-        
-        // Calculate reset time (next minute boundary)
+        let used = Self::count_since(&requests, window_start);
+
+        // Reset time of a request log: when its oldest request in the window expires
         let system_now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default();
-        let current_second = system_now.as_secs() % 60;
-        let seconds_until_reset = 60 - current_second;
-        let reset_timestamp = system_now.as_secs() as i64 + seconds_until_reset as i64;
+        let reset_at = |requests: &[Instant]| {
+            let seconds_until_reset =
+                Self::oldest_since(requests, window_start).map_or(0, |oldest| {
+                    (oldest + self.window)
+                        .saturating_duration_since(now)
+                        .as_secs()
+                });
+            system_now.as_secs() as i64 + seconds_until_reset as i64
+        };
+
+        // Per-category budgets for categories with a dedicated limit
+        let category_requests = self.category_requests.read().await;
+        let categories = self
+            .category_limits
+            .iter()
+            .map(|(category, &limit)| {
+                let requests = category_requests
+                    .get(category)
+                    .map_or(&[][..], Vec::as_slice);
+                let used = Self::count_since(requests, window_start);
+                (
+                    category.clone(),
+                    CategoryRateLimitStats {
+                        limit,
+                        remaining: limit.saturating_sub(used),
+                        used,
+                        reset: reset_at(requests),
+                    },
+                )
+            })
+            .collect();
 
         RateLimitStats {
             limit: self.limit,
             remaining: self.limit.saturating_sub(used),
             used,
-            reset: reset_timestamp,
+            current_usage: used,
+            reset: reset_at(&requests),
+            categories,
         }
     }
 }
 
+/// Error returned when a request is rejected by the rate limiter
+///
+/// Carried inside `anyhow::Error` so API layers can downcast it and give
+/// clients a structured backoff hint.
+#[derive(Debug, Clone)]
+pub struct RateLimitExceeded {
+    pub category: String,
+    pub limit: u32,
+    /// Seconds until the bucket has room again
+    pub retry_after_secs: u64,
+}
+
+impl std::fmt::Display for RateLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Rate limit exceeded: {} requests/minute limit reached for {}. Please wait before retrying.",
+            self.limit, self.category
+        )
+    }
+}
+
+impl std::error::Error for RateLimitExceeded {}
+
+impl From<&RateLimitExceeded> for crate::domain::ApiError {
+    fn from(limited: &RateLimitExceeded) -> Self {
+        Self::RateLimited {
+            message: limited.to_string(),
+            retry_after_secs: limited.retry_after_secs,
+        }
+    }
+}
+
+/// Rate limit statistics for a category with a dedicated limit
+#[derive(Debug, Clone)]
+pub struct CategoryRateLimitStats {
+    pub limit: u32,
+    pub remaining: u32,
+    pub used: u32,
+    /// When this category's oldest request in the window expires (Unix timestamp)
+    pub reset: i64,
+}
+
 /// Rate limit statistics
 #[derive(Debug, Clone)]
 pub struct RateLimitStats {
     pub limit: u32,
     pub remaining: u32,
     pub used: u32,
+    /// Requests counted in the trailing 60-second window
+    pub current_usage: u32,
     pub reset: i64, // Unix timestamp
+    /// Budgets for categories with a dedicated limit
+    pub categories: HashMap<String, CategoryRateLimitStats>,
 }
 
 #[cfg(test)]
@@ -89,12 +324,12 @@ mod tests {
     #[tokio::test]
     async fn test_rate_limiter_allows_requests_within_limit() {
         let limiter = RateLimiter::new(5);
-        
+
         // First 5 requests should be allowed
         for _ in 0..5 {
             assert!(limiter.check_and_record().await);
         }
-        
+
         // 6th request should be denied
         assert!(!limiter.check_and_record().await);
     }
@@ -102,12 +337,12 @@ mod tests {
     #[tokio::test]
     async fn test_rate_limiter_resets_after_window() {
         let limiter = RateLimiter::new(2);
-        
+
         // Use up the limit
         assert!(limiter.check_and_record().await);
         assert!(limiter.check_and_record().await);
         assert!(!limiter.check_and_record().await);
-        
+
         // Wait for window to pass (in real scenario, this would be 60 seconds)
         // For testing, we'll just verify the logic works
         let stats = limiter.get_stats().await;
@@ -117,31 +352,116 @@ mod tests {
     #[tokio::test]
     async fn test_rate_limiter_stats() {
         let limiter = RateLimiter::new(10);
-        
+
         // Make some requests
         for _ in 0..3 {
             limiter.check_and_record().await;
         }
-        
+
         let stats = limiter.get_stats().await;
         assert_eq!(stats.limit, 10);
         assert_eq!(stats.used, 3);
         assert_eq!(stats.remaining, 7);
+        assert_eq!(stats.current_usage, 3);
         assert!(stats.reset > 0);
     }
 
+    #[tokio::test]
+    async fn test_rate_limiter_no_double_burst_across_minute_boundary() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::with_clock(4, clock.clone());
+
+        // Burst at ":59"
+        clock.advance(Duration::from_secs(59));
+        for _ in 0..4 {
+            assert!(limiter.check_and_record().await);
+        }
+
+        // Two seconds later (":01") a fixed window would have reset; the
+        // sliding window still counts the earlier burst
+        clock.advance(Duration::from_secs(2));
+        assert!(!limiter.check_and_record().await);
+        assert_eq!(limiter.get_stats().await.current_usage, 4);
+
+        // Just before the burst leaves the window it is still denied
+        clock.advance(Duration::from_secs(57));
+        assert!(!limiter.check_and_record().await);
+
+        // Once 60 seconds have passed since the burst, budget frees up
+        clock.advance(Duration::from_secs(1));
+        assert!(limiter.check_and_record().await);
+        let stats = limiter.get_stats().await;
+        assert_eq!(stats.current_usage, 1);
+        assert_eq!(stats.limit, 4);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_smooths_requests_over_window() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::with_clock(2, clock.clone());
+
+        assert!(limiter.check_and_record().await);
+        clock.advance(Duration::from_secs(30));
+        assert!(limiter.check_and_record().await);
+        assert!(!limiter.check_and_record().await);
+
+        // First request expires at t=60, second at t=90
+        clock.advance(Duration::from_secs(30));
+        assert!(limiter.check_and_record().await);
+        assert!(!limiter.check_and_record().await);
+
+        clock.advance(Duration::from_secs(30));
+        assert!(limiter.check_and_record().await);
+        assert_eq!(limiter.get_stats().await.current_usage, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_category_limits() {
+        let limiter = RateLimiter::new(3)
+            .with_category_limits(HashMap::from([("historical".to_string(), 1)]));
+
+        // Dedicated bucket is exhausted independently
+        assert!(limiter.check_and_record_for("historical").await);
+        assert!(!limiter.check_and_record_for("historical").await);
+
+        // Unlisted categories share the global bucket with check_and_record
+        assert!(limiter.check_and_record_for("floor_prices").await);
+        assert!(limiter.check_and_record().await);
+        assert_eq!(limiter.remaining_for("floor_prices").await, 1);
+
+        let stats = limiter.get_stats().await;
+        assert_eq!(stats.used, 2);
+        assert_eq!(stats.categories["historical"].remaining, 0);
+        assert_eq!(limiter.limit_for("historical"), 1);
+        assert_eq!(limiter.limit_for("floor_prices"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_category_reset_follows_its_own_requests() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::with_clock(3, clock.clone())
+            .with_category_limits(HashMap::from([("historical".to_string(), 1)]));
+
+        // The category's request expires 30 seconds before the global one
+        assert!(limiter.check_and_record_for("historical").await);
+        clock.advance(Duration::from_secs(30));
+        assert!(limiter.check_and_record().await);
+
+        let stats = limiter.get_stats().await;
+        assert_eq!(stats.reset - stats.categories["historical"].reset, 30);
+    }
+
     #[tokio::test]
     async fn test_rate_limiter_high_limit() {
         let limiter = RateLimiter::new(1000);
-        
+
         // Should allow many requests
         for _ in 0..100 {
             assert!(limiter.check_and_record().await);
         }
-        
+
         let stats = limiter.get_stats().await;
         assert_eq!(stats.used, 100);
         assert_eq!(stats.remaining, 900);
     }
 }
-
@@ -1,8 +1,48 @@
 use crate::domain::CacheRepository;
 use async_trait::async_trait;
 use deadpool_redis::redis::AsyncCommands;
-use deadpool_redis::{Config, Pool, Runtime};
-use tracing::{error, info};
+use deadpool_redis::{Config, Pool, PoolConfig, Runtime, Timeouts};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info};
+
+/// Connection pool settings for [`RedisRepository`]
+#[derive(Deserialize, Debug, Clone)]
+pub struct RedisPoolConfig {
+    /// Maximum open connections (default: 16)
+    #[serde(default = "default_max_size")]
+    pub max_size: usize,
+    /// Milliseconds to wait for a free or new connection before treating
+    /// Redis as unavailable (default: 2000)
+    #[serde(default = "default_connection_timeout_ms")]
+    pub connection_timeout_ms: u64,
+    /// Seconds an unused connection is kept open; 0 keeps them forever
+    /// (default: 300)
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for RedisPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: default_max_size(),
+            connection_timeout_ms: default_connection_timeout_ms(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+        }
+    }
+}
+
+fn default_max_size() -> usize {
+    16
+}
+
+fn default_connection_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    300
+}
 
 pub struct RedisRepository {
     pool: Option<Pool>,
@@ -10,24 +50,109 @@ pub struct RedisRepository {
 
 impl RedisRepository {
     pub fn new(url: Option<String>) -> Self {
-        if let Some(redis_url) = url {
-            match Config::from_url(&redis_url).create_pool(Some(Runtime::Tokio1)) {
-                Ok(pool) => {
-                    info!("Redis connection pool initialized");
-                    Self { pool: Some(pool) }
-                }
-                Err(e) => {
-                    error!("Failed to create Redis connection pool: {}", e);
-                    Self { pool: None }
+        Self::with_config(url, &RedisPoolConfig::default())
+    }
+
+    /// Create a repository with explicit pool settings
+    ///
+    /// Connections are opened lazily, so an unreachable Redis only shows up
+    /// as failed lookups (treated as cache misses) and in [`Self::ping`].
+    pub fn with_config(url: Option<String>, config: &RedisPoolConfig) -> Self {
+        let Some(redis_url) = url else {
+            info!("Redis URL not provided, caching disabled");
+            return Self { pool: None };
+        };
+
+        let timeout = Some(Duration::from_millis(config.connection_timeout_ms));
+        let mut pool_config = Config::from_url(redis_url);
+        pool_config.pool = Some(PoolConfig {
+            max_size: config.max_size.max(1),
+            timeouts: Timeouts {
+                wait: timeout,
+                create: timeout,
+                recycle: timeout,
+            },
+            ..PoolConfig::default()
+        });
+
+        match pool_config.create_pool(Some(Runtime::Tokio1)) {
+            Ok(pool) => {
+                info!(
+                    "Redis connection pool initialized (max {} connections, {}ms timeout)",
+                    config.max_size, config.connection_timeout_ms
+                );
+                if config.idle_timeout_secs > 0 {
+                    spawn_idle_reaper(&pool, Duration::from_secs(config.idle_timeout_secs));
                 }
+                Self { pool: Some(pool) }
             }
-        } else {
-            info!("Redis URL not provided, caching disabled");
-            Self { pool: None }
+            Err(e) => {
+                error!("Failed to create Redis connection pool: {}", e);
+                Self { pool: None }
+            }
+        }
+    }
+
+    /// Whether a Redis URL was configured
+    pub fn is_configured(&self) -> bool {
+        self.pool.is_some()
+    }
+
+    /// Send a PING and return its round-trip time
+    ///
+    /// Fails if Redis is unconfigured or unreachable. The outcome is
+    /// exported as the `redis_up` and `redis_ping_latency_seconds` gauges.
+    pub async fn ping(&self) -> anyhow::Result<Duration> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Redis is not configured"))?;
+
+        let started = Instant::now();
+        let result: anyhow::Result<()> = async {
+            let mut conn = pool.get().await?;
+            let _: String = deadpool_redis::redis::cmd("PING").query_async(&mut conn).await?;
+            Ok(())
         }
+        .await;
+        let latency = started.elapsed();
+
+        metrics::gauge!("redis_up").set(if result.is_ok() { 1.0 } else { 0.0 });
+        if result.is_ok() {
+            metrics::gauge!("redis_ping_latency_seconds").set(latency.as_secs_f64());
+        }
+        result.map(|()| latency)
     }
 }
 
+/// Periodically close connections unused for longer than `idle_timeout`
+///
+/// Holds only a weak reference, so the task ends with the pool. Skipped
+/// outside a Tokio runtime.
+fn spawn_idle_reaper(pool: &Pool, idle_timeout: Duration) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let weak = pool.weak();
+    let period = (idle_timeout / 2).max(Duration::from_secs(1));
+    runtime.spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            let Some(pool) = weak.upgrade() else {
+                break;
+            };
+            let closed = pool
+                .retain(|_, metrics| metrics.last_used() < idle_timeout)
+                .removed
+                .len();
+            if closed > 0 {
+                debug!("Closed {} idle Redis connections", closed);
+            }
+        }
+    });
+}
+
 #[async_trait]
 impl CacheRepository for RedisRepository {
     async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
@@ -60,4 +185,77 @@ impl CacheRepository for RedisRepository {
         }
         Ok(())
     }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        if let Some(pool) = &self.pool {
+            match pool.get().await {
+                Ok(mut conn) => {
+                    let _: i64 = conn.del(key).await?;
+                }
+                Err(e) => {
+                    error!("Failed to get Redis connection from pool: {}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn ttl(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        if let Some(pool) = &self.pool {
+            match pool.get().await {
+                Ok(mut conn) => {
+                    // -2: no such key, -1: no expiry
+                    let ttl: i64 = conn.ttl(key).await?;
+                    return Ok(u64::try_from(ttl).ok());
+                }
+                Err(e) => {
+                    error!("Failed to get Redis connection from pool: {}", e);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_with_ttl(&self, key: &str) -> anyhow::Result<Option<(String, Option<u64>)>> {
+        if let Some(pool) = &self.pool {
+            match pool.get().await {
+                Ok(mut conn) => {
+                    // One pipelined round trip; failures read as a miss, like `get`
+                    let result: Option<(Option<String>, i64)> = deadpool_redis::redis::pipe()
+                        .get(key)
+                        .ttl(key)
+                        .query_async(&mut conn)
+                        .await
+                        .ok();
+                    return Ok(result.and_then(|(value, ttl)| Some((value?, u64::try_from(ttl).ok()))));
+                }
+                Err(e) => {
+                    error!("Failed to get Redis connection from pool: {}", e);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nothing listens on port 1, so connections are refused
+    const DEAD_REDIS_URL: &str = "redis://127.0.0.1:1";
+
+    #[tokio::test]
+    async fn test_dead_redis_reads_as_cache_miss() {
+        let config = RedisPoolConfig {
+            connection_timeout_ms: 200,
+            ..RedisPoolConfig::default()
+        };
+        let redis = RedisRepository::with_config(Some(DEAD_REDIS_URL.to_string()), &config);
+
+        assert!(redis.is_configured());
+        assert!(redis.ping().await.is_err());
+        assert_eq!(redis.get("kaspa:any").await.unwrap(), None);
+        assert!(redis.set("kaspa:any", "1", 60).await.is_ok());
+    }
 }
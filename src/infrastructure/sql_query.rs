@@ -0,0 +1,293 @@
+//! Read-only SQL over the Parquet cache (requires the `datafusion` feature).
+//!
+//! Each cache category directory that holds Parquet files is registered as
+//! an external table named after the category (e.g. `historical`,
+//! `orders`), with the `data` (JSON string) and `cached_at` columns written
+//! by [`ParquetStore`](super::ParquetStore). Entries stored in the records
+//! layout (floor prices, orders) add one column per record field; rows from
+//! the other layout have those columns null.
+//!
+//! Only a single `SELECT` (or `WITH ... SELECT`) statement is accepted; DDL,
+//! DML and session statements are rejected by the planner as well. Results
+//! are capped at a row limit and a wall-clock timeout.
+
+use super::parquet_store::categories;
+use datafusion::execution::options::ParquetReadOptions;
+use datafusion::prelude::{SQLOptions, SessionContext};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::debug;
+use utoipa::ToSchema;
+
+/// Default maximum number of rows returned by a query
+pub const DEFAULT_MAX_ROWS: usize = 1000;
+/// Default query timeout in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Result of a SQL query
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SqlQueryResult {
+    /// Column names in result order
+    pub columns: Vec<String>,
+    /// Rows as JSON objects keyed by column name
+    pub rows: Vec<Value>,
+    /// Number of rows returned
+    pub row_count: usize,
+    /// True if more rows matched than the row limit allowed
+    pub truncated: bool,
+    /// Time spent planning and executing the query
+    pub elapsed_ms: u64,
+}
+
+/// Reasons a SQL query can fail
+#[derive(Debug)]
+pub enum SqlQueryError {
+    /// The statement is not a single read-only SELECT
+    Rejected(String),
+    /// The statement is a SELECT but could not be planned or executed
+    Invalid(String),
+    /// The query ran longer than the configured timeout
+    Timeout(Duration),
+}
+
+impl std::fmt::Display for SqlQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected(msg) => write!(f, "Query rejected: {}", msg),
+            Self::Invalid(msg) => write!(f, "Query failed: {}", msg),
+            Self::Timeout(limit) => write!(f, "Query exceeded the {}s time limit", limit.as_secs()),
+        }
+    }
+}
+
+impl std::error::Error for SqlQueryError {}
+
+/// DataFusion-backed query engine over a Parquet cache directory
+#[derive(Debug, Clone)]
+pub struct ParquetSqlEngine {
+    base_path: PathBuf,
+    max_rows: usize,
+    timeout: Duration,
+}
+
+impl ParquetSqlEngine {
+    /// Create an engine over the given Parquet cache directory
+    pub fn new(base_path: &str) -> Self {
+        Self {
+            base_path: PathBuf::from(base_path),
+            max_rows: DEFAULT_MAX_ROWS,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        }
+    }
+
+    /// Set the row and time limits applied to every query
+    pub fn with_limits(mut self, max_rows: usize, timeout: Duration) -> Self {
+        self.max_rows = max_rows.max(1);
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run a read-only SQL statement and return its rows as JSON
+    pub async fn query(&self, sql: &str) -> Result<SqlQueryResult, SqlQueryError> {
+        let sql = Self::validate(sql)?;
+        let started = Instant::now();
+
+        let result = tokio::time::timeout(self.timeout, self.execute(sql)).await;
+        let (columns, rows, truncated) = result.map_err(|_| SqlQueryError::Timeout(self.timeout))??;
+
+        Ok(SqlQueryResult {
+            row_count: rows.len(),
+            columns,
+            rows,
+            truncated,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Accept a single SELECT/WITH statement, ignoring one trailing semicolon
+    fn validate(sql: &str) -> Result<&str, SqlQueryError> {
+        let sql = sql.trim();
+        let sql = sql.strip_suffix(';').unwrap_or(sql).trim_end();
+
+        if sql.is_empty() {
+            return Err(SqlQueryError::Rejected("statement is empty".to_string()));
+        }
+        if sql.contains(';') {
+            return Err(SqlQueryError::Rejected(
+                "only a single statement is allowed".to_string(),
+            ));
+        }
+
+        let keyword = sql
+            .trim_start_matches('(')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        if keyword != "SELECT" && keyword != "WITH" {
+            return Err(SqlQueryError::Rejected(
+                "only SELECT statements are allowed".to_string(),
+            ));
+        }
+
+        Ok(sql)
+    }
+
+    async fn execute(&self, sql: &str) -> Result<(Vec<String>, Vec<Value>, bool), SqlQueryError> {
+        let ctx = SessionContext::new();
+        for category in categories::ALL {
+            let dir = self.base_path.join(category);
+            if !Self::has_parquet_files(&dir) {
+                continue;
+            }
+            ctx.register_parquet(
+                category,
+                dir.to_string_lossy().as_ref(),
+                ParquetReadOptions::default().file_extension(".parquet"),
+            )
+            .await
+            .map_err(|e| SqlQueryError::Invalid(e.to_string()))?;
+            debug!("Registered SQL table '{}' from {:?}", category, dir);
+        }
+
+        let options = SQLOptions::new()
+            .with_allow_ddl(false)
+            .with_allow_dml(false)
+            .with_allow_statements(false);
+        let df = ctx
+            .sql_with_options(sql, options)
+            .await
+            .map_err(|e| SqlQueryError::Invalid(e.to_string()))?;
+
+        let columns = df
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+
+        // Fetch one extra row to detect truncation
+        let batches = df
+            .limit(0, Some(self.max_rows + 1))
+            .map_err(|e| SqlQueryError::Invalid(e.to_string()))?
+            .collect()
+            .await
+            .map_err(|e| SqlQueryError::Invalid(e.to_string()))?;
+
+        let mut writer = arrow_json::ArrayWriter::new(Vec::new());
+        writer
+            .write_batches(&batches.iter().collect::<Vec<_>>())
+            .and_then(|_| writer.finish())
+            .map_err(|e| SqlQueryError::Invalid(e.to_string()))?;
+        let buf = writer.into_inner();
+        let mut rows: Vec<Value> = if buf.is_empty() {
+            Vec::new()
+        } else {
+            serde_json::from_slice(&buf).map_err(|e| SqlQueryError::Invalid(e.to_string()))?
+        };
+
+        let truncated = rows.len() > self.max_rows;
+        rows.truncate(self.max_rows);
+        Ok((columns, rows, truncated))
+    }
+
+    fn has_parquet_files(dir: &Path) -> bool {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.path().extension().is_some_and(|ext| ext == "parquet"))
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::ParquetStore;
+    use serde_json::json;
+
+    fn engine_with_entries(dir: &Path, count: usize) -> ParquetSqlEngine {
+        let store = ParquetStore::new(dir.to_str().unwrap());
+        for i in 0..count {
+            store
+                .write_simple(categories::FLOOR_PRICES, &format!("T{}", i), &json!({"i": i}), 60)
+                .unwrap();
+        }
+        ParquetSqlEngine::new(dir.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_select_over_cache_category() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine_with_entries(dir.path(), 3);
+
+        let result = engine
+            .query("SELECT count(*) AS n FROM floor_prices;")
+            .await
+            .unwrap();
+
+        assert_eq!(result.columns, vec!["n"]);
+        assert_eq!(result.rows, vec![json!({"n": 3})]);
+        assert!(!result.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_row_limit_truncates() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine_with_entries(dir.path(), 5).with_limits(2, Duration::from_secs(10));
+
+        let result = engine.query("SELECT data FROM floor_prices").await.unwrap();
+
+        assert_eq!(result.row_count, 2);
+        assert!(result.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_select_statements() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine_with_entries(dir.path(), 1);
+
+        for sql in [
+            "DROP TABLE floor_prices",
+            "INSERT INTO floor_prices VALUES ('x', 1)",
+            "CREATE EXTERNAL TABLE t STORED AS PARQUET LOCATION '/etc'",
+            "SELECT 1; SELECT 2",
+            "SET datafusion.execution.batch_size = 1",
+            "",
+        ] {
+            assert!(
+                matches!(engine.query(sql).await, Err(SqlQueryError::Rejected(_))),
+                "{} should be rejected",
+                sql
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_columns_are_queryable_next_to_simple_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ParquetStore::new(dir.path().to_str().unwrap());
+        store
+            .write_json(
+                categories::FLOOR_PRICES,
+                "all",
+                &json!([{"ticker": "NACHO", "floor_price": 0.5}, {"ticker": "KREX", "floor_price": 2.0}]),
+                60,
+            )
+            .unwrap();
+        store
+            .write_simple(categories::FLOOR_PRICES, "NACHO", &json!({"ticker": "NACHO"}), 60)
+            .unwrap();
+        let engine = ParquetSqlEngine::new(dir.path().to_str().unwrap());
+
+        let result = engine
+            .query("SELECT ticker FROM floor_prices WHERE floor_price > 1")
+            .await
+            .unwrap();
+        assert_eq!(result.rows, vec![json!({"ticker": "KREX"})]);
+    }
+}
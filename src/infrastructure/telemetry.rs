@@ -0,0 +1,181 @@
+//! OpenTelemetry trace export and W3C trace-context propagation.
+//!
+//! Export is enabled by setting `OTEL_EXPORTER_OTLP_ENDPOINT` (e.g.
+//! `http://localhost:4318`); spans are then sent over OTLP/HTTP in batches.
+//! When it is unset no tracer is installed and the helpers here are no-ops,
+//! so logging behaves exactly as before.
+//!
+//! Incoming `traceparent` headers become the parent of the `http_request`
+//! span, and the current span's context is injected into kaspa.com and
+//! GitHub requests so a trace follows a request into the upstreams.
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_http::{Bytes, HeaderExtractor, HeaderInjector, HttpError, Request, Response};
+use opentelemetry_otlp::WithHttpConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use reqwest::header::HeaderMap;
+use reqwest::RequestBuilder;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Environment variable holding the OTLP collector endpoint
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Service name reported on exported spans
+const SERVICE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Timeout for one OTLP export request
+const EXPORT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// OTLP/HTTP client built on the gateway's own reqwest
+///
+/// The batch exporter sends from its own thread, outside the tokio runtime,
+/// so this wraps reqwest's blocking client.
+#[derive(Debug)]
+struct ExportClient(reqwest::blocking::Client);
+
+impl ExportClient {
+    fn new() -> anyhow::Result<Self> {
+        // The blocking client starts its own runtime, which can't happen on a
+        // runtime thread
+        let client = std::thread::spawn(|| {
+            reqwest::blocking::Client::builder()
+                .timeout(EXPORT_TIMEOUT)
+                .build()
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("Thread building the OTLP HTTP client panicked"))?
+        .context("Failed to build the OTLP HTTP client")?;
+        Ok(Self(client))
+    }
+}
+
+#[async_trait]
+impl opentelemetry_http::HttpClient for ExportClient {
+    async fn send_bytes(&self, request: Request<Bytes>) -> Result<Response<Bytes>, HttpError> {
+        let response = self.0.execute(request.try_into()?)?;
+        let mut builder = Response::builder().status(response.status());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = response.headers().clone();
+        }
+        Ok(builder.body(response.bytes()?)?)
+    }
+}
+
+/// Build an OTLP tracer provider if `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+///
+/// The exporter reads the endpoint (and the other standard `OTEL_EXPORTER_OTLP_*`
+/// variables) itself. Call [`SdkTracerProvider::shutdown`] before exiting
+/// to flush buffered spans.
+pub fn tracer_provider_from_env() -> anyhow::Result<Option<SdkTracerProvider>> {
+    if std::env::var(OTLP_ENDPOINT_ENV).ok().filter(|v| !v.trim().is_empty()).is_none() {
+        return Ok(None);
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_http_client(ExportClient::new()?)
+        .build()
+        .context("Failed to build OTLP span exporter")?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+
+    Ok(Some(provider))
+}
+
+/// Tracer for the `tracing-opentelemetry` layer
+pub fn tracer(provider: &SdkTracerProvider) -> SdkTracer {
+    provider.tracer(SERVICE_NAME)
+}
+
+/// Parent the given span on the trace context carried by incoming headers
+pub fn set_parent_from_headers(span: &tracing::Span, headers: &HeaderMap) {
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    let _ = span.set_parent(parent);
+}
+
+/// Add `traceparent`/`tracestate` headers for the current span to a request
+pub fn inject_trace_context(request: RequestBuilder) -> RequestBuilder {
+    let context = tracing::Span::current().context();
+    let mut headers = HeaderMap::new();
+    TraceContextPropagator::new().inject_context(&context, &mut HeaderInjector(&mut headers));
+    request.headers(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_incoming_trace_context_is_propagated_upstream() {
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer(&provider)));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut incoming = HeaderMap::new();
+            incoming.insert("traceparent", TRACEPARENT.parse().unwrap());
+
+            let span = tracing::info_span!("http_request");
+            set_parent_from_headers(&span, &incoming);
+            let _entered = span.enter();
+
+            let request = inject_trace_context(reqwest::Client::new().get("http://localhost/"))
+                .build()
+                .unwrap();
+            let traceparent = request.headers()["traceparent"].to_str().unwrap();
+            assert!(
+                traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"),
+                "{}",
+                traceparent
+            );
+            assert!(span.context().span().span_context().is_valid());
+        });
+    }
+
+    #[test]
+    fn test_no_headers_without_tracer() {
+        let request = inject_trace_context(reqwest::Client::new().get("http://localhost/"))
+            .build()
+            .unwrap();
+        assert!(!request.headers().contains_key("traceparent"));
+    }
+
+    #[tokio::test]
+    async fn test_export_client_returns_status_and_body() {
+        use opentelemetry_http::HttpClient;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = axum::Router::new().fallback(|body: axum::body::Bytes| async move {
+            (axum::http::StatusCode::ACCEPTED, body)
+        });
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = ExportClient::new().unwrap();
+        let request = Request::post(format!("http://{}/v1/traces", addr))
+            .body(Bytes::from_static(b"spans"))
+            .unwrap();
+        // Sent the way the batch exporter does: blocking, off the runtime
+        let response = tokio::task::spawn_blocking(move || {
+            futures::executor::block_on(client.send_bytes(request))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(response.status(), 202);
+        assert_eq!(response.body().as_ref(), b"spans");
+    }
+}
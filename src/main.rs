@@ -27,8 +27,13 @@
 //! - `GITHUB_TOKEN`: GitHub personal access token (optional)
 //!   - If set: Uses authenticated requests (5,000 req/hour limit)
 //!   - If not set: Uses unauthenticated requests (60 req/hour limit for public repos)
+//! - `KASPACOM_TOKEN`: kaspa.com API token (optional)
+//!   - If set: Sent as `Authorization: Bearer` on every kaspa.com request
 //! - `REDIS_URL`: Redis connection string (default: redis://localhost:6379)
 //! - `RUST_LOG`: Logging level (default: info)
+//! - `OTEL_EXPORTER_OTLP_ENDPOINT`: OTLP/HTTP collector for trace export (optional)
+//! - `CACHE_SECONDARY_PATH`: Read-only fallback Parquet cache directory (optional)
+//! - `ADMIN_API_KEYS`: Comma-separated `identity:key` pairs for admin endpoints (optional)
 //!
 //! # Quick Start
 //!
@@ -46,21 +51,20 @@
 //! curl "http://localhost:3010/v1/api/github/owner/repo/path"
 //! ```
 
-mod api;
-mod application;
-mod domain;
-mod infrastructure;
+use krcbot_kaspacom_gatewayapi::{api, application, domain, infrastructure};
 
 use crate::api::routes::create_router;
 use crate::api::state::AppState;
-use crate::application::{CacheService, ContentService, ExchangeIndex, KaspaComService, TickerService};
+use crate::application::{CacheService, ContentService, ExchangeIndex, KaspaComService, PriceTickFeeds, TickerService};
 use crate::domain::{RepoConfig, TokensConfig};
-use crate::infrastructure::{GitHubRepository, KaspaComClient, LocalFileRepository, ParquetStore, RateLimiter, RedisRepository};
+use crate::infrastructure::{telemetry, GitHubRepository, KaspaComClient, LocalFileRepository, ParquetStore, RateLimiter, RedisRepository};
 use anyhow::Context;
 use serde::Deserialize;
 use std::env;
 use std::fs;
+use std::future::IntoFuture;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 /// Top-level application configuration loaded from `config.yaml`.
@@ -73,22 +77,413 @@ struct Config {
     /// Rate limiting configuration for kaspa.com API
     #[serde(default)]
     rate_limit: RateLimitConfig,
+    /// Background cache warming for configured tokens
+    #[serde(default)]
+    cache_warming: CacheWarmingConfig,
+    /// Redis connection pool settings
+    #[serde(default)]
+    redis: infrastructure::RedisPoolConfig,
+    /// Parquet cache size cap
+    #[serde(default)]
+    cache: CacheConfig,
+    /// Caching of upstream 404s
+    #[serde(default)]
+    negative_cache: NegativeCacheConfig,
+    /// Redis/Parquet TTL overrides per data class (HOT, WARM, COLD, STATIC)
+    #[serde(default)]
+    cache_ttls: application::cache_service::TtlConfig,
+    /// Timeout and retry settings for kaspa.com requests
+    #[serde(default)]
+    kaspacom_client: infrastructure::KaspaComClientConfig,
+    /// Fail-fast behaviour during kaspa.com outages
+    #[serde(default)]
+    circuit_breaker: infrastructure::CircuitBreakerConfig,
+    /// Concurrency settings for bulk endpoints
+    #[serde(default)]
+    bulk: BulkConfig,
+    /// Cross-exchange price aggregation settings
+    #[serde(default)]
+    aggregation: AggregationConfig,
+    /// Token logo image proxy
+    #[serde(default)]
+    logo_proxy: LogoProxyConfig,
+    /// Gateway used to resolve `ipfs://` URIs in NFT responses
+    #[serde(default)]
+    ipfs: IpfsConfig,
+    /// Hot reload of the local data volume
+    #[serde(default)]
+    data_watch: DataWatchConfig,
+    /// Reads from the local data volume
+    #[serde(default)]
+    local_data: LocalDataConfig,
+    /// Saving the exchange index across restarts
+    #[serde(default)]
+    exchange_index: ExchangeIndexConfig,
+    /// Token name search
+    #[serde(default)]
+    ticker_search: TickerSearchConfig,
+    /// Operation names always given their own GraphQL metric label
+    #[serde(default)]
+    graphql_metrics: GraphqlMetricsConfig,
+    /// Broadcast capacity, poll interval and connection cap for live-update feeds
+    #[serde(default)]
+    live_feed: application::live_feed::LiveFeedConfig,
+    /// Read-only SQL over the Parquet cache (`datafusion` feature)
+    #[cfg(feature = "datafusion")]
+    #[serde(default)]
+    sql_query: SqlQueryConfig,
+    /// Cache entries reported by `GET /v1/admin/freshness` with their max age
+    #[serde(default)]
+    freshness: Vec<application::kaspacom_service::FreshnessTarget>,
+    /// Client API keys and the routes that require them
+    #[serde(default)]
+    api_auth: api::auth::ApiAuthConfig,
+    /// Per-exchange data-quality flags and aggregate weights, keyed by exchange name
+    #[serde(default)]
+    exchange_meta: std::collections::HashMap<String, application::ticker_service::ExchangeMeta>,
     /// List of allowed repositories that can be accessed through the API
     allowed_repos: Vec<RepoConfig>,
 }
 
+impl Config {
+    /// First allowed repository, used as the ticker service's default source
+    fn default_repo(&self) -> anyhow::Result<RepoConfig> {
+        self.allowed_repos.first().cloned().context(
+            "No repositories configured: add at least one entry (source, owner, repo) \
+             under `allowed_repos` in config.yaml",
+        )
+    }
+}
+
+/// Limits for the admin SQL query endpoint
+#[cfg(feature = "datafusion")]
+#[derive(Deserialize, Debug, Clone)]
+struct SqlQueryConfig {
+    /// Maximum rows returned per query
+    #[serde(default = "default_sql_max_rows")]
+    max_rows: usize,
+    /// Wall-clock limit per query in seconds
+    #[serde(default = "default_sql_timeout_secs")]
+    timeout_secs: u64,
+}
+
+#[cfg(feature = "datafusion")]
+impl Default for SqlQueryConfig {
+    fn default() -> Self {
+        Self {
+            max_rows: default_sql_max_rows(),
+            timeout_secs: default_sql_timeout_secs(),
+        }
+    }
+}
+
+#[cfg(feature = "datafusion")]
+fn default_sql_max_rows() -> usize {
+    infrastructure::sql_query::DEFAULT_MAX_ROWS
+}
+
+#[cfg(feature = "datafusion")]
+fn default_sql_timeout_secs() -> u64 {
+    infrastructure::sql_query::DEFAULT_TIMEOUT_SECS
+}
+
 /// Rate limiting configuration
 #[derive(Deserialize, Debug, Clone, Default)]
 struct RateLimitConfig {
     /// Maximum requests per minute to kaspa.com API
     #[serde(default = "default_requests_per_minute")]
     requests_per_minute: u32,
+    /// Dedicated per-category limits, keyed by cache category (e.g. `historical`)
+    #[serde(default)]
+    per_category: std::collections::HashMap<String, u32>,
 }
 
 fn default_requests_per_minute() -> u32 {
     1000
 }
 
+/// Bulk endpoint configuration
+#[derive(Deserialize, Debug, Clone)]
+struct BulkConfig {
+    /// Maximum concurrent upstream fetches shared by all bulk requests, and
+    /// the files one aggregated content page fetches at once
+    #[serde(default = "default_bulk_max_concurrency")]
+    max_concurrency: usize,
+}
+
+impl Default for BulkConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: default_bulk_max_concurrency(),
+        }
+    }
+}
+
+fn default_bulk_max_concurrency() -> usize {
+    application::kaspacom_service::DEFAULT_BULK_CONCURRENCY
+}
+
+/// Filesystem watching of `DATA_PATH`
+#[derive(Deserialize, Debug, Clone)]
+struct DataWatchConfig {
+    /// Update the exchange index when token directories change (default: false)
+    #[serde(default = "default_data_watch_enabled")]
+    enabled: bool,
+    /// Quiet period before a burst of changes is applied (default: 2000)
+    #[serde(default = "default_data_watch_debounce_ms")]
+    debounce_ms: u64,
+}
+
+impl Default for DataWatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_data_watch_enabled(),
+            debounce_ms: default_data_watch_debounce_ms(),
+        }
+    }
+}
+
+fn default_data_watch_enabled() -> bool {
+    false
+}
+
+fn default_data_watch_debounce_ms() -> u64 {
+    infrastructure::local_file::DEFAULT_WATCH_DEBOUNCE.as_millis() as u64
+}
+
+/// Local data volume configuration
+#[derive(Deserialize, Debug, Clone, Default)]
+struct LocalDataConfig {
+    /// Read paths missing from the volume from GitHub (default: false)
+    #[serde(default)]
+    github_fallback: bool,
+}
+
+/// Exchange index persistence
+#[derive(Deserialize, Debug, Clone)]
+struct ExchangeIndexConfig {
+    /// Save each rebuild under `CACHE_PATH` and load it at startup (default: true)
+    #[serde(default = "default_exchange_index_persist")]
+    persist: bool,
+    /// Oldest saved index loaded at startup, in seconds (default: 86400)
+    #[serde(default = "default_exchange_index_max_age_secs")]
+    max_age_secs: i64,
+}
+
+impl Default for ExchangeIndexConfig {
+    fn default() -> Self {
+        Self {
+            persist: default_exchange_index_persist(),
+            max_age_secs: default_exchange_index_max_age_secs(),
+        }
+    }
+}
+
+fn default_exchange_index_persist() -> bool {
+    true
+}
+
+fn default_exchange_index_max_age_secs() -> i64 {
+    86400
+}
+
+/// Token name search configuration
+#[derive(Deserialize, Debug, Clone)]
+struct TickerSearchConfig {
+    /// Most results `/v1/api/ticker/search` returns (default: 20)
+    #[serde(default = "default_ticker_search_max_results")]
+    max_results: usize,
+}
+
+impl Default for TickerSearchConfig {
+    fn default() -> Self {
+        Self {
+            max_results: default_ticker_search_max_results(),
+        }
+    }
+}
+
+fn default_ticker_search_max_results() -> usize {
+    application::ticker_service::DEFAULT_MAX_SEARCH_RESULTS
+}
+
+/// GraphQL metric label configuration
+#[derive(Deserialize, Debug, Clone, Default)]
+struct GraphqlMetricsConfig {
+    /// Operation names reserved a label up front, so they're never folded
+    /// into `other` once the per-operation label cap is reached
+    #[serde(default)]
+    operations: Vec<String>,
+}
+
+/// Logo image proxy configuration
+#[derive(Deserialize, Debug, Clone)]
+struct LogoProxyConfig {
+    /// Whether `/v1/api/kaspa/logo/{ticker}` serves images (default: false)
+    #[serde(default)]
+    enabled: bool,
+    /// Largest logo accepted from an upstream host, in bytes (default: 1 MiB)
+    #[serde(default = "default_logo_proxy_max_bytes")]
+    max_bytes: usize,
+}
+
+impl Default for LogoProxyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: default_logo_proxy_max_bytes(),
+        }
+    }
+}
+
+fn default_logo_proxy_max_bytes() -> usize {
+    1024 * 1024
+}
+
+/// IPFS resolution configuration
+#[derive(Deserialize, Debug, Clone)]
+struct IpfsConfig {
+    /// HTTP gateway `ipfs://CID/path` is rewritten to (default: https://ipfs.io/ipfs)
+    #[serde(default = "default_ipfs_gateway")]
+    gateway: String,
+}
+
+impl Default for IpfsConfig {
+    fn default() -> Self {
+        Self {
+            gateway: default_ipfs_gateway(),
+        }
+    }
+}
+
+fn default_ipfs_gateway() -> String {
+    application::kaspacom_service::DEFAULT_IPFS_GATEWAY.to_string()
+}
+
+/// Cross-exchange aggregation configuration
+#[derive(Deserialize, Debug, Clone)]
+struct AggregationConfig {
+    /// Drop exchange prices more than this many median absolute deviations
+    /// from the median before averaging (disabled when unset)
+    #[serde(default)]
+    outlier_mad_threshold: Option<f64>,
+    /// Drop exchange prices deviating more than this percent from the
+    /// median before averaging (disabled when unset)
+    #[serde(default)]
+    outlier_max_deviation_pct: Option<f64>,
+    /// Apply outlier exclusion to the VWAP as well (default: true)
+    #[serde(default = "default_outlier_filter_vwap")]
+    outlier_filter_vwap: bool,
+    /// Repository calls run concurrently across all requests (default: 10)
+    #[serde(default = "default_repo_concurrency")]
+    repo_concurrency: usize,
+    /// Compute change from the first and last price in the requested range
+    /// instead of trusting the data's `percentage` field (default: false)
+    #[serde(default)]
+    change_from_history: bool,
+    /// Days before today searched for an exchange's latest data file
+    /// (default: 2, max: 14); longer ranges search their whole span
+    #[serde(default = "default_stats_lookback_days")]
+    stats_lookback_days: i64,
+}
+
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            outlier_mad_threshold: None,
+            outlier_max_deviation_pct: None,
+            outlier_filter_vwap: default_outlier_filter_vwap(),
+            repo_concurrency: default_repo_concurrency(),
+            change_from_history: false,
+            stats_lookback_days: default_stats_lookback_days(),
+        }
+    }
+}
+
+fn default_outlier_filter_vwap() -> bool {
+    true
+}
+
+fn default_repo_concurrency() -> usize {
+    application::ticker_service::DEFAULT_REPO_CONCURRENCY
+}
+
+fn default_stats_lookback_days() -> i64 {
+    application::ticker_service::DEFAULT_STATS_LOOKBACK_DAYS
+}
+
+/// Cache warming configuration
+#[derive(Deserialize, Debug, Clone)]
+struct CacheWarmingConfig {
+    /// Whether the background warming task runs (default: true)
+    #[serde(default = "default_cache_warming_enabled")]
+    enabled: bool,
+    /// Minutes between warming runs (default: 10)
+    #[serde(default = "default_cache_warming_interval_minutes")]
+    interval_minutes: u64,
+}
+
+impl Default for CacheWarmingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_cache_warming_enabled(),
+            interval_minutes: default_cache_warming_interval_minutes(),
+        }
+    }
+}
+
+fn default_cache_warming_enabled() -> bool {
+    true
+}
+
+fn default_cache_warming_interval_minutes() -> u64 {
+    10
+}
+
+/// Parquet cache size configuration
+#[derive(Deserialize, Debug, Clone)]
+struct CacheConfig {
+    /// Size cap for the Parquet cache in MiB; 0 disables eviction (default: 0)
+    #[serde(default)]
+    max_size_mb: u64,
+    /// Minutes between eviction runs (default: 15)
+    #[serde(default = "default_cache_eviction_interval_minutes")]
+    eviction_interval_minutes: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_size_mb: 0,
+            eviction_interval_minutes: default_cache_eviction_interval_minutes(),
+        }
+    }
+}
+
+fn default_cache_eviction_interval_minutes() -> u64 {
+    15
+}
+
+/// Negative cache configuration
+#[derive(Deserialize, Debug, Clone)]
+struct NegativeCacheConfig {
+    /// Seconds an upstream 404 is remembered; 0 disables (default: 60)
+    #[serde(default = "default_negative_cache_ttl_secs")]
+    ttl_secs: u64,
+}
+
+impl Default for NegativeCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_negative_cache_ttl_secs(),
+        }
+    }
+}
+
+fn default_negative_cache_ttl_secs() -> u64 {
+    application::cache_service::ttl::NEGATIVE_REDIS_SECS
+}
+
 /// Server configuration settings.
 ///
 /// Defines how the HTTP server should bind and what CORS origins to allow.
@@ -100,9 +495,42 @@ struct ServerConfig {
     /// Port number to listen on (default: 3010)
     #[serde(default = "default_port")]
     port: u16,
-    /// Comma-separated list of allowed CORS origins (default: "*")
-    #[serde(default = "default_allowed_origins")]
-    allowed_origins: String,
+    /// Allowed CORS origins, methods and headers (default: any)
+    #[serde(default)]
+    cors: api::cors::CorsConfig,
+    /// Deprecated comma-separated origin list; use `cors.allowed_origins`
+    #[serde(default)]
+    allowed_origins: Option<String>,
+    /// Seconds before an unfinished request is answered with 408 (default: 35)
+    #[serde(default = "default_request_timeout_secs")]
+    request_timeout_secs: u64,
+    /// Largest accepted request body in bytes (default: 1 MiB)
+    #[serde(default = "default_max_body_bytes")]
+    max_body_bytes: usize,
+    /// Seconds in-flight requests may take to finish after a shutdown
+    /// signal before their connections are dropped (default: 20)
+    #[serde(default = "default_shutdown_grace_secs")]
+    shutdown_grace_secs: u64,
+}
+
+impl ServerConfig {
+    /// CORS layer from `cors`, honoring the deprecated `allowed_origins`
+    /// when `cors.allowed_origins` is left at its default
+    fn cors_layer(&self) -> anyhow::Result<tower_http::cors::CorsLayer> {
+        let mut cors = self.cors.clone();
+        if let Some(origins) = &self.allowed_origins {
+            tracing::warn!("server.allowed_origins is deprecated; use server.cors.allowed_origins");
+            if cors.allowed_origins == api::cors::CorsConfig::default().allowed_origins {
+                cors.allowed_origins = origins
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|origin| !origin.is_empty())
+                    .map(String::from)
+                    .collect();
+            }
+        }
+        cors.layer()
+    }
 }
 
 fn default_host() -> String {
@@ -111,8 +539,31 @@ fn default_host() -> String {
 fn default_port() -> u16 {
     3010
 }
-fn default_allowed_origins() -> String {
-    "*".to_string()
+fn default_request_timeout_secs() -> u64 {
+    api::routes::RequestLimits::default().timeout.as_secs()
+}
+fn default_shutdown_grace_secs() -> u64 {
+    20
+}
+fn default_max_body_bytes() -> usize {
+    api::routes::RequestLimits::default().max_body_bytes
+}
+
+/// Parse admin API keys from a comma-separated list of `identity:key` pairs.
+///
+/// Returns a map from key to identity; malformed entries are skipped.
+fn parse_admin_api_keys(raw: &str) -> std::collections::HashMap<String, String> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (identity, key) = entry.trim().split_once(':')?;
+            let (identity, key) = (identity.trim(), key.trim());
+            if identity.is_empty() || key.is_empty() {
+                tracing::warn!("Ignoring malformed ADMIN_API_KEYS entry");
+                return None;
+            }
+            Some((key.to_string(), identity.to_string()))
+        })
+        .collect()
 }
 
 #[tokio::main]
@@ -131,34 +582,82 @@ async fn main() -> anyhow::Result<()> {
         std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
     );
 
+    // Optional OTLP trace export (OTEL_EXPORTER_OTLP_ENDPOINT)
+    let tracer_provider = telemetry::tracer_provider_from_env()?;
+    let otel_layer = tracer_provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(telemetry::tracer(provider)));
+
     if log_format.eq_ignore_ascii_case("json") {
         tracing_subscriber::registry()
             .with(env_filter)
+            .with(otel_layer)
             .with(tracing_subscriber::fmt::layer().json())
             .init();
     } else {
         tracing_subscriber::registry()
             .with(env_filter)
+            .with(otel_layer)
             .with(tracing_subscriber::fmt::layer())
             .init();
     }
+    if tracer_provider.is_some() {
+        tracing::info!("Exporting traces via OTLP");
+    }
 
     // Load Config
     let config_content = fs::read_to_string("config.yaml")
         .context("Failed to read config.yaml - ensure file exists in working directory")?;
     let config: Config = serde_yaml::from_str(&config_content)
         .context("Failed to parse config.yaml - check YAML syntax and structure")?;
+    let cors = config.server.cors_layer().context("Invalid CORS settings under server.cors in config.yaml")?;
 
     let redis_url = env::var("REDIS_URL").ok();
 
     // Infrastructure
-    let github_repo = Arc::new(GitHubRepository::new(github_token));
-    let redis_repo = Arc::new(RedisRepository::new(redis_url));
+    let redis_repo = Arc::new(RedisRepository::with_config(redis_url, &config.redis));
+    // Conditional requests (If-None-Match) keep unchanged files off the rate limit
+    let github_repo = Arc::new(
+        GitHubRepository::new(github_token).with_etag_cache(redis_repo.clone()),
+    );
 
     // Try to initialize local file repository (for Docker volume mounts)
-    let data_path = std::env::var("DATA_PATH").unwrap_or_else(|_| "/app/data".to_string());
+    let configured_data_path = std::env::var("DATA_PATH").ok().filter(|p| !p.is_empty());
+    let data_path = configured_data_path.clone().unwrap_or_else(|| "/app/data".to_string());
+    // Cancelled on SIGTERM/Ctrl+C; stops background tasks and starts the drain
+    let shutdown = CancellationToken::new();
+
+    let cache_path = env::var("CACHE_PATH").unwrap_or_else(|_| "data/cache".to_string());
+
+    let mut exchange_index = ExchangeIndex::new(&data_path).with_shutdown(shutdown.clone());
+    if config.exchange_index.persist {
+        exchange_index = exchange_index.with_persistence(
+            std::path::Path::new(&cache_path).join("exchange-index.json"),
+            config.exchange_index.max_age_secs,
+        );
+    }
+    let exchange_index = Arc::new(exchange_index);
     let local_repo: Option<Arc<LocalFileRepository>> = {
-        let repo = Arc::new(LocalFileRepository::new(&data_path));
+        // Keep the exchange index in step with the data-sync job
+        let repo = if config.data_watch.enabled {
+            let index = exchange_index.clone();
+            Arc::new(LocalFileRepository::with_watch(
+                &data_path,
+                std::time::Duration::from_millis(config.data_watch.debounce_ms),
+                move |tokens| {
+                    let index = index.clone();
+                    tokio::spawn(async move {
+                        for token in tokens {
+                            if let Err(e) = index.add_token(&token).await {
+                                tracing::warn!("Failed to update exchange index for {}: {}", token, e);
+                            }
+                        }
+                    });
+                },
+            ))
+        } else {
+            Arc::new(LocalFileRepository::new(&data_path))
+        };
         if repo.is_available() {
             tracing::info!("Local filesystem repository available at: {}", data_path);
             Some(repo)
@@ -170,40 +669,48 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize exchange index if local repo is available
     let exchange_index: Option<Arc<ExchangeIndex>> = if local_repo.is_some() {
-        let index = Arc::new(ExchangeIndex::new(&data_path));
+        // Serve the saved index until the rebuild replaces it
+        if let Err(e) = exchange_index.load_persisted().await {
+            tracing::warn!("Failed to load saved exchange index: {}", e);
+        }
         // Build index in background (non-blocking)
-        let index_clone = index.clone();
-        tokio::spawn(async move {
-            if let Err(e) = index_clone.rebuild().await {
-                tracing::warn!("Failed to build exchange index: {}", e);
-            }
-        });
-        Some(index)
+        exchange_index.spawn_rebuild();
+        Some(exchange_index)
     } else {
         None
     };
 
     // Get default repo for ticker service (first allowed repo)
-    let default_repo = config
-        .allowed_repos
-        .first()
-        .cloned()
-        .expect("At least one allowed repo must be configured");
+    let default_repo = config.default_repo()?;
 
     // Application
-    let content_service = Arc::new(ContentService::new(
-        github_repo.clone(),
-        redis_repo.clone(),
-        config.allowed_repos.clone(),
-    ));
-
-    let ticker_service = Arc::new(TickerService::with_local(
-        github_repo,
-        local_repo.map(|r| r as Arc<dyn crate::domain::ContentRepository>),
-        redis_repo.clone(),
-        default_repo,
-        exchange_index,
-    ));
+    let content_service = Arc::new(
+        ContentService::new(github_repo, redis_repo.clone(), config.allowed_repos.clone())
+            .with_fetch_concurrency(config.bulk.max_concurrency),
+    );
+
+    // GitHub reads share ContentService's listing and file cache
+    let ticker_service = Arc::new(
+        TickerService::with_local(
+            content_service.repository(),
+            local_repo.map(|r| r as Arc<dyn crate::domain::ContentRepository>),
+            redis_repo.clone(),
+            default_repo,
+            exchange_index.clone(),
+        )
+        .with_allowed_repos(config.allowed_repos.clone())
+        .with_outlier_rules(application::ticker_service::OutlierRules {
+            mad_threshold: config.aggregation.outlier_mad_threshold,
+            max_deviation_pct: config.aggregation.outlier_max_deviation_pct,
+            apply_to_vwap: config.aggregation.outlier_filter_vwap,
+        })
+        .with_repo_concurrency(config.aggregation.repo_concurrency)
+        .with_change_from_history(config.aggregation.change_from_history)
+        .with_stats_lookback_days(config.aggregation.stats_lookback_days)
+        .with_max_search_results(config.ticker_search.max_results)
+        .with_github_fallback(config.local_data.github_fallback)
+        .with_exchange_meta(config.exchange_meta.clone()),
+    );
 
     // ========================================================================
     // Kaspa.com L1 Marketplace API (heavy-cache layer)
@@ -220,39 +727,200 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Loaded {} tokens from configuration", tokens_config.get_tokens().len());
 
     // Initialize Parquet cache storage
-    let cache_path = env::var("CACHE_PATH").unwrap_or_else(|_| "data/cache".to_string());
-    let parquet_store = Arc::new(ParquetStore::new(&cache_path));
+    let secondary_cache_path = env::var("CACHE_SECONDARY_PATH").ok().filter(|p| !p.is_empty());
+    let parquet_store = Arc::new(
+        ParquetStore::new(&cache_path).with_secondary_path(secondary_cache_path.as_deref()),
+    );
     tracing::info!("Parquet cache storage initialized at: {}", cache_path);
+    if let Some(ref secondary) = secondary_cache_path {
+        tracing::info!("Parquet secondary read-only cache path: {}", secondary);
+    }
+
+    // Keep the Parquet cache under its size cap, oldest entries first
+    if config.cache.max_size_mb > 0 {
+        let store = parquet_store.clone();
+        let max_bytes = config.cache.max_size_mb * 1024 * 1024;
+        let interval_minutes = config.cache.eviction_interval_minutes.max(1);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(interval_minutes * 60));
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
+                let store = store.clone();
+                match tokio::task::spawn_blocking(move || store.evict_to_size(max_bytes)).await {
+                    Ok(Ok(report)) => {
+                        metrics::counter!("parquet_cache_evicted_bytes_total")
+                            .increment(report.bytes_reclaimed);
+                        if report.bytes_remaining > max_bytes {
+                            tracing::warn!(
+                                "Parquet cache is {} bytes after eviction, above its {} byte cap (pinned entries?)",
+                                report.bytes_remaining,
+                                max_bytes
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => tracing::warn!("Parquet cache eviction failed: {}", e),
+                    Err(e) => tracing::warn!("Parquet cache eviction task panicked: {}", e),
+                }
+            }
+        });
+        tracing::info!(
+            "Parquet cache capped at {} MiB, checked every {} minutes",
+            config.cache.max_size_mb,
+            interval_minutes
+        );
+    }
 
     // Initialize rate limiter for kaspa.com API
-    let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit.requests_per_minute));
+    let rate_limiter = Arc::new(
+        RateLimiter::new(config.rate_limit.requests_per_minute)
+            .with_category_limits(config.rate_limit.per_category.clone()),
+    );
     tracing::info!("Rate limiter initialized: {} requests/minute", config.rate_limit.requests_per_minute);
+    for (category, limit) in &config.rate_limit.per_category {
+        tracing::info!("Rate limit for {}: {} requests/minute", category, limit);
+    }
 
     // Initialize Kaspa.com API client
-    let kaspacom_client = Arc::new(KaspaComClient::new());
+    let kaspacom_token = env::var("KASPACOM_TOKEN").ok();
+    let kaspacom_client = Arc::new(
+        KaspaComClient::from_config(&config.kaspacom_client).with_api_token(kaspacom_token),
+    );
+    if kaspacom_client.is_authenticated() {
+        tracing::info!("KASPACOM_TOKEN found - using authenticated kaspa.com requests");
+    } else {
+        tracing::info!("KASPACOM_TOKEN not found in env - using unauthenticated kaspa.com requests");
+    }
+    tracing::info!(
+        "kaspa.com client: {}s timeout, {} retries, {}ms base backoff",
+        config.kaspacom_client.request_timeout_secs,
+        config.kaspacom_client.max_retries,
+        config.kaspacom_client.base_backoff_ms
+    );
 
     // Create tiered cache service (Redis + Parquet)
-    let cache_service = Arc::new(CacheService::new(
-        redis_repo,
-        parquet_store,
-        kaspacom_client,
-        rate_limiter.clone(),
-    ));
+    let cache_service = Arc::new(
+        CacheService::new(
+            redis_repo.clone(),
+            parquet_store,
+            kaspacom_client,
+            rate_limiter.clone(),
+        )
+        .with_negative_ttl(config.negative_cache.ttl_secs)
+        .with_ttls(config.cache_ttls)
+        .with_circuit_breaker(infrastructure::CircuitBreaker::new(config.circuit_breaker.clone())),
+    );
+    tracing::info!("Upstream 404s cached for {}s", config.negative_cache.ttl_secs);
+    if config.cache_ttls != application::cache_service::TtlConfig::default() {
+        tracing::info!("Cache TTL overrides: {:?}", config.cache_ttls);
+    }
+    tracing::info!(
+        "kaspa.com circuit breaker: open after {} consecutive failures, {}s cooldown",
+        config.circuit_breaker.failure_threshold,
+        config.circuit_breaker.cooldown_secs
+    );
 
     // Create Kaspa.com service
-    let kaspacom_service = Arc::new(KaspaComService::new(
-        cache_service,
-        tokens_config,
-    ));
+    let mut kaspacom_service = KaspaComService::new(cache_service, tokens_config)
+        .with_bulk_concurrency(config.bulk.max_concurrency)
+        .with_ipfs_gateway(config.ipfs.gateway.clone());
+    tracing::info!("Bulk endpoint concurrency: {}", config.bulk.max_concurrency);
+    tracing::info!("IPFS URIs resolved through {}", config.ipfs.gateway);
+    if config.logo_proxy.enabled {
+        kaspacom_service = kaspacom_service.with_logo_proxy(config.logo_proxy.max_bytes);
+        tracing::info!("Logo proxy enabled (max {} bytes per image)", config.logo_proxy.max_bytes);
+    }
+    if !config.freshness.is_empty() {
+        tracing::info!("Freshness SLA check covers {} cache entries", config.freshness.len());
+        kaspacom_service = kaspacom_service.with_freshness_targets(config.freshness.clone());
+    }
+    let kaspacom_service = Arc::new(kaspacom_service);
+
+    // Periodically pre-fetch hot data for configured tokens
+    if config.cache_warming.enabled {
+        let warm_service = kaspacom_service.clone();
+        let interval_minutes = config.cache_warming.interval_minutes.max(1);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(interval_minutes * 60));
+            // Abandons a warm-up in progress too
+            shutdown
+                .run_until_cancelled(async {
+                    loop {
+                        interval.tick().await;
+                        warm_service.warm_cache().await;
+                    }
+                })
+                .await;
+            tracing::info!("Cache warming stopped");
+        });
+        tracing::info!("Cache warming enabled: every {} minutes", interval_minutes);
+    } else {
+        tracing::info!("Cache warming disabled");
+    }
+
+    // Admin API keys: comma-separated "identity:key" pairs
+    let admin_api_keys = parse_admin_api_keys(&env::var("ADMIN_API_KEYS").unwrap_or_default());
+    if admin_api_keys.is_empty() {
+        tracing::info!("ADMIN_API_KEYS not set - admin endpoints disabled");
+    } else {
+        tracing::info!("Admin endpoints enabled for {} API key(s)", admin_api_keys.len());
+    }
+
+    let api_key_auth = api::auth::ApiKeyAuth::new(&config.api_auth);
+    if api_key_auth.is_enabled() {
+        tracing::info!(
+            "API key required for {} route prefix(es), {} key(s) configured",
+            config.api_auth.protected_routes.len(),
+            config.api_auth.api_keys.len()
+        );
+        if config.api_auth.api_keys.is_empty() {
+            tracing::warn!("api_auth protects routes but no api_keys are configured");
+        }
+    }
+
+    let price_ticks = Arc::new(
+        PriceTickFeeds::new(ticker_service.clone(), config.live_feed.clone()).with_shutdown(shutdown.clone()),
+    );
 
     let state = AppState {
         content_service,
         ticker_service,
         kaspacom_service,
+        price_ticks,
         rate_limiter,
+        admin_api_keys: Arc::new(admin_api_keys),
+        api_key_auth: Arc::new(api_key_auth),
+        redis_repo,
+        data_path: configured_data_path,
+        exchange_index,
+        #[cfg(feature = "datafusion")]
+        sql_engine: Arc::new(
+            infrastructure::ParquetSqlEngine::new(&cache_path).with_limits(
+                config.sql_query.max_rows,
+                std::time::Duration::from_secs(config.sql_query.timeout_secs),
+            ),
+        ),
     };
 
-    let app = create_router(state, config.server.allowed_origins.clone());
+    let limits = api::routes::RequestLimits {
+        timeout: std::time::Duration::from_secs(config.server.request_timeout_secs.max(1)),
+        max_body_bytes: config.server.max_body_bytes,
+    };
+    tracing::info!(
+        "Request timeout {}s, max body {} bytes",
+        limits.timeout.as_secs(),
+        limits.max_body_bytes
+    );
+    api::metric_labels::seed_graphql_labels(
+        config.graphql_metrics.operations.iter().map(String::as_str),
+    );
+    let app = create_router(state, cors, limits);
 
     // Allow PORT env var override
     let port = env::var("PORT")
@@ -266,11 +934,48 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("GitRows Rust API server running at http://{}", addr);
     tracing::info!("Allowed repos: {:?}", config.allowed_repos);
 
-    // Graceful shutdown handling
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .context("Server error during operation")?;
+    // Graceful shutdown: stop accepting connections on a signal, then give
+    // in-flight requests `shutdown_grace_secs` to finish before dropping them
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown_signal().await;
+            shutdown.cancel();
+        }
+    });
+    let grace = std::time::Duration::from_secs(config.server.shutdown_grace_secs);
+    let mut server = tokio::spawn(
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .into_future(),
+    );
+    tokio::select! {
+        result = &mut server => result.context("Server task failed")?.context("Server error during operation")?,
+        _ = shutdown.cancelled() => {
+            tracing::info!(
+                "Draining {} in-flight request(s), up to {}s",
+                api::in_flight::in_flight_requests(),
+                grace.as_secs()
+            );
+            match tokio::time::timeout(grace, &mut server).await {
+                Ok(result) => result.context("Server task failed")?.context("Server error during operation")?,
+                Err(_) => {
+                    tracing::warn!(
+                        "Shutdown grace period elapsed, dropping {} in-flight request(s)",
+                        api::in_flight::in_flight_requests()
+                    );
+                    server.abort();
+                }
+            }
+        }
+    }
+
+    // Flush buffered spans
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            tracing::warn!("Failed to flush OTLP traces: {}", e);
+        }
+    }
 
     Ok(())
 }
@@ -303,3 +1008,42 @@ async fn shutdown_signal() {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_CONFIG: &str = "server: {}\n";
+
+    #[test]
+    fn test_empty_allowed_repos_is_a_descriptive_error() {
+        let config: Config =
+            serde_yaml::from_str(&format!("{}allowed_repos: []\n", BASE_CONFIG)).unwrap();
+
+        let err = config.default_repo().unwrap_err();
+
+        assert!(err.to_string().contains("allowed_repos"), "{}", err);
+    }
+
+    #[test]
+    fn test_default_repo_is_first_allowed_repo() {
+        let config: Config = serde_yaml::from_str(&format!(
+            "{}allowed_repos:\n  - {{source: github, owner: KaspaDev, repo: data}}\n",
+            BASE_CONFIG
+        ))
+        .unwrap();
+
+        assert_eq!(config.default_repo().unwrap().owner, "KaspaDev");
+    }
+
+    #[test]
+    fn test_deprecated_allowed_origins_feed_cors() {
+        let server = |yaml: &str| serde_yaml::from_str::<ServerConfig>(yaml).unwrap();
+
+        assert!(server("{}").cors_layer().is_ok());
+        // The old comma-separated list still applies, and is validated too
+        let legacy = "allowed_origins: \"https://a.example, https://b.example\"\ncors: { allow_credentials: true, allowed_methods: [GET], allowed_headers: [x-api-key] }";
+        assert!(server(legacy).cors_layer().is_ok());
+        assert!(server("allowed_origins: \"*\"\ncors: { allow_credentials: true }").cors_layer().is_err());
+    }
+}
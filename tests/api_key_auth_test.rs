@@ -0,0 +1,67 @@
+//! Protected routes reject missing keys and keys over their budget.
+//!
+//! Run with: `cargo test --test api_key_auth_test`
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use common::app_state;
+use krcbot_kaspacom_gatewayapi::api::auth::{ApiAuthConfig, ApiKeyAuth, ApiKeyConfig};
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Router protecting `/v1/api/kaspa` with one key allowed one request a minute
+fn app(cache_dir: &std::path::Path) -> axum::Router {
+    let mut state = app_state(None, None, cache_dir);
+    state.api_key_auth = Arc::new(ApiKeyAuth::new(&ApiAuthConfig {
+        protected_routes: vec!["/v1/api/kaspa".to_string()],
+        api_keys: vec![ApiKeyConfig {
+            name: "trader".to_string(),
+            key: "secret".to_string(),
+            requests_per_minute: Some(1),
+        }],
+        ..ApiAuthConfig::default()
+    }));
+    create_router(state, CorsConfig::default().layer().unwrap(), RequestLimits::default())
+}
+
+async fn get(app: &axum::Router, path: &str, key: Option<&str>) -> axum::response::Response {
+    let mut request = Request::get(path);
+    if let Some(key) = key {
+        request = request.header("X-API-Key", key);
+    }
+    app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_missing_or_unknown_key_is_unauthorized() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = app(dir.path());
+
+    let missing = get(&app, "/v1/api/kaspa/unknown", None).await;
+    assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+    let unknown = get(&app, "/v1/api/kaspa/unknown", Some("wrong")).await;
+    assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+
+    // Paths merely sharing the prefix's characters stay open
+    let sibling = get(&app, "/v1/api/kaspa-other", None).await;
+    assert_ne!(sibling.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_key_over_budget_is_rate_limited() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = app(dir.path());
+
+    // Unknown routes fall through to the 404 handler once authenticated
+    let first = get(&app, "/v1/api/kaspa/unknown", Some("secret")).await;
+    assert_eq!(first.status(), StatusCode::NOT_FOUND);
+
+    let second = get(&app, "/v1/api/kaspa/unknown", Some("secret")).await;
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = second.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+    assert!((1..=60).contains(&retry_after), "retry after {}", retry_after);
+}
@@ -0,0 +1,67 @@
+//! Tiered cache promotion: a Parquet hit must be copied into the hot cache
+//! so the next lookup never reaches Parquet or the upstream API.
+//!
+//! Run with: `cargo test --test cache_promotion_test`
+
+use krcbot_kaspacom_gatewayapi::application::cache_service::ttl;
+use krcbot_kaspacom_gatewayapi::application::CacheService;
+use krcbot_kaspacom_gatewayapi::domain::CacheRepository;
+use krcbot_kaspacom_gatewayapi::infrastructure::{InMemoryCache, KaspaComClient, ParquetStore, RateLimiter};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const REDIS_KEY: &str = "kaspa:floor_price:NACHO";
+const CATEGORY: &str = "floor_prices";
+const KEY: &str = "NACHO";
+
+async fn get(service: &CacheService, fetches: &Arc<AtomicUsize>) -> Value {
+    let fetches = fetches.clone();
+    service
+        .get_cached(
+            REDIS_KEY,
+            CATEGORY,
+            KEY,
+            (ttl::HOT_REDIS_SECS, ttl::HOT_PARQUET_SECS),
+            move || async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                Ok(json!({"ticker": "NACHO", "floor_price": 999.0}))
+            },
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_parquet_hit_is_promoted_to_redis() {
+    let dir = tempfile::tempdir().unwrap();
+    let redis = Arc::new(InMemoryCache::new());
+    let parquet = Arc::new(ParquetStore::new(dir.path().to_str().unwrap()));
+    let service = CacheService::new(
+        redis.clone(),
+        parquet.clone(),
+        Arc::new(KaspaComClient::new()),
+        Arc::new(RateLimiter::new(60)),
+    );
+
+    // Seed the warm tier only
+    let seeded = json!({"ticker": "NACHO", "floor_price": 0.5});
+    parquet.write_simple(CATEGORY, KEY, &seeded, ttl::HOT_PARQUET_SECS).unwrap();
+    assert!(redis.get(REDIS_KEY).await.unwrap().is_none());
+
+    let fetches = Arc::new(AtomicUsize::new(0));
+    assert_eq!(get(&service, &fetches).await, seeded);
+
+    // Promoted into the hot tier with the hot TTL
+    let promoted: Value = serde_json::from_str(&redis.get(REDIS_KEY).await.unwrap().unwrap()).unwrap();
+    assert_eq!(promoted, seeded);
+    assert_eq!(redis.ttl_of(REDIS_KEY), Some(ttl::HOT_REDIS_SECS));
+
+    // With Parquet gone, the second call can only be answered by the hot tier
+    parquet.delete(CATEGORY, KEY).unwrap();
+    assert_eq!(get(&service, &fetches).await, seeded);
+    assert_eq!(fetches.load(Ordering::SeqCst), 0);
+
+    let stats = service.get_stats().unwrap();
+    assert_eq!(stats.cache_hits, 2);
+}
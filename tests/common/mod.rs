@@ -0,0 +1,67 @@
+//! Fixtures shared by the integration tests.
+
+use krcbot_kaspacom_gatewayapi::api::auth::{ApiAuthConfig, ApiKeyAuth};
+use krcbot_kaspacom_gatewayapi::api::state::AppState;
+use krcbot_kaspacom_gatewayapi::application::live_feed::LiveFeedConfig;
+use krcbot_kaspacom_gatewayapi::application::{
+    CacheService, ContentService, KaspaComService, PriceTickFeeds, TickerService,
+};
+use krcbot_kaspacom_gatewayapi::domain::{RepoConfig, TokensConfig};
+use krcbot_kaspacom_gatewayapi::infrastructure::{
+    GitHubRepository, InMemoryCache, KaspaComClient, LocalFileRepository, ParquetStore, RateLimiter,
+    RedisRepository,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// App state with no Redis, caching to Parquet under `cache_dir`
+///
+/// Kaspa.com calls go to `upstream` when given (e.g. a mock server), and
+/// ticker data is read from `data_dir` when given instead of GitHub.
+pub fn app_state(upstream: Option<&str>, data_dir: Option<&Path>, cache_dir: &Path) -> AppState {
+    let redis_repo = Arc::new(RedisRepository::new(None));
+    let github_repo = Arc::new(GitHubRepository::new(None));
+    let rate_limiter = Arc::new(RateLimiter::new(60));
+    let client = match upstream {
+        Some(upstream) => KaspaComClient::with_base_url(upstream),
+        None => KaspaComClient::new(),
+    };
+    let cache = Arc::new(CacheService::new(
+        Arc::new(InMemoryCache::new()),
+        Arc::new(ParquetStore::new(cache_dir.to_str().unwrap())),
+        Arc::new(client),
+        rate_limiter.clone(),
+    ));
+    let repo = RepoConfig {
+        source: "github".to_string(),
+        owner: "KaspaDev".to_string(),
+        repo: "KaspaDev-KaspaCom-Gateway-Data".to_string(),
+    };
+
+    let ticker_service = Arc::new(match data_dir {
+        Some(data_dir) => TickerService::new(
+            Arc::new(LocalFileRepository::new(data_dir)),
+            Arc::new(InMemoryCache::new()),
+            repo.clone(),
+        ),
+        None => TickerService::new(github_repo.clone(), redis_repo.clone(), repo.clone()),
+    });
+
+    AppState {
+        content_service: Arc::new(ContentService::new(github_repo, redis_repo.clone(), vec![repo])),
+        price_ticks: Arc::new(PriceTickFeeds::new(ticker_service.clone(), LiveFeedConfig::default())),
+        ticker_service,
+        kaspacom_service: Arc::new(KaspaComService::new(cache, TokensConfig { tokens: HashMap::new() })),
+        rate_limiter,
+        admin_api_keys: Arc::new(HashMap::new()),
+        api_key_auth: Arc::new(ApiKeyAuth::new(&ApiAuthConfig::default())),
+        redis_repo,
+        data_path: None,
+        exchange_index: None,
+        #[cfg(feature = "datafusion")]
+        sql_engine: Arc::new(krcbot_kaspacom_gatewayapi::infrastructure::ParquetSqlEngine::new(
+            cache_dir.to_str().unwrap(),
+        )),
+    }
+}
@@ -0,0 +1,66 @@
+//! `?fresh=true` only bypasses the cache for callers with a valid API key.
+//!
+//! Run with: `cargo test --test fresh_bypass_test`
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use common::app_state;
+use krcbot_kaspacom_gatewayapi::api::auth::{ApiAuthConfig, ApiKeyAuth, ApiKeyConfig};
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Serve one floor price for every path, counting the calls
+async fn spawn_counting_upstream(calls: Arc<AtomicUsize>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new().fallback(move || async move {
+        calls.fetch_add(1, Ordering::SeqCst);
+        axum::Json(serde_json::json!([{ "ticker": "NACHO", "floor_price": 0.5 }]))
+    });
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_fresh_requires_an_api_key() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let upstream = spawn_counting_upstream(calls.clone()).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut state = app_state(Some(&upstream), None, dir.path());
+    state.api_key_auth = Arc::new(ApiKeyAuth::new(&ApiAuthConfig {
+        api_keys: vec![ApiKeyConfig {
+            name: "trader".to_string(),
+            key: "secret".to_string(),
+            requests_per_minute: None,
+        }],
+        ..ApiAuthConfig::default()
+    }));
+    let app = create_router(state, CorsConfig::default().layer().unwrap(), RequestLimits::default());
+
+    let get = |key: Option<&str>| {
+        let mut request = Request::get("/v1/api/kaspa/floor-price?ticker=NACHO&fresh=true");
+        if let Some(key) = key {
+            request = request.header("X-API-Key", key);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    // The first request fills the cache
+    assert_eq!(get(None).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Anonymous and unknown-key callers are served from it
+    assert_eq!(get(None).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(get(Some("wrong")).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // A valid key refetches
+    assert_eq!(get(Some("secret")).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
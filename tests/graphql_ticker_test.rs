@@ -0,0 +1,142 @@
+//! GraphQL queries backed by `TickerService` read the exchange data
+//! repository through the `AppState` in the schema context.
+//!
+//! Run with: `cargo test --test graphql_ticker_test`
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use common::app_state;
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use krcbot_kaspacom_gatewayapi::api::state::AppState;
+use serde_json::{json, Value};
+use std::path::Path;
+use tower::ServiceExt;
+
+/// Create `<token>/<exchange>/` directories with one raw file each
+fn write_exchange_data(data_dir: &Path, pairs: &[(&str, &str)]) {
+    for (token, exchange) in pairs {
+        let dir = data_dir.join(format!("{}/{}/2024/01", token, exchange));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("2024-01-01-raw.json"), json!({ "data": [] }).to_string()).unwrap();
+    }
+}
+
+/// Write today's raw file for `token` on `exchange` with one trade per hour
+fn write_todays_trades(data_dir: &Path, token: &str, exchange: &str) {
+    let now = chrono::Utc::now();
+    let today = now.date_naive();
+    let dir = data_dir.join(format!("{}/{}/{}", token, exchange, today.format("%Y/%m")));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let trades: Vec<Value> = (1..=3)
+        .rev()
+        .map(|hours_ago| {
+            json!({
+                "timestamp": (now - chrono::Duration::hours(hours_ago)).timestamp_millis(),
+                "last": 0.1 * hours_ago as f64,
+                "high": 0.4,
+                "low": 0.05,
+                "quoteVolume": 100.0,
+            })
+        })
+        .collect();
+    std::fs::write(
+        dir.join(format!("{}-raw.json", today.format("%Y-%m-%d"))),
+        json!({ "data": trades }).to_string(),
+    )
+    .unwrap();
+}
+
+async fn graphql(state: &AppState, query: &str) -> Value {
+    let app = create_router(state.clone(), CorsConfig::default().layer().unwrap(), RequestLimits::default());
+    let request = Request::post("/graphql")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "query": query }).to_string()))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_available_tickers_and_exchanges_queries() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    write_exchange_data(data_dir.path(), &[("kaspa", "mexc"), ("kaspa", "xeggex"), ("nacho", "xeggex")]);
+    let state = app_state(None, Some(data_dir.path()), cache_dir.path());
+
+    let body = graphql(
+        &state,
+        "{ availableTickers { tickers count } exchanges { count exchanges { exchange tokens tokenCount } } }",
+    )
+    .await;
+    assert!(body.get("errors").is_none(), "{}", body);
+
+    let tickers = &body["data"]["availableTickers"];
+    assert_eq!(tickers["count"], 2);
+    let mut names: Vec<&str> = tickers["tickers"].as_array().unwrap().iter().map(|t| t.as_str().unwrap()).collect();
+    names.sort();
+    assert_eq!(names, ["kaspa", "nacho"]);
+
+    let exchanges = &body["data"]["exchanges"];
+    assert_eq!(exchanges["count"], 2);
+    let xeggex = exchanges["exchanges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["exchange"] == "xeggex")
+        .unwrap();
+    assert_eq!(xeggex["tokenCount"], 2);
+    assert_eq!(xeggex["tokens"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_ticker_stats_and_history_queries() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    write_todays_trades(data_dir.path(), "kaspa", "mexc");
+    let state = app_state(None, Some(data_dir.path()), cache_dir.path());
+
+    let body = graphql(
+        &state,
+        r#"{
+            tickerStats(token: "KASPA", range: "today") {
+                token range
+                exchanges { exchange last dataPoints reliable }
+                aggregate { avgPrice exchangeCount }
+            }
+            tickerHistory(token: "kaspa", range: "7d", resolution: "1h") {
+                token resolution
+                data { timestamp open close volume }
+            }
+        }"#,
+    )
+    .await;
+    assert!(body.get("errors").is_none(), "{}", body);
+
+    let stats = &body["data"]["tickerStats"];
+    assert_eq!(stats["token"], "KASPA");
+    assert_eq!(stats["range"], "today");
+    assert_eq!(stats["exchanges"][0]["exchange"], "mexc");
+    assert!(stats["exchanges"][0]["last"].is_number());
+    assert_eq!(stats["aggregate"]["exchangeCount"], 1);
+
+    let history = &body["data"]["tickerHistory"];
+    assert_eq!(history["resolution"], "1h");
+    assert!(!history["data"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ticker_stats_rejects_unknown_range() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    write_todays_trades(data_dir.path(), "kaspa", "mexc");
+    let state = app_state(None, Some(data_dir.path()), cache_dir.path());
+
+    let body = graphql(&state, r#"{ tickerStats(token: "kaspa", range: "1y") { token } }"#).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "VALIDATION_ERROR", "{}", body);
+}
@@ -0,0 +1,75 @@
+//! `/health/ready` reports each dependency and answers 503 when one fails.
+//!
+//! Run with: `cargo test --test readiness_test`
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use common::app_state;
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::handlers::READY_PING_TIMEOUT;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use krcbot_kaspacom_gatewayapi::api::state::AppState;
+use krcbot_kaspacom_gatewayapi::infrastructure::RedisRepository;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+async fn ready(state: AppState) -> (StatusCode, serde_json::Value) {
+    let app = create_router(state, CorsConfig::default().layer().unwrap(), RequestLimits::default());
+    let response = app
+        .oneshot(Request::get("/health/ready").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_ready_when_dependencies_are_up_or_unconfigured() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = app_state(None, None, dir.path());
+    state.data_path = Some(dir.path().to_str().unwrap().to_string());
+
+    let (status, body) = ready(state).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["failed"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_not_ready_when_data_path_is_missing() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = app_state(None, None, dir.path());
+    state.data_path = Some("/nonexistent/local-data".to_string());
+
+    let (status, body) = ready(state).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["failed"], serde_json::json!(["data_path"]));
+}
+
+#[tokio::test]
+async fn test_unresponsive_redis_fails_within_the_ping_timeout() {
+    // Accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut open = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            open.push(socket);
+        }
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = app_state(None, None, dir.path());
+    state.redis_repo = Arc::new(RedisRepository::new(Some(format!("redis://{}", addr))));
+
+    let started = Instant::now();
+    let (status, body) = ready(state).await;
+    assert!(started.elapsed() < READY_PING_TIMEOUT + Duration::from_secs(3));
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["failed"], serde_json::json!(["redis"]));
+}
@@ -0,0 +1,147 @@
+//! The sold-orders export streams cached windows a batch at a time, and the
+//! sold-orders endpoints only page when asked to.
+//!
+//! Run with: `cargo test --test sold_orders_export_test`
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use common::app_state;
+use futures::StreamExt;
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use krcbot_kaspacom_gatewayapi::api::state::AppState;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Orders in the exported window: more than two Parquet batches
+const ORDERS: usize = 2500;
+
+/// Serve `ORDERS` sold orders, counting upstream calls
+async fn spawn_sold_orders_upstream(calls: Arc<AtomicUsize>) -> String {
+    let orders: Vec<Value> = (0..ORDERS)
+        .map(|i| {
+            json!({
+                "_id": format!("order{}", i),
+                "ticker": "NACHO",
+                "amount": 1000 + i,
+                "pricePerToken": 0.0001,
+                "totalPrice": 0.1,
+                "sellerAddress": "kaspa:qz0",
+                "createdAt": 1_727_000_000_000i64 - i as i64 * 60_000,
+                "status": "completed",
+            })
+        })
+        .collect();
+    let app = axum::Router::new().route(
+        "/api/sold-orders",
+        axum::routing::get(move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            let orders = orders.clone();
+            async move { axum::Json(orders) }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// Export the window, returning the body's chunk sizes (in lines) and lines
+async fn export(state: AppState) -> (Vec<usize>, Vec<Value>) {
+    let app = create_router(state, CorsConfig::default().layer().unwrap(), RequestLimits::default());
+    let response = app
+        .oneshot(Request::get("/v1/api/kaspa/sold-orders/export").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+
+    let mut chunks = response.into_body().into_data_stream();
+    let mut chunk_lines = Vec::new();
+    let mut lines = Vec::new();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.unwrap();
+        let before = lines.len();
+        lines.extend(
+            chunk
+                .split(|b| *b == b'\n')
+                .filter(|line| !line.is_empty())
+                .map(|line| serde_json::from_slice::<Value>(line).unwrap()),
+        );
+        chunk_lines.push(lines.len() - before);
+    }
+    (chunk_lines, lines)
+}
+
+#[tokio::test]
+async fn test_cached_export_is_streamed_in_batches() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let upstream = spawn_sold_orders_upstream(calls.clone()).await;
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(Some(&upstream), None, dir.path());
+
+    // A miss fetches the window once and caches it
+    let (_, lines) = export(state.clone()).await;
+    assert_eq!(lines.len(), ORDERS);
+
+    // The cached window is sent a batch at a time, never all at once
+    let (chunk_lines, lines) = export(state).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(lines.len(), ORDERS);
+    assert_eq!(chunk_lines, [1024, 1024, ORDERS - 2048]);
+    assert_eq!(lines[0]["_id"], "order0");
+    assert_eq!(lines[ORDERS - 1]["_id"], format!("order{}", ORDERS - 1));
+}
+
+/// Send `request` to a fresh router, returning its JSON body
+async fn request_json(state: AppState, request: Request<Body>) -> Value {
+    let app = create_router(state, CorsConfig::default().layer().unwrap(), RequestLimits::default());
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_sold_orders_are_an_array_unless_paged() {
+    let upstream = spawn_sold_orders_upstream(Arc::new(AtomicUsize::new(0))).await;
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(Some(&upstream), None, dir.path());
+    let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+    // Existing clients keep getting a bare array
+    let body = request_json(state.clone(), get("/v1/api/kaspa/sold-orders")).await;
+    assert_eq!(body.as_array().unwrap().len(), ORDERS);
+
+    // `limit`, `after` or `since` opts into the page envelope
+    let body = request_json(state.clone(), get("/v1/api/kaspa/sold-orders?limit=10")).await;
+    assert_eq!(body["items"].as_array().unwrap().len(), 10);
+    assert_eq!(body["hasMore"], true);
+    let cursor = body["nextCursor"].as_str().unwrap().to_string();
+    let body = request_json(state.clone(), get(&format!("/v1/api/kaspa/sold-orders?after={}", cursor))).await;
+    assert_eq!(body["items"].as_array().unwrap().len(), ORDERS - 10);
+    assert_eq!(body["hasMore"], false);
+
+    // A poll with `since` gets the envelope too, echoing `since` when
+    // nothing is newer so the client has a timestamp to pass back
+    let body = request_json(state.clone(), get("/v1/api/kaspa/sold-orders?since=1727000000000")).await;
+    assert_eq!(body["items"].as_array().unwrap().len(), 0);
+    assert_eq!(body["latestTimestamp"], 1_727_000_000_000i64);
+
+    // GraphQL keeps `soldOrders` a list and caps page sizes like REST
+    let graphql = |query: &str| {
+        Request::post("/graphql")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "query": query }).to_string()))
+            .unwrap()
+    };
+    let body = request_json(state.clone(), graphql("{ soldOrders { id } }")).await;
+    assert_eq!(body["data"]["soldOrders"].as_array().unwrap().len(), ORDERS);
+    let body = request_json(state, graphql("{ soldOrdersPage(limit: 5000) { items { id } hasMore } }")).await;
+    assert_eq!(body["data"]["soldOrdersPage"]["items"].as_array().unwrap().len(), 1000);
+    assert_eq!(body["data"]["soldOrdersPage"]["hasMore"], true);
+}
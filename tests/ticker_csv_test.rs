@@ -0,0 +1,106 @@
+//! Ticker history/timeseries serve CSV when asked, JSON otherwise.
+//!
+//! Run with: `cargo test --test ticker_csv_test`
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use common::app_state;
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use krcbot_kaspacom_gatewayapi::api::state::AppState;
+use serde_json::{json, Value};
+use std::path::Path;
+use tower::ServiceExt;
+
+/// Write yesterday's raw file for kaspa on mexc with trades at 10:00,
+/// 11:00 and 12:00 UTC, so every trade falls on the file's own day whenever
+/// the test runs
+fn write_exchange_data(data_dir: &Path) {
+    let yesterday = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
+    let dir = data_dir.join(format!("kaspa/mexc/{}", yesterday.format("%Y/%m")));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let trades: Vec<Value> = (10..=12)
+        .map(|hour| {
+            json!({
+                "timestamp": yesterday.and_hms_opt(hour, 0, 0).unwrap().and_utc().timestamp_millis(),
+                "last": 0.1 * (13 - hour) as f64,
+                "quoteVolume": 100.0 * (hour - 9) as f64,
+            })
+        })
+        .collect();
+    std::fs::write(
+        dir.join(format!("{}-raw.json", yesterday.format("%Y-%m-%d"))),
+        json!({ "data": trades }).to_string(),
+    )
+    .unwrap();
+}
+
+async fn get(state: &AppState, uri: &str, accept: Option<&str>) -> Response {
+    let app = create_router(state.clone(), CorsConfig::default().layer().unwrap(), RequestLimits::default());
+    let mut request = Request::get(uri);
+    if let Some(accept) = accept {
+        request = request.header(header::ACCEPT, accept);
+    }
+    app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+async fn body_text(response: Response) -> String {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+fn content_type(response: &Response) -> &str {
+    response.headers()[header::CONTENT_TYPE].to_str().unwrap()
+}
+
+#[tokio::test]
+async fn test_history_and_timeseries_csv_match_json() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    write_exchange_data(data_dir.path());
+    let state = app_state(None, Some(data_dir.path()), cache_dir.path());
+
+    let cases = [
+        ("/v1/api/ticker/KASPA/history?range=7d&resolution=1h", "timestamp,open,high,low,close,volume"),
+        ("/v1/api/ticker/kaspa/timeseries?range=7d&resolution=1h", "timestamp,price"),
+    ];
+    for (uri, header_line) in cases {
+        let response = get(&state, uri, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(content_type(&response).starts_with("application/json"));
+        let json: Value = serde_json::from_str(&body_text(response).await).unwrap();
+        let points = json["data"].as_array().unwrap().len();
+        assert!(points > 0, "{}: no data points", uri);
+
+        let by_accept = get(&state, uri, Some("text/csv")).await;
+        let by_query = get(&state, &format!("{}&format=csv", uri), None).await;
+        for response in [by_accept, by_query] {
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(content_type(&response).starts_with("text/csv"));
+            let csv = body_text(response).await;
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(lines[0], header_line);
+            assert_eq!(lines.len(), points + 1, "{}: {}", uri, csv);
+            assert!(lines[1..]
+                .iter()
+                .all(|line| line.split(',').count() == header_line.split(',').count()));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_format_is_rejected() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    write_exchange_data(data_dir.path());
+    let state = app_state(None, Some(data_dir.path()), cache_dir.path());
+
+    let response = get(&state, "/v1/api/ticker/kaspa/history?format=xml", None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+}
@@ -0,0 +1,46 @@
+//! Upstream 404s must reach clients as 404 with a `NOT_FOUND` code, not 500.
+//!
+//! Run with: `cargo test --test upstream_not_found_test`
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::app_state;
+use krcbot_kaspacom_gatewayapi::api::cors::CorsConfig;
+use krcbot_kaspacom_gatewayapi::api::routes::{create_router, RequestLimits};
+use krcbot_kaspacom_gatewayapi::api::state::AppState;
+use serde_json::Value;
+use tower::ServiceExt;
+
+/// Serve 404 for every path, like the upstream API does for unknown tickers
+async fn spawn_not_found_upstream() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new().fallback(|| async { (StatusCode::NOT_FOUND, "token not found") });
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+async fn get(uri: &str, state: AppState) -> (StatusCode, Value) {
+    let app = create_router(state, CorsConfig::default().layer().unwrap(), RequestLimits::default());
+    let response = app
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_upstream_404_maps_to_404() {
+    let upstream = spawn_not_found_upstream().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    for uri in ["/v1/api/kaspa/token-info/NOSUCHTOKEN", "/v1/api/kaspa/krc721/collection/NOSUCHNFT"] {
+        let (status, body) = get(uri, app_state(Some(&upstream), None, dir.path())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}: {}", uri, body);
+        assert_eq!(body["code"], "NOT_FOUND");
+    }
+}